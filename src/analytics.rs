@@ -0,0 +1,344 @@
+/// analytics of the graph the stored triples form, the nodes being the
+/// subjects and the IRI and blank node objects, the edges the triples
+/// between them
+///
+/// Components are weakly connected, edges joining nodes whichever way
+/// they point.  PageRank follows edges from subject to object, a node
+/// without outgoing edges spreading its rank over every node.
+///
+/// The results render as a text report, as CSV with a row per node, or as
+/// triples about each node to store alongside the data.
+///
+use crate::data::{RdfName, Subject};
+use crate::prefixes::PrefixMap;
+use crate::report::{display, table};
+use std::collections::{BTreeMap, HashMap};
+
+pub const ANALYTICS: &str = "https://triples.navicore.tech/analytics#";
+
+/// the probability of following an edge rather than jumping to any node
+pub const DAMPING: f64 = 0.85;
+
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-10;
+
+/// Nodes and the edges between them, as indexes into `nodes`.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    nodes: Vec<String>,
+    index: HashMap<String, usize>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the index of `name`, added when new
+    pub fn add_node(&mut self, name: &str) -> usize {
+        if let Some(&n) = self.index.get(name) {
+            return n;
+        }
+        self.nodes.push(name.to_string());
+        self.index.insert(name.to_string(), self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    /// an edge from `from` to `to`, adding either node when new
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        let edge = (self.add_node(from), self.add_node(to));
+        self.edges.push(edge);
+    }
+
+    #[must_use]
+    pub fn nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[must_use]
+    pub fn edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// the weakly connected component of each node, numbered from 1 by
+    /// decreasing size, ties by their first node name
+    #[must_use]
+    pub fn components(&self) -> Vec<usize> {
+        fn root(parents: &mut [usize], mut n: usize) -> usize {
+            while parents[n] != n {
+                parents[n] = parents[parents[n]];
+                n = parents[n];
+            }
+            n
+        }
+
+        let mut parents: Vec<usize> = (0..self.nodes.len()).collect();
+        for &(from, to) in &self.edges {
+            let (from, to) = (root(&mut parents, from), root(&mut parents, to));
+            if from != to {
+                parents[from.max(to)] = from.min(to);
+            }
+        }
+
+        // size and least name of each root
+        let mut roots: HashMap<usize, (usize, &str)> = HashMap::new();
+        for n in 0..self.nodes.len() {
+            let name = self.nodes[n].as_str();
+            let entry = roots.entry(root(&mut parents, n)).or_insert((0, name));
+            entry.0 += 1;
+            entry.1 = entry.1.min(name);
+        }
+        let mut ordered: Vec<(usize, (usize, &str))> = roots.into_iter().collect();
+        ordered.sort_by(|(_, (a_size, a_name)), (_, (b_size, b_name))| {
+            b_size.cmp(a_size).then(a_name.cmp(b_name))
+        });
+        let numbers: HashMap<usize, usize> = ordered
+            .iter()
+            .enumerate()
+            .map(|(number, (root, _))| (*root, number + 1))
+            .collect();
+        (0..self.nodes.len())
+            .map(|n| numbers[&root(&mut parents, n)])
+            .collect()
+    }
+
+    /// the edges into and out of each node
+    #[must_use]
+    pub fn degrees(&self) -> Vec<(usize, usize)> {
+        let mut degrees = vec![(0, 0); self.nodes.len()];
+        for &(from, to) in &self.edges {
+            degrees[from].1 += 1;
+            degrees[to].0 += 1;
+        }
+        degrees
+    }
+
+    /// the PageRank of each node with `damping`, iterated until it
+    /// settles, the ranks summing to 1
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn pagerank(&self, damping: f64) -> Vec<f64> {
+        let count = self.nodes.len();
+        if count == 0 {
+            return Vec::new();
+        }
+        let n = count as f64;
+        let out: Vec<usize> = self.degrees().iter().map(|(_, out)| *out).collect();
+        let mut ranks = vec![1.0 / n; count];
+        for _ in 0..MAX_ITERATIONS {
+            let dangling: f64 = (0..count).filter(|&v| out[v] == 0).map(|v| ranks[v]).sum();
+            let mut next = vec![(1.0 - damping) / n + damping * dangling / n; count];
+            for &(from, to) in &self.edges {
+                next[to] += damping * ranks[from] / out[from] as f64;
+            }
+            let change: f64 = ranks.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+            ranks = next;
+            if change < TOLERANCE {
+                break;
+            }
+        }
+        ranks
+    }
+
+    /// the components, degrees and PageRank of every node
+    #[must_use]
+    pub fn analyse(&self) -> Analytics {
+        let components = self.components();
+        let degrees = self.degrees();
+        let ranks = self.pagerank(DAMPING);
+        let mut nodes: Vec<NodeMetrics> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(n, node)| NodeMetrics {
+                node: node.clone(),
+                component: components[n],
+                in_degree: degrees[n].0,
+                out_degree: degrees[n].1,
+                pagerank: ranks[n],
+            })
+            .collect();
+        nodes.sort_by(|a, b| {
+            b.pagerank
+                .total_cmp(&a.pagerank)
+                .then_with(|| a.node.cmp(&b.node))
+        });
+        Analytics {
+            nodes,
+            edges: self.edges.len(),
+        }
+    }
+}
+
+/// What the analytics found of one node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMetrics {
+    pub node: String,
+    pub component: usize,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    pub pagerank: f64,
+}
+
+/// What the analytics found of a graph.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Analytics {
+    /// by decreasing PageRank, ties by name
+    pub nodes: Vec<NodeMetrics>,
+    pub edges: usize,
+}
+
+impl Analytics {
+    /// the nodes of each component, the largest first
+    #[must_use]
+    pub fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes = Vec::new();
+        for node in &self.nodes {
+            if sizes.len() < node.component {
+                sizes.resize(node.component, 0);
+            }
+            sizes[node.component - 1] += 1;
+        }
+        sizes
+    }
+
+    /// how many nodes have each in-degree and out-degree, by degree
+    #[must_use]
+    pub fn degree_distribution(&self) -> BTreeMap<usize, (usize, usize)> {
+        let mut distribution = BTreeMap::new();
+        for node in &self.nodes {
+            distribution.entry(node.in_degree).or_insert((0, 0)).0 += 1;
+            distribution.entry(node.out_degree).or_insert((0, 0)).1 += 1;
+        }
+        distribution
+    }
+
+    /// the analytics as text tables, the `top` nodes by PageRank last,
+    /// names compacted by `prefixes`
+    #[must_use]
+    pub fn report(&self, top: usize, prefixes: &PrefixMap) -> String {
+        let sizes = self.component_sizes();
+        let mut text = format!(
+            "nodes {}, edges {}, components {}\n\n",
+            self.nodes.len(),
+            self.edges,
+            sizes.len()
+        );
+
+        let rows: Vec<Vec<String>> = sizes
+            .iter()
+            .enumerate()
+            .map(|(n, size)| vec![(n + 1).to_string(), size.to_string()])
+            .collect();
+        text.push_str(&table(&["component", "nodes"], &rows, &[true, true]));
+
+        let rows: Vec<Vec<String>> = self
+            .degree_distribution()
+            .iter()
+            .map(|(degree, (incoming, outgoing))| {
+                vec![
+                    degree.to_string(),
+                    incoming.to_string(),
+                    outgoing.to_string(),
+                ]
+            })
+            .collect();
+        text.push('\n');
+        text.push_str(&table(&["degree", "in", "out"], &rows, &[true, true, true]));
+
+        let rows: Vec<Vec<String>> = self
+            .nodes
+            .iter()
+            .take(top)
+            .map(|node| {
+                vec![
+                    display(&node.node, prefixes),
+                    node.component.to_string(),
+                    node.in_degree.to_string(),
+                    node.out_degree.to_string(),
+                    format!("{:.6}", node.pagerank),
+                ]
+            })
+            .collect();
+        if !rows.is_empty() {
+            text.push('\n');
+            text.push_str(&table(
+                &["node", "component", "in", "out", "pagerank"],
+                &rows,
+                &[false, true, true, true, true],
+            ));
+        }
+        text
+    }
+
+    /// a header and a row per node of its name, component, degrees and
+    /// PageRank
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("node,component,in_degree,out_degree,pagerank\n");
+        for node in &self.nodes {
+            csv.push_str(&format!(
+                "{},{},{},{},{:.6}\n",
+                crate::csv::sanitize_csv_field(&node.node),
+                node.component,
+                node.in_degree,
+                node.out_degree,
+                node.pagerank
+            ));
+        }
+        csv
+    }
+
+    /// each node with its component, degrees and PageRank as literals of
+    /// the `ANALYTICS` predicates
+    #[must_use]
+    pub fn to_subjects(&self) -> Vec<Subject> {
+        let predicate = |local: &str| RdfName::new(format!("{ANALYTICS}{local}"));
+        self.nodes
+            .iter()
+            .map(|node| {
+                let mut subject = Subject::new(RdfName::new(node.node.clone()));
+                subject.add(predicate("component"), node.component.to_string());
+                subject.add(predicate("inDegree"), node.in_degree.to_string());
+                subject.add(predicate("outDegree"), node.out_degree.to_string());
+                subject.add(predicate("pagerank"), format!("{:.6}", node.pagerank));
+                subject
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_hubs_and_splits_components() {
+        // a, b and c all point at hub, which points at d; e <-> f apart
+        let mut graph = Graph::new();
+        for from in ["a", "b", "c"] {
+            graph.add_edge(from, "hub");
+        }
+        graph.add_edge("hub", "d");
+        graph.add_edge("e", "f");
+        graph.add_edge("f", "e");
+        graph.add_node("g");
+
+        let analytics = graph.analyse();
+        assert_eq!(analytics.component_sizes(), [5, 2, 1]);
+        let total: f64 = analytics.nodes.iter().map(|n| n.pagerank).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        let node = |name: &str| analytics.nodes.iter().find(|n| n.node == name).unwrap();
+        assert!(node("hub").pagerank > node("a").pagerank);
+        assert!(node("d").pagerank > node("hub").pagerank);
+        assert_eq!(node("a").pagerank, node("g").pagerank);
+
+        let hub = node("hub");
+        assert_eq!((hub.component, hub.in_degree, hub.out_degree), (1, 3, 1));
+        assert_eq!(node("e").component, 2);
+        // a, b, c and g have no edges in, d and g none out
+        assert_eq!(analytics.degree_distribution()[&0], (4, 2));
+    }
+}
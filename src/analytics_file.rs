@@ -0,0 +1,89 @@
+/// functions in support of graph analytics of the db
+///
+/// The analytics are written as text or as CSV with a row per node, and
+/// can be stored as triples in `ANALYTICS_GRAPH` to be queried with the
+/// rest of the data.
+///
+use crate::analytics::{Analytics, Graph};
+use crate::data::RdfName;
+use crate::db_api::{DbApi, ANALYTICS_GRAPH};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// analyse the graph of the triples of `predicates`, IRIs or prefixed
+/// names, or of every triple when empty
+///
+/// # Errors
+///
+/// return `Err` if a predicate can not be expanded or the db can not be
+/// read
+pub async fn analyse(
+    predicates: &[String],
+    db_api: &DbApi,
+) -> Result<Analytics, Box<dyn std::error::Error>> {
+    let prefixes = db_api.preferred_prefixes().await?;
+    let predicates = predicates
+        .iter()
+        .map(|p| prefixes.resolve(p))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut graph = Graph::new();
+    for (subject, object) in db_api.edges(&predicates).await? {
+        match object {
+            Some(object) => graph.add_edge(&subject, &object),
+            None => {
+                graph.add_node(&subject);
+            }
+        }
+    }
+    Ok(graph.analyse())
+}
+
+/// write the analytics as a text report to `out`, the `top` nodes by
+/// PageRank listed
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read or `out` written
+pub async fn write_report<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    analytics: &Analytics,
+    top: usize,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prefixes = db_api.preferred_prefixes().await?;
+    out.write_all(analytics.report(top, &prefixes).as_bytes())
+        .await?;
+    Ok(())
+}
+
+/// write the analytics as CSV, a row per node, to `out`
+///
+/// # Errors
+///
+/// return `Err` if `out` can not be written
+pub async fn write_csv<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    analytics: &Analytics,
+) -> Result<(), Box<dyn std::error::Error>> {
+    out.write_all(analytics.to_csv().as_bytes()).await?;
+    Ok(())
+}
+
+/// store the analytics as triples in `ANALYTICS_GRAPH`, replacing those of
+/// any earlier run, returning how many nodes were stored
+///
+/// # Errors
+///
+/// return `Err` on db read or write errors
+pub async fn store(
+    analytics: &Analytics,
+    db_api: &DbApi,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    db_api.clear_conclusions(ANALYTICS_GRAPH).await?;
+    let graph = RdfName::new(ANALYTICS_GRAPH.to_string());
+    let subjects = analytics.to_subjects();
+    for subject in &subjects {
+        db_api.insert_into(subject, Some(&graph)).await?;
+    }
+    Ok(subjects.len())
+}
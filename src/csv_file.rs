@@ -1,35 +1,69 @@
-use crate::csv::get_display_name;
+use crate::csv::{get_display_name, sanitize_csv_field};
 use crate::data::RdfName;
 use crate::db_api::DbApi;
-use std::collections::HashMap;
+use futures::TryStreamExt;
+use std::collections::{BTreeSet, HashMap};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tracing::error;
 
-/// write csv format to stdout all db entries
+/// a predicate, how many columns its objects need and its header
+type Column = (RdfName, usize, String);
+
+/// the csv columns, in `columns` order when given, by predicate name
+/// otherwise
+async fn column_counts(
+    columns: Option<&[String]>,
+    export_ns_name: bool,
+    rdf_type: Option<&str>,
+    db_api: &DbApi,
+) -> Result<Vec<Column>, Box<dyn std::error::Error>> {
+    let mut cardinality = Vec::new();
+    for (predicate, count) in db_api.predicate_cardinality(rdf_type).await? {
+        let header = get_display_name(&predicate, export_ns_name)?;
+        cardinality.push((predicate, count, header));
+    }
+    let Some(columns) = columns else {
+        return Ok(cardinality);
+    };
+
+    // columns name predicates in full or by their display name, and columns
+    // no subject has a value for are kept empty
+    Ok(columns
+        .iter()
+        .map(|column| {
+            cardinality
+                .iter()
+                .find(|(predicate, _, header)| predicate.to_string() == *column || header == column)
+                .cloned()
+                .unwrap_or_else(|| (RdfName::new(column.clone()), 1, column.clone()))
+        })
+        .collect())
+}
+
+/// write csv format of all db entries to `out`, stdout for the cli, one row
+/// per subject and one column per predicate object, `pred1`, `pred2`, ...
+/// for predicates with many objects
+///
+/// `columns` picks and orders the predicate columns, all predicates by name
+/// when absent, and `rdf_type` restricts rows to subjects of that type.
+/// Column counts come from one aggregate query and rows from a single
+/// cursor ordered by subject.
 ///
 /// # Errors
 ///
-/// return `Err` on db read errors
-pub async fn export_csv(
+/// return `Err` on db read or write errors
+pub async fn export_csv<W: AsyncWrite + Unpin>(
+    out: &mut W,
     export_ns_name: bool,
     subject_column_name: Option<String>,
+    columns: Option<&[String]>,
+    rdf_type: Option<&str>,
     db_api: &DbApi,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let subject_names: Vec<RdfName> = db_api.get_subject_names().await?;
-    let mut headers_map: HashMap<RdfName, usize> = HashMap::new();
-
-    // First pass: determine headers
-    for name in &subject_names {
-        if let Some(subject) = db_api.query(name).await? {
-            for (predicate, objects) in subject.predicate_object_pairs() {
-                let count = headers_map.entry(predicate.clone()).or_insert(0);
-                *count = (*count).max(objects.len());
-            }
-        }
-    }
+    let counts = column_counts(columns, export_ns_name, rdf_type, db_api).await?;
 
     let mut headers = vec![subject_column_name.unwrap_or_else(|| "subject".to_string())];
-    for (predicate, max_count) in &headers_map {
-        let predicate_display_name = get_display_name(predicate, export_ns_name)?;
+    for (_, max_count, predicate_display_name) in &counts {
         for i in 0..*max_count {
             if *max_count > 1 {
                 headers.push(format!("{}{}", predicate_display_name, i + 1)); // for example: pred1, pred2, ...
@@ -38,42 +72,64 @@ pub async fn export_csv(
             }
         }
     }
+    let headers: Vec<String> = headers.iter().map(|h| sanitize_csv_field(h)).collect();
+    out.write_all(format!("{}\n", headers.join(",")).as_bytes())
+        .await?;
 
-    // Write out header to stdout
-    println!("{}", headers.join(","));
-
-    // Second pass: write out rows for each subject
-    for name in &subject_names {
-        if let Some(subject) = db_api.query(name).await? {
-            let subject_display_name = get_display_name(subject.name(), export_ns_name)?;
-            let mut line = vec![subject_display_name];
-            for (predicate, max_count) in &headers_map {
-                //let predicate_display_name = get_display_name(predicate, export_ns_name)?;
-                if let Some(objects) = subject.get(predicate) {
-                    let mut objects_vec: Vec<_> = objects.iter().collect();
-                    objects_vec.sort(); // sort to keep order consistent
-                    for i in 0..*max_count {
-                        if i < objects_vec.len() {
-                            line.push(objects_vec[i].clone());
-                        } else {
-                            line.push(String::new()); // fill with empty strings for missing values
-                        }
-                    }
-                } else {
-                    for _ in 0..*max_count {
-                        line.push(String::new()); // fill with empty strings for missing predicates
-                    }
-                }
+    let mut current: Option<(String, HashMap<String, BTreeSet<String>>)> = None;
+    let mut triples = db_api.stream_triples(rdf_type);
+    loop {
+        let next = triples.try_next().await?;
+        let finished = match (&current, &next) {
+            (Some((subject, _)), Some((next_subject, ..))) => subject != next_subject,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if finished {
+            if let Some((subject, objects)) = current.take() {
+                let line = row(&subject, &objects, &counts, export_ns_name)?;
+                out.write_all(format!("{line}\n").as_bytes()).await?;
             }
-
-            // Write out row to stdout
-            println!("{}", line.join(","));
         }
+        let Some((subject, predicate, object, _)) = next else {
+            break;
+        };
+        current
+            .get_or_insert_with(|| (subject, HashMap::new()))
+            .1
+            .entry(predicate)
+            .or_default()
+            .insert(object);
     }
 
+    out.flush().await?;
     Ok(())
 }
 
+/// one csv line, objects sorted to keep order consistent and missing ones
+/// left empty
+fn row(
+    subject: &str,
+    objects: &HashMap<String, BTreeSet<String>>,
+    counts: &[Column],
+    export_ns_name: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let subject_display_name =
+        get_display_name(&RdfName::new(subject.to_string()), export_ns_name)?;
+    let mut line = vec![sanitize_csv_field(&subject_display_name)];
+    for (predicate, max_count, _) in counts {
+        let mut values = objects
+            .get(&predicate.to_string())
+            .into_iter()
+            .flatten()
+            .map(|object| sanitize_csv_field(object));
+        for _ in 0..*max_count {
+            line.push(values.next().unwrap_or_default());
+        }
+    }
+    Ok(line.join(","))
+}
+
 /// read csv from stdin and load db
 ///
 /// # Errors
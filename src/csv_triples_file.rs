@@ -7,25 +7,32 @@ use crate::csv;
 use crate::data::RdfName;
 use crate::data::Subject;
 use crate::db_api::DbApi;
-use tokio::io::{stdin, AsyncBufReadExt, BufReader};
-
-fn print_csv(subject: &str, predicate: &str, object: &str) {
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+async fn write_csv<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    subject: &str,
+    predicate: &str,
+    object: &str,
+) -> std::io::Result<()> {
     let sanitized_object = csv::sanitize_csv_field(object);
-    println!("{subject},{predicate},{sanitized_object}");
+    out.write_all(format!("{subject},{predicate},{sanitized_object}\n").as_bytes())
+        .await
 }
 
-/// write csv format to stdout all db entries
+/// write csv format of all db entries to `out`, stdout for the cli
 ///
 /// # Errors
 ///
-/// return `Err` on db read errors
-pub async fn export_csv(
+/// return `Err` on db read or write errors
+pub async fn export_csv<W: AsyncWrite + Unpin>(
+    out: &mut W,
     export_ns_name: bool,
     export_headers: bool,
     db_api: &DbApi,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if export_headers {
-        print_csv("Subject", "Predicate", "Object");
+        write_csv(out, "Subject", "Predicate", "Object").await?;
     };
 
     let subject_names = db_api.get_subject_names().await?;
@@ -40,12 +47,13 @@ pub async fn export_csv(
 
                 for object in objects {
                     // Print the CSV line for each object associated with the subject-predicate pair
-                    print_csv(&rdf_sub_name, &rdf_predicate_name, object);
+                    write_csv(out, &rdf_sub_name, &rdf_predicate_name, object).await?;
                 }
             }
         }
     }
 
+    out.flush().await?;
     Ok(())
 }
 fn parse_csv_line(line: &str) -> Result<(String, String, String), &'static str> {
@@ -74,26 +82,24 @@ fn parse_csv_line(line: &str) -> Result<(String, String, String), &'static str>
     }
 }
 
-/// read csv from stdin and load db
+/// read csv from `reader`, stdin for the cli, and load db
 ///
 /// # Errors
 ///
 /// return `Err` if any entry can not be loaded
-pub async fn import_csv(
+pub async fn import_csv<R: AsyncBufRead + Unpin>(
+    mut reader: R,
     default_subject_ns: Option<String>,
     default_predicate_ns: Option<String>,
     skip_headers: bool,
     db_api: &DbApi,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let stdin = stdin();
-    let mut reader = BufReader::new(stdin);
-
     let mut discard = String::new();
     if skip_headers {
         reader.read_line(&mut discard).await?;
     };
 
-    let tx = db_api.begin_txn().await?;
+    let mut tx = db_api.begin_txn().await?;
 
     let mut line = String::new();
     while reader.read_line(&mut line).await? != 0 {
@@ -112,7 +118,9 @@ pub async fn import_csv(
         let mut subject_entry = Subject::new(RdfName::new(rdf_sub_name.to_string()));
         subject_entry.add(RdfName::new(rdf_predicate_name), object);
 
-        db_api.insert(&subject_entry).await?;
+        db_api
+            .insert_into_txn(&mut tx, &subject_entry, None)
+            .await?;
         line.clear();
     }
     tx.commit().await?;
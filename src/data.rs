@@ -1,4 +1,6 @@
-use std::collections::{HashMap, HashSet};
+use crate::iri;
+use crate::vocab::XSD;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,6 +11,11 @@ pub enum TriplesError {
     NoSubjectDeclaired,
     PreviousSubjectNotComplete,
     NotImplemented { trace: String },
+    UnknownFormat { path: String },
+    NoMatchingFiles { pattern: String },
+    Inconsistent { violations: Vec<Violation> },
+    ShapeViolations { count: usize },
+    NoPath { from: String, to: String },
     // Add more error variants here as needed.
 }
 impl std::error::Error for TriplesError {}
@@ -23,10 +30,53 @@ impl fmt::Display for TriplesError {
             Self::NoSubjectDeclaired => write!(f, "can not load predicate without a subject"),
             Self::PreviousSubjectNotComplete => write!(f, "previous subject stanza not terminated"),
             Self::NotImplemented { trace } => write!(f, "{trace} not implemented"),
+            Self::UnknownFormat { path } => write!(f, "can not tell the format of {path}"),
+            Self::NoMatchingFiles { pattern } => write!(f, "no files match {pattern}"),
+            Self::ShapeViolations { count } => write!(f, "{count} SHACL violations"),
+            Self::NoPath { from, to } => write!(f, "no path from {from} to {to}"),
+            Self::Inconsistent { violations } => {
+                write!(f, "{} constraint violations", violations.len())?;
+                for violation in violations {
+                    write!(f, "\n{violation}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
+/// the error a reader reports for malformed input
+pub(crate) fn parse_error(reason: impl fmt::Display) -> TriplesError {
+    TriplesError::ParseError {
+        reason: reason.to_string(),
+    }
+}
+
+/// numbers compare as numbers, anything else as text
+pub(crate) fn compare(left: &str, right: &str) -> std::cmp::Ordering {
+    match (left.parse::<f64>(), right.parse::<f64>()) {
+        (Ok(l), Ok(r)) => l.total_cmp(&r),
+        _ => left.cmp(right),
+    }
+}
+
+/// A match of a constraint, the values of its variables ordered by name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Violation {
+    pub rule: String,
+    pub bindings: BTreeMap<String, String>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.rule)?;
+        for (name, value) in &self.bindings {
+            write!(f, " ?{name}={value}")?;
+        }
+        Ok(())
+    }
+}
+
 /// Represents an RDF name (a simple IRI validation is performed).
 #[derive(Debug, Ord, PartialOrd, Clone, PartialEq, Eq, Hash)]
 pub struct RdfName(String);
@@ -132,19 +182,223 @@ impl Subject {
     }
 }
 
+/// Blank node labels for the nodes of one imported document.
+///
+/// Labels carry an id drawn for the document as well as a counter, so the
+/// blank nodes of documents loaded into one store stay apart, and labels
+/// the document itself uses are renamed the same way.
+#[derive(Debug)]
+pub struct BlankNodes {
+    document: String,
+    labels: HashMap<String, String>,
+    next: usize,
+}
+
+impl Default for BlankNodes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlankNodes {
+    #[must_use]
+    pub fn new() -> Self {
+        let mut document = uuid::Uuid::new_v4().simple().to_string();
+        document.truncate(12);
+        Self {
+            document,
+            labels: HashMap::new(),
+            next: 0,
+        }
+    }
+
+    /// a new blank node
+    pub fn fresh(&mut self) -> String {
+        let label = format!("_:b{}x{}", self.next, self.document);
+        self.next += 1;
+        label
+    }
+
+    /// the blank node a document's own `label` names
+    pub fn label(&mut self, label: &str) -> String {
+        if let Some(renamed) = self.labels.get(label) {
+            return renamed.clone();
+        }
+        let renamed = self.fresh();
+        self.labels.insert(label.to_string(), renamed.clone());
+        renamed
+    }
+}
+
+/// An RDF term as it appears in an object position or in query results.
+///
+/// The store keeps objects as plain text, `Term::from_object` recovers the
+/// kind of term from that text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Term {
+    Iri(String),
+    BlankNode(String),
+    Literal {
+        value: String,
+        datatype: Option<String>,
+        language: Option<String>,
+    },
+}
+
+impl Term {
+    /// a literal without datatype or language tag
+    #[must_use]
+    pub fn literal(value: impl Into<String>) -> Self {
+        Self::Literal {
+            value: value.into(),
+            datatype: None,
+            language: None,
+        }
+    }
+
+    /// classify text from the objects table
+    #[must_use]
+    pub fn from_object(object: &str) -> Self {
+        if let Some(label) = object.strip_prefix("_:") {
+            Self::BlankNode(label.to_string())
+        } else if is_iri(object) {
+            Self::Iri(object.to_string())
+        } else {
+            Self::literal(object)
+        }
+    }
+
+    /// text for the objects table, `None` for a literal it can not hold:
+    /// one with a language tag, or a datatype other than the one its
+    /// lexical form reads back as
+    #[must_use]
+    pub fn to_object(&self) -> Option<String> {
+        match self {
+            Self::Iri(iri) => Some(iri.clone()),
+            Self::BlankNode(label) => Some(format!("_:{label}")),
+            Self::Literal {
+                language: Some(_), ..
+            } => None,
+            Self::Literal {
+                value,
+                datatype: Some(datatype),
+                ..
+            } => (datatype.strip_prefix(XSD) == Some(lexical_datatype(value).unwrap_or("string")))
+                .then(|| value.clone()),
+            Self::Literal { value, .. } => Some(value.clone()),
+        }
+    }
+
+    /// the IRI, blank node label or lexical form
+    #[must_use]
+    pub fn value(&self) -> &str {
+        match self {
+            Self::Iri(value) | Self::BlankNode(value) | Self::Literal { value, .. } => value,
+        }
+    }
+}
+
+/// N-Triples syntax
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Iri(iri) => write!(f, "<{iri}>"),
+            Self::BlankNode(label) => write!(f, "_:{label}"),
+            Self::Literal {
+                value,
+                datatype,
+                language,
+            } => {
+                write!(f, "\"")?;
+                for c in value.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\r' => write!(f, "\\r")?,
+                        '\t' => write!(f, "\\t")?,
+                        _ => write!(f, "{c}")?,
+                    }
+                }
+                write!(f, "\"")?;
+                if let Some(language) = language {
+                    write!(f, "@{language}")
+                } else if let Some(datatype) = datatype {
+                    write!(f, "^^<{datatype}>")
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// the XSD datatype, by local name, that a literal's lexical form has in
+/// Turtle shorthand, `None` for plain strings
+///
+/// Only the forms of the Turtle grammar count, so `INF`, `NaN` or `1.`
+/// are plain strings even though Rust would parse them as numbers.
+#[must_use]
+pub fn lexical_datatype(value: &str) -> Option<&'static str> {
+    if value == "true" || value == "false" {
+        return Some("boolean");
+    }
+    let digits = |text: &str| text.bytes().all(|b| b.is_ascii_digit());
+    let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (
+            mantissa,
+            Some(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)),
+        ),
+        None => (unsigned, None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+    if !digits(whole) || !fraction.is_none_or(digits) {
+        return None;
+    }
+
+    match (fraction, exponent) {
+        (None, None) if !whole.is_empty() => Some("integer"),
+        (Some(fraction), None) if !fraction.is_empty() => Some("decimal"),
+        (fraction, Some(exponent))
+            if !exponent.is_empty()
+                && digits(exponent)
+                && !(whole.is_empty() && fraction.is_none_or(str::is_empty)) =>
+        {
+            Some("double")
+        }
+        _ => None,
+    }
+}
+
+/// true if the text looks like an absolute IRI rather than a literal value
+#[must_use]
+pub fn is_iri(text: &str) -> bool {
+    let Some((scheme, rest)) = text.split_once(':') else {
+        return false;
+    };
+    let mut scheme_chars = scheme.chars();
+    scheme_chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme_chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'))
+        && (rest.starts_with('/') || scheme.eq_ignore_ascii_case("urn"))
+        && !text.contains(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'))
+}
+
+/// split an IRI for display as a prefixed name, an IRI without a valid
+/// local name has an empty namespace and is its own local name
+///
 /// # Errors
 ///
-/// Will return `Err` if function cannot separate ns from base name
+/// Will return `Err` if `name_string` is not an IRI
 pub fn extract_namespace_and_local_name(name_string: &str) -> Result<(&str, &str), TriplesError> {
-    if let Some(idx) = name_string.rfind('#') {
-        let (ns, name) = name_string.split_at(idx + 1);
-        if name.contains('/') {
-            return Ok(("", name_string));
-        }
-        return Ok((ns, name));
-    } else if let Some(idx) = name_string.rfind('/') {
-        let (ns, name) = name_string.split_at(idx + 1);
-        return Ok((ns, &name[1..]));
+    if let Some(parts) = iri::split(name_string) {
+        return Ok(parts);
+    }
+    if is_iri(name_string) {
+        return Ok(("", name_string));
     }
 
     Err(TriplesError::InvalidIRI {
@@ -187,6 +441,55 @@ mod tests {
         assert_eq!(subject.get(&predicate_iri), None);
     }
 
+    #[test]
+    fn term_from_object() {
+        assert_eq!(
+            Term::from_object("https://www.example.com/x"),
+            Term::Iri("https://www.example.com/x".to_string())
+        );
+        assert_eq!(
+            Term::from_object("urn:uuid:1234"),
+            Term::Iri("urn:uuid:1234".to_string())
+        );
+        assert_eq!(Term::from_object("_:b0"), Term::BlankNode("b0".to_string()));
+        assert_eq!(
+            Term::from_object("time: 12:00"),
+            Term::literal("time: 12:00")
+        );
+        assert_eq!(
+            Term::literal("say \"hi\"\n").to_string(),
+            r#""say \"hi\"\n""#
+        );
+    }
+
+    #[test]
+    fn lexical_datatypes() {
+        for (value, datatype) in [
+            ("true", Some("boolean")),
+            ("-42", Some("integer")),
+            ("+12345678901234567890", Some("integer")),
+            ("3.14", Some("decimal")),
+            ("-.5", Some("decimal")),
+            ("1e10", Some("double")),
+            ("1.E-3", Some("double")),
+            (".5e+2", Some("double")),
+            ("1.", None),
+            (".", None),
+            ("e5", None),
+            (".e5", None),
+            ("1e", None),
+            ("INF", None),
+            ("-inf", None),
+            ("NaN", None),
+            ("infinity", None),
+            ("0x1F", None),
+            ("1_000", None),
+            ("", None),
+        ] {
+            assert_eq!(lexical_datatype(value), datatype, "{value}");
+        }
+    }
+
     #[test]
     fn subject_non_existent_predicate() {
         let subject_iri = RdfName::new("https://www.example.com/subject".to_string());
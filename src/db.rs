@@ -1,10 +1,57 @@
 // #[cfg(feature = "postgres")]
 // use anyhow::Error;
+use crate::inference::{retract_unsupported, INFERRED_GRAPH, RDFS_DELETE_TRIGGER};
+use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::Pool;
 use sqlx::Sqlite;
 use std::fs::File;
+use std::str::FromStr;
 use tracing::debug;
 
+/// the characters `char::is_whitespace` accepts, as the body of a GLOB
+/// character class
+const WHITESPACE: &str =
+    "\t\n\u{b}\u{c}\r \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}\u{2029}\u{202f}\u{205f}\u{3000}";
+
+/// SQL true when `column` holds a node of the graph rather than a
+/// literal, a blank node or an absolute IRI by exactly the rule of
+/// `data::is_iri`: a scheme before the first `:`, then `/` unless the
+/// scheme is `urn`, and no whitespace, `<`, `>` or `"`
+#[must_use]
+pub fn node_object(column: &str) -> String {
+    let colon = format!("instr({column}, ':')");
+    let scheme = format!("substr({column}, 1, {colon} - 1)");
+    format!(
+        "({column} GLOB '_:*' \
+        OR ({colon} > 1 \
+            AND {scheme} GLOB '[A-Za-z]*' \
+            AND {scheme} NOT GLOB '*[^A-Za-z0-9+.-]*' \
+            AND (substr({column}, {colon} + 1, 1) = '/' OR lower({scheme}) = 'urn') \
+            AND {column} NOT GLOB '*[{WHITESPACE}<>\"]*'))"
+    )
+}
+
+/// SQL true when the graph id in `column` is not excluded from reads: the
+/// GLOB over graph names bound to `param` is NULL, the triple is in the
+/// default graph, or its graph's name does not match the GLOB.  A macro
+/// rather than a function so queries stay `&'static str` literals.
+macro_rules! visible_graph {
+    ($column:literal, $param:literal) => {
+        concat!(
+            "(",
+            $param,
+            " IS NULL OR ",
+            $column,
+            " IS NULL OR ",
+            $column,
+            " NOT IN (SELECT id FROM names WHERE name GLOB ",
+            $param,
+            "))"
+        )
+    };
+}
+pub(crate) use visible_graph;
+
 /// # Errors
 ///
 /// Will return `Err` if function cannot create db table
@@ -79,6 +126,270 @@ async fn create_triples_table(pool: &Pool<Sqlite>) -> Result<(), Box<dyn std::er
     Ok(())
 }
 
+/// Triples of a named graph reference the graph IRI in `names`, the
+/// default graph is `NULL`.  Stores created before graphs existed gain the
+/// column here and keep all their triples in the default graph.
+///
+/// # Errors
+///
+/// Will return `Err` if function cannot alter db table
+#[cfg(all(feature = "sqlite", not(feature = "disable-sqlite")))]
+async fn add_triples_graph_column(pool: &Pool<Sqlite>) -> Result<(), Box<dyn std::error::Error>> {
+    let exists: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM pragma_table_info('triples') WHERE name = 'graph'")
+            .fetch_one(pool)
+            .await?;
+
+    if exists.0 == 0 {
+        sqlx::query("ALTER TABLE triples ADD COLUMN graph INTEGER REFERENCES names(id)")
+            .execute(pool)
+            .await?;
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_graph ON triples (graph)")
+        .execute(pool)
+        .await?;
+
+    debug!("db triples graph column initialized");
+    Ok(())
+}
+
+/// Composite indexes for lookups by any two of predicate, subject and
+/// object: predicate first for a predicate with a subject or object, and
+/// object first for an object with a subject.
+///
+/// # Errors
+///
+/// Will return `Err` if function cannot create db index
+#[cfg(all(feature = "sqlite", not(feature = "disable-sqlite")))]
+async fn create_composite_indexes(pool: &Pool<Sqlite>) -> Result<(), Box<dyn std::error::Error>> {
+    sqlx::query(
+        r"
+        CREATE INDEX IF NOT EXISTS idx_pso ON triples (predicate, subject, object);
+        CREATE INDEX IF NOT EXISTS idx_pos ON triples (predicate, object, subject);
+        CREATE INDEX IF NOT EXISTS idx_osp ON triples (object, subject, predicate);
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    debug!("db triples composite indexes initialized");
+    Ok(())
+}
+
+/// Prefixes declared by imported files, reused on export.  A later
+/// declaration of the same prefix or namespace replaces the earlier one.
+///
+/// # Errors
+///
+/// Will return `Err` if function cannot create db table
+#[cfg(all(feature = "sqlite", not(feature = "disable-sqlite")))]
+async fn create_namespaces_table(pool: &Pool<Sqlite>) -> Result<(), Box<dyn std::error::Error>> {
+    sqlx::query(
+        r"
+        CREATE TABLE IF NOT EXISTS namespaces (
+            prefix TEXT PRIMARY KEY,
+            namespace TEXT UNIQUE NOT NULL
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    debug!("db namespaces table initialized");
+    Ok(())
+}
+
+/// Per predicate cardinalities for the query planner.  Triggers keep the
+/// counts current on every insert and delete, and stores created before
+/// the table existed are counted once when it is first created.
+///
+/// # Errors
+///
+/// Will return `Err` if function cannot create db table
+#[cfg(all(feature = "sqlite", not(feature = "disable-sqlite")))]
+async fn create_predicate_stats_table(
+    pool: &Pool<Sqlite>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let exists: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'predicate_stats'",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query(
+        r"
+        CREATE TABLE IF NOT EXISTS predicate_stats (
+            predicate INTEGER PRIMARY KEY,
+            triples INTEGER NOT NULL DEFAULT 0,
+            subjects INTEGER NOT NULL DEFAULT 0,
+            objects INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (predicate) REFERENCES names(id)
+        );
+
+        CREATE TRIGGER IF NOT EXISTS trg_predicate_stats_insert AFTER INSERT ON triples
+        BEGIN
+            INSERT OR IGNORE INTO predicate_stats (predicate) VALUES (NEW.predicate);
+            UPDATE predicate_stats SET
+                triples = triples + 1,
+                subjects = subjects + NOT EXISTS (
+                    SELECT 1 FROM triples
+                    WHERE subject = NEW.subject AND predicate = NEW.predicate AND id <> NEW.id
+                ),
+                objects = objects + NOT EXISTS (
+                    SELECT 1 FROM triples
+                    WHERE object = NEW.object AND predicate = NEW.predicate AND id <> NEW.id
+                )
+            WHERE predicate = NEW.predicate;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_predicate_stats_delete AFTER DELETE ON triples
+        BEGIN
+            UPDATE predicate_stats SET
+                triples = triples - 1,
+                subjects = subjects - NOT EXISTS (
+                    SELECT 1 FROM triples
+                    WHERE subject = OLD.subject AND predicate = OLD.predicate
+                ),
+                objects = objects - NOT EXISTS (
+                    SELECT 1 FROM triples
+                    WHERE object = OLD.object AND predicate = OLD.predicate
+                )
+            WHERE predicate = OLD.predicate;
+        END;
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    if exists.0 == 0 {
+        sqlx::query(
+            r"
+            INSERT INTO predicate_stats (predicate, triples, subjects, objects)
+            SELECT predicate, COUNT(*), COUNT(DISTINCT subject), COUNT(DISTINCT object)
+            FROM triples
+            GROUP BY predicate
+            ",
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    debug!("db predicate_stats table initialized");
+    Ok(())
+}
+
+/// A full-text index of the literal objects, kept current by triggers as
+/// objects are added.  Stores created before the index existed have their
+/// literals indexed once when it is first created.
+///
+/// # Errors
+///
+/// Will return `Err` if function cannot create db table
+#[cfg(all(feature = "sqlite", not(feature = "disable-sqlite")))]
+async fn create_objects_fts_table(pool: &Pool<Sqlite>) -> Result<(), Box<dyn std::error::Error>> {
+    let exists: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'objects_fts'",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query(&format!(
+        r"
+        CREATE VIRTUAL TABLE IF NOT EXISTS objects_fts
+            USING fts5(object, content = 'objects', content_rowid = 'id');
+
+        CREATE TRIGGER IF NOT EXISTS trg_objects_fts_insert AFTER INSERT ON objects
+        WHEN NOT {new}
+        BEGIN
+            INSERT INTO objects_fts (rowid, object) VALUES (NEW.id, NEW.object);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_objects_fts_delete AFTER DELETE ON objects
+        WHEN NOT {old}
+        BEGIN
+            INSERT INTO objects_fts (objects_fts, rowid, object)
+            VALUES ('delete', OLD.id, OLD.object);
+        END;
+        ",
+        new = node_object("NEW.object"),
+        old = node_object("OLD.object")
+    ))
+    .execute(pool)
+    .await?;
+
+    if exists.0 == 0 {
+        sqlx::query(&format!(
+            "INSERT INTO objects_fts (rowid, object) SELECT id, object FROM objects WHERE NOT {}",
+            node_object("object")
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    debug!("db objects_fts table initialized");
+    Ok(())
+}
+
+/// How each conclusion of a user-defined rule was derived, a derivation
+/// naming the rule and the content of the triples it matched.  A trigger
+/// retracts a conclusion once every derivation of it has lost a premise,
+/// and what rests on the conclusion in turn.  RDFS inferences are dropped
+/// and derived again after a delete, their own trigger rechecks the
+/// conclusions resting on them once it is done.
+///
+/// # Errors
+///
+/// Will return `Err` if function cannot create db table
+#[cfg(all(feature = "sqlite", not(feature = "disable-sqlite")))]
+async fn create_derivations_table(pool: &Pool<Sqlite>) -> Result<(), Box<dyn std::error::Error>> {
+    sqlx::query(&format!(
+        r"
+        CREATE TABLE IF NOT EXISTS derivations (
+            id INTEGER PRIMARY KEY,
+            triple INTEGER NOT NULL,
+            rule TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS derivation_premises (
+            derivation INTEGER NOT NULL REFERENCES derivations(id) ON DELETE CASCADE,
+            subject INTEGER NOT NULL,
+            predicate INTEGER NOT NULL,
+            object INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_derivations_triple ON derivations (triple);
+        CREATE INDEX IF NOT EXISTS idx_derivation_premises_derivation
+            ON derivation_premises (derivation);
+        CREATE INDEX IF NOT EXISTS idx_derivation_premises_content
+            ON derivation_premises (subject, predicate, object);
+
+        CREATE TRIGGER IF NOT EXISTS trg_derivations_delete AFTER DELETE ON triples
+        WHEN OLD.graph IS NOT (SELECT id FROM names WHERE name = '{inferred}')
+            OR NOT EXISTS (SELECT 1 FROM sqlite_master
+                WHERE type = 'trigger' AND name = '{rdfs_delete}')
+        BEGIN
+            {retract}
+            DELETE FROM derivation_premises WHERE derivation IN (
+                SELECT id FROM derivations WHERE triple = OLD.id
+            );
+            DELETE FROM derivations WHERE triple = OLD.id;
+        END;
+        ",
+        inferred = INFERRED_GRAPH,
+        rdfs_delete = RDFS_DELETE_TRIGGER,
+        retract = retract_unsupported(
+            "lost.subject = OLD.subject AND lost.predicate = OLD.predicate \
+            AND lost.object = OLD.object"
+        )
+    ))
+    .execute(pool)
+    .await?;
+
+    debug!("db derivations table initialized");
+    Ok(())
+}
+
 /// # Errors
 ///
 /// Will return `Err` if function cannot init db file
@@ -94,7 +405,9 @@ pub async fn init(db_location: String) -> Result<Pool<Sqlite>, Box<dyn std::erro
         File::create(&db_location)?;
     }
 
-    let pool = Pool::connect(&db_url).await?;
+    // inference triggers derive from the triples their own inserts add
+    let options = SqliteConnectOptions::from_str(&db_url)?.pragma("recursive_triggers", "ON");
+    let pool = Pool::connect_with(options).await?;
 
     sqlx::query(
         r"
@@ -113,6 +426,18 @@ pub async fn init(db_location: String) -> Result<Pool<Sqlite>, Box<dyn std::erro
 
     create_triples_table(&pool).await?;
 
+    add_triples_graph_column(&pool).await?;
+
+    create_composite_indexes(&pool).await?;
+
+    create_predicate_stats_table(&pool).await?;
+
+    create_namespaces_table(&pool).await?;
+
+    create_derivations_table(&pool).await?;
+
+    create_objects_fts_table(&pool).await?;
+
     Ok(pool)
 }
 
@@ -139,9 +464,49 @@ pub async fn init(db_location: String) -> Result<Pool<Sqlite>, Box<dyn std::erro
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::is_iri;
     use std::fs;
     use tokio::runtime::Runtime;
 
+    #[test]
+    fn test_node_object_agrees_with_is_iri() {
+        let db_location = "/tmp/test_node_object.db";
+        let _ = fs::remove_file(db_location);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let pool = init(db_location.to_string()).await.unwrap();
+            let sql = format!("SELECT {} FROM (SELECT ? AS value)", node_object("value"));
+            for text in [
+                "http://example.com/a",
+                "urn:isbn:0451450523",
+                "URN:x",
+                "a+b.c-d://host",
+                "_:b0",
+                // literals that merely look like they open with a scheme
+                "note:todo:/later",
+                "a!b:/c",
+                "1abc:/x",
+                ":/x",
+                "mailto:someone@example.com",
+                "http://example.com/a\u{a0}b",
+                "http://example.com/\u{2003}",
+                "http://example.com/\u{b}",
+                "say \"http://x\"",
+                "plain text",
+            ] {
+                let (node,): (bool,) = sqlx::query_as(&sql)
+                    .bind(text)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                assert_eq!(node, is_iri(text) || text.starts_with("_:"), "{text}");
+            }
+        });
+
+        let _ = fs::remove_file(db_location);
+    }
+
     #[test]
     fn test_init() {
         let db_location = "/tmp/test_init.db";
@@ -1,7 +1,25 @@
 use crate::data::RdfName;
 use crate::data::Subject;
+use crate::data::Term;
+use crate::prefixes::PrefixMap;
+use crate::profile::PredicateProfile;
+use crate::rdfs;
+use crate::search::{self, SearchHit};
+use crate::sparql_data::{
+    AskQuery, GraphTarget, QueryForm, QueryResults, SelectClause, Solutions, SparqlQuery,
+    UpdateOperation,
+};
+use crate::sparql_plan::{Explanation, Plan, PredicateStats, Statistics};
+use crate::sparql_sql;
+use crate::stats::{GraphStats, Stats, StorageStats};
+use crate::traversal::{self, Direction, Step};
+use crate::vocab::RDF_TYPE;
+use futures::stream::BoxStream;
+use futures::TryStreamExt;
 use sqlx::Pool;
+use sqlx::Row;
 use sqlx::Sqlite;
+use sqlx::SqliteConnection;
 use sqlx::Transaction;
 use tracing::debug;
 
@@ -9,14 +27,145 @@ use tracing::debug;
 // use sqlx::Postgres;
 
 use crate::db;
+use crate::inference::INFERRED_GRAPH;
 
 #[must_use]
 pub fn str_to_string(s: &str) -> String {
     s.to_string()
 }
 
+/// The named graph holding the conclusions of user-defined rules.
+pub const RULES_GRAPH: &str = "urn:triples:inferred:rules";
+
+/// The named graph holding the conclusions of OWL 2 RL reasoning.
+pub const OWL_GRAPH: &str = "urn:triples:inferred:owl";
+
+/// The named graph holding the results of graph analytics.
+pub const ANALYTICS_GRAPH: &str = "urn:triples:inferred:analytics";
+
+/// A subject, predicate, object and graph, `None` for the default graph.
+pub type StoredTriple = (String, String, String, Option<String>);
+
+/// the statement matching triples by the given conditions on their
+/// subject `?1`, predicate `?2` and object `?3`, a statement per pattern
+/// so only bound terms are tested and the composite indexes serve them
+macro_rules! find_sql {
+    ($($condition:literal),*) => {
+        concat!(
+            r"
+        SELECT subjects.name, predicates.name, objects.object, graphs.name
+        FROM triples
+        JOIN names AS subjects ON triples.subject = subjects.id
+        JOIN names AS predicates ON triples.predicate = predicates.id
+        JOIN objects AS objects ON triples.object = objects.id
+        LEFT JOIN names AS graphs ON triples.graph = graphs.id
+        WHERE ", db::visible_graph!("triples.graph", "?4"),
+            $("\n        AND ", $condition,)*
+        )
+    };
+}
+
+/// the joins stepping from the nodes of `source` along `direction`, each
+/// whether it steps forward and its `FROM ... WHERE ...` clause, selecting
+/// `subjects.name`, `predicates.name` and `objects.object`.  `source`
+/// provides `node`, the node stepped from, and the predicates are bound
+/// from `?{first_predicate}` on
+fn traversal_joins(
+    direction: Direction,
+    source: &str,
+    node: &str,
+    predicates: usize,
+    first_predicate: usize,
+) -> Vec<(bool, String)> {
+    let mut filter = String::from(db::visible_graph!("triples.graph", "?1"));
+    if predicates > 0 {
+        let placeholders: Vec<String> = (first_predicate..first_predicate + predicates)
+            .map(|n| format!("?{n}"))
+            .collect();
+        filter.push_str(&format!(
+            " AND predicates.name IN ({})",
+            placeholders.join(", ")
+        ));
+    }
+    let mut joins = Vec::new();
+    if direction.outgoing() {
+        joins.push((
+            true,
+            format!(
+                "{source} JOIN names AS subjects ON subjects.name = {node} \
+                JOIN triples ON triples.subject = subjects.id \
+                JOIN names AS predicates ON triples.predicate = predicates.id \
+                JOIN objects ON triples.object = objects.id \
+                WHERE {} AND {filter}",
+                db::node_object("objects.object")
+            ),
+        ));
+    }
+    if direction.incoming() {
+        joins.push((
+            false,
+            format!(
+                "{source} JOIN objects ON objects.object = {node} \
+                JOIN triples ON triples.object = objects.id \
+                JOIN names AS subjects ON triples.subject = subjects.id \
+                JOIN names AS predicates ON triples.predicate = predicates.id \
+                WHERE {filter}"
+            ),
+        ));
+    }
+    joins
+}
+
+/// the recursive `steps(s, p, o, forward, node, hop)` of the triples within
+/// `?3` hops of `?2`, taken a hop at a time while `condition` holds of the
+/// step before
+fn steps_cte(direction: Direction, predicates: usize, condition: &str) -> String {
+    let step = |(forward, join): (bool, String), hop: &str, condition: &str| {
+        format!(
+            "SELECT subjects.name, predicates.name, objects.object, {forward}, {}, {hop} \
+            {join}{condition}",
+            reached(forward)
+        )
+    };
+    let mut steps: Vec<String> = traversal_joins(
+        direction,
+        "FROM (SELECT ?2 AS node) AS start",
+        "start.node",
+        predicates,
+        4,
+    )
+    .into_iter()
+    .map(|join| step(join, "1", ""))
+    .collect();
+    steps.extend(
+        traversal_joins(direction, "FROM steps", "steps.node", predicates, 4)
+            .into_iter()
+            .map(|join| {
+                step(
+                    join,
+                    "steps.hop + 1",
+                    &format!(" AND steps.hop < ?3{condition}"),
+                )
+            }),
+    );
+    format!(
+        "WITH RECURSIVE steps(s, p, o, forward, node, hop) AS ({})",
+        steps.join(" UNION ")
+    )
+}
+
+/// the node a join of `traversal_joins` reaches
+const fn reached(forward: bool) -> &'static str {
+    if forward {
+        "objects.object"
+    } else {
+        "subjects.name"
+    }
+}
+
 pub struct DbApi {
     pool: Pool<Sqlite>,
+    include_inferred: bool,
 }
 
 impl DbApi {
@@ -28,14 +177,60 @@ impl DbApi {
     pub async fn new(db_location: String) -> Result<Self, Box<dyn std::error::Error>> {
         let pool = db::init(db_location.clone()).await?;
         debug!("db {db_location} initialized");
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            include_inferred: true,
+        })
+    }
+
+    /// Whether reads, exports and queries see the triples of
+    /// `INFERRED_GRAPH` and the graphs under it, they do unless turned off
+    /// here.
+    pub fn set_include_inferred(&mut self, include_inferred: bool) {
+        self.include_inferred = include_inferred;
+    }
+
+    /// a GLOB over the names of the graphs reads leave out, bound to the
+    /// parameter of `db::visible_graph!`
+    fn excluded_graphs(&self) -> Option<String> {
+        (!self.include_inferred).then(|| format!("{INFERRED_GRAPH}*"))
+    }
+
+    /// Materialises RDFS entailments into `INFERRED_GRAPH` and keeps them
+    /// current as triples are inserted and deleted, returning how many
+    /// triples are inferred.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if inference cannot be enabled.
+    pub async fn enable_rdfs(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        rdfs::enable(&self.pool).await
+    }
+
+    /// Stops RDFS inference and removes the inferred triples, returning how
+    /// many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if inference cannot be disabled.
+    pub async fn disable_rdfs(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        rdfs::disable(&self.pool).await
+    }
+
+    /// Whether RDFS inference is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the db cannot be read.
+    pub async fn rdfs_enabled(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        rdfs::is_enabled(&self.pool).await
     }
 
     /// # Errors
     ///
     /// Will return `Err` if db cannot start a transaction
     #[cfg(all(feature = "sqlite", not(feature = "disable-sqlite")))]
-    pub async fn begin_txn(&self) -> Result<Transaction<Sqlite>, Box<dyn std::error::Error>> {
+    pub async fn begin_txn(&self) -> Result<Transaction<'_, Sqlite>, Box<dyn std::error::Error>> {
         let pool = &self.pool;
 
         let tx: Transaction<Sqlite> = pool.begin().await?;
@@ -43,8 +238,10 @@ impl DbApi {
         Ok(tx)
     }
 
-    async fn get_or_insert_name(&self, name: &str) -> Result<i64, sqlx::Error> {
-        let pool = &self.pool;
+    async fn get_or_insert_name(
+        conn: &mut SqliteConnection,
+        name: &str,
+    ) -> Result<i64, sqlx::Error> {
         let query = "
         -- Try to insert the item
         INSERT OR IGNORE INTO names (name) VALUES (?);
@@ -56,14 +253,16 @@ impl DbApi {
         let row: (i64,) = sqlx::query_as(query)
             .bind(name)
             .bind(name)
-            .fetch_one(pool)
+            .fetch_one(conn)
             .await?;
 
         Ok(row.0)
     }
 
-    async fn get_or_insert_object(&self, object: &str) -> Result<i64, sqlx::Error> {
-        let pool = &self.pool;
+    async fn get_or_insert_object(
+        conn: &mut SqliteConnection,
+        object: &str,
+    ) -> Result<i64, sqlx::Error> {
         let query = "
         -- Try to insert the item
         INSERT OR IGNORE INTO objects (object) VALUES (?);
@@ -75,29 +274,63 @@ impl DbApi {
         let row: (i64,) = sqlx::query_as(query)
             .bind(object)
             .bind(object)
-            .fetch_one(pool)
+            .fetch_one(conn)
             .await?;
 
         Ok(row.0)
     }
 
     async fn insert_triple(
-        &self,
+        conn: &mut SqliteConnection,
         subject_id: i64,
         predicate_id: i64,
         object_id: i64,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        sqlx::query(
+        graph_id: Option<i64>,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        let result = sqlx::query(
             r"
-        INSERT INTO triples (subject, predicate, object) VALUES (?1, ?2, ?3)
+        INSERT INTO triples (subject, predicate, object, graph) VALUES (?1, ?2, ?3, ?4)
         ",
         )
         .bind(subject_id)
         .bind(predicate_id)
         .bind(object_id)
-        .execute(&self.pool)
+        .bind(graph_id)
+        .execute(conn)
         .await?;
 
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn write_subject(
+        conn: &mut SqliteConnection,
+        subject: &Subject,
+        graph: Option<&RdfName>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let graph_id = match graph {
+            Some(graph) => Some(Self::get_or_insert_name(conn, &graph.to_string()).await?),
+            None => None,
+        };
+        let fetched_subject_id =
+            Self::get_or_insert_name(conn, &subject.name().to_string()).await?;
+
+        for (predicate, objects) in subject.predicate_object_pairs() {
+            let fetched_predicate_id =
+                Self::get_or_insert_name(conn, &predicate.to_string()).await?;
+
+            for object in objects {
+                let fetched_object_id = Self::get_or_insert_object(conn, object).await?;
+                Self::insert_triple(
+                    conn,
+                    fetched_subject_id,
+                    fetched_predicate_id,
+                    fetched_object_id,
+                    graph_id,
+                )
+                .await?;
+            }
+        }
+
         Ok(())
     }
 
@@ -107,20 +340,1053 @@ impl DbApi {
     ///
     /// Will return `Err` if insertion cannot be performed.
     pub async fn insert(&self, subject: &Subject) -> Result<(), Box<dyn std::error::Error>> {
-        let fetched_subject_id = self.get_or_insert_name(&subject.name().to_string()).await?;
+        self.insert_into(subject, None).await
+    }
 
-        for (predicate, objects) in subject.predicate_object_pairs() {
-            let fetched_predicate_id = self.get_or_insert_name(&predicate.to_string()).await?;
+    /// Inserts a given RDF subject into a named graph, or into the default
+    /// graph when `graph` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if insertion cannot be performed.
+    pub async fn insert_into(
+        &self,
+        subject: &Subject,
+        graph: Option<&RdfName>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.pool.acquire().await?;
+        Self::write_subject(&mut conn, subject, graph).await
+    }
+
+    /// Inserts a given RDF subject into a named graph, or the default
+    /// graph, as part of `tx`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if insertion cannot be performed.
+    pub async fn insert_into_txn(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        subject: &Subject,
+        graph: Option<&RdfName>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Self::write_subject(tx, subject, graph).await
+    }
+
+    async fn write_conclusion(
+        conn: &mut SqliteConnection,
+        graph: &str,
+        subject: &str,
+        predicate: &str,
+        object: &str,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        let graph_id = Self::get_or_insert_name(conn, graph).await?;
+        let subject_id = Self::get_or_insert_name(conn, subject).await?;
+        let predicate_id = Self::get_or_insert_name(conn, predicate).await?;
+        let object_id = Self::get_or_insert_object(conn, object).await?;
+        Self::insert_triple(conn, subject_id, predicate_id, object_id, Some(graph_id)).await
+    }
+
+    /// Inserts a conclusion of rules into `graph`, one of `RULES_GRAPH` or
+    /// `OWL_GRAPH`, returning the id of the stored triple.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if insertion cannot be performed.
+    pub async fn insert_conclusion(
+        &self,
+        graph: &str,
+        subject: &str,
+        predicate: &str,
+        object: &str,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        let mut conn = self.pool.acquire().await?;
+        Self::write_conclusion(&mut conn, graph, subject, predicate, object).await
+    }
+
+    /// Inserts a conclusion of rules into `graph` as part of `tx`,
+    /// returning the id of the stored triple.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if insertion cannot be performed.
+    pub async fn insert_conclusion_txn(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        graph: &str,
+        subject: &str,
+        predicate: &str,
+        object: &str,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        Self::write_conclusion(tx, graph, subject, predicate, object).await
+    }
+
+    async fn write_derivation(
+        conn: &mut SqliteConnection,
+        triple: i64,
+        rule: &str,
+        premises: &[i64],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let derivation = sqlx::query("INSERT INTO derivations (triple, rule) VALUES (?1, ?2)")
+            .bind(triple)
+            .bind(rule)
+            .execute(&mut *conn)
+            .await?
+            .last_insert_rowid();
+
+        for premise in premises {
+            sqlx::query(
+                r"
+            INSERT INTO derivation_premises (derivation, subject, predicate, object)
+            SELECT ?1, subject, predicate, object FROM triples WHERE id = ?2
+            ",
+            )
+            .bind(derivation)
+            .bind(premise)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Records that the stored triple `triple` follows by `rule` from the
+    /// stored triples `premises`, so that it is retracted when no triple
+    /// with their content is left.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the derivation cannot be stored.
+    pub async fn insert_derivation(
+        &self,
+        triple: i64,
+        rule: &str,
+        premises: &[i64],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.pool.acquire().await?;
+        Self::write_derivation(&mut conn, triple, rule, premises).await
+    }
+
+    /// Records a derivation as `insert_derivation` does, as part of `tx`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the derivation cannot be stored.
+    pub async fn insert_derivation_txn(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        triple: i64,
+        rule: &str,
+        premises: &[i64],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Self::write_derivation(tx, triple, rule, premises).await
+    }
+
+    /// Retracts every conclusion stored in `graph`, and what rests on
+    /// them, returning how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if deletion cannot be performed.
+    pub async fn clear_conclusions(&self, graph: &str) -> Result<u64, Box<dyn std::error::Error>> {
+        self.clear(&GraphTarget::Named(graph.to_string())).await
+    }
+
+    /// Retracts every conclusion stored in `graph`, and what rests on
+    /// them, as part of `tx`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if deletion cannot be performed.
+    pub async fn clear_conclusions_txn(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        graph: &str,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        self.clear_txn(tx, &GraphTarget::Named(graph.to_string()))
+            .await
+    }
 
+    async fn remove_subject(
+        conn: &mut SqliteConnection,
+        subject: &Subject,
+        graph: Option<&RdfName>,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let graph = graph.map(ToString::to_string);
+        let mut deleted = 0;
+
+        for (predicate, objects) in subject.predicate_object_pairs() {
             for object in objects {
-                let fetched_object_id = self.get_or_insert_object(object).await?;
-                self.insert_triple(fetched_subject_id, fetched_predicate_id, fetched_object_id)
-                    .await?;
+                deleted += sqlx::query(
+                    r"
+                DELETE FROM triples
+                WHERE subject = (SELECT id FROM names WHERE name = ?1)
+                AND predicate = (SELECT id FROM names WHERE name = ?2)
+                AND object = (SELECT id FROM objects WHERE object = ?3)
+                AND ((?4 IS NULL AND graph IS NULL)
+                    OR graph = (SELECT id FROM names WHERE name = ?4))
+                ",
+                )
+                .bind(subject.name().to_string())
+                .bind(predicate.to_string())
+                .bind(object)
+                .bind(&graph)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Deletes the predicate/object pairs of a given RDF subject from a
+    /// named graph, or from the default graph when `graph` is `None`.
+    /// Returns the number of triples removed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if deletion cannot be performed.
+    pub async fn delete_from(
+        &self,
+        subject: &Subject,
+        graph: Option<&RdfName>,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut conn = self.pool.acquire().await?;
+        Self::remove_subject(&mut conn, subject, graph).await
+    }
+
+    async fn clear_graphs(
+        conn: &mut SqliteConnection,
+        target: &GraphTarget,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let result = match target {
+            GraphTarget::All => sqlx::query("DELETE FROM triples").execute(conn).await?,
+            GraphTarget::Default => {
+                sqlx::query("DELETE FROM triples WHERE graph IS NULL")
+                    .execute(conn)
+                    .await?
+            }
+            GraphTarget::Named(graph) => {
+                sqlx::query(
+                    "DELETE FROM triples WHERE graph = (SELECT id FROM names WHERE name = ?1)",
+                )
+                .bind(graph)
+                .execute(conn)
+                .await?
+            }
+        };
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes every triple of the targeted graphs, returning how many were
+    /// removed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if deletion cannot be performed.
+    pub async fn clear(&self, target: &GraphTarget) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut conn = self.pool.acquire().await?;
+        Self::clear_graphs(&mut conn, target).await
+    }
+
+    /// Deletes every triple of the targeted graphs as part of `tx`,
+    /// returning how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if deletion cannot be performed.
+    pub async fn clear_txn(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        target: &GraphTarget,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        Self::clear_graphs(tx, target).await
+    }
+
+    /// Streams every triple as subject, predicate, object and graph name,
+    /// ordered by subject name, so exports need not hold the whole database
+    /// in memory.  With `of_type` only subjects with that `rdf:type` are
+    /// streamed.
+    pub fn stream_triples(
+        &self,
+        of_type: Option<&str>,
+    ) -> BoxStream<'_, Result<StoredTriple, sqlx::Error>> {
+        sqlx::query_as(concat!(
+            r"
+        SELECT subjects.name, predicates.name, objects.object, graphs.name
+        FROM triples
+        JOIN names AS subjects ON triples.subject = subjects.id
+        JOIN names AS predicates ON triples.predicate = predicates.id
+        JOIN objects AS objects ON triples.object = objects.id
+        LEFT JOIN names AS graphs ON triples.graph = graphs.id
+        WHERE (?1 IS NULL OR triples.subject IN (
+            SELECT typed.subject FROM triples AS typed
+            WHERE typed.predicate = (SELECT id FROM names WHERE name = ?2)
+                AND typed.object = (SELECT id FROM objects WHERE object = ?1)
+                AND ",
+            db::visible_graph!("typed.graph", "?3"),
+            r"
+        ))
+        AND ",
+            db::visible_graph!("triples.graph", "?3"),
+            r"
+        ORDER BY subjects.name
+        "
+        ))
+        .bind(of_type.map(str::to_string))
+        .bind(RDF_TYPE)
+        .bind(self.excluded_graphs())
+        .fetch(&self.pool)
+    }
+
+    /// Streams the triples matching a pattern, each of `s`, `p` and `o`
+    /// either the term to match or `None` to match any, in no particular
+    /// order.  Literals with a language tag, or a datatype their lexical
+    /// form is not stored as, match nothing.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` items if the data cannot be queried from the database.
+    pub fn find(
+        &self,
+        s: Option<&RdfName>,
+        p: Option<&RdfName>,
+        o: Option<&Term>,
+    ) -> BoxStream<'_, Result<StoredTriple, sqlx::Error>> {
+        let o = match o.map(Term::to_object) {
+            Some(None) => return Box::pin(futures::stream::empty()),
+            o => o.flatten(),
+        };
+        let sql = match (s.is_some(), p.is_some(), o.is_some()) {
+            (false, false, false) => find_sql!(),
+            (true, false, false) => {
+                find_sql!("triples.subject = (SELECT id FROM names WHERE name = ?1)")
+            }
+            (false, true, false) => {
+                find_sql!("triples.predicate = (SELECT id FROM names WHERE name = ?2)")
+            }
+            (false, false, true) => {
+                find_sql!("triples.object = (SELECT id FROM objects WHERE object = ?3)")
+            }
+            (true, true, false) => find_sql!(
+                "triples.subject = (SELECT id FROM names WHERE name = ?1)",
+                "triples.predicate = (SELECT id FROM names WHERE name = ?2)"
+            ),
+            (true, false, true) => find_sql!(
+                "triples.subject = (SELECT id FROM names WHERE name = ?1)",
+                "triples.object = (SELECT id FROM objects WHERE object = ?3)"
+            ),
+            (false, true, true) => find_sql!(
+                "triples.predicate = (SELECT id FROM names WHERE name = ?2)",
+                "triples.object = (SELECT id FROM objects WHERE object = ?3)"
+            ),
+            (true, true, true) => find_sql!(
+                "triples.subject = (SELECT id FROM names WHERE name = ?1)",
+                "triples.predicate = (SELECT id FROM names WHERE name = ?2)",
+                "triples.object = (SELECT id FROM objects WHERE object = ?3)"
+            ),
+        };
+        sqlx::query_as(sql)
+            .bind(s.map(RdfName::to_string))
+            .bind(p.map(RdfName::to_string))
+            .bind(o)
+            .bind(self.excluded_graphs())
+            .fetch(&self.pool)
+    }
+
+    async fn read_identified_triples(
+        &self,
+        conn: &mut SqliteConnection,
+        predicates: Option<&[String]>,
+    ) -> Result<Vec<(i64, String, String, String)>, Box<dyn std::error::Error>> {
+        let mut sql = String::from(concat!(
+            r"
+        SELECT triples.id, subjects.name, predicates.name, objects.object
+        FROM triples
+        JOIN names AS subjects ON triples.subject = subjects.id
+        JOIN names AS predicates ON triples.predicate = predicates.id
+        JOIN objects AS objects ON triples.object = objects.id
+        WHERE ",
+            db::visible_graph!("triples.graph", "?1")
+        ));
+        if let Some(predicates) = predicates {
+            let placeholders: Vec<String> =
+                (2..predicates.len() + 2).map(|n| format!("?{n}")).collect();
+            let placeholders = placeholders.join(", ");
+            sql.push_str(&format!(" AND predicates.name IN ({placeholders})"));
+        }
+
+        let mut query = sqlx::query_as(&sql).bind(self.excluded_graphs());
+        for predicate in predicates.unwrap_or_default() {
+            query = query.bind(predicate);
+        }
+        Ok(query.fetch_all(conn).await?)
+    }
+
+    /// Returns the stored triples of `predicates`, or every triple when
+    /// `None`, as their id, subject, predicate and object.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn identified_triples(
+        &self,
+        predicates: Option<&[String]>,
+    ) -> Result<Vec<(i64, String, String, String)>, Box<dyn std::error::Error>> {
+        let mut conn = self.pool.acquire().await?;
+        self.read_identified_triples(&mut conn, predicates).await
+    }
+
+    /// Returns the stored triples as `identified_triples` does, reading
+    /// within `tx`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn identified_triples_txn(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        predicates: Option<&[String]>,
+    ) -> Result<Vec<(i64, String, String, String)>, Box<dyn std::error::Error>> {
+        self.read_identified_triples(tx, predicates).await
+    }
+
+    /// Returns each predicate with the most distinct objects any one subject
+    /// has for it, ordered by predicate name.  With `of_type` only subjects
+    /// with that `rdf:type` are counted.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn predicate_cardinality(
+        &self,
+        of_type: Option<&str>,
+    ) -> Result<Vec<(RdfName, usize)>, Box<dyn std::error::Error>> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(concat!(
+            r"
+        SELECT predicates.name, MAX(counts.n)
+        FROM (
+            SELECT triples.predicate, COUNT(DISTINCT triples.object) AS n
+            FROM triples
+            WHERE (?1 IS NULL OR triples.subject IN (
+                SELECT typed.subject FROM triples AS typed
+                WHERE typed.predicate = (SELECT id FROM names WHERE name = ?2)
+                    AND typed.object = (SELECT id FROM objects WHERE object = ?1)
+                    AND ",
+            db::visible_graph!("typed.graph", "?3"),
+            r"
+            ))
+            AND ",
+            db::visible_graph!("triples.graph", "?3"),
+            r"
+            GROUP BY triples.predicate, triples.subject
+        ) AS counts
+        JOIN names AS predicates ON counts.predicate = predicates.id
+        GROUP BY predicates.name
+        ORDER BY predicates.name
+        "
+        ))
+        .bind(of_type)
+        .bind(RDF_TYPE)
+        .bind(self.excluded_graphs())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(name, n)| (RdfName::new(name), usize::try_from(n).unwrap_or(0)))
+            .collect())
+    }
+
+    /// Returns the counts of each predicate, its triples, distinct subjects
+    /// and objects and the fewest and most objects one subject has for it,
+    /// ordered by predicate name.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn predicate_profiles(
+        &self,
+    ) -> Result<Vec<PredicateProfile>, Box<dyn std::error::Error>> {
+        let rows: Vec<(String, i64, i64, i64, i64, i64)> = sqlx::query_as(concat!(
+            r"
+        SELECT predicates.name, SUM(counts.n), COUNT(*), objects.n, MIN(counts.n), MAX(counts.n)
+        FROM (
+            SELECT triples.predicate, COUNT(DISTINCT triples.object) AS n
+            FROM triples
+            WHERE ",
+            db::visible_graph!("triples.graph", "?1"),
+            r"
+            GROUP BY triples.predicate, triples.subject
+        ) AS counts
+        JOIN (
+            SELECT triples.predicate, COUNT(DISTINCT triples.object) AS n
+            FROM triples
+            WHERE ",
+            db::visible_graph!("triples.graph", "?1"),
+            r"
+            GROUP BY triples.predicate
+        ) AS objects ON counts.predicate = objects.predicate
+        JOIN names AS predicates ON counts.predicate = predicates.id
+        GROUP BY predicates.name
+        ORDER BY predicates.name
+        "
+        ))
+        .bind(self.excluded_graphs())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let count = |n: i64| u64::try_from(n).unwrap_or(0);
+        Ok(rows
+            .into_iter()
+            .map(
+                |(predicate, triples, subjects, objects, min, max)| PredicateProfile {
+                    predicate,
+                    triples: count(triples),
+                    subjects: count(subjects),
+                    objects: count(objects),
+                    min_per_subject: count(min),
+                    max_per_subject: count(max),
+                    ..PredicateProfile::default()
+                },
+            )
+            .collect())
+    }
+
+    /// Streams the distinct objects of each predicate, ordered by predicate
+    /// name and then object.
+    pub fn stream_predicate_objects(&self) -> BoxStream<'_, Result<(String, String), sqlx::Error>> {
+        sqlx::query_as(concat!(
+            r"
+        SELECT DISTINCT predicates.name, objects.object
+        FROM triples
+        JOIN names AS predicates ON triples.predicate = predicates.id
+        JOIN objects AS objects ON triples.object = objects.id
+        WHERE ",
+            db::visible_graph!("triples.graph", "?1"),
+            r"
+        ORDER BY predicates.name, objects.object
+        "
+        ))
+        .bind(self.excluded_graphs())
+        .fetch(&self.pool)
+    }
+
+    /// Counts the triples, distinct subjects, predicates and objects of the
+    /// store and of each graph, the instances of each class, and the size
+    /// of the db file and of each table and index.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn stats(&self) -> Result<Stats, Box<dyn std::error::Error>> {
+        let count = |n: i64| u64::try_from(n).unwrap_or(0);
+        let (triples, subjects, predicates, objects): (i64, i64, i64, i64) =
+            sqlx::query_as(concat!(
+                r"
+        SELECT COUNT(*), COUNT(DISTINCT subject), COUNT(DISTINCT predicate),
+            COUNT(DISTINCT object)
+        FROM triples
+        WHERE ",
+                db::visible_graph!("triples.graph", "?1")
+            ))
+            .bind(self.excluded_graphs())
+            .fetch_one(&self.pool)
+            .await?;
+
+        let graphs: Vec<(Option<String>, i64, i64, i64, i64)> = sqlx::query_as(concat!(
+            r"
+        SELECT graphs.name, COUNT(*), COUNT(DISTINCT triples.subject),
+            COUNT(DISTINCT triples.predicate), COUNT(DISTINCT triples.object)
+        FROM triples
+        LEFT JOIN names AS graphs ON triples.graph = graphs.id
+        WHERE ",
+            db::visible_graph!("triples.graph", "?1"),
+            r"
+        GROUP BY triples.graph
+        ORDER BY graphs.name IS NOT NULL, graphs.name
+        "
+        ))
+        .bind(self.excluded_graphs())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (page_count, page_size): (i64, i64) = sqlx::query_as(
+            "SELECT page_count, page_size FROM pragma_page_count(), pragma_page_size()",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        // dbstat is only there when SQLite is built with it
+        let storage: Vec<(String, String, i64)> = sqlx::query_as(
+            r"
+        SELECT dbstat.name, COALESCE(schema.type, 'table'), SUM(dbstat.pgsize)
+        FROM dbstat
+        LEFT JOIN sqlite_schema AS schema ON dbstat.name = schema.name
+        GROUP BY dbstat.name
+        ORDER BY SUM(dbstat.pgsize) DESC, dbstat.name
+        ",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        Ok(Stats {
+            triples: count(triples),
+            subjects: count(subjects),
+            predicates: count(predicates),
+            objects: count(objects),
+            graphs: graphs
+                .into_iter()
+                .map(
+                    |(graph, triples, subjects, predicates, objects)| GraphStats {
+                        graph,
+                        triples: count(triples),
+                        subjects: count(subjects),
+                        predicates: count(predicates),
+                        objects: count(objects),
+                    },
+                )
+                .collect(),
+            classes: self
+                .class_counts()
+                .await?
+                .into_iter()
+                .map(|(class, instances)| (class.to_string(), instances))
+                .collect(),
+            file_size: count(page_count * page_size),
+            storage: storage
+                .into_iter()
+                .map(|(name, kind, bytes)| StorageStats {
+                    name,
+                    kind,
+                    bytes: count(bytes),
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns each class with how many subjects have it as their
+    /// `rdf:type`, ordered by class name.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn class_counts(&self) -> Result<Vec<(RdfName, u64)>, Box<dyn std::error::Error>> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(concat!(
+            r"
+        SELECT classes.object, COUNT(DISTINCT triples.subject)
+        FROM triples
+        JOIN objects AS classes ON triples.object = classes.id
+        WHERE triples.predicate = (SELECT id FROM names WHERE name = ?1)
+            AND ",
+            db::visible_graph!("triples.graph", "?2"),
+            r"
+        GROUP BY classes.object
+        ORDER BY classes.object
+        "
+        ))
+        .bind(RDF_TYPE)
+        .bind(self.excluded_graphs())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(class, n)| (RdfName::new(class), u64::try_from(n).unwrap_or(0)))
+            .collect())
+    }
+
+    /// Returns, for each class and each predicate its instances use, how
+    /// many instances have the predicate and the fewest and most objects
+    /// one of them has for it, ordered by class and predicate name.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn class_predicate_counts(
+        &self,
+    ) -> Result<Vec<(String, String, u64, u64, u64)>, Box<dyn std::error::Error>> {
+        let rows: Vec<(String, String, i64, i64, i64)> = sqlx::query_as(concat!(
+            r"
+        SELECT classes.object, predicates.name, COUNT(*), MIN(counts.n), MAX(counts.n)
+        FROM (
+            SELECT typed.object AS class, triples.predicate, COUNT(DISTINCT triples.object) AS n
+            FROM triples AS typed
+            JOIN triples ON triples.subject = typed.subject
+            WHERE typed.predicate = (SELECT id FROM names WHERE name = ?1)
+                AND ",
+            db::visible_graph!("typed.graph", "?2"),
+            r"
+                AND ",
+            db::visible_graph!("triples.graph", "?2"),
+            r"
+            GROUP BY typed.object, triples.predicate, triples.subject
+        ) AS counts
+        JOIN objects AS classes ON counts.class = classes.id
+        JOIN names AS predicates ON counts.predicate = predicates.id
+        GROUP BY classes.object, predicates.name
+        ORDER BY classes.object, predicates.name
+        "
+        ))
+        .bind(RDF_TYPE)
+        .bind(self.excluded_graphs())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let count = |n: i64| u64::try_from(n).unwrap_or(0);
+        Ok(rows
+            .into_iter()
+            .map(|(class, predicate, subjects, min, max)| {
+                (class, predicate, count(subjects), count(min), count(max))
+            })
+            .collect())
+    }
+
+    /// Returns the triples within `hops` edges of `resource`, followed in
+    /// `direction` and along `predicates` only unless empty, each with the
+    /// fewest hops it is from `resource`, nearest first.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn neighbourhood(
+        &self,
+        resource: &str,
+        hops: usize,
+        direction: Direction,
+        predicates: &[String],
+    ) -> Result<Vec<Step>, Box<dyn std::error::Error>> {
+        let sql = format!(
+            "{} SELECT s, p, o, forward, MIN(hop) FROM steps \
+            GROUP BY s, p, o, forward \
+            ORDER BY MIN(hop), s, p, o",
+            steps_cte(direction, predicates.len(), "")
+        );
+
+        let mut query = sqlx::query_as(&sql)
+            .bind(self.excluded_graphs())
+            .bind(resource)
+            .bind(i64::try_from(hops).unwrap_or(i64::MAX));
+        for predicate in predicates {
+            query = query.bind(predicate);
+        }
+        let rows: Vec<(String, String, String, bool, i64)> = query.fetch_all(&self.pool).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(subject, predicate, object, forward, hops)| Step {
+                subject,
+                predicate,
+                object,
+                forward,
+                hops: usize::try_from(hops).unwrap_or(0),
+            })
+            .collect())
+    }
+
+    /// Returns the triples of a shortest path from `from` to `to` of at
+    /// most `max_hops` edges, followed in `direction` and along
+    /// `predicates` only unless empty, or `None` when there is none.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn shortest_path(
+        &self,
+        from: &str,
+        to: &str,
+        max_hops: usize,
+        direction: Direction,
+        predicates: &[String],
+    ) -> Result<Option<Vec<Step>>, Box<dyn std::error::Error>> {
+        if from == to {
+            return Ok(Some(Vec::new()));
+        }
+        let target = format!("?{}", predicates.len() + 4);
+        let sql = format!(
+            "{} SELECT s, p, o, forward, hop, node = {target} FROM steps",
+            steps_cte(
+                direction,
+                predicates.len(),
+                &format!(" AND steps.node <> {target}")
+            )
+        );
+
+        let mut query = sqlx::query_as::<_, (String, String, String, bool, i64, bool)>(&sql)
+            .bind(self.excluded_graphs())
+            .bind(from)
+            .bind(i64::try_from(max_hops).unwrap_or(i64::MAX));
+        for predicate in predicates {
+            query = query.bind(predicate);
+        }
+        query = query.bind(to);
+
+        // the steps come a hop at a time, so the walk stops with the hop
+        // that first reaches `to`
+        let mut rows = query.fetch(&self.pool);
+        let mut steps = Vec::new();
+        let mut reached = None;
+        while let Some((subject, predicate, object, forward, hops, found)) = rows.try_next().await?
+        {
+            let hops = usize::try_from(hops).unwrap_or(0);
+            if reached.is_some_and(|level| hops > level) {
+                break;
+            }
+            if found {
+                reached = Some(hops);
+            }
+            steps.push(Step {
+                subject,
+                predicate,
+                object,
+                forward,
+                hops,
+            });
+        }
+        drop(rows);
+
+        Ok(reached.and_then(|_| traversal::shortest_path(from, to, &steps)))
+    }
+
+    /// Returns every node reachable from `resource`, followed in
+    /// `direction` and along `predicates` only unless empty, however many
+    /// edges away, ordered by name.  `resource` itself is left out even
+    /// when a cycle leads back to it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn reachable(
+        &self,
+        resource: &str,
+        direction: Direction,
+        predicates: &[String],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let steps: Vec<String> =
+            traversal_joins(direction, "FROM reach", "reach.node", predicates.len(), 3)
+                .into_iter()
+                .map(|(forward, join)| format!("SELECT {} {join}", reached(forward)))
+                .collect();
+        let sql = format!(
+            "WITH RECURSIVE reach(node) AS (SELECT ?2 UNION {}) \
+            SELECT node FROM reach WHERE node <> ?2 ORDER BY node",
+            steps.join(" UNION ")
+        );
+
+        let mut query = sqlx::query_scalar(&sql)
+            .bind(self.excluded_graphs())
+            .bind(resource);
+        for predicate in predicates {
+            query = query.bind(predicate);
+        }
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+
+    /// Returns each distinct triple of `predicates`, or of every predicate
+    /// when empty, as its subject and its object when that is an IRI or
+    /// blank node, ordered by subject.  Triples of `ANALYTICS_GRAPH` are
+    /// left out so results never feed the next analysis.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn edges(
+        &self,
+        predicates: &[String],
+    ) -> Result<Vec<(String, Option<String>)>, Box<dyn std::error::Error>> {
+        let mut sql = format!(
+            concat!(
+                r"
+        SELECT DISTINCT subjects.name, predicates.name,
+            CASE WHEN {} THEN objects.object END
+        FROM triples
+        JOIN names AS subjects ON triples.subject = subjects.id
+        JOIN names AS predicates ON triples.predicate = predicates.id
+        JOIN objects AS objects ON triples.object = objects.id
+        WHERE ",
+                db::visible_graph!("triples.graph", "?1"),
+                r"
+        AND (triples.graph IS NULL
+            OR triples.graph <> (SELECT id FROM names WHERE name = ?2))
+        "
+            ),
+            db::node_object("objects.object")
+        );
+        if !predicates.is_empty() {
+            let placeholders: Vec<String> =
+                (3..3 + predicates.len()).map(|n| format!("?{n}")).collect();
+            sql.push_str(&format!(
+                "AND predicates.name IN ({})\n",
+                placeholders.join(", ")
+            ));
+        }
+        sql.push_str("ORDER BY subjects.name");
+
+        let mut query = sqlx::query_as(&sql)
+            .bind(self.excluded_graphs())
+            .bind(ANALYTICS_GRAPH);
+        for predicate in predicates {
+            query = query.bind(predicate);
+        }
+        let rows: Vec<(String, String, Option<String>)> = query.fetch_all(&self.pool).await?;
+        Ok(rows
+            .into_iter()
+            .map(|(subject, _, object)| (subject, object))
+            .collect())
+    }
+
+    /// Returns the subjects with a literal matching `text`, every word of
+    /// it, of `predicates` only unless empty, each with its best matching
+    /// triple and best first.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `text` has no words or the data cannot be
+    /// queried from the database.
+    pub async fn search(
+        &self,
+        text: &str,
+        predicates: &[String],
+    ) -> Result<Vec<SearchHit>, Box<dyn std::error::Error>> {
+        let mut filter = String::new();
+        if !predicates.is_empty() {
+            let placeholders: Vec<String> =
+                (3..3 + predicates.len()).map(|n| format!("?{n}")).collect();
+            filter = format!("AND predicates.name IN ({})", placeholders.join(", "));
+        }
+        let sql = format!(
+            concat!(
+                r"
+        WITH matches AS (
+            SELECT rowid AS object, bm25(objects_fts) AS rank
+            FROM objects_fts WHERE objects_fts MATCH ?2
+        ), hits AS (
+            SELECT subjects.name AS subject, predicates.name AS predicate,
+                objects.object AS object, matches.rank AS rank,
+                ROW_NUMBER() OVER (
+                    PARTITION BY triples.subject
+                    ORDER BY matches.rank, predicates.name, objects.object
+                ) AS n
+            FROM matches
+            JOIN triples ON triples.object = matches.object
+            JOIN names AS subjects ON triples.subject = subjects.id
+            JOIN names AS predicates ON triples.predicate = predicates.id
+            JOIN objects ON triples.object = objects.id
+            WHERE ",
+                db::visible_graph!("triples.graph", "?1"),
+                r"
+            {}
+        )
+        SELECT subject, predicate, object, -rank FROM hits
+        WHERE n = 1
+        ORDER BY rank, subject
+        "
+            ),
+            filter
+        );
+
+        let mut query = sqlx::query_as(&sql)
+            .bind(self.excluded_graphs())
+            .bind(search::match_expression(text)?);
+        for predicate in predicates {
+            query = query.bind(predicate);
+        }
+        let rows: Vec<(String, String, String, f64)> = query.fetch_all(&self.pool).await?;
+        Ok(rows
+            .into_iter()
+            .map(|(subject, predicate, object, score)| SearchHit {
+                subject,
+                predicate,
+                object,
+                score,
+            })
+            .collect())
+    }
+
+    /// Returns every subject of a named graph, or of the default graph when
+    /// `graph` is `None`, ordered by subject name.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn graph_subjects(
+        &self,
+        graph: Option<&RdfName>,
+    ) -> Result<Vec<Subject>, Box<dyn std::error::Error>> {
+        let results: Vec<(String, String, String)> = sqlx::query_as(concat!(
+            r"
+        SELECT subjects.name, predicates.name, objects.object
+        FROM triples
+        JOIN names AS subjects ON triples.subject = subjects.id
+        JOIN names AS predicates ON triples.predicate = predicates.id
+        JOIN objects AS objects ON triples.object = objects.id
+        WHERE ((?1 IS NULL AND triples.graph IS NULL)
+            OR triples.graph = (SELECT id FROM names WHERE name = ?1))
+            AND ",
+            db::visible_graph!("triples.graph", "?2"),
+            r"
+        ORDER BY subjects.name
+        "
+        ))
+        .bind(graph.map(ToString::to_string))
+        .bind(self.excluded_graphs())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut subjects: Vec<Subject> = Vec::new();
+        for (subject_iri, predicate_iri, object_value) in results {
+            if subjects
+                .last()
+                .is_none_or(|s| s.name().to_string() != subject_iri)
+            {
+                subjects.push(Subject::new(RdfName::new(subject_iri)));
+            }
+            if let Some(subject) = subjects.last_mut() {
+                subject.add(RdfName::new(predicate_iri), object_value);
             }
         }
 
+        Ok(subjects)
+    }
+
+    /// Applies the operations of a SPARQL Update request in order, in one
+    /// transaction so a request that fails part way changes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any operation cannot be performed.
+    pub async fn update(
+        &self,
+        operations: &[UpdateOperation],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut tx = self.begin_txn().await?;
+        for operation in operations {
+            match operation {
+                UpdateOperation::InsertData(quads) | UpdateOperation::DeleteData(quads) => {
+                    for quad in quads {
+                        let mut subject = Subject::new(RdfName::new(quad.subject.clone()));
+                        subject.add(RdfName::new(quad.predicate.clone()), quad.object.clone());
+                        let graph = quad.graph.clone().map(RdfName::new);
+                        if matches!(operation, UpdateOperation::InsertData(_)) {
+                            Self::write_subject(&mut tx, &subject, graph.as_ref()).await?;
+                        } else {
+                            Self::remove_subject(&mut tx, &subject, graph.as_ref()).await?;
+                        }
+                    }
+                }
+                UpdateOperation::Clear(target) => {
+                    Self::clear_graphs(&mut tx, target).await?;
+                }
+            }
+        }
+        tx.commit().await?;
+
         Ok(())
     }
+
     /// returns a `Subject` from the database.
     ///
     /// # Errors
@@ -133,7 +1399,7 @@ impl DbApi {
         let pool = &self.pool;
 
         // Use the provided subject name to query the database for all predicate/object pairs
-        let results: Vec<(String, String)> = sqlx::query_as(
+        let results: Vec<(String, String)> = sqlx::query_as(concat!(
             r"
         SELECT predicates.name, objects.object
         FROM triples
@@ -141,9 +1407,11 @@ impl DbApi {
         JOIN names AS predicates ON triples.predicate = predicates.id
         JOIN objects AS objects ON triples.object = objects.id
         WHERE subjects.name = ?1
-        ",
-        )
+            AND ",
+            db::visible_graph!("triples.graph", "?2")
+        ))
         .bind(subject_name.to_string())
+        .bind(self.excluded_graphs())
         .fetch_all(pool)
         .await?;
 
@@ -172,13 +1440,19 @@ impl DbApi {
     pub async fn get_subject_names(&self) -> Result<Vec<RdfName>, Box<dyn std::error::Error>> {
         let pool = &self.pool;
 
-        let names_strings: Vec<String> = sqlx::query_scalar(
+        let names_strings: Vec<String> = sqlx::query_scalar(concat!(
             r"
-            SELECT DISTINCT subjects.name
+            SELECT subjects.name
             FROM triples
             JOIN names AS subjects ON triples.subject = subjects.id
-            ",
-        )
+            WHERE ",
+            db::visible_graph!("triples.graph", "?1"),
+            r"
+            GROUP BY subjects.name
+            ORDER BY subjects.name
+            "
+        ))
+        .bind(self.excluded_graphs())
         .fetch_all(pool)
         .await?;
 
@@ -201,13 +1475,19 @@ impl DbApi {
     pub async fn get_predicate_names(&self) -> Result<Vec<RdfName>, Box<dyn std::error::Error>> {
         let pool = &self.pool;
 
-        let names_strings: Vec<String> = sqlx::query_scalar(
+        let names_strings: Vec<String> = sqlx::query_scalar(concat!(
             r"
-            SELECT DISTINCT predicates.name
+            SELECT predicates.name
             FROM triples
             JOIN names AS predicates ON triples.predicate = predicates.id
-            ",
-        )
+            WHERE ",
+            db::visible_graph!("triples.graph", "?1"),
+            r"
+            GROUP BY predicates.name
+            ORDER BY predicates.name
+            "
+        ))
+        .bind(self.excluded_graphs())
         .fetch_all(pool)
         .await?;
 
@@ -221,6 +1501,242 @@ impl DbApi {
 
         Ok(names_rdf)
     }
+
+    /// Records a prefix declared by imported data, replacing any earlier
+    /// mapping of the same prefix or namespace.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the prefix cannot be stored.
+    pub async fn insert_namespace(
+        &self,
+        prefix: &str,
+        namespace: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.pool.acquire().await?;
+        Self::write_namespace(&mut conn, prefix, namespace).await
+    }
+
+    /// Records a prefix declared by imported data as part of `tx`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the prefix cannot be stored.
+    pub async fn insert_namespace_txn(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        prefix: &str,
+        namespace: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Self::write_namespace(tx, prefix, namespace).await
+    }
+
+    async fn write_namespace(
+        conn: &mut SqliteConnection,
+        prefix: &str,
+        namespace: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("INSERT OR REPLACE INTO namespaces (prefix, namespace) VALUES (?1, ?2)")
+            .bind(prefix)
+            .bind(namespace)
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Reads the prefixes declared by imported data.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn namespaces(&self) -> Result<PrefixMap, Box<dyn std::error::Error>> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT prefix, namespace FROM namespaces ORDER BY prefix")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut prefixes = PrefixMap::new();
+        for (prefix, namespace) in rows {
+            prefixes.insert(&prefix, &namespace);
+        }
+
+        Ok(prefixes)
+    }
+
+    /// Reads the prefixes names are written with, those declared by
+    /// imported data first and then the well-known vocabulary names.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn preferred_prefixes(&self) -> Result<PrefixMap, Box<dyn std::error::Error>> {
+        let mut prefixes = self.namespaces().await?;
+        prefixes.extend(&PrefixMap::well_known());
+        Ok(prefixes)
+    }
+
+    /// Reads the per predicate cardinalities used by the query planner.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data cannot be queried from the database.
+    pub async fn planner_statistics(&self) -> Result<Statistics, Box<dyn std::error::Error>> {
+        let rows: Vec<(String, i64, i64, i64)> = sqlx::query_as(
+            r"
+            SELECT predicates.name, stats.triples, stats.subjects, stats.objects
+            FROM predicate_stats AS stats
+            JOIN names AS predicates ON stats.predicate = predicates.id
+            WHERE stats.triples > 0
+            ",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut statistics = Statistics::default();
+        for (predicate, triples, subjects, objects) in rows {
+            statistics.predicates.insert(
+                predicate,
+                PredicateStats {
+                    triples,
+                    subjects,
+                    objects,
+                },
+            );
+        }
+
+        Ok(statistics)
+    }
+
+    /// Plans and translates a SPARQL query without running it, along with
+    /// the database's own plan for the generated SQL.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the query can not be planned or translated.
+    pub async fn explain(
+        &self,
+        query: &SparqlQuery,
+    ) -> Result<Explanation, Box<dyn std::error::Error>> {
+        let plan = Plan::new(query, &self.planner_statistics().await?);
+        let sql = self.scoped(sparql_sql::compile_plan(query, &plan)?);
+        let explain_sql = format!("EXPLAIN QUERY PLAN {}", sql.sql);
+        let mut sql_query = sqlx::query(&explain_sql);
+        for param in &sql.params {
+            sql_query = sql_query.bind(param);
+        }
+        let rows = sql_query.fetch_all(&self.pool).await?;
+        let mut database_plan = Vec::with_capacity(rows.len());
+        for row in rows {
+            database_plan.push(row.try_get::<String, _>("detail")?);
+        }
+
+        Ok(Explanation {
+            plan,
+            sql,
+            database_plan,
+        })
+    }
+
+    /// a compiled query reading `triples` through a common table expression
+    /// of the same name that leaves out the excluded graph
+    fn scoped(&self, mut compiled: sparql_sql::SqlQuery) -> sparql_sql::SqlQuery {
+        if let Some(graphs) = self.excluded_graphs() {
+            compiled.sql = format!(
+                concat!(
+                    "WITH triples AS (SELECT * FROM main.triples WHERE ",
+                    db::visible_graph!("graph", "?"),
+                    ") {}"
+                ),
+                compiled.sql
+            );
+            compiled.params.splice(0..0, [graphs.clone(), graphs]);
+        }
+        compiled
+    }
+
+    async fn planned(
+        &self,
+        query: &SparqlQuery,
+    ) -> Result<sparql_sql::SqlQuery, Box<dyn std::error::Error>> {
+        let plan = Plan::new(query, &self.planner_statistics().await?);
+        let compiled = self.scoped(sparql_sql::compile_plan(query, &plan)?);
+        debug!("sparql sql: {}", compiled.sql);
+        Ok(compiled)
+    }
+
+    /// Evaluates a SPARQL SELECT query, translated to a single SQL statement
+    /// with the triple patterns ordered by the query planner.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the query can not be translated or executed.
+    pub async fn select(
+        &self,
+        query: &SparqlQuery,
+    ) -> Result<Solutions, Box<dyn std::error::Error>> {
+        let compiled = self.planned(query).await?;
+
+        let mut sql_query = sqlx::query(&compiled.sql);
+        for param in &compiled.params {
+            sql_query = sql_query.bind(param);
+        }
+        let rows = sql_query.fetch_all(&self.pool).await?;
+
+        let mut solutions = Solutions {
+            variables: compiled.variables,
+            rows: Vec::with_capacity(rows.len()),
+        };
+        for row in rows {
+            let mut values = Vec::with_capacity(solutions.variables.len());
+            for idx in 0..solutions.variables.len() {
+                let value = row.try_get::<Option<String>, _>(idx)?;
+                values.push(value.as_deref().map(Term::from_object));
+            }
+            solutions.rows.push(values);
+        }
+
+        Ok(solutions)
+    }
+
+    /// Evaluates a SPARQL ASK query.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the query can not be translated or executed.
+    pub async fn ask(&self, query: &AskQuery) -> Result<bool, Box<dyn std::error::Error>> {
+        let select = SparqlQuery {
+            select_clause: SelectClause {
+                distinct: false,
+                variables: vec![],
+            },
+            triples_block: query.triples_block.clone(),
+            filters: query.filters.clone(),
+        };
+        let compiled = self.planned(&select).await?;
+        let sql = format!("{} LIMIT 1", compiled.sql);
+
+        let mut sql_query = sqlx::query(&sql);
+        for param in &compiled.params {
+            sql_query = sql_query.bind(param);
+        }
+        Ok(sql_query.fetch_optional(&self.pool).await?.is_some())
+    }
+
+    /// Evaluates a SPARQL query of any supported form.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the query can not be translated or executed.
+    pub async fn evaluate(
+        &self,
+        query: &QueryForm,
+    ) -> Result<QueryResults, Box<dyn std::error::Error>> {
+        match query {
+            QueryForm::Select(select) => Ok(QueryResults::Solutions(self.select(select).await?)),
+            QueryForm::Ask(ask) => Ok(QueryResults::Boolean(self.ask(ask).await?)),
+        }
+    }
 }
 
 #[cfg(test)]
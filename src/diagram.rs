@@ -0,0 +1,260 @@
+/// GraphViz DOT and Mermaid flowchart rendering of subjects, for looking
+/// at models rather than processing them
+///
+/// Subjects and the IRIs and blank nodes they reference are nodes, IRI
+/// valued objects are edges and literals are folded into node labels.
+/// Names are written with prefixes where the prefix map has them.
+///
+use crate::data::{Subject, Term};
+use crate::prefixes::PrefixMap;
+use crate::vocab::RDF_TYPE;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+/// fill colours for node types, cycled when there are more types
+const PALETTE: &[&str] = &[
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462", "#b3de69", "#fccde5",
+    "#d9d9d9", "#bc80bd", "#ccebc5", "#ffed6f",
+];
+
+/// literals longer than this are shortened in labels
+const MAX_LITERAL_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Default)]
+pub struct DiagramOptions {
+    /// predicate IRIs to draw, all when empty
+    pub predicates: Vec<String>,
+    /// fill nodes by their first `rdf:type` and show types in labels
+    /// instead of drawing `rdf:type` edges
+    pub color_by_type: bool,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    lines: Vec<String>,
+    node_type: Option<String>,
+}
+
+/// The nodes and edges to draw, with stable `n0`, `n1`, ... ids in name
+/// order.
+#[derive(Debug)]
+pub struct Diagram {
+    nodes: BTreeMap<String, Node>,
+    ids: BTreeMap<String, String>,
+    edges: BTreeSet<(String, String, String)>,
+    types: Vec<String>,
+}
+
+fn shorten(value: &str) -> String {
+    let mut chars = value.chars();
+    let short: String = chars.by_ref().take(MAX_LITERAL_CHARS).collect();
+    if chars.next().is_some() {
+        format!("{short}…")
+    } else {
+        short
+    }
+}
+
+impl Diagram {
+    #[must_use]
+    pub fn new(subjects: &[Subject], prefixes: &PrefixMap, options: &DiagramOptions) -> Self {
+        let name = |iri: &str| prefixes.compact(iri).unwrap_or_else(|| iri.to_string());
+        let drawn = |predicate: &str| {
+            options.predicates.is_empty() || options.predicates.iter().any(|p| p == predicate)
+        };
+
+        let mut nodes: BTreeMap<String, Node> = BTreeMap::new();
+        let mut edges = BTreeSet::new();
+        for subject in subjects {
+            let subject_name = subject.name().to_string();
+            let mut pairs: Vec<_> = subject.predicate_object_pairs().collect();
+            pairs.sort_by_key(|(predicate, _)| predicate.to_string());
+
+            let mut node = Node::default();
+            for (predicate, objects) in pairs {
+                let predicate = predicate.to_string();
+                let mut objects: Vec<&String> = objects.iter().collect();
+                objects.sort();
+                if options.color_by_type && predicate == RDF_TYPE {
+                    node.node_type = objects.first().map(|t| (*t).clone());
+                    for object in objects {
+                        node.lines.push(format!("a {}", name(object)));
+                    }
+                    continue;
+                }
+                if !drawn(&predicate) {
+                    continue;
+                }
+                for object in objects {
+                    match Term::from_object(object) {
+                        Term::Literal { .. } => {
+                            node.lines
+                                .push(format!("{}: {}", name(&predicate), shorten(object)))
+                        }
+                        _ => {
+                            edges.insert((subject_name.clone(), name(&predicate), object.clone()));
+                        }
+                    }
+                }
+            }
+            nodes.insert(subject_name, node);
+        }
+        for (_, _, object) in &edges {
+            nodes.entry(object.clone()).or_default();
+        }
+
+        let ids = nodes
+            .keys()
+            .enumerate()
+            .map(|(idx, node)| (node.clone(), format!("n{idx}")))
+            .collect();
+        let types: BTreeSet<String> = nodes.values().filter_map(|n| n.node_type.clone()).collect();
+        let mut diagram = Self {
+            nodes,
+            ids,
+            edges,
+            types: types.into_iter().collect(),
+        };
+        for (iri, node) in &mut diagram.nodes {
+            node.lines.insert(0, name(iri));
+        }
+        diagram
+    }
+
+    fn color(&self, node: &Node) -> Option<&'static str> {
+        let node_type = node.node_type.as_ref()?;
+        let idx = self.types.iter().position(|t| t == node_type)?;
+        Some(PALETTE[idx % PALETTE.len()])
+    }
+
+    /// the diagram as a GraphViz `digraph`
+    #[must_use]
+    pub fn dot(&self) -> String {
+        let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut text = String::from(
+            "digraph triples {\n    rankdir=LR;\n    node [shape=box, fontname=\"Helvetica\"];\n",
+        );
+        for (iri, node) in &self.nodes {
+            let label: Vec<String> = node.lines.iter().map(|l| escape(l)).collect();
+            let _ = write!(
+                text,
+                "    {} [label=\"{}\"",
+                self.ids[iri],
+                label.join("\\n")
+            );
+            if let Some(color) = self.color(node) {
+                let _ = write!(text, ", style=filled, fillcolor=\"{color}\"");
+            }
+            text.push_str("];\n");
+        }
+        for (from, predicate, to) in &self.edges {
+            let _ = writeln!(
+                text,
+                "    {} -> {} [label=\"{}\"];",
+                self.ids[from],
+                self.ids[to],
+                escape(predicate)
+            );
+        }
+        text.push_str("}\n");
+        text
+    }
+
+    /// the diagram as a Mermaid `flowchart`
+    #[must_use]
+    pub fn mermaid(&self) -> String {
+        let escape = |text: &str| {
+            text.replace('&', "#amp;")
+                .replace('"', "#quot;")
+                .replace('|', "#124;")
+                .replace('<', "#lt;")
+                .replace('>', "#gt;")
+        };
+        let mut text = String::from("flowchart LR\n");
+        for (iri, node) in &self.nodes {
+            let label: Vec<String> = node.lines.iter().map(|l| escape(l)).collect();
+            let _ = writeln!(text, "    {}[\"{}\"]", self.ids[iri], label.join("<br/>"));
+        }
+        for (from, predicate, to) in &self.edges {
+            let _ = writeln!(
+                text,
+                "    {} -->|\"{}\"| {}",
+                self.ids[from],
+                escape(predicate),
+                self.ids[to]
+            );
+        }
+        for (idx, _) in self.types.iter().enumerate() {
+            let _ = writeln!(
+                text,
+                "    classDef t{idx} fill:{}",
+                PALETTE[idx % PALETTE.len()]
+            );
+        }
+        for (iri, node) in &self.nodes {
+            if let Some(idx) = node
+                .node_type
+                .as_ref()
+                .and_then(|t| self.types.iter().position(|known| known == t))
+            {
+                let _ = writeln!(text, "    class {} t{idx}", self.ids[iri]);
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::RdfName;
+    use crate::vocab::RDFS_LABEL;
+
+    const BRICK: &str = "https://brickschema.org/schema/Brick#";
+
+    fn model() -> (Vec<Subject>, PrefixMap) {
+        let mut room = Subject::new(RdfName::new("http://example.com/room1".to_string()));
+        room.add(RdfName::new(RDF_TYPE.to_string()), format!("{BRICK}Room"));
+        room.add(
+            RdfName::new(format!("{BRICK}hasPoint")),
+            "http://example.com/temp1".to_string(),
+        );
+        room.add(
+            RdfName::new(RDFS_LABEL.to_string()),
+            "Room \"101\"".to_string(),
+        );
+        let mut prefixes = PrefixMap::well_known();
+        prefixes.insert("ex", "http://example.com/");
+        (vec![room], prefixes)
+    }
+
+    #[test]
+    fn renders_dot() {
+        let (subjects, prefixes) = model();
+        let options = DiagramOptions {
+            color_by_type: true,
+            ..DiagramOptions::default()
+        };
+        let dot = Diagram::new(&subjects, &prefixes, &options).dot();
+        assert!(dot.contains(
+            "n0 [label=\"ex:room1\\na brick:Room\\nrdfs:label: Room \\\"101\\\"\", style=filled, fillcolor=\"#8dd3c7\"];"
+        ));
+        assert!(dot.contains("n1 [label=\"ex:temp1\"];"));
+        assert!(dot.contains("n0 -> n1 [label=\"brick:hasPoint\"];"));
+        assert!(!dot.contains("rdf:type"));
+    }
+
+    #[test]
+    fn renders_mermaid_with_filtered_predicates() {
+        let (subjects, prefixes) = model();
+        let options = DiagramOptions {
+            predicates: vec![RDF_TYPE.to_string()],
+            color_by_type: false,
+        };
+        let mermaid = Diagram::new(&subjects, &prefixes, &options).mermaid();
+        assert_eq!(
+            mermaid,
+            "flowchart LR\n    n0[\"ex:room1\"]\n    n1[\"brick:Room\"]\n    n0 -->|\"rdf:type\"| n1\n"
+        );
+    }
+}
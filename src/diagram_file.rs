@@ -0,0 +1,132 @@
+/// functions in support of diagram export
+///
+/// The selected subjects are drawn as a GraphViz digraph or a Mermaid
+/// flowchart, text for another tool to render, as in
+/// `triples export-dot | dot -Tsvg > model.svg`.
+///
+use crate::data::{RdfName, Subject, Term};
+use crate::db_api::DbApi;
+use crate::diagram::{Diagram, DiagramOptions};
+use crate::ttl_file;
+use std::collections::HashSet;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::trace;
+
+/// what to draw: everything, or what `root` reaches in `depth` steps
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// a subject IRI or prefixed name to start from
+    pub root: Option<String>,
+    pub depth: usize,
+    /// predicate IRIs or prefixed names to draw, all when empty
+    pub predicates: Vec<String>,
+    pub color_by_type: bool,
+}
+
+/// the subjects reached from `root` by following IRI and blank node objects
+/// of `predicates`, or of any predicate when empty, at most `depth` times
+async fn reachable(
+    root: String,
+    depth: usize,
+    predicates: &[String],
+    db_api: &DbApi,
+) -> Result<Vec<Subject>, Box<dyn std::error::Error>> {
+    let mut subjects = Vec::new();
+    let mut seen = HashSet::from([root.clone()]);
+    let mut frontier = vec![root];
+    for level in 0..=depth {
+        let mut next = Vec::new();
+        for name in frontier {
+            let Some(subject) = db_api.query(&RdfName::new(name)).await? else {
+                continue;
+            };
+            if level < depth {
+                for (predicate, objects) in subject.predicate_object_pairs() {
+                    let predicate = predicate.to_string();
+                    if !predicates.is_empty() && !predicates.contains(&predicate) {
+                        continue;
+                    }
+                    for object in objects {
+                        let is_node = !matches!(Term::from_object(object), Term::Literal { .. });
+                        if is_node && seen.insert(object.clone()) {
+                            next.push(object.clone());
+                        }
+                    }
+                }
+            }
+            subjects.push(subject);
+        }
+        frontier = next;
+    }
+    Ok(subjects)
+}
+
+async fn diagram(
+    selection: &Selection,
+    db_api: &DbApi,
+) -> Result<Diagram, Box<dyn std::error::Error>> {
+    let preferred = db_api.preferred_prefixes().await?;
+    let options = DiagramOptions {
+        predicates: selection
+            .predicates
+            .iter()
+            .map(|p| preferred.resolve(p))
+            .collect::<Result<_, _>>()?,
+        color_by_type: selection.color_by_type,
+    };
+
+    let subjects = match &selection.root {
+        Some(root) => {
+            let root = preferred.resolve(root)?;
+            reachable(root, selection.depth, &options.predicates, db_api).await?
+        }
+        None => {
+            let mut subjects = Vec::new();
+            for name in &db_api.get_subject_names().await? {
+                if let Some(subject) = db_api.query(name).await? {
+                    subjects.push(subject);
+                }
+            }
+            subjects
+        }
+    };
+    let mut prefixes = ttl_file::compute_prefixes(&subjects, &preferred)?;
+    // rdf:type is left out of turtle prefixes, written `a` there
+    prefixes.extend(&preferred);
+
+    Ok(Diagram::new(&subjects, &prefixes, &options))
+}
+
+/// write the selected subjects to `out` as a GraphViz DOT digraph
+///
+/// # Errors
+///
+/// return `Err` if a name can not be expanded, or on db read or write errors
+pub async fn export_dot<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    selection: &Selection,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("export_dot");
+    let diagram = diagram(selection, db_api).await?;
+    out.write_all(diagram.dot().as_bytes()).await?;
+    out.flush().await?;
+    Ok(())
+}
+
+/// write the selected subjects to `out` as a Mermaid flowchart
+///
+/// # Errors
+///
+/// return `Err` if a name can not be expanded, or on db read or write errors
+pub async fn export_mermaid<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    selection: &Selection,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("export_mermaid");
+    let diagram = diagram(selection, db_api).await?;
+    out.write_all(diagram.mermaid().as_bytes()).await?;
+    out.flush().await?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! names and SQL shared by the schema and the reasoners
+//!
+//! The tables and triggers `db` creates refer to the inferred graph and to
+//! the RDFS delete trigger, and retract conclusions the same way `rdfs`
+//! does, so these live below both.
+
+/// The named graph holding triples derived by inference rather than
+/// asserted, the graphs named under it hold other derived triples.
+pub const INFERRED_GRAPH: &str = "urn:triples:inferred";
+
+/// The trigger re-deriving RDFS inferences after a delete.
+pub const RDFS_DELETE_TRIGGER: &str = "trg_rdfs_delete";
+
+/// SQL true when the triple content held by the `subject`, `predicate`
+/// and `object` columns of `alias` is stored, in any graph
+fn stored(alias: &str) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM triples AS stored WHERE stored.subject = {alias}.subject \
+        AND stored.predicate = {alias}.predicate AND stored.object = {alias}.object)"
+    )
+}
+
+/// the statements retracting conclusions that have a premise matching
+/// `lost`, a condition on the `derivation_premises` row `lost`, which is
+/// no longer stored, and no derivation left whose premises all are.  The
+/// derivations that lost a premise are dropped too.
+#[must_use]
+pub fn retract_unsupported(lost: &str) -> String {
+    let lost_stored = stored("lost");
+    let used_stored = stored("used");
+    format!(
+        "DELETE FROM triples WHERE id IN (
+            SELECT derivations.triple FROM derivation_premises AS lost
+            JOIN derivations ON lost.derivation = derivations.id
+            WHERE ({lost}) AND NOT {lost_stored}
+        ) AND NOT EXISTS (
+            SELECT 1 FROM derivations AS other
+            WHERE other.triple = triples.id AND NOT EXISTS (
+                SELECT 1 FROM derivation_premises AS used
+                WHERE used.derivation = other.id AND NOT {used_stored}
+            )
+        );
+        DELETE FROM derivations WHERE id IN (
+            SELECT lost.derivation FROM derivation_premises AS lost
+            WHERE ({lost}) AND NOT {lost_stored}
+        );
+        DELETE FROM derivation_premises WHERE derivation IN (
+            SELECT lost.derivation FROM derivation_premises AS lost
+            WHERE ({lost}) AND NOT {lost_stored}
+        );\n"
+    )
+}
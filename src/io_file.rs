@@ -0,0 +1,307 @@
+/// functions in support of reading and writing named files
+///
+/// Inputs are given by glob, a final `.gz` or `.zst` extension compresses
+/// or decompresses a file on the fly, and the generic `import` and
+/// `export` commands pick a format from the extension before it.
+///
+use crate::csv_triples_file;
+use crate::data::TriplesError;
+use crate::db_api::DbApi;
+use crate::jsonl_file;
+use crate::jsonld::Context;
+use crate::jsonld_file;
+use crate::parquet_file;
+use crate::rdfxml_file;
+use crate::ttl_file;
+use crate::turtle_writer::TurtleOptions;
+use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
+use async_compression::tokio::write::{GzipEncoder, ZstdEncoder};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+use tokio::fs::File;
+use tokio::io::{stdout, AsyncBufRead, AsyncWrite, AsyncWriteExt, BufReader, BufWriter, Stdout};
+use tracing::{info, trace};
+
+/// a reader over a file's decompressed content
+pub type Input = Box<dyn AsyncBufRead + Unpin + Send>;
+
+/// a writer compressing into a file, `shutdown` it to finish the file
+pub type Output = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// the data formats told apart by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// `.ttl` and `.turtle`
+    Turtle,
+    /// `.nt`, read with the N-Triples reader
+    NTriples,
+    /// `.csv` with one subject, predicate, object triple per line
+    TriplesCsv,
+    /// `.jsonld`
+    JsonLd,
+    /// `.jsonl`, one subject per line
+    JsonLines,
+    /// `.rdf` and `.owl`
+    RdfXml,
+    /// `.parquet`, a long table of triples, written only
+    Parquet,
+}
+
+/// compression told apart by a final `.gz` or `.zst` extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+impl Compression {
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        match extension(path).as_deref() {
+            Some("gz" | "gzip") => Self::Gzip,
+            Some("zst" | "zstd") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+}
+
+impl FileFormat {
+    /// the format named by the extension before any compression extension,
+    /// `data.ttl.gz` is turtle
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let path = match Compression::from_path(path) {
+            Compression::None => path,
+            _ => Path::new(path.file_stem()?),
+        };
+        match extension(path).as_deref() {
+            Some("ttl" | "turtle") => Some(Self::Turtle),
+            Some("nt") => Some(Self::NTriples),
+            Some("csv") => Some(Self::TriplesCsv),
+            Some("jsonld") => Some(Self::JsonLd),
+            Some("jsonl") => Some(Self::JsonLines),
+            Some("rdf" | "owl") => Some(Self::RdfXml),
+            Some("parquet") => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// the files matching each glob pattern, in pattern order then path order
+///
+/// # Errors
+///
+/// return `Err` if a pattern is malformed or matches no files
+pub fn expand_inputs(patterns: &[String]) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        let mut matches = glob::glob(pattern)?.collect::<Result<Vec<_>, _>>()?;
+        if matches.is_empty() {
+            return Err(Box::new(TriplesError::NoMatchingFiles {
+                pattern: pattern.clone(),
+            }));
+        }
+        matches.sort();
+        paths.append(&mut matches);
+    }
+    Ok(paths)
+}
+
+/// open a file for reading, decompressing it if its extension says so
+///
+/// # Errors
+///
+/// return `Err` if the file can not be opened
+pub async fn open(path: &Path) -> std::io::Result<Input> {
+    trace!("open {}", path.display());
+    let file = BufReader::new(File::open(path).await?);
+    Ok(match Compression::from_path(path) {
+        Compression::None => Box::new(file),
+        Compression::Gzip => {
+            let mut decoder = GzipDecoder::new(file);
+            decoder.multiple_members(true);
+            Box::new(BufReader::new(decoder))
+        }
+        Compression::Zstd => {
+            let mut decoder = ZstdDecoder::new(file);
+            decoder.multiple_members(true);
+            Box::new(BufReader::new(decoder))
+        }
+    })
+}
+
+/// create a file for writing, compressing it if its extension says so
+///
+/// # Errors
+///
+/// return `Err` if the file can not be created
+pub async fn create(path: &Path) -> std::io::Result<Output> {
+    trace!("create {}", path.display());
+    let file = BufWriter::new(File::create(path).await?);
+    Ok(match Compression::from_path(path) {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(GzipEncoder::new(file)),
+        Compression::Zstd => Box::new(ZstdEncoder::new(file)),
+    })
+}
+
+/// stdout that flushes on `shutdown`, which tokio's does not, so a write
+/// still in flight is not lost when the process exits
+struct FlushingStdout(Stdout);
+
+impl AsyncWrite for FlushingStdout {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+}
+
+/// the file at `path`, or stdout when there is none
+///
+/// # Errors
+///
+/// return `Err` if the file can not be created
+pub async fn output(path: Option<&str>) -> std::io::Result<Output> {
+    match path {
+        Some(path) => create(Path::new(path)).await,
+        None => Ok(Box::new(FlushingStdout(stdout()))),
+    }
+}
+
+fn format_of(path: &Path) -> Result<FileFormat, TriplesError> {
+    FileFormat::from_path(path).ok_or_else(|| TriplesError::UnknownFormat {
+        path: path.display().to_string(),
+    })
+}
+
+/// load every file matching `patterns`, each in the format its extension
+/// names, with the default options of that format
+///
+/// # Errors
+///
+/// return `Err` if a file can not be matched, read or loaded
+pub async fn import(patterns: &[String], db_api: &DbApi) -> Result<(), Box<dyn std::error::Error>> {
+    let paths = expand_inputs(patterns)?;
+    // check every format before loading anything
+    let formats = paths
+        .iter()
+        .map(|path| format_of(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(path) = paths
+        .iter()
+        .zip(&formats)
+        .find_map(|(path, format)| (*format == FileFormat::Parquet).then_some(path))
+    {
+        return Err(Box::new(TriplesError::NotImplemented {
+            trace: format!("importing {}", path.display()),
+        }));
+    }
+
+    for (path, format) in paths.iter().zip(formats) {
+        info!("importing {}", path.display());
+        let reader = open(path).await?;
+        match format {
+            FileFormat::Turtle => ttl_file::import_turtle(reader, db_api).await?,
+            FileFormat::NTriples => ttl_file::import_ntriples(reader, db_api).await?,
+            FileFormat::TriplesCsv => {
+                csv_triples_file::import_csv(reader, None, None, false, db_api).await?;
+            }
+            FileFormat::JsonLd => {
+                jsonld_file::import_jsonld(reader, &Context::new(), db_api).await?;
+            }
+            FileFormat::JsonLines => {
+                jsonl_file::import_jsonl(reader, None, None, db_api).await?;
+            }
+            FileFormat::RdfXml => {
+                let base = rdfxml_file::file_base(path)?;
+                rdfxml_file::import_rdfxml(reader, &base, db_api).await?;
+            }
+            FileFormat::Parquet => unreachable!("rejected above"),
+        }
+    }
+    Ok(())
+}
+
+/// write the entire db to `path` in the format its extension names, with
+/// full IRIs so the file loads back unchanged
+///
+/// # Errors
+///
+/// return `Err` if the file can not be written
+pub async fn export(path: &Path, db_api: &DbApi) -> Result<(), Box<dyn std::error::Error>> {
+    let format = format_of(path)?;
+    let mut out = create(path).await?;
+    match format {
+        FileFormat::Turtle => {
+            ttl_file::export_turtle(&mut out, false, None, TurtleOptions::default(), db_api)
+                .await?;
+        }
+        FileFormat::NTriples => ttl_file::export_ntriples(&mut out, db_api).await?,
+        FileFormat::TriplesCsv => {
+            csv_triples_file::export_csv(&mut out, true, false, db_api).await?;
+        }
+        FileFormat::JsonLd => jsonld_file::export_jsonld(&mut out, None, db_api).await?,
+        FileFormat::JsonLines => jsonl_file::export_jsonl(&mut out, true, false, db_api).await?,
+        FileFormat::RdfXml => rdfxml_file::export_rdfxml(&mut out, db_api).await?,
+        FileFormat::Parquet => {
+            parquet_file::export_long(&mut out, parquet_file::DEFAULT_BATCH_SIZE, db_api).await?;
+        }
+    }
+    out.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_from_extensions() {
+        let format = |path: &str| FileFormat::from_path(Path::new(path));
+        assert_eq!(format("data.ttl"), Some(FileFormat::Turtle));
+        assert_eq!(format("data.TTL.gz"), Some(FileFormat::Turtle));
+        assert_eq!(format("dir.d/data.nt.zst"), Some(FileFormat::NTriples));
+        assert_eq!(format("triples.csv"), Some(FileFormat::TriplesCsv));
+        assert_eq!(format("data.jsonld"), Some(FileFormat::JsonLd));
+        assert_eq!(format("ontology.owl.gz"), Some(FileFormat::RdfXml));
+        assert_eq!(format("subjects.jsonl"), Some(FileFormat::JsonLines));
+        assert_eq!(format("triples.parquet"), Some(FileFormat::Parquet));
+        assert_eq!(format("data.json"), None);
+        assert_eq!(format("data.gz"), None);
+
+        assert_eq!(
+            Compression::from_path(Path::new("a.csv.gz")),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("a.ttl.zst")),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("a.ttl")),
+            Compression::None
+        );
+    }
+}
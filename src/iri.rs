@@ -0,0 +1,278 @@
+/// splitting IRIs into a namespace and a local name for Turtle prefixed
+/// names, and joining them back
+///
+/// Local names follow the `PN_LOCAL` rule of
+/// <https://www.w3.org/TR/turtle/#grammar-production-PN_LOCAL>, without the
+/// `:` and `%` escapes that the line parser does not read; names needing
+/// them are written as full `<iri>` references instead.
+///
+/// For every `split(iri) == Some((ns, local))`, `join(ns, local) == iri`.
+///
+const DELIMITERS: [char; 3] = ['#', '/', ':'];
+
+/// split after the last `#`, `/` or `:`, if what follows is a valid local
+/// name.  The local name may be empty, as in `ex:`.
+#[must_use]
+pub fn split(iri: &str) -> Option<(&str, &str)> {
+    let idx = iri.rfind(DELIMITERS)?;
+    let (namespace, local) = iri.split_at(idx + 1);
+    is_local_name(local).then_some((namespace, local))
+}
+
+/// the IRI a prefixed name expands to
+#[must_use]
+pub fn join(namespace: &str, local: &str) -> String {
+    format!("{namespace}{local}")
+}
+
+/// a namespace given with or without its final delimiter, as the
+/// `--subject-default-ns` options take it, `/` is added when it has none
+#[must_use]
+pub fn as_namespace(base: &str) -> String {
+    if base.ends_with(DELIMITERS) {
+        base.to_string()
+    } else {
+        format!("{base}/")
+    }
+}
+
+/// the namespace of an IRI, `None` if it has no valid local name
+#[must_use]
+pub fn namespace(iri: &str) -> Option<&str> {
+    split(iri).map(|(namespace, _)| namespace)
+}
+
+/// the local name of an IRI, or the whole IRI if it has no valid one
+#[must_use]
+pub fn local_name(iri: &str) -> &str {
+    split(iri).map_or(iri, |(_, local)| local)
+}
+
+/// the scheme, authority and path of an IRI, without query or fragment
+fn components(iri: &str) -> (Option<&str>, Option<&str>, &str) {
+    let (scheme, rest) = match iri.split_once(':') {
+        Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme), rest),
+        _ => (None, iri),
+    };
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    match rest.strip_prefix("//") {
+        Some(rest) => {
+            let idx = rest.find('/').unwrap_or(rest.len());
+            (scheme, Some(&rest[..idx]), &rest[idx..])
+        }
+        None => (scheme, None, rest),
+    }
+}
+
+fn is_scheme(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// `path` with `.` and `..` segments removed
+fn remove_dot_segments(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').collect();
+    let mut output: Vec<&str> = Vec::new();
+    for (idx, segment) in segments.iter().enumerate() {
+        let last = idx == segments.len() - 1;
+        match *segment {
+            "." => {}
+            ".." => {
+                if output.len() > 1 || output.first().is_some_and(|s| !s.is_empty()) {
+                    output.pop();
+                }
+            }
+            segment => {
+                output.push(segment);
+                continue;
+            }
+        }
+        if last {
+            output.push("");
+        }
+    }
+    output.join("/")
+}
+
+/// resolve a relative reference against a base IRI, RFC 3986 section 5.2
+#[must_use]
+pub fn resolve(base: &str, reference: &str) -> String {
+    let (path, suffix) = reference
+        .find(['?', '#'])
+        .map_or((reference, ""), |idx| reference.split_at(idx));
+    if let Some((scheme, _)) = reference.split_once(':') {
+        if is_scheme(scheme) {
+            return reference.to_string();
+        }
+    }
+
+    let (scheme, authority, base_path) = components(base);
+    let scheme = scheme.map(|s| format!("{s}:")).unwrap_or_default();
+    let authority_part = authority.map(|a| format!("//{a}")).unwrap_or_default();
+
+    if reference.starts_with("//") {
+        return format!("{scheme}{reference}");
+    }
+    if path.is_empty() {
+        let without_fragment = base.split('#').next().unwrap_or(base);
+        return if suffix.starts_with('?') {
+            format!(
+                "{}{suffix}",
+                without_fragment.split('?').next().unwrap_or(base)
+            )
+        } else {
+            format!("{without_fragment}{suffix}")
+        };
+    }
+    let merged = if path.starts_with('/') {
+        path.to_string()
+    } else if authority.is_some() && base_path.is_empty() {
+        format!("/{path}")
+    } else {
+        match base_path.rfind('/') {
+            Some(idx) => format!("{}{path}", &base_path[..=idx]),
+            None => path.to_string(),
+        }
+    };
+    format!(
+        "{scheme}{authority_part}{}{suffix}",
+        remove_dot_segments(&merged)
+    )
+}
+
+const fn is_pn_chars_base(c: char) -> bool {
+    matches!(c,
+        'A'..='Z'
+        | 'a'..='z'
+        | '\u{00C0}'..='\u{00D6}'
+        | '\u{00D8}'..='\u{00F6}'
+        | '\u{00F8}'..='\u{02FF}'
+        | '\u{0370}'..='\u{037D}'
+        | '\u{037F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+const fn is_pn_chars_u(c: char) -> bool {
+    is_pn_chars_base(c) || c == '_'
+}
+
+const fn is_pn_chars(c: char) -> bool {
+    is_pn_chars_u(c)
+        || matches!(c,
+            '-' | '0'..='9' | '\u{00B7}' | '\u{0300}'..='\u{036F}' | '\u{203F}'..='\u{2040}')
+}
+
+/// true if `local` can follow `prefix:` unescaped
+#[must_use]
+pub fn is_local_name(local: &str) -> bool {
+    let mut chars = local.chars();
+    let Some(first) = chars.next() else {
+        return true;
+    };
+    if !(is_pn_chars_u(first) || first.is_ascii_digit()) {
+        return false;
+    }
+    let rest = chars.as_str();
+    rest.chars().all(|c| is_pn_chars(c) || c == '.') && !rest.ends_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completes_namespaces() {
+        assert_eq!(
+            as_namespace("http://example.com/id"),
+            "http://example.com/id/"
+        );
+        assert_eq!(
+            as_namespace("http://example.com/id/"),
+            "http://example.com/id/"
+        );
+        assert_eq!(
+            as_namespace("http://example.com/id#"),
+            "http://example.com/id#"
+        );
+        assert_eq!(
+            join(&as_namespace("http://example.com/id#"), "room1"),
+            "http://example.com/id#room1"
+        );
+    }
+
+    #[test]
+    fn splits_hash_and_slash_iris() {
+        assert_eq!(
+            split("http://qudt.org/vocab/unit/DEG_C"),
+            Some(("http://qudt.org/vocab/unit/", "DEG_C"))
+        );
+        assert_eq!(
+            split("https://brickschema.org/schema/Brick#Air_Temperature_Sensor"),
+            Some((
+                "https://brickschema.org/schema/Brick#",
+                "Air_Temperature_Sensor"
+            ))
+        );
+        assert_eq!(
+            split("http://k8p.navicore.tech/property/k8p_type"),
+            Some(("http://k8p.navicore.tech/property/", "k8p_type"))
+        );
+        assert_eq!(
+            split("urn:isbn:0451450523"),
+            Some(("urn:isbn:", "0451450523"))
+        );
+        assert_eq!(
+            split("http://example.com/"),
+            Some(("http://example.com/", ""))
+        );
+    }
+
+    #[test]
+    fn join_inverts_split() {
+        for iri in [
+            "http://qudt.org/vocab/unit/DEG_C",
+            "http://example#Room_101",
+            "http://example.com/a.b",
+            "http://example.com/caf\u{e9}",
+        ] {
+            let (namespace, local) = split(iri).unwrap();
+            assert_eq!(join(namespace, local), iri);
+        }
+    }
+
+    #[test]
+    fn resolves_relative_references() {
+        let base = "http://example.com/a/b/c.rdf#frag";
+        assert_eq!(resolve(base, "#x"), "http://example.com/a/b/c.rdf#x");
+        assert_eq!(resolve(base, ""), "http://example.com/a/b/c.rdf");
+        assert_eq!(resolve(base, "d"), "http://example.com/a/b/d");
+        assert_eq!(resolve(base, "../d"), "http://example.com/a/d");
+        assert_eq!(resolve(base, "./d/."), "http://example.com/a/b/d/");
+        assert_eq!(resolve(base, "/d"), "http://example.com/d");
+        assert_eq!(resolve(base, "//other.org/x"), "http://other.org/x");
+        assert_eq!(resolve(base, "urn:x:y"), "urn:x:y");
+        assert_eq!(resolve("http://example.com", "x"), "http://example.com/x");
+    }
+
+    #[test]
+    fn rejects_invalid_local_names() {
+        assert_eq!(split("http://example.com/a."), None);
+        assert_eq!(split("http://example.com/-a"), None);
+        assert_eq!(split("http://example.com/q?x=1"), None);
+        assert_eq!(split("http://example.com/50%25"), None);
+        assert_eq!(split("no-separator"), None);
+        assert!(is_local_name("3rd_floor"));
+        assert!(is_local_name("a.b-c"));
+        assert_eq!(
+            local_name("http://example.com/q?x=1"),
+            "http://example.com/q?x=1"
+        );
+    }
+}
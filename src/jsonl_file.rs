@@ -0,0 +1,281 @@
+/// functions in support of JSON Lines file handling, one subject per line
+/// as `{"@id": subject, predicate: [objects]}` for tools like jq and Spark
+///
+/// Names are exported in full or as their local names, which an import
+/// expands again against default namespaces.  Typed lines keep numbers and
+/// booleans as JSON values and nodes as `{"@id": ...}` objects.
+///
+use crate::csv::get_display_name;
+use crate::data::{is_iri, RdfName, Subject, Term, TriplesError};
+use crate::db_api::DbApi;
+use crate::iri;
+use serde_json::{Map, Number, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::trace;
+
+/// blank node labels are kept whole, other names shortened like csv
+fn display_name(name: &RdfName, export_ns_name: bool) -> Result<String, TriplesError> {
+    let name_string = name.to_string();
+    if name_string.starts_with("_:") {
+        Ok(name_string)
+    } else {
+        get_display_name(name, export_ns_name)
+    }
+}
+
+/// an object as JSON, with `typed` IRIs and blank nodes as `{"@id": ...}`
+/// and numbers and booleans as JSON scalars when they read back unchanged
+fn object_value(object: &str, typed: bool) -> Value {
+    if !typed {
+        return Value::String(object.to_string());
+    }
+    match Term::from_object(object) {
+        Term::Iri(_) | Term::BlankNode(_) => {
+            let mut map = Map::new();
+            map.insert("@id".to_string(), Value::String(object.to_string()));
+            Value::Object(map)
+        }
+        Term::Literal { .. } => {
+            let number = object
+                .parse::<i64>()
+                .ok()
+                .map(Number::from)
+                .or_else(|| object.parse::<f64>().ok().and_then(Number::from_f64))
+                .filter(|n| n.to_string() == object);
+            match (number, object) {
+                (Some(number), _) => Value::Number(number),
+                (None, "true") => Value::Bool(true),
+                (None, "false") => Value::Bool(false),
+                (None, _) => Value::String(object.to_string()),
+            }
+        }
+    }
+}
+
+/// one line for `subject`, predicates and objects in sorted order
+///
+/// # Errors
+///
+/// return `Err` if a name can not be shortened
+pub fn subject_line(
+    subject: &Subject,
+    export_ns_name: bool,
+    typed: bool,
+) -> Result<String, TriplesError> {
+    let mut pairs: Vec<(String, Vec<&String>)> = Vec::new();
+    for (predicate, objects) in subject.predicate_object_pairs() {
+        let mut objects: Vec<&String> = objects.iter().collect();
+        objects.sort();
+        pairs.push((display_name(predicate, export_ns_name)?, objects));
+    }
+    pairs.sort();
+
+    let mut map = Map::new();
+    map.insert(
+        "@id".to_string(),
+        Value::String(display_name(subject.name(), export_ns_name)?),
+    );
+    for (predicate, objects) in pairs {
+        // short names shared by predicates of different namespaces merge
+        let values = map
+            .entry(predicate)
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(values) = values {
+            values.extend(objects.into_iter().map(|o| object_value(o, typed)));
+        }
+    }
+    Ok(Value::Object(map).to_string())
+}
+
+/// write JSON Lines of all db entries to `out`, stdout for the cli
+///
+/// # Errors
+///
+/// return `Err` on db read or write errors
+pub async fn export_jsonl<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    export_ns_name: bool,
+    typed: bool,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("export_jsonl");
+    for name in &db_api.get_subject_names().await? {
+        if let Some(subject) = db_api.query(name).await? {
+            let line = subject_line(&subject, export_ns_name, typed)?;
+            out.write_all(format!("{line}\n").as_bytes()).await?;
+        }
+    }
+    out.flush().await?;
+    Ok(())
+}
+
+/// a full name, short names are placed in `default_ns`
+fn qualify(name: &str, default_ns: Option<&str>) -> Result<String, TriplesError> {
+    if name.starts_with("_:") || is_iri(name) {
+        return Ok(name.to_string());
+    }
+    match default_ns {
+        Some(ns) => Ok(iri::join(&iri::as_namespace(ns), name)),
+        None => Err(TriplesError::InvalidIRI {
+            uri: name.to_string(),
+        }),
+    }
+}
+
+/// the stored form of a JSON value, `{"@id": ...}` naming a subject
+fn object_string(
+    value: &Value,
+    default_subject_ns: Option<&str>,
+) -> Result<Option<String>, TriplesError> {
+    Ok(match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::Object(map) => match map.get("@id") {
+            Some(Value::String(id)) => Some(qualify(id, default_subject_ns)?),
+            _ => {
+                return Err(TriplesError::ParseError {
+                    reason: format!("object value without an @id: {value}"),
+                })
+            }
+        },
+        Value::Array(_) => {
+            return Err(TriplesError::ParseError {
+                reason: format!("nested array value: {value}"),
+            })
+        }
+    })
+}
+
+/// read one subject from a line
+///
+/// # Errors
+///
+/// return `Err` if the line is not a JSON object with a string `@id`, or
+/// a short name has no namespace to go in
+pub fn parse_subject_line(
+    line: &str,
+    default_subject_ns: Option<&str>,
+    default_predicate_ns: Option<&str>,
+) -> Result<Subject, TriplesError> {
+    let value: Value = serde_json::from_str(line).map_err(|e| TriplesError::ParseError {
+        reason: e.to_string(),
+    })?;
+    let Value::Object(map) = value else {
+        return Err(TriplesError::ParseError {
+            reason: "line is not a JSON object".to_string(),
+        });
+    };
+    let Some(Value::String(id)) = map.get("@id") else {
+        return Err(TriplesError::NoSubjectDeclaired);
+    };
+
+    let mut subject = Subject::new(RdfName::new(qualify(id, default_subject_ns)?));
+    for (predicate, values) in &map {
+        if predicate == "@id" {
+            continue;
+        }
+        let predicate = RdfName::new(qualify(predicate, default_predicate_ns)?);
+        let values = match values {
+            Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            if let Some(object) = object_string(value, default_subject_ns)? {
+                subject.add(predicate.clone(), object);
+            }
+        }
+    }
+    Ok(subject)
+}
+
+/// read JSON Lines from `reader`, stdin for the cli, and load db
+///
+/// # Errors
+///
+/// return `Err` naming the line if any entry can not be loaded
+pub async fn import_jsonl<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+    default_subject_ns: Option<&str>,
+    default_predicate_ns: Option<&str>,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("import_jsonl");
+    let mut tx = db_api.begin_txn().await?;
+
+    let mut line = String::new();
+    let mut line_number = 0;
+    while reader.read_line(&mut line).await? != 0 {
+        line_number += 1;
+        if !line.trim().is_empty() {
+            let subject = parse_subject_line(&line, default_subject_ns, default_predicate_ns)
+                .map_err(|e| TriplesError::ParseError {
+                    reason: format!("line {line_number}: {e}"),
+                })?;
+            db_api.insert_into_txn(&mut tx, &subject, None).await?;
+        }
+        line.clear();
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_values_read_back_unchanged() {
+        assert_eq!(object_value("42", true), Value::from(42));
+        assert_eq!(object_value("21.5", true), Value::from(21.5));
+        assert_eq!(object_value("007", true), Value::from("007"));
+        assert_eq!(object_value("1e3", true), Value::from("1e3"));
+        assert_eq!(object_value("true", true), Value::from(true));
+        assert_eq!(object_value("42", false), Value::from("42"));
+        assert_eq!(
+            object_value("http://example.com/x", true),
+            serde_json::json!({"@id": "http://example.com/x"})
+        );
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        let mut subject = Subject::new(RdfName::new("http://example.com/id/room1".to_string()));
+        subject.add(
+            RdfName::new("http://example.com/data/area".to_string()),
+            "12.5".to_string(),
+        );
+        subject.add(
+            RdfName::new("http://example.com/data/next".to_string()),
+            "http://example.com/id/room2".to_string(),
+        );
+
+        let line = subject_line(&subject, false, true).unwrap();
+        assert_eq!(
+            line,
+            r#"{"@id":"room1","area":[12.5],"next":[{"@id":"http://example.com/id/room2"}]}"#
+        );
+
+        let read = parse_subject_line(
+            &line,
+            Some("http://example.com/id"),
+            Some("http://example.com/data/"),
+        )
+        .unwrap();
+        assert_eq!(read.name(), subject.name());
+        assert_eq!(ntriples_sorted(&read), ntriples_sorted(&subject));
+
+        assert!(parse_subject_line(&line, None, None).is_err());
+    }
+
+    fn ntriples_sorted(subject: &Subject) -> Vec<String> {
+        let mut lines: Vec<String> = crate::turtle_writer::ntriples(subject)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        lines.sort();
+        lines
+    }
+}
@@ -0,0 +1,775 @@
+/// JSON-LD 1.1 expansion into subjects and compaction back out
+///
+/// Contexts come from the document, from a local context supplied by the
+/// caller, or through a loader for referenced ones; nothing is fetched
+/// from the network here.  Supported are term definitions with `@id`,
+/// `@type` (`@id`, `@vocab` or a datatype) and `@container: @list`,
+/// compact IRIs, `@vocab`, `@base`, embedded and referenced nodes,
+/// `@graph`, `@reverse`, value objects and lists.  Datatypes and language
+/// tags are not stored, a literal keeps its lexical form only.
+///
+/// Written documents are framed as one node object per subject in
+/// `@graph`, compacted with the given context.
+///
+use crate::data::{is_iri, parse_error, BlankNodes, RdfName, Subject, TriplesError};
+use crate::iri;
+use crate::prefixes::PrefixMap;
+use crate::vocab::{RDF_FIRST, RDF_NIL, RDF_REST, RDF_TYPE};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// resolves a context referenced by IRI to its JSON
+pub type ContextLoader<'a> = &'a dyn Fn(&str) -> Result<Value, TriplesError>;
+
+/// a loader for callers with no referenced contexts to offer
+///
+/// # Errors
+///
+/// return `Err` naming the reference, always
+pub fn no_remote_contexts(reference: &str) -> Result<Value, TriplesError> {
+    Err(parse_error(format!(
+        "context {reference} is not available locally"
+    )))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TermDefinition {
+    id: String,
+    type_mapping: Option<String>,
+    list: bool,
+}
+
+/// an active context: term definitions, `@vocab` and `@base`, plus the
+/// JSON it was read from so written documents can carry it unchanged
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context {
+    terms: BTreeMap<String, TermDefinition>,
+    vocab: Option<String>,
+    base: Option<String>,
+    source: Vec<Value>,
+}
+
+impl Context {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a context whose terms are the prefixes of `prefixes`
+    #[must_use]
+    pub fn from_prefixes(prefixes: &PrefixMap) -> Self {
+        let source: Map<String, Value> = prefixes
+            .iter()
+            .map(|(prefix, namespace)| (prefix.to_string(), json!(namespace)))
+            .collect();
+        // a map of plain strings needs no loader
+        Self::new()
+            .merge(&Value::Object(source), &no_remote_contexts)
+            .unwrap_or_default()
+    }
+
+    /// the `@context` value to write with compacted output
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match self.source.as_slice() {
+            [] => json!({}),
+            [one] => one.clone(),
+            many => Value::Array(many.to_vec()),
+        }
+    }
+
+    /// the prefixes this context defines, terms mapped to an IRI ending in
+    /// `/` or `#`
+    #[must_use]
+    pub fn prefixes(&self) -> PrefixMap {
+        let mut prefixes = PrefixMap::new();
+        for (term, definition) in &self.terms {
+            if definition.id.ends_with(['/', '#']) && !term.contains(':') {
+                prefixes.insert(term, &definition.id);
+            }
+        }
+        prefixes
+    }
+
+    /// this context updated by a local `@context` value
+    ///
+    /// # Errors
+    ///
+    /// return `Err` if the value is not a valid context or a referenced
+    /// context can not be loaded
+    pub fn merge(&self, value: &Value, load: ContextLoader) -> Result<Self, TriplesError> {
+        self.merge_checked(value, load, &mut HashSet::new())
+    }
+
+    fn merge_checked(
+        &self,
+        value: &Value,
+        load: ContextLoader,
+        loading: &mut HashSet<String>,
+    ) -> Result<Self, TriplesError> {
+        match value {
+            Value::Null => Ok(Self::new()),
+            Value::Array(values) => {
+                let mut context = self.clone();
+                for value in values {
+                    context = context.merge_checked(value, load, loading)?;
+                }
+                Ok(context)
+            }
+            Value::String(reference) => {
+                if !loading.insert(reference.clone()) {
+                    return Err(parse_error(format!("recursive context {reference}")));
+                }
+                let document = load(reference)?;
+                let inner = document.get("@context").unwrap_or(&document);
+                let context = self.merge_checked(inner, load, loading)?;
+                loading.remove(reference);
+                Ok(context)
+            }
+            Value::Object(definitions) => self.define_all(definitions),
+            other => Err(parse_error(format!("invalid context {other}"))),
+        }
+    }
+
+    fn define_all(&self, definitions: &Map<String, Value>) -> Result<Self, TriplesError> {
+        let mut context = self.clone();
+        if let Some(base) = definitions.get("@base") {
+            context.base = base.as_str().map(str::to_string);
+        }
+        if let Some(vocab) = definitions.get("@vocab") {
+            context.vocab = vocab
+                .as_str()
+                .map(|vocab| context.expand_iri(vocab, true).unwrap_or_default());
+        }
+
+        let mut defined = HashMap::new();
+        for term in definitions.keys().filter(|key| !key.starts_with('@')) {
+            context.define(term, definitions, &mut defined)?;
+        }
+        context.source.push(Value::Object(definitions.clone()));
+        Ok(context)
+    }
+
+    /// define `term` after any term its definition depends on, `defined`
+    /// is false while a definition is in progress to catch cycles
+    fn define(
+        &mut self,
+        term: &str,
+        definitions: &Map<String, Value>,
+        defined: &mut HashMap<String, bool>,
+    ) -> Result<(), TriplesError> {
+        match defined.get(term) {
+            Some(true) => return Ok(()),
+            Some(false) => return Err(parse_error(format!("cyclic term definition {term}"))),
+            None => {}
+        }
+        defined.insert(term.to_string(), false);
+
+        let definition = &definitions[term];
+        let (id, type_mapping, list) = match definition {
+            Value::Null => {
+                self.terms.remove(term);
+                defined.insert(term.to_string(), true);
+                return Ok(());
+            }
+            Value::String(id) => (Some(id.as_str()), None, false),
+            Value::Object(fields) => (
+                fields.get("@id").and_then(Value::as_str),
+                fields.get("@type").and_then(Value::as_str),
+                fields.get("@container").is_some_and(|c| {
+                    c == "@list" || c.as_array().is_some_and(|a| a.contains(&json!("@list")))
+                }),
+            ),
+            other => {
+                return Err(parse_error(format!(
+                    "invalid definition of {term}: {other}"
+                )))
+            }
+        };
+
+        // terms this definition refers to must be defined first
+        for reference in [id, type_mapping].into_iter().flatten() {
+            let prefix = reference.split_once(':').map_or(reference, |(p, _)| p);
+            if prefix != term && definitions.contains_key(prefix) {
+                self.define(prefix, definitions, defined)?;
+            }
+        }
+
+        let id = match id {
+            Some(id) => self.expand_iri(id, true),
+            // a compact IRI or absolute IRI term names itself
+            None if term.contains(':') => self.expand_iri(term, true),
+            None => self.vocab.as_ref().map(|vocab| format!("{vocab}{term}")),
+        }
+        .ok_or_else(|| parse_error(format!("term {term} has no IRI")))?;
+        let type_mapping = type_mapping.map(|t| match t {
+            "@id" | "@vocab" => t.to_string(),
+            t => self.expand_iri(t, true).unwrap_or_else(|| t.to_string()),
+        });
+
+        self.terms.insert(
+            term.to_string(),
+            TermDefinition {
+                id,
+                type_mapping,
+                list,
+            },
+        );
+        defined.insert(term.to_string(), true);
+        Ok(())
+    }
+
+    /// expand a term, compact IRI or relative IRI, `vocab` for property and
+    /// type positions where terms and `@vocab` apply
+    fn expand_iri(&self, value: &str, vocab: bool) -> Option<String> {
+        if value.starts_with('@') {
+            return Some(value.to_string());
+        }
+        if vocab {
+            if let Some(definition) = self.terms.get(value) {
+                return Some(definition.id.clone());
+            }
+        }
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if prefix == "_" || suffix.starts_with("//") {
+                return Some(value.to_string());
+            }
+            if let Some(definition) = self.terms.get(prefix) {
+                return Some(format!("{}{suffix}", definition.id));
+            }
+            return Some(value.to_string());
+        }
+        if vocab {
+            return self.vocab.as_ref().map(|v| format!("{v}{value}"));
+        }
+        Some(match &self.base {
+            Some(base) => iri::resolve(base, value),
+            None => value.to_string(),
+        })
+    }
+
+    /// the shortest form of an IRI: a term or `@vocab` relative name in
+    /// `vocab` positions, else a compact IRI, else the IRI itself
+    fn compact_iri(&self, iri: &str, vocab: bool) -> String {
+        if iri.starts_with("_:") {
+            return iri.to_string();
+        }
+        if vocab {
+            if let Some((term, _)) = self
+                .terms
+                .iter()
+                .find(|(_, d)| d.id == iri && !d.list && d.type_mapping.is_none())
+                .or_else(|| self.terms.iter().find(|(_, d)| d.id == iri && !d.list))
+            {
+                return term.clone();
+            }
+            if let Some(local) = self
+                .vocab
+                .as_ref()
+                .and_then(|v| iri.strip_prefix(v.as_str()))
+            {
+                if !local.is_empty() && !local.contains(':') && !self.terms.contains_key(local) {
+                    return local.to_string();
+                }
+            }
+        }
+        self.terms
+            .iter()
+            .filter(|(term, _)| !term.contains(':'))
+            .filter_map(|(term, d)| {
+                iri.strip_prefix(d.id.as_str())
+                    .filter(|local| !local.is_empty() && !local.starts_with("//"))
+                    .map(|local| format!("{term}:{local}"))
+            })
+            .min_by_key(String::len)
+            .unwrap_or_else(|| iri.to_string())
+    }
+}
+
+/// collects triples grouped by subject in the order subjects are met
+struct Expander<'a> {
+    load: ContextLoader<'a>,
+    subjects: Vec<Subject>,
+    index: HashMap<String, usize>,
+    blank_nodes: BlankNodes,
+}
+
+fn as_list(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Null => Vec::new(),
+        item => vec![item],
+    }
+}
+
+impl Expander<'_> {
+    fn add(&mut self, subject: &str, predicate: &str, object: String) {
+        let idx = *self.index.entry(subject.to_string()).or_insert_with(|| {
+            self.subjects
+                .push(Subject::new(RdfName::new(subject.to_string())));
+            self.subjects.len() - 1
+        });
+        self.subjects[idx].add(RdfName::new(predicate.to_string()), object);
+    }
+
+    fn node_id(&mut self, id: &str, context: &Context) -> Result<String, TriplesError> {
+        let id = context
+            .expand_iri(id, false)
+            .ok_or_else(|| parse_error(format!("invalid @id {id}")))?;
+        Ok(if id.starts_with("_:") {
+            self.blank_nodes.label(&id)
+        } else {
+            id
+        })
+    }
+
+    fn property(key: &str, context: &Context) -> Option<String> {
+        context
+            .expand_iri(key, true)
+            .filter(|iri| iri.contains(':') && !iri.starts_with("_:"))
+    }
+
+    /// add the triples of a node object and return its subject
+    fn node(
+        &mut self,
+        node: &Map<String, Value>,
+        context: &Context,
+    ) -> Result<String, TriplesError> {
+        let merged;
+        let context = match node.get("@context") {
+            Some(local) => {
+                merged = context.merge(local, self.load)?;
+                &merged
+            }
+            None => context,
+        };
+        let id = match node.get("@id").map(|id| (id, id.as_str())) {
+            Some((_, Some(id))) => self.node_id(id, context)?,
+            Some((id, None)) => return Err(parse_error(format!("invalid @id {id}"))),
+            None => self.blank_nodes.fresh(),
+        };
+
+        for (key, value) in node {
+            match key.as_str() {
+                "@context" | "@id" | "@index" => {}
+                "@type" => {
+                    for item in as_list(value) {
+                        let iri = item
+                            .as_str()
+                            .and_then(|t| context.expand_iri(t, true))
+                            .ok_or_else(|| parse_error(format!("invalid @type {item}")))?;
+                        let iri = if iri.starts_with("_:") {
+                            self.blank_nodes.label(&iri)
+                        } else {
+                            iri
+                        };
+                        self.add(&id, RDF_TYPE, iri);
+                    }
+                }
+                "@graph" => {
+                    // named graphs are read into the default graph
+                    for item in as_list(value) {
+                        if let Value::Object(inner) = item {
+                            self.node(inner, context)?;
+                        }
+                    }
+                }
+                "@reverse" => {
+                    let Value::Object(properties) = value else {
+                        return Err(parse_error("@reverse must be an object"));
+                    };
+                    for (key, value) in properties {
+                        let Some(predicate) = Self::property(key, context) else {
+                            continue;
+                        };
+                        for object in self.values(value, key, context)? {
+                            self.add(&object, &predicate, id.clone());
+                        }
+                    }
+                }
+                key if key.starts_with('@') => {}
+                key => {
+                    // keys that do not expand to an IRI are dropped
+                    let Some(predicate) = Self::property(key, context) else {
+                        continue;
+                    };
+                    let is_list = context.terms.get(key).is_some_and(|d| d.list);
+                    let objects = if is_list {
+                        vec![self.list(&as_list(value), key, context)?]
+                    } else {
+                        self.values(value, key, context)?
+                    };
+                    for object in objects {
+                        self.add(&id, &predicate, object);
+                    }
+                }
+            }
+        }
+        Ok(id)
+    }
+
+    /// the objects a property value stands for
+    fn values(
+        &mut self,
+        value: &Value,
+        term: &str,
+        context: &Context,
+    ) -> Result<Vec<String>, TriplesError> {
+        let type_mapping = context
+            .terms
+            .get(term)
+            .and_then(|d| d.type_mapping.as_deref());
+        let mut objects = Vec::new();
+        for item in as_list(value) {
+            match item {
+                Value::String(text) => objects.push(match type_mapping {
+                    Some("@id") => self.node_id(text, context)?,
+                    Some("@vocab") => context
+                        .expand_iri(text, true)
+                        .unwrap_or_else(|| text.clone()),
+                    _ => text.clone(),
+                }),
+                Value::Number(_) | Value::Bool(_) => objects.push(item.to_string()),
+                Value::Array(items) => {
+                    for item in items {
+                        objects.extend(self.values(item, term, context)?);
+                    }
+                }
+                Value::Object(fields) => {
+                    if let Some(value) = fields.get("@value") {
+                        match value {
+                            Value::Null => {}
+                            Value::String(text) => objects.push(text.clone()),
+                            scalar => objects.push(scalar.to_string()),
+                        }
+                    } else if let Some(items) = fields.get("@list") {
+                        objects.push(self.list(&as_list(items), term, context)?);
+                    } else if let Some(items) = fields.get("@set") {
+                        objects.extend(self.values(items, term, context)?);
+                    } else {
+                        objects.push(self.node(fields, context)?);
+                    }
+                }
+                Value::Null => {}
+            }
+        }
+        Ok(objects)
+    }
+
+    /// an `rdf:first`/`rdf:rest` chain of the items, `rdf:nil` when empty
+    fn list(
+        &mut self,
+        items: &[&Value],
+        term: &str,
+        context: &Context,
+    ) -> Result<String, TriplesError> {
+        let mut objects = Vec::new();
+        for item in items {
+            objects.extend(self.values(item, term, context)?);
+        }
+        let mut rest = RDF_NIL.to_string();
+        for object in objects.into_iter().rev() {
+            let node = self.blank_nodes.fresh();
+            self.add(&node, RDF_FIRST, object);
+            self.add(&node, RDF_REST, rest);
+            rest = node;
+        }
+        Ok(rest)
+    }
+}
+
+/// the subjects of a JSON-LD document, read with `context` as the active
+/// context the document's own `@context` applies on top of
+///
+/// # Errors
+///
+/// return `Err` if the document is not valid JSON-LD or a referenced
+/// context can not be loaded
+pub fn expand(
+    document: &Value,
+    context: &Context,
+    load: ContextLoader,
+) -> Result<Vec<Subject>, TriplesError> {
+    let mut expander = Expander {
+        load,
+        subjects: Vec::new(),
+        index: HashMap::new(),
+        blank_nodes: BlankNodes::new(),
+    };
+
+    match document {
+        Value::Array(nodes) => {
+            for node in nodes {
+                let Value::Object(node) = node else {
+                    return Err(parse_error(format!("expected a node object, not {node}")));
+                };
+                expander.node(node, context)?;
+            }
+        }
+        Value::Object(node) => {
+            // a top level object holding only a context and a graph is not
+            // itself a node
+            let only_graph = node.contains_key("@graph")
+                && node.keys().all(|k| k == "@context" || k == "@graph");
+            if only_graph {
+                let context = match node.get("@context") {
+                    Some(local) => context.merge(local, load)?,
+                    None => context.clone(),
+                };
+                for item in as_list(&node["@graph"]) {
+                    if let Value::Object(inner) = item {
+                        expander.node(inner, &context)?;
+                    }
+                }
+            } else {
+                expander.node(node, context)?;
+            }
+        }
+        other => {
+            return Err(parse_error(format!(
+                "expected a JSON-LD document, not {other}"
+            )))
+        }
+    }
+    Ok(expander.subjects)
+}
+
+/// one subject as a compacted node object
+#[must_use]
+pub fn compact_subject(subject: &Subject, context: &Context) -> Value {
+    let mut node = Map::new();
+    node.insert(
+        "@id".to_string(),
+        json!(context.compact_iri(&subject.name().to_string(), false)),
+    );
+
+    let mut pairs: Vec<_> = subject.predicate_object_pairs().collect();
+    pairs.sort_by_key(|(predicate, _)| predicate.to_string());
+    for (predicate, objects) in pairs {
+        let predicate = predicate.to_string();
+        let mut objects: Vec<&String> = objects.iter().collect();
+        objects.sort();
+
+        if predicate == RDF_TYPE {
+            let types: Vec<Value> = objects
+                .iter()
+                .map(|t| json!(context.compact_iri(t, true)))
+                .collect();
+            node.insert("@type".to_string(), single_or_array(types));
+            continue;
+        }
+
+        let term = context.compact_iri(&predicate, true);
+        let type_mapping = context
+            .terms
+            .get(&term)
+            .and_then(|d| d.type_mapping.as_deref());
+        let values: Vec<Value> = objects
+            .into_iter()
+            .map(|object| {
+                let is_node = object.starts_with("_:") || is_iri(object);
+                match (is_node, type_mapping) {
+                    (true, Some("@id")) => json!(context.compact_iri(object, false)),
+                    (true, Some("@vocab")) => json!(context.compact_iri(object, true)),
+                    (true, _) => json!({ "@id": context.compact_iri(object, false) }),
+                    (false, Some("@id" | "@vocab")) => json!({ "@value": object }),
+                    (false, _) => json!(object),
+                }
+            })
+            .collect();
+        node.insert(term, single_or_array(values));
+    }
+    Value::Object(node)
+}
+
+fn single_or_array(mut values: Vec<Value>) -> Value {
+    if values.len() == 1 {
+        values.remove(0)
+    } else {
+        Value::Array(values)
+    }
+}
+
+/// a compacted document framed as one node object per subject
+#[must_use]
+pub fn compact(subjects: &[Subject], context: &Context) -> Value {
+    let graph: Vec<Value> = subjects
+        .iter()
+        .map(|subject| compact_subject(subject, context))
+        .collect();
+    json!({ "@context": context.to_value(), "@graph": graph })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triples(subjects: &[Subject]) -> Vec<(String, String, String)> {
+        let mut triples: Vec<_> = subjects
+            .iter()
+            .flat_map(|s| {
+                s.predicate_object_pairs().flat_map(move |(p, objects)| {
+                    objects
+                        .iter()
+                        .map(move |o| (s.name().to_string(), p.to_string(), o.clone()))
+                })
+            })
+            .collect();
+        triples.sort();
+        triples
+    }
+
+    fn context(value: Value) -> Context {
+        Context::new().merge(&value, &no_remote_contexts).unwrap()
+    }
+
+    #[test]
+    fn expands_terms_prefixes_and_vocab() {
+        let document = json!({
+            "@context": {
+                "@vocab": "http://example.com/vocab#",
+                "schema": "http://schema.org/",
+                "name": "schema:name",
+                "knows": { "@id": "schema:knows", "@type": "@id" }
+            },
+            "@id": "http://example.com/alice",
+            "@type": "schema:Person",
+            "name": "Alice",
+            "age": 42,
+            "knows": "http://example.com/bob",
+            "ignored:": null
+        });
+        let subjects = expand(&document, &Context::new(), &no_remote_contexts).unwrap();
+        assert_eq!(
+            triples(&subjects),
+            vec![
+                (
+                    "http://example.com/alice".to_string(),
+                    "http://example.com/vocab#age".to_string(),
+                    "42".to_string()
+                ),
+                (
+                    "http://example.com/alice".to_string(),
+                    "http://schema.org/knows".to_string(),
+                    "http://example.com/bob".to_string()
+                ),
+                (
+                    "http://example.com/alice".to_string(),
+                    "http://schema.org/name".to_string(),
+                    "Alice".to_string()
+                ),
+                (
+                    "http://example.com/alice".to_string(),
+                    RDF_TYPE.to_string(),
+                    "http://schema.org/Person".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn expands_nested_nodes_lists_and_graphs() {
+        let local = context(json!({ "ex": "http://example.com/" }));
+        let document = json!({
+            "@graph": [
+                {
+                    "@id": "ex:room",
+                    "ex:sensor": { "ex:unit": { "@id": "ex:DEG_C" } },
+                    "ex:order": { "@list": ["a", "b"] },
+                    "@reverse": { "ex:contains": { "@id": "ex:building" } }
+                }
+            ]
+        });
+        let subjects = expand(&document, &local, &no_remote_contexts).unwrap();
+        let triples = triples(&subjects);
+
+        let sensor = triples
+            .iter()
+            .find(|(_, p, _)| p == "http://example.com/sensor")
+            .map(|(_, _, o)| o.clone())
+            .unwrap();
+        assert!(sensor.starts_with("_:"));
+        assert!(triples.contains(&(
+            sensor,
+            "http://example.com/unit".to_string(),
+            "http://example.com/DEG_C".to_string()
+        )));
+        assert!(triples.contains(&(
+            "http://example.com/building".to_string(),
+            "http://example.com/contains".to_string(),
+            "http://example.com/room".to_string()
+        )));
+        let firsts: Vec<&str> = triples
+            .iter()
+            .filter(|(_, p, _)| p == RDF_FIRST)
+            .map(|(_, _, o)| o.as_str())
+            .collect();
+        assert_eq!(firsts.len(), 2);
+        assert!(triples
+            .iter()
+            .any(|(_, p, o)| p == RDF_REST && o == RDF_NIL));
+    }
+
+    #[test]
+    fn loads_referenced_contexts() {
+        let load = |reference: &str| -> Result<Value, TriplesError> {
+            assert_eq!(reference, "units.jsonld");
+            Ok(json!({ "@context": { "unit": "http://qudt.org/vocab/unit/" } }))
+        };
+        let document = json!({
+            "@context": "units.jsonld",
+            "@id": "unit:DEG_C",
+            "unit:symbol": "°C"
+        });
+        let subjects = expand(&document, &Context::new(), &load).unwrap();
+        assert_eq!(
+            subjects[0].name().to_string(),
+            "http://qudt.org/vocab/unit/DEG_C"
+        );
+
+        let document = json!({ "@context": "https://schema.org/", "@id": "x" });
+        assert!(expand(&document, &Context::new(), &no_remote_contexts).is_err());
+    }
+
+    #[test]
+    fn compacts_one_object_per_subject_and_round_trips() {
+        let context = context(json!({
+            "ex": "http://example.com/",
+            "schema": "http://schema.org/",
+            "name": "schema:name",
+            "knows": { "@id": "schema:knows", "@type": "@id" }
+        }));
+        let mut alice = Subject::new(RdfName::new("http://example.com/alice".to_string()));
+        alice.add(
+            RdfName::new(RDF_TYPE.to_string()),
+            "http://schema.org/Person".to_string(),
+        );
+        alice.add(
+            RdfName::new("http://schema.org/name".to_string()),
+            "Alice".to_string(),
+        );
+        alice.add(
+            RdfName::new("http://schema.org/knows".to_string()),
+            "http://example.com/bob".to_string(),
+        );
+        alice.add(
+            RdfName::new("http://example.com/likes".to_string()),
+            "http://example.com/tea".to_string(),
+        );
+
+        let document = compact(std::slice::from_ref(&alice), &context);
+        assert_eq!(
+            document["@graph"][0],
+            json!({
+                "@id": "ex:alice",
+                "@type": "schema:Person",
+                "name": "Alice",
+                "knows": "ex:bob",
+                "ex:likes": { "@id": "ex:tea" }
+            })
+        );
+
+        let subjects = expand(&document, &Context::new(), &no_remote_contexts).unwrap();
+        assert_eq!(triples(&subjects), triples(&[alice]));
+    }
+}
@@ -0,0 +1,118 @@
+/// functions in support of JSON-LD file handling
+///
+/// Documents are expanded with their own context over one given on the
+/// command line, local context files only, and the db is exported
+/// compacted, one node object per subject.
+///
+use crate::data::TriplesError;
+use crate::db_api::DbApi;
+use crate::jsonld::{self, Context};
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::trace;
+
+/// load a context referenced by a document from a local file, remote
+/// contexts are never fetched
+///
+/// # Errors
+///
+/// return `Err` if the reference is remote or the file can not be read
+pub fn load_local_context(reference: &str) -> Result<Value, TriplesError> {
+    if reference.starts_with("http://") || reference.starts_with("https://") {
+        return Err(TriplesError::ParseError {
+            reason: format!(
+                "remote context {reference} is not fetched, pass a local copy with --context"
+            ),
+        });
+    }
+    let path = reference.strip_prefix("file://").unwrap_or(reference);
+    let text = std::fs::read_to_string(path).map_err(|e| TriplesError::ParseError {
+        reason: format!("can not read context {path}: {e}"),
+    })?;
+    serde_json::from_str(&text).map_err(|e| TriplesError::ParseError {
+        reason: format!("invalid context {path}: {e}"),
+    })
+}
+
+/// read a context file, either a bare context or a document holding one
+///
+/// # Errors
+///
+/// return `Err` if the file can not be read or is not a valid context
+pub fn read_context(path: &str) -> Result<Context, Box<dyn std::error::Error>> {
+    let document = load_local_context(path)?;
+    let value = document.get("@context").unwrap_or(&document);
+    Ok(Context::new().merge(value, &load_local_context)?)
+}
+
+/// read a JSON-LD document from `reader`, stdin for the cli, and load db,
+/// with `context` applied before the document's own
+///
+/// # Errors
+///
+/// return `Err` if the document can not be read, expanded or loaded
+pub async fn import_jsonld<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+    context: &Context,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("import_jsonld");
+    let mut text = String::new();
+    reader.read_to_string(&mut text).await?;
+    let document: Value = serde_json::from_str(&text)?;
+    let subjects = jsonld::expand(&document, context, &load_local_context)?;
+
+    // keep the document's prefixes for later exports, as for turtle
+    let declared = match document.get("@context") {
+        Some(local) => context.merge(local, &load_local_context)?,
+        None => context.clone(),
+    };
+
+    let mut tx = db_api.begin_txn().await?;
+    for subject in &subjects {
+        db_api.insert_into_txn(&mut tx, subject, None).await?;
+    }
+    for (prefix, namespace) in declared.prefixes().iter() {
+        db_api
+            .insert_namespace_txn(&mut tx, prefix, namespace)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// export the entire db to `out` as compacted JSON-LD, one node object per
+/// subject and line, with `context` or else the stored and well-known
+/// prefixes
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read or `out` written
+pub async fn export_jsonld<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    context: Option<&Context>,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("export_jsonld");
+    let context = match context {
+        Some(context) => context.clone(),
+        None => Context::from_prefixes(&db_api.preferred_prefixes().await?),
+    };
+
+    out.write_all(format!("{{\"@context\": {},\n\"@graph\": [", context.to_value()).as_bytes())
+        .await?;
+    let mut separator = "\n";
+    for name in &db_api.get_subject_names().await? {
+        if let Some(subject) = db_api.query(name).await? {
+            let node = jsonld::compact_subject(&subject, &context);
+            out.write_all(format!("{separator}{node}").as_bytes())
+                .await?;
+            separator = ",\n";
+        }
+    }
+    out.write_all(b"\n]}\n").await?;
+    out.flush().await?;
+
+    Ok(())
+}
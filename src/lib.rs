@@ -1,14 +1,51 @@
 use lalrpop_util::lalrpop_mod;
 
+pub mod analytics;
+pub mod analytics_file;
 pub mod csv;
 pub mod csv_file;
 pub mod csv_triples_file;
 pub mod data;
 pub mod db;
 pub mod db_api;
+pub mod diagram;
+pub mod diagram_file;
+pub mod inference;
+pub mod io_file;
+pub mod iri;
+pub mod jsonl_file;
+pub mod jsonld;
+pub mod jsonld_file;
+pub mod ntriples;
+pub mod owl;
+pub mod parquet_file;
+pub mod prefixes;
+pub mod profile;
+pub mod profile_file;
+pub mod rdfs;
+pub mod rdfxml;
+pub mod rdfxml_file;
+pub mod report;
+pub mod rules;
+pub mod rules_file;
+pub mod search;
+pub mod search_file;
+pub mod shacl;
+pub mod shacl_file;
 pub mod sparql_data;
+pub mod sparql_file;
+pub mod sparql_plan;
+pub mod sparql_results;
+pub mod sparql_server;
+pub mod sparql_sql;
+pub mod stats;
+pub mod stats_file;
+pub mod traversal;
+pub mod traversal_file;
 pub mod ttl_file;
 pub mod turtle_stream;
+pub mod turtle_writer;
+pub mod vocab;
 
 lalrpop_mod!(#[allow(clippy::complexity, clippy::pedantic, clippy::restriction, clippy::nursery)] pub sparql);
 
@@ -1,17 +1,62 @@
 use clap::Parser;
+use std::path::Path;
+use tokio::io::{stdin, AsyncWriteExt, BufReader};
+use triples::analytics_file;
 use triples::csv_file;
 use triples::csv_triples_file;
-use triples::db_api::DbApi;
+use triples::data::TriplesError;
+use triples::db_api::{DbApi, ANALYTICS_GRAPH, OWL_GRAPH, RULES_GRAPH};
+use triples::diagram_file::{self, Selection};
+use triples::io_file;
+use triples::jsonl_file;
+use triples::jsonld::Context;
+use triples::jsonld_file;
+use triples::owl;
+use triples::parquet_file;
+use triples::profile_file;
+use triples::rdfxml_file;
+use triples::rules_file;
+use triples::search_file;
+use triples::shacl_file;
+use triples::sparql_file;
+use triples::sparql_results::ResultFormat;
+use triples::sparql_server;
+use triples::stats_file;
+use triples::traversal::Direction;
+use triples::traversal_file::{self, PathOptions};
 use triples::ttl_file;
+use triples::turtle_writer::TurtleOptions;
 
 #[derive(Parser, Debug, Clone)]
 enum Command {
-    ImportTurtle,
-    ExportTurtle,
+    ImportTurtle(ImportTurtleArgs),
+    ExportTurtle(ExportTurtleArgs),
     ImportCSV(ImportCsvArgs),
     ExportCSV(ExportCsvArgs),
     ImportTriplesCSV(ImportTriplesCsvArgs),
     ExportTriplesCSV(ExportTriplesCsvArgs),
+    Query(QueryArgs),
+    Serve(ServeArgs),
+    ImportJsonld(ImportJsonldArgs),
+    ExportJsonld(ExportJsonldArgs),
+    ImportRdfxml(ImportRdfxmlArgs),
+    ExportRdfxml(ExportRdfxmlArgs),
+    ImportJsonl(ImportJsonlArgs),
+    ExportJsonl(ExportJsonlArgs),
+    ExportParquet(ExportParquetArgs),
+    ExportDot(DiagramArgs),
+    ExportMermaid(DiagramArgs),
+    InferRdfs(InferRdfsArgs),
+    InferOwl(InferOwlArgs),
+    Validate(ValidateArgs),
+    Profile(ProfileArgs),
+    Stats(StatsArgs),
+    Path(PathArgs),
+    Analyze(AnalyzeArgs),
+    Search(SearchArgs),
+    Infer(InferArgs),
+    Import(ImportArgs),
+    Export(ExportArgs),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -20,10 +65,64 @@ struct Args {
     #[arg(short, long, default_value = "/tmp/triples.db")]
     db_location: String,
 
+    /// leave triples derived by inference out of exports and queries
+    #[arg(long, default_value = "false")]
+    exclude_inferred: bool,
+
     #[clap(subcommand)]
     command: Command,
 }
 
+#[derive(Parser, Debug, Clone)]
+struct ImportTurtleArgs {
+    /// files or globs to read, `.gz` and `.zst` are decompressed, stdin
+    /// when absent
+    #[arg(long, num_args = 1..)]
+    input: Vec<String>,
+
+    /// SHACL shapes the whole batch must conform to before any of it is
+    /// imported, the validation report is written to stdout when it does not
+    #[arg(long)]
+    validate: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ExportTurtleArgs {
+    /// file to write, compressed if it ends in `.gz` or `.zst`, stdout when
+    /// absent
+    #[arg(long)]
+    output: Option<String>,
+
+    /// sort the output and relabel blank nodes so an unchanged db always
+    /// exports identical turtle
+    #[arg(long, default_value = "false")]
+    canonical: bool,
+
+    /// turtle file whose `@prefix` declarations name the exported namespaces
+    #[arg(long)]
+    prefixes: Option<String>,
+
+    /// spaces per level of nesting
+    #[arg(long, default_value = "4")]
+    indent: usize,
+
+    /// objects of one predicate written on a line before wrapping
+    #[arg(long, default_value = "1")]
+    max_objects_per_line: usize,
+
+    /// nest blank nodes used once as `[ ... ]`, import-turtle can not read them
+    #[arg(long, default_value = "false")]
+    nest_blank_nodes: bool,
+
+    /// write rdf:first/rdf:rest lists as `( ... )`, import-turtle can not read them
+    #[arg(long, default_value = "false")]
+    collections: bool,
+
+    /// write values spanning lines as long strings, import-turtle can not read them
+    #[arg(long, default_value = "false")]
+    long_strings: bool,
+}
+
 #[derive(Parser, Debug, Clone)]
 struct ImportCsvArgs {
     #[arg(long)]
@@ -38,6 +137,11 @@ struct ImportCsvArgs {
 
 #[derive(Parser, Debug, Clone)]
 struct ImportTriplesCsvArgs {
+    /// files or globs to read, `.gz` and `.zst` are decompressed, stdin
+    /// when absent
+    #[arg(long, num_args = 1..)]
+    input: Vec<String>,
+
     #[arg(long)]
     subject_default_ns: Option<String>,
 
@@ -50,15 +154,34 @@ struct ImportTriplesCsvArgs {
 
 #[derive(Parser, Debug, Clone)]
 struct ExportCsvArgs {
+    /// file to write, compressed if it ends in `.gz` or `.zst`, stdout when
+    /// absent
+    #[arg(long)]
+    output: Option<String>,
+
     #[arg(long, default_value = "false")]
     export_ns_name: bool,
 
     #[arg(long)]
     subject_column_name: Option<String>,
+
+    /// predicate columns to export in this order, as full IRIs or display
+    /// names, all predicates ordered by name when absent
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// export only subjects with this `rdf:type` IRI
+    #[arg(long)]
+    rdf_type: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
 struct ExportTriplesCsvArgs {
+    /// file to write, compressed if it ends in `.gz` or `.zst`, stdout when
+    /// absent
+    #[arg(long)]
+    output: Option<String>,
+
     #[arg(long, default_value = "false")]
     export_ns_name: bool,
 
@@ -66,16 +189,368 @@ struct ExportTriplesCsvArgs {
     export_headers: bool,
 }
 
+#[derive(Parser, Debug, Clone)]
+struct QueryArgs {
+    /// SPARQL query text, read from stdin when absent
+    query: Option<String>,
+
+    /// print the query plan and generated SQL instead of running the query
+    #[arg(long, default_value = "false")]
+    explain: bool,
+
+    /// result format: tsv, csv, json or xml
+    #[arg(long, default_value = "tsv")]
+    format: ResultFormat,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ServeArgs {
+    /// address to listen on for SPARQL protocol requests
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    bind: String,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ImportJsonldArgs {
+    /// files or globs to read, `.gz` and `.zst` are decompressed, stdin
+    /// when absent
+    #[arg(long, num_args = 1..)]
+    input: Vec<String>,
+
+    /// JSON-LD context file applied before each document's own `@context`
+    #[arg(long)]
+    context: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ExportJsonldArgs {
+    /// file to write, compressed if it ends in `.gz` or `.zst`, stdout when
+    /// absent
+    #[arg(long)]
+    output: Option<String>,
+
+    /// JSON-LD context file to compact with, the stored prefixes when absent
+    #[arg(long)]
+    context: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ImportRdfxmlArgs {
+    /// files or globs to read, `.gz` and `.zst` are decompressed, stdin
+    /// when absent
+    #[arg(long, num_args = 1..)]
+    input: Vec<String>,
+
+    /// base IRI for relative references of documents without `xml:base`,
+    /// the location of each file when absent
+    #[arg(long)]
+    base: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ExportRdfxmlArgs {
+    /// file to write, compressed if it ends in `.gz` or `.zst`, stdout when
+    /// absent
+    #[arg(long)]
+    output: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ImportJsonlArgs {
+    /// files or globs to read, `.gz` and `.zst` are decompressed, stdin
+    /// when absent
+    #[arg(long, num_args = 1..)]
+    input: Vec<String>,
+
+    /// namespace for `@id`s that are not full IRIs
+    #[arg(long)]
+    subject_default_ns: Option<String>,
+
+    /// namespace for keys that are not full IRIs
+    #[arg(long)]
+    predicate_default_ns: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ExportJsonlArgs {
+    /// file to write, compressed if it ends in `.gz` or `.zst`, stdout when
+    /// absent
+    #[arg(long)]
+    output: Option<String>,
+
+    #[arg(long, default_value = "false")]
+    export_ns_name: bool,
+
+    /// write IRIs as `{"@id": ...}` and numbers and booleans as JSON values
+    /// rather than strings
+    #[arg(long, default_value = "false")]
+    typed: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ExportParquetArgs {
+    /// file to write, stdout when absent
+    #[arg(long)]
+    output: Option<String>,
+
+    /// one row per subject with a typed column per predicate, instead of
+    /// one row per triple
+    #[arg(long, default_value = "false")]
+    wide: bool,
+
+    #[arg(long, default_value = "false")]
+    export_ns_name: bool,
+
+    #[arg(long)]
+    subject_column_name: Option<String>,
+
+    /// rows per Arrow record batch
+    #[arg(long, default_value_t = parquet_file::DEFAULT_BATCH_SIZE)]
+    batch_size: usize,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct DiagramArgs {
+    /// file to write, stdout when absent
+    #[arg(long)]
+    output: Option<String>,
+
+    /// subject IRI or prefixed name to start from, every subject when absent
+    #[arg(long)]
+    root: Option<String>,
+
+    /// how many edges away from `--root` to follow
+    #[arg(long, default_value = "2")]
+    depth: usize,
+
+    /// predicates to draw, as IRIs or prefixed names, all when absent
+    #[arg(long, value_delimiter = ',')]
+    predicates: Vec<String>,
+
+    /// colour nodes by `rdf:type` instead of drawing type edges
+    #[arg(long, default_value = "false")]
+    color_by_type: bool,
+}
+
+impl DiagramArgs {
+    fn selection(&self) -> Selection {
+        Selection {
+            root: self.root.clone(),
+            depth: self.depth,
+            predicates: self.predicates.clone(),
+            color_by_type: self.color_by_type,
+        }
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+struct InferRdfsArgs {
+    /// stop inferring and remove the inferred triples
+    #[arg(long, default_value = "false")]
+    disable: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ValidateArgs {
+    /// turtle file of SHACL shapes to validate the db against
+    #[arg(long)]
+    shapes: String,
+
+    /// file to write the validation report to, stdout when absent
+    #[arg(long)]
+    output: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ProfileArgs {
+    /// file to write the report to, stdout when absent
+    #[arg(long)]
+    output: Option<String>,
+
+    /// also write SHACL shapes drafted from the data to this turtle file
+    #[arg(long)]
+    shapes: Option<String>,
+
+    /// also write a VoID description of the data to this turtle file
+    #[arg(long)]
+    void: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct StatsArgs {
+    /// file to write the statistics to, stdout when absent
+    #[arg(long)]
+    output: Option<String>,
+
+    /// write a VoID description in turtle instead of text
+    #[arg(long, default_value = "false")]
+    void: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct PathArgs {
+    /// IRI or prefixed name to start from
+    from: String,
+
+    /// IRI or prefixed name to reach
+    to: String,
+
+    /// the most edges the path may have
+    #[arg(long, default_value = "10")]
+    max_hops: usize,
+
+    /// follow edges out from subject to object, in against them, or both
+    #[arg(long, default_value = "out")]
+    direction: Direction,
+
+    /// predicates to follow, as IRIs or prefixed names, all when absent
+    #[arg(long, value_delimiter = ',')]
+    predicates: Vec<String>,
+
+    /// file to write the path to, stdout when absent
+    #[arg(long)]
+    output: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct AnalyzeArgs {
+    /// predicates whose triples are the edges, as IRIs or prefixed names,
+    /// all when absent
+    #[arg(long, value_delimiter = ',')]
+    predicates: Vec<String>,
+
+    /// how many nodes of the highest PageRank the report lists
+    #[arg(long, default_value = "10")]
+    top: usize,
+
+    /// write a CSV row per node instead of the report
+    #[arg(long, default_value = "false")]
+    csv: bool,
+
+    /// also store the results as triples, replacing those of earlier runs
+    #[arg(long, default_value = "false")]
+    write: bool,
+
+    /// remove stored results without analysing again
+    #[arg(long, default_value = "false")]
+    clear: bool,
+
+    /// file to write the report or CSV to, stdout when absent
+    #[arg(long)]
+    output: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct SearchArgs {
+    /// words that must all appear in a literal, `*` ending a prefix
+    text: String,
+
+    /// predicates whose literals are searched, as IRIs or prefixed names,
+    /// all when absent
+    #[arg(long, value_delimiter = ',')]
+    predicates: Vec<String>,
+
+    /// the most subjects to list
+    #[arg(long, default_value = "20")]
+    limit: usize,
+
+    /// file to write the hits to, stdout when absent
+    #[arg(long)]
+    output: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct InferOwlArgs {
+    /// remove the OWL 2 RL conclusions without inferring again
+    #[arg(long, default_value = "false")]
+    clear: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct InferArgs {
+    /// N3 rule files or globs, evaluated together until nothing new follows
+    #[arg(long, num_args = 1.., required_unless_present = "clear")]
+    rules: Vec<String>,
+
+    /// retract the conclusions of earlier runs without inferring again
+    #[arg(long, default_value = "false")]
+    clear: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ImportArgs {
+    /// files or globs to load, each in the format of its extension: `.ttl`,
+    /// `.nt`, `.jsonld`, `.jsonl`, `.rdf`, `.owl` or triples `.csv`,
+    /// optionally `.gz` or `.zst` compressed
+    #[arg(long, num_args = 1.., required = true)]
+    input: Vec<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ExportArgs {
+    /// file to write in the format of its extension: `.ttl`, `.nt`,
+    /// `.jsonld`, `.jsonl`, `.rdf`, `.owl`, `.parquet` or triples `.csv`,
+    /// optionally `.gz` or `.zst` compressed
+    #[arg(long)]
+    output: String,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
     let args = Args::parse();
 
-    let db_api = DbApi::new(args.db_location).await?;
+    let mut db_api = DbApi::new(args.db_location).await?;
+    db_api.set_include_inferred(!args.exclude_inferred);
 
     match args.command {
-        Command::ImportTurtle => ttl_file::import_turtle(&db_api).await?,
-        Command::ExportTurtle => ttl_file::export_turtle(&db_api).await?,
+        Command::ImportTurtle(import_turtle_args) => {
+            if let Some(shapes) = &import_turtle_args.validate {
+                let shapes = shacl_file::read_shapes(Path::new(shapes)).await?;
+                let (mut subjects, mut prefixes) = (Vec::new(), Vec::new());
+                if import_turtle_args.input.is_empty() {
+                    (subjects, prefixes) = ttl_file::read_turtle(BufReader::new(stdin())).await?;
+                }
+                for path in io_file::expand_inputs(&import_turtle_args.input)? {
+                    let (more, declared) =
+                        ttl_file::read_turtle(io_file::open(&path).await?).await?;
+                    subjects.extend(more);
+                    prefixes.extend(declared);
+                }
+                let mut out = io_file::output(None).await?;
+                let imported =
+                    shacl_file::import_validated(&mut out, &subjects, &prefixes, &shapes, &db_api)
+                        .await;
+                out.shutdown().await?;
+                imported?;
+            } else {
+                if import_turtle_args.input.is_empty() {
+                    ttl_file::import_turtle(BufReader::new(stdin()), &db_api).await?;
+                }
+                for path in io_file::expand_inputs(&import_turtle_args.input)? {
+                    ttl_file::import_turtle(io_file::open(&path).await?, &db_api).await?;
+                }
+            }
+        }
+        Command::ExportTurtle(export_turtle_args) => {
+            let mut out = io_file::output(export_turtle_args.output.as_deref()).await?;
+            ttl_file::export_turtle(
+                &mut out,
+                export_turtle_args.canonical,
+                export_turtle_args.prefixes.as_deref(),
+                TurtleOptions {
+                    indent: export_turtle_args.indent,
+                    max_objects_per_line: export_turtle_args.max_objects_per_line,
+                    sorted: false,
+                    nest_blank_nodes: export_turtle_args.nest_blank_nodes,
+                    collections: export_turtle_args.collections,
+                    long_strings: export_turtle_args.long_strings,
+                },
+                &db_api,
+            )
+            .await?;
+            out.shutdown().await?;
+        }
         Command::ImportCSV(import_csv_args) => {
             csv_file::import_csv(
                 &import_csv_args.subject_default_ns,
@@ -87,29 +562,269 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             )?;
         }
         Command::ExportCSV(export_csv_args) => {
+            let mut out = io_file::output(export_csv_args.output.as_deref()).await?;
+            let columns = (!export_csv_args.columns.is_empty()).then_some(&export_csv_args.columns);
             csv_file::export_csv(
+                &mut out,
                 export_csv_args.export_ns_name,
                 export_csv_args.subject_column_name,
+                columns.map(Vec::as_slice),
+                export_csv_args.rdf_type.as_deref(),
                 &db_api,
             )
             .await?;
+            out.shutdown().await?;
         }
         Command::ImportTriplesCSV(import_csv_args) => {
-            csv_triples_file::import_csv(
-                import_csv_args.subject_default_ns,
-                import_csv_args.predicate_default_ns,
-                import_csv_args.skip_headers,
-                &db_api,
-            )
-            .await?;
+            if import_csv_args.input.is_empty() {
+                csv_triples_file::import_csv(
+                    BufReader::new(stdin()),
+                    import_csv_args.subject_default_ns.clone(),
+                    import_csv_args.predicate_default_ns.clone(),
+                    import_csv_args.skip_headers,
+                    &db_api,
+                )
+                .await?;
+            }
+            for path in io_file::expand_inputs(&import_csv_args.input)? {
+                csv_triples_file::import_csv(
+                    io_file::open(&path).await?,
+                    import_csv_args.subject_default_ns.clone(),
+                    import_csv_args.predicate_default_ns.clone(),
+                    import_csv_args.skip_headers,
+                    &db_api,
+                )
+                .await?;
+            }
         }
         Command::ExportTriplesCSV(export_csv_args) => {
+            let mut out = io_file::output(export_csv_args.output.as_deref()).await?;
             csv_triples_file::export_csv(
+                &mut out,
                 export_csv_args.export_ns_name,
                 export_csv_args.export_headers,
                 &db_api,
             )
             .await?;
+            out.shutdown().await?;
+        }
+        Command::Query(query_args) => {
+            let text = match query_args.query {
+                Some(text) => text,
+                None => sparql_file::read_query().await?,
+            };
+            if query_args.explain {
+                sparql_file::explain(&text, &db_api).await?;
+            } else {
+                sparql_file::query(&text, query_args.format, &db_api).await?;
+            }
+        }
+        Command::Serve(serve_args) => sparql_server::serve(&serve_args.bind, db_api).await?,
+        Command::ImportJsonld(import_jsonld_args) => {
+            let context = match import_jsonld_args.context.as_deref() {
+                Some(path) => jsonld_file::read_context(path)?,
+                None => Context::new(),
+            };
+            if import_jsonld_args.input.is_empty() {
+                jsonld_file::import_jsonld(BufReader::new(stdin()), &context, &db_api).await?;
+            }
+            for path in io_file::expand_inputs(&import_jsonld_args.input)? {
+                jsonld_file::import_jsonld(io_file::open(&path).await?, &context, &db_api).await?;
+            }
+        }
+        Command::ExportJsonld(export_jsonld_args) => {
+            let context = export_jsonld_args
+                .context
+                .as_deref()
+                .map(jsonld_file::read_context)
+                .transpose()?;
+            let mut out = io_file::output(export_jsonld_args.output.as_deref()).await?;
+            jsonld_file::export_jsonld(&mut out, context.as_ref(), &db_api).await?;
+            out.shutdown().await?;
+        }
+        Command::ImportRdfxml(import_rdfxml_args) => {
+            let base = import_rdfxml_args.base.as_deref();
+            if import_rdfxml_args.input.is_empty() {
+                let base = base.unwrap_or(rdfxml_file::STDIN_BASE);
+                rdfxml_file::import_rdfxml(BufReader::new(stdin()), base, &db_api).await?;
+            }
+            for path in io_file::expand_inputs(&import_rdfxml_args.input)? {
+                let base = match base {
+                    Some(base) => base.to_string(),
+                    None => rdfxml_file::file_base(&path)?,
+                };
+                rdfxml_file::import_rdfxml(io_file::open(&path).await?, &base, &db_api).await?;
+            }
+        }
+        Command::ExportRdfxml(export_rdfxml_args) => {
+            let mut out = io_file::output(export_rdfxml_args.output.as_deref()).await?;
+            rdfxml_file::export_rdfxml(&mut out, &db_api).await?;
+            out.shutdown().await?;
+        }
+        Command::ImportJsonl(import_jsonl_args) => {
+            let subject_ns = import_jsonl_args.subject_default_ns.as_deref();
+            let predicate_ns = import_jsonl_args.predicate_default_ns.as_deref();
+            if import_jsonl_args.input.is_empty() {
+                let reader = BufReader::new(stdin());
+                jsonl_file::import_jsonl(reader, subject_ns, predicate_ns, &db_api).await?;
+            }
+            for path in io_file::expand_inputs(&import_jsonl_args.input)? {
+                let reader = io_file::open(&path).await?;
+                jsonl_file::import_jsonl(reader, subject_ns, predicate_ns, &db_api).await?;
+            }
+        }
+        Command::ExportJsonl(export_jsonl_args) => {
+            let mut out = io_file::output(export_jsonl_args.output.as_deref()).await?;
+            jsonl_file::export_jsonl(
+                &mut out,
+                export_jsonl_args.export_ns_name,
+                export_jsonl_args.typed,
+                &db_api,
+            )
+            .await?;
+            out.shutdown().await?;
+        }
+        Command::ExportParquet(export_parquet_args) => {
+            let mut out = io_file::output(export_parquet_args.output.as_deref()).await?;
+            let batch_size = export_parquet_args.batch_size.max(1);
+            if export_parquet_args.wide {
+                parquet_file::export_wide(
+                    &mut out,
+                    export_parquet_args.export_ns_name,
+                    export_parquet_args.subject_column_name,
+                    batch_size,
+                    &db_api,
+                )
+                .await?;
+            } else {
+                parquet_file::export_long(&mut out, batch_size, &db_api).await?;
+            }
+            out.shutdown().await?;
+        }
+        Command::ExportDot(diagram_args) => {
+            let mut out = io_file::output(diagram_args.output.as_deref()).await?;
+            diagram_file::export_dot(&mut out, &diagram_args.selection(), &db_api).await?;
+            out.shutdown().await?;
+        }
+        Command::ExportMermaid(diagram_args) => {
+            let mut out = io_file::output(diagram_args.output.as_deref()).await?;
+            diagram_file::export_mermaid(&mut out, &diagram_args.selection(), &db_api).await?;
+            out.shutdown().await?;
+        }
+        Command::InferRdfs(infer_rdfs_args) => {
+            if infer_rdfs_args.disable {
+                db_api.disable_rdfs().await?;
+            } else {
+                db_api.enable_rdfs().await?;
+            }
+        }
+        Command::InferOwl(infer_owl_args) => {
+            if infer_owl_args.clear {
+                db_api.clear_conclusions(OWL_GRAPH).await?;
+            } else {
+                owl::infer(&db_api).await?;
+            }
+        }
+        Command::Validate(validate_args) => {
+            let shapes = shacl_file::read_shapes(Path::new(&validate_args.shapes)).await?;
+            let report = shacl_file::validate(&shapes, &db_api).await?;
+            let mut out = io_file::output(validate_args.output.as_deref()).await?;
+            shacl_file::write_report(&mut out, &report).await?;
+            out.shutdown().await?;
+            if report.violations() > 0 {
+                return Err(TriplesError::ShapeViolations {
+                    count: report.violations(),
+                }
+                .into());
+            }
+        }
+        Command::Profile(profile_args) => {
+            let profile = profile_file::profile(&db_api).await?;
+            let mut out = io_file::output(profile_args.output.as_deref()).await?;
+            profile_file::write_report(&mut out, &profile, &db_api).await?;
+            out.shutdown().await?;
+            if let Some(path) = &profile_args.shapes {
+                let mut out = io_file::create(Path::new(path)).await?;
+                profile_file::write_shapes(&mut out, &profile, &db_api).await?;
+                out.shutdown().await?;
+            }
+            if let Some(path) = &profile_args.void {
+                let mut out = io_file::create(Path::new(path)).await?;
+                profile_file::write_void(&mut out, &profile, &db_api).await?;
+                out.shutdown().await?;
+            }
+        }
+        Command::Stats(stats_args) => {
+            let stats = db_api.stats().await?;
+            let mut out = io_file::output(stats_args.output.as_deref()).await?;
+            if stats_args.void {
+                stats_file::write_void(&mut out, &stats, &db_api).await?;
+            } else {
+                stats_file::write_report(&mut out, &stats, &db_api).await?;
+            }
+            out.shutdown().await?;
+        }
+        Command::Path(path_args) => {
+            let options = PathOptions {
+                max_hops: path_args.max_hops,
+                direction: path_args.direction,
+                predicates: path_args.predicates.clone(),
+            };
+            let mut out = io_file::output(path_args.output.as_deref()).await?;
+            let written = traversal_file::write_path(
+                &mut out,
+                &path_args.from,
+                &path_args.to,
+                &options,
+                &db_api,
+            )
+            .await;
+            out.shutdown().await?;
+            written?;
+        }
+        Command::Analyze(analyze_args) => {
+            if analyze_args.clear {
+                db_api.clear_conclusions(ANALYTICS_GRAPH).await?;
+            } else {
+                let analytics = analytics_file::analyse(&analyze_args.predicates, &db_api).await?;
+                if analyze_args.write {
+                    analytics_file::store(&analytics, &db_api).await?;
+                }
+                let mut out = io_file::output(analyze_args.output.as_deref()).await?;
+                if analyze_args.csv {
+                    analytics_file::write_csv(&mut out, &analytics).await?;
+                } else {
+                    analytics_file::write_report(&mut out, &analytics, analyze_args.top, &db_api)
+                        .await?;
+                }
+                out.shutdown().await?;
+            }
+        }
+        Command::Search(search_args) => {
+            let mut out = io_file::output(search_args.output.as_deref()).await?;
+            let written = search_file::write_hits(
+                &mut out,
+                &search_args.text,
+                &search_args.predicates,
+                search_args.limit,
+                &db_api,
+            )
+            .await;
+            out.shutdown().await?;
+            written?;
+        }
+        Command::Infer(infer_args) => {
+            if infer_args.clear {
+                db_api.clear_conclusions(RULES_GRAPH).await?;
+            } else {
+                let rules =
+                    rules_file::read_rules(&io_file::expand_inputs(&infer_args.rules)?).await?;
+                rules_file::infer(rules, RULES_GRAPH, &db_api).await?;
+            }
+        }
+        Command::Import(import_args) => io_file::import(&import_args.input, &db_api).await?,
+        Command::Export(export_args) => {
+            io_file::export(Path::new(&export_args.output), &db_api).await?;
         }
     }
 
@@ -0,0 +1,198 @@
+/// A reader for N-Triples <https://www.w3.org/TR/n-triples/>
+///
+/// Each line holds one triple, so a document is read a line at a time.
+/// Like the rest of the store only lexical forms are kept, language tags
+/// and datatypes are read but dropped.
+///
+use crate::data::{parse_error, RdfName, Subject, Term, TriplesError};
+
+/// the value of an escaped string or IRI, rejecting unknown escapes
+///
+/// # Errors
+///
+/// return `Err` if an escape sequence is not one N-Triples allows
+pub fn unescape(text: &str) -> Result<String, TriplesError> {
+    let mut value = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => value.push('\t'),
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('b') => value.push('\u{8}'),
+            Some('f') => value.push('\u{c}'),
+            Some(c @ ('"' | '\'' | '\\')) => value.push(c),
+            Some(c @ ('u' | 'U')) => {
+                let len = if c == 'u' { 4 } else { 8 };
+                let hex: String = chars.by_ref().take(len).collect();
+                let decoded = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| parse_error(format!("bad escape \\{c}{hex}")))?;
+                value.push(decoded);
+            }
+            other => return Err(parse_error(format!("bad escape \\{other:?}"))),
+        }
+    }
+    Ok(value)
+}
+
+/// the term at the start of `text`, after any white space, and the text
+/// following it
+///
+/// # Errors
+///
+/// return `Err` if `text` does not start with an IRI, blank node or literal
+pub fn term(text: &str) -> Result<(Term, &str), TriplesError> {
+    let text = text.trim_start();
+    if let Some(rest) = text.strip_prefix('<') {
+        let end = rest
+            .find('>')
+            .ok_or_else(|| parse_error(format!("unterminated IRI {text}")))?;
+        return Ok((Term::Iri(unescape(&rest[..end])?), &rest[end + 1..]));
+    }
+    if let Some(rest) = text.strip_prefix("_:") {
+        // a label may hold dots but not end with one, that is the terminator
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '"'))
+            .unwrap_or(rest.len());
+        let label = rest[..end].trim_end_matches('.');
+        if label.is_empty() {
+            return Err(parse_error(format!("empty blank node label {text}")));
+        }
+        return Ok((Term::BlankNode(label.to_string()), &rest[label.len()..]));
+    }
+    if let Some(rest) = text.strip_prefix('"') {
+        let end = rest
+            .char_indices()
+            .scan(false, |escaped, (idx, c)| {
+                let close = c == '"' && !*escaped;
+                *escaped = c == '\\' && !*escaped;
+                Some((idx, close))
+            })
+            .find_map(|(idx, close)| close.then_some(idx))
+            .ok_or_else(|| parse_error(format!("unterminated literal {text}")))?;
+        let value = unescape(&rest[..end])?;
+        let rest = &rest[end + 1..];
+        if let Some(tagged) = rest.strip_prefix('@') {
+            let end = tagged
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                .unwrap_or(tagged.len());
+            let literal = Term::Literal {
+                value,
+                datatype: None,
+                language: Some(tagged[..end].to_string()),
+            };
+            return Ok((literal, &tagged[end..]));
+        }
+        if let Some(typed) = rest.strip_prefix("^^") {
+            let (datatype, rest) = term(typed)?;
+            let Term::Iri(datatype) = datatype else {
+                return Err(parse_error(format!("datatype is not an IRI in {text}")));
+            };
+            let literal = Term::Literal {
+                value,
+                datatype: Some(datatype),
+                language: None,
+            };
+            return Ok((literal, rest));
+        }
+        return Ok((Term::literal(value), rest));
+    }
+    Err(parse_error(format!("expected a term at {text}")))
+}
+
+/// the text the store keeps for a term, a literal by its lexical form
+fn object_text(term: Term) -> String {
+    match term {
+        Term::BlankNode(label) => format!("_:{label}"),
+        Term::Iri(value) | Term::Literal { value, .. } => value,
+    }
+}
+
+/// the triple of one line as a subject, `None` for a blank or comment line
+///
+/// # Errors
+///
+/// return `Err` if the line is not an N-Triples statement
+pub fn parse_line(line: &str) -> Result<Option<Subject>, TriplesError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let (subject, rest) = term(line)?;
+    if matches!(subject, Term::Literal { .. }) {
+        return Err(parse_error(format!("literal subject in {line}")));
+    }
+    let (predicate, rest) = term(rest)?;
+    let Term::Iri(predicate) = predicate else {
+        return Err(parse_error(format!("predicate is not an IRI in {line}")));
+    };
+    let (object, rest) = term(rest)?;
+    let end = rest.trim_start();
+    let trailing = end
+        .strip_prefix('.')
+        .map(str::trim_start)
+        .ok_or_else(|| parse_error(format!("missing . at the end of {line}")))?;
+    if !(trailing.is_empty() || trailing.starts_with('#')) {
+        return Err(parse_error(format!("unexpected {trailing} after {line}")));
+    }
+
+    let mut triple = Subject::new(RdfName::new(object_text(subject)));
+    triple.add(RdfName::new(predicate), object_text(object));
+    Ok(Some(triple))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triple(line: &str) -> (String, String, String) {
+        let subject = parse_line(line).unwrap().unwrap();
+        let (predicate, objects) = subject.predicate_object_pairs().next().unwrap();
+        (
+            subject.name().to_string(),
+            predicate.to_string(),
+            objects.iter().next().unwrap().clone(),
+        )
+    }
+
+    #[test]
+    fn reads_every_kind_of_term() {
+        let p = "http://example.com/p";
+        for (object, value) in [
+            (r#""plain""#, "plain"),
+            (r#""chat"@fr-BE"#, "chat"),
+            (r#""5"^^<http://www.w3.org/2001/XMLSchema#integer>"#, "5"),
+            (r#""a \"b\"é\n""#, "a \"b\"é\n"),
+            ("<urn:isbn:0451450523>", "urn:isbn:0451450523"),
+            ("_:b1.x", "_:b1.x"),
+        ] {
+            let line = format!("_:s <{p}> {object} .");
+            assert_eq!(
+                triple(&line),
+                ("_:s".to_string(), p.to_string(), value.to_string()),
+                "{line}"
+            );
+        }
+        assert_eq!(triple("_:s <urn:p> _:o. # done").2, "_:o");
+    }
+
+    #[test]
+    fn rejects_what_is_not_a_statement() {
+        assert!(parse_line("  # a comment").unwrap().is_none());
+        for line in [
+            r#""s" <urn:p> <urn:o> ."#,
+            "<urn:s> _:p <urn:o> .",
+            "<urn:s> <urn:p> <urn:o>",
+            r#"<urn:s> <urn:p> "bad \q" ."#,
+            "<urn:s> <urn:p> <urn:o> . <urn:o>",
+        ] {
+            assert!(parse_line(line).is_err(), "{line}");
+        }
+    }
+}
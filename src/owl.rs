@@ -0,0 +1,274 @@
+/// OWL 2 RL reasoning, the rules of the OWL 2 RL profile evaluated over
+/// the store by the rules engine
+///
+/// The rules for equality (`owl:sameAs`), property axioms (inverse,
+/// symmetric, transitive, functional, equivalent and sub properties,
+/// domains and ranges), class expressions with `owl:someValuesFrom`,
+/// `owl:allValuesFrom`, `owl:hasValue` and `owl:maxCardinality` of 0 or 1,
+/// class axioms and the schema vocabulary are included.  Their conclusions
+/// are stored in `OWL_GRAPH` and retracted with their premises like those
+/// of any rule.
+///
+/// The constraints, such as `owl:disjointWith` and `owl:differentFrom`,
+/// are checked once nothing more follows and reported as
+/// `TriplesError::Inconsistent`.
+///
+/// Rules that walk RDF lists (intersections, unions, keys, property
+/// chains and the `owl:AllDisjoint*` axioms) are left out, as are the
+/// reflexive `eq-ref` and `scm-cls` rules that hold of everything and
+/// select nothing.  So that no reflexive triples are concluded either,
+/// `eq-trans`, `scm-sco` and `scm-spo` skip them, and `eq-rep-s` and
+/// `eq-rep-o` leave `owl:sameAs` itself to `eq-sym` and `eq-trans`.
+///
+use crate::data::TriplesError;
+use crate::db_api::{DbApi, OWL_GRAPH};
+use crate::rules::{parse_rules, Rule};
+use crate::rules_file;
+
+const PREFIXES: &str = r"
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix owl: <http://www.w3.org/2002/07/owl#> .
+@prefix log: <http://www.w3.org/2000/10/swap/log#> .
+";
+
+/// the rules by their names in the OWL 2 RL profile tables
+const RULES: &[(&str, &str)] = &[
+    // equality
+    ("eq-sym", "{ ?x owl:sameAs ?y } => { ?y owl:sameAs ?x } ."),
+    (
+        "eq-trans",
+        "{ ?x owl:sameAs ?y . ?y owl:sameAs ?z . ?x log:notEqualTo ?z } => { ?x owl:sameAs ?z } .",
+    ),
+    (
+        "eq-rep-s",
+        "{ ?s owl:sameAs ?s2 . ?s ?p ?o . ?p log:notEqualTo owl:sameAs } => { ?s2 ?p ?o } .",
+    ),
+    (
+        "eq-rep-p",
+        "{ ?p owl:sameAs ?p2 . ?s ?p ?o } => { ?s ?p2 ?o } .",
+    ),
+    (
+        "eq-rep-o",
+        "{ ?o owl:sameAs ?o2 . ?s ?p ?o . ?p log:notEqualTo owl:sameAs } => { ?s ?p ?o2 } .",
+    ),
+    (
+        "eq-diff1",
+        "{ ?x owl:sameAs ?y . ?x owl:differentFrom ?y } => false .",
+    ),
+    // properties
+    ("prp-dom", "{ ?p rdfs:domain ?c . ?x ?p ?y } => { ?x a ?c } ."),
+    ("prp-rng", "{ ?p rdfs:range ?c . ?x ?p ?y } => { ?y a ?c } ."),
+    (
+        "prp-fp",
+        "{ ?p a owl:FunctionalProperty . ?x ?p ?y1 . ?x ?p ?y2 . ?y1 log:notEqualTo ?y2 }
+            => { ?y1 owl:sameAs ?y2 } .",
+    ),
+    (
+        "prp-ifp",
+        "{ ?p a owl:InverseFunctionalProperty . ?x1 ?p ?y . ?x2 ?p ?y . ?x1 log:notEqualTo ?x2 }
+            => { ?x1 owl:sameAs ?x2 } .",
+    ),
+    (
+        "prp-irp",
+        "{ ?p a owl:IrreflexiveProperty . ?x ?p ?x } => false .",
+    ),
+    (
+        "prp-symp",
+        "{ ?p a owl:SymmetricProperty . ?x ?p ?y } => { ?y ?p ?x } .",
+    ),
+    (
+        "prp-asyp",
+        "{ ?p a owl:AsymmetricProperty . ?x ?p ?y . ?y ?p ?x } => false .",
+    ),
+    (
+        "prp-trp",
+        "{ ?p a owl:TransitiveProperty . ?x ?p ?y . ?y ?p ?z } => { ?x ?p ?z } .",
+    ),
+    (
+        "prp-spo1",
+        "{ ?p1 rdfs:subPropertyOf ?p2 . ?x ?p1 ?y } => { ?x ?p2 ?y } .",
+    ),
+    (
+        "prp-eqp1",
+        "{ ?p1 owl:equivalentProperty ?p2 . ?x ?p1 ?y } => { ?x ?p2 ?y } .",
+    ),
+    (
+        "prp-eqp2",
+        "{ ?p1 owl:equivalentProperty ?p2 . ?x ?p2 ?y } => { ?x ?p1 ?y } .",
+    ),
+    (
+        "prp-pdw",
+        "{ ?p1 owl:propertyDisjointWith ?p2 . ?x ?p1 ?y . ?x ?p2 ?y } => false .",
+    ),
+    (
+        "prp-inv1",
+        "{ ?p1 owl:inverseOf ?p2 . ?x ?p1 ?y } => { ?y ?p2 ?x } .",
+    ),
+    (
+        "prp-inv2",
+        "{ ?p1 owl:inverseOf ?p2 . ?x ?p2 ?y } => { ?y ?p1 ?x } .",
+    ),
+    (
+        "prp-npa1",
+        "{ ?x owl:sourceIndividual ?i1 ; owl:assertionProperty ?p ; owl:targetIndividual ?i2 .
+            ?i1 ?p ?i2 } => false .",
+    ),
+    // classes
+    ("cls-nothing2", "{ ?x a owl:Nothing } => false ."),
+    (
+        "cls-com",
+        "{ ?c1 owl:complementOf ?c2 . ?x a ?c1 . ?x a ?c2 } => false .",
+    ),
+    (
+        "cls-svf1",
+        "{ ?x owl:someValuesFrom ?y ; owl:onProperty ?p . ?u ?p ?v . ?v a ?y } => { ?u a ?x } .",
+    ),
+    (
+        "cls-svf2",
+        "{ ?x owl:someValuesFrom owl:Thing ; owl:onProperty ?p . ?u ?p ?v } => { ?u a ?x } .",
+    ),
+    (
+        "cls-avf",
+        "{ ?x owl:allValuesFrom ?y ; owl:onProperty ?p . ?u a ?x . ?u ?p ?v } => { ?v a ?y } .",
+    ),
+    (
+        "cls-hv1",
+        "{ ?x owl:hasValue ?y ; owl:onProperty ?p . ?u a ?x } => { ?u ?p ?y } .",
+    ),
+    (
+        "cls-hv2",
+        "{ ?x owl:hasValue ?y ; owl:onProperty ?p . ?u ?p ?y } => { ?u a ?x } .",
+    ),
+    (
+        "cls-maxc1",
+        "{ ?x owl:maxCardinality ?n ; owl:onProperty ?p . ?n log:equalTo 0 .
+            ?u a ?x . ?u ?p ?y } => false .",
+    ),
+    (
+        "cls-maxc2",
+        "{ ?x owl:maxCardinality ?n ; owl:onProperty ?p . ?n log:equalTo 1 .
+            ?u a ?x . ?u ?p ?y1 . ?u ?p ?y2 . ?y1 log:notEqualTo ?y2 }
+            => { ?y1 owl:sameAs ?y2 } .",
+    ),
+    // class axioms
+    (
+        "cax-sco",
+        "{ ?c1 rdfs:subClassOf ?c2 . ?x a ?c1 } => { ?x a ?c2 } .",
+    ),
+    (
+        "cax-eqc1",
+        "{ ?c1 owl:equivalentClass ?c2 . ?x a ?c1 } => { ?x a ?c2 } .",
+    ),
+    (
+        "cax-eqc2",
+        "{ ?c1 owl:equivalentClass ?c2 . ?x a ?c2 } => { ?x a ?c1 } .",
+    ),
+    (
+        "cax-dw",
+        "{ ?c1 owl:disjointWith ?c2 . ?x a ?c1 . ?x a ?c2 } => false .",
+    ),
+    // schema
+    (
+        "scm-sco",
+        "{ ?c1 rdfs:subClassOf ?c2 . ?c2 rdfs:subClassOf ?c3 . ?c1 log:notEqualTo ?c3 }
+            => { ?c1 rdfs:subClassOf ?c3 } .",
+    ),
+    (
+        "scm-eqc1",
+        "{ ?c1 owl:equivalentClass ?c2 }
+            => { ?c1 rdfs:subClassOf ?c2 . ?c2 rdfs:subClassOf ?c1 } .",
+    ),
+    (
+        "scm-eqc2",
+        "{ ?c1 rdfs:subClassOf ?c2 . ?c2 rdfs:subClassOf ?c1 } => { ?c1 owl:equivalentClass ?c2 } .",
+    ),
+    (
+        "scm-spo",
+        "{ ?p1 rdfs:subPropertyOf ?p2 . ?p2 rdfs:subPropertyOf ?p3 . ?p1 log:notEqualTo ?p3 }
+            => { ?p1 rdfs:subPropertyOf ?p3 } .",
+    ),
+    (
+        "scm-eqp1",
+        "{ ?p1 owl:equivalentProperty ?p2 }
+            => { ?p1 rdfs:subPropertyOf ?p2 . ?p2 rdfs:subPropertyOf ?p1 } .",
+    ),
+    (
+        "scm-eqp2",
+        "{ ?p1 rdfs:subPropertyOf ?p2 . ?p2 rdfs:subPropertyOf ?p1 }
+            => { ?p1 owl:equivalentProperty ?p2 } .",
+    ),
+    (
+        "scm-dom1",
+        "{ ?p rdfs:domain ?c1 . ?c1 rdfs:subClassOf ?c2 } => { ?p rdfs:domain ?c2 } .",
+    ),
+    (
+        "scm-dom2",
+        "{ ?p2 rdfs:domain ?c . ?p1 rdfs:subPropertyOf ?p2 } => { ?p1 rdfs:domain ?c } .",
+    ),
+    (
+        "scm-rng1",
+        "{ ?p rdfs:range ?c1 . ?c1 rdfs:subClassOf ?c2 } => { ?p rdfs:range ?c2 } .",
+    ),
+    (
+        "scm-rng2",
+        "{ ?p2 rdfs:range ?c . ?p1 rdfs:subPropertyOf ?p2 } => { ?p1 rdfs:range ?c } .",
+    ),
+];
+
+/// the OWL 2 RL rules, each named as in the profile
+///
+/// # Errors
+///
+/// return `Err` if a rule can not be parsed
+pub fn rules() -> Result<Vec<Rule>, TriplesError> {
+    let mut rules = Vec::new();
+    for (name, text) in RULES {
+        for mut rule in parse_rules(&format!("{PREFIXES}{text}"), name)? {
+            rule.name = (*name).to_string();
+            rules.push(rule);
+        }
+    }
+    Ok(rules)
+}
+
+/// replace the OWL 2 RL closure of earlier runs by the closure of the
+/// store, returning how many triples were concluded
+///
+/// # Errors
+///
+/// return `Err` if the closure can not be stored, or
+/// `TriplesError::Inconsistent` with every violated constraint, keeping the
+/// closure of the earlier run
+pub async fn infer(db_api: &DbApi) -> Result<u64, Box<dyn std::error::Error>> {
+    rules_file::infer(rules()?, OWL_GRAPH, db_api).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_parse() {
+        let rules = rules().unwrap();
+        assert_eq!(rules.len(), RULES.len());
+        let constraints: Vec<&str> = rules
+            .iter()
+            .filter(|rule| rule.is_constraint())
+            .map(|rule| rule.name.as_str())
+            .collect();
+        assert_eq!(
+            constraints,
+            [
+                "eq-diff1",
+                "prp-irp",
+                "prp-asyp",
+                "prp-pdw",
+                "prp-npa1",
+                "cls-nothing2",
+                "cls-com",
+                "cls-maxc1",
+                "cax-dw"
+            ]
+        );
+    }
+}
@@ -0,0 +1,306 @@
+/// functions in support of Apache Parquet export for analytics
+///
+/// Triples are streamed from the db into Arrow record batches of
+/// `batch_size` rows, so only one batch is held in memory.
+///
+use crate::csv::get_display_name;
+use crate::data::{lexical_datatype, RdfName, Term};
+use crate::db_api::DbApi;
+use crate::vocab::XSD;
+use arrow_array::builder::{BooleanBuilder, Float64Builder, Int64Builder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use futures::TryStreamExt;
+use parquet::arrow::AsyncArrowWriter;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::io::AsyncWrite;
+use tracing::trace;
+
+/// the rows per record batch unless the cli says otherwise
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// the Arrow type of a wide table column, from the datatypes of its values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Boolean,
+    Integer,
+    Double,
+    Text,
+}
+
+impl ColumnType {
+    fn of(object: &str) -> Self {
+        match Term::from_object(object) {
+            Term::Literal { .. } => match lexical_datatype(object) {
+                Some("boolean") => Self::Boolean,
+                // integers beyond the range of Int64 widen like decimals
+                Some("integer") if object.parse::<i64>().is_err() => Self::Double,
+                Some("integer") => Self::Integer,
+                Some("double" | "decimal") => Self::Double,
+                _ => Self::Text,
+            },
+            _ => Self::Text,
+        }
+    }
+
+    /// the narrowest type holding values of both types
+    fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Integer | Self::Double, Self::Integer | Self::Double) => Self::Double,
+            _ => Self::Text,
+        }
+    }
+
+    const fn data_type(self) -> DataType {
+        match self {
+            Self::Boolean => DataType::Boolean,
+            Self::Integer => DataType::Int64,
+            Self::Double => DataType::Float64,
+            Self::Text => DataType::Utf8,
+        }
+    }
+}
+
+enum ColumnBuilder {
+    Boolean(BooleanBuilder),
+    Integer(Int64Builder),
+    Double(Float64Builder),
+    Text(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::Boolean => Self::Boolean(BooleanBuilder::new()),
+            ColumnType::Integer => Self::Integer(Int64Builder::new()),
+            ColumnType::Double => Self::Double(Float64Builder::new()),
+            ColumnType::Text => Self::Text(StringBuilder::new()),
+        }
+    }
+
+    /// values were typed by `ColumnType::of`, which only types as Integer
+    /// or Double what parses as one, so a value is never dropped
+    fn append(&mut self, value: Option<&str>) {
+        match self {
+            Self::Boolean(b) => b.append_option(value.map(|v| v == "true")),
+            Self::Integer(b) => b.append_option(value.and_then(|v| v.parse().ok())),
+            Self::Double(b) => b.append_option(value.and_then(|v| v.parse().ok())),
+            Self::Text(b) => b.append_option(value),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Boolean(b) => Arc::new(b.finish()),
+            Self::Integer(b) => Arc::new(b.finish()),
+            Self::Double(b) => Arc::new(b.finish()),
+            Self::Text(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+fn finish_batch(
+    schema: &SchemaRef,
+    builders: &mut [ColumnBuilder],
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let columns = builders.iter_mut().map(ColumnBuilder::finish).collect();
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+/// the XSD datatype IRI of a literal, `None` for IRIs and blank nodes
+fn datatype(object: &str) -> Option<String> {
+    match Term::from_object(object) {
+        Term::Literal { .. } => Some(format!(
+            "{XSD}{}",
+            lexical_datatype(object).unwrap_or("string")
+        )),
+        _ => None,
+    }
+}
+
+/// write every triple to `out` as a long table of subject, predicate,
+/// object, datatype and graph columns
+///
+/// # Errors
+///
+/// return `Err` on db read or write errors
+pub async fn export_long<W: AsyncWrite + Unpin + Send>(
+    out: &mut W,
+    batch_size: usize,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("export_long");
+    let schema: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new("subject", DataType::Utf8, false),
+        Field::new("predicate", DataType::Utf8, false),
+        Field::new("object", DataType::Utf8, false),
+        Field::new("datatype", DataType::Utf8, true),
+        Field::new("graph", DataType::Utf8, true),
+    ]));
+    let mut writer = AsyncArrowWriter::try_new(out, schema.clone(), None)?;
+    let mut builders: Vec<ColumnBuilder> = (0..schema.fields().len())
+        .map(|_| ColumnBuilder::new(ColumnType::Text))
+        .collect();
+
+    let mut rows = 0;
+    let mut triples = db_api.stream_triples(None);
+    while let Some((subject, predicate, object, graph)) = triples.try_next().await? {
+        builders[0].append(Some(&subject));
+        builders[1].append(Some(&predicate));
+        builders[2].append(Some(&object));
+        builders[3].append(datatype(&object).as_deref());
+        builders[4].append(graph.as_deref());
+        rows += 1;
+        if rows == batch_size {
+            writer.write(&finish_batch(&schema, &mut builders)?).await?;
+            rows = 0;
+        }
+    }
+    if rows > 0 {
+        writer.write(&finish_batch(&schema, &mut builders)?).await?;
+    }
+    writer.close().await?;
+    Ok(())
+}
+
+/// per predicate, the most objects any subject has and their type
+async fn wide_columns(
+    db_api: &DbApi,
+) -> Result<BTreeMap<String, (usize, ColumnType)>, Box<dyn std::error::Error>> {
+    let mut columns: BTreeMap<String, (usize, ColumnType)> = BTreeMap::new();
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut current: Option<String> = None;
+
+    let mut triples = db_api.stream_triples(None);
+    while let Some((subject, predicate, object, _)) = triples.try_next().await? {
+        if current.as_ref() != Some(&subject) {
+            counts.clear();
+            current = Some(subject);
+        }
+        let count = counts.entry(predicate.clone()).or_insert(0);
+        *count += 1;
+        let object_type = ColumnType::of(&object);
+        let column = columns.entry(predicate).or_insert((0, object_type));
+        column.0 = column.0.max(*count);
+        column.1 = column.1.join(object_type);
+    }
+    Ok(columns)
+}
+
+/// write one row per subject to `out`, pivoted like `csv_file::export_csv`
+/// with `pred1`, `pred2`, ... columns for predicates with many objects, and
+/// each column typed from the datatypes of its values
+///
+/// # Errors
+///
+/// return `Err` on db read or write errors
+pub async fn export_wide<W: AsyncWrite + Unpin + Send>(
+    out: &mut W,
+    export_ns_name: bool,
+    subject_column_name: Option<String>,
+    batch_size: usize,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("export_wide");
+    // first pass: column counts and types, without holding any triples
+    let columns = wide_columns(db_api).await?;
+
+    let mut fields = vec![Field::new(
+        subject_column_name.unwrap_or_else(|| "subject".to_string()),
+        DataType::Utf8,
+        false,
+    )];
+    let mut builders = vec![ColumnBuilder::new(ColumnType::Text)];
+    for (predicate, (max_count, column_type)) in &columns {
+        let display_name = get_display_name(&RdfName::new(predicate.clone()), export_ns_name)?;
+        for i in 0..*max_count {
+            let name = if *max_count > 1 {
+                format!("{display_name}{}", i + 1)
+            } else {
+                display_name.clone()
+            };
+            fields.push(Field::new(name, column_type.data_type(), true));
+            builders.push(ColumnBuilder::new(*column_type));
+        }
+    }
+    let schema: SchemaRef = Arc::new(Schema::new(fields));
+    let mut writer = AsyncArrowWriter::try_new(out, schema.clone(), None)?;
+
+    // second pass: one row per run of triples with the same subject
+    let mut rows = 0;
+    let mut current: Option<(String, BTreeMap<String, Vec<String>>)> = None;
+    let mut triples = db_api.stream_triples(None);
+    loop {
+        let next = triples.try_next().await?;
+        let finished = match (&current, &next) {
+            (Some((subject, _)), Some((next_subject, ..))) => subject != next_subject,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if finished {
+            if let Some((subject, mut values)) = current.take() {
+                builders[0].append(Some(&subject));
+                let mut idx = 1;
+                for (predicate, (max_count, _)) in &columns {
+                    let mut objects = values.remove(predicate).unwrap_or_default();
+                    objects.sort();
+                    for i in 0..*max_count {
+                        builders[idx].append(objects.get(i).map(String::as_str));
+                        idx += 1;
+                    }
+                }
+                rows += 1;
+                if rows == batch_size {
+                    writer.write(&finish_batch(&schema, &mut builders)?).await?;
+                    rows = 0;
+                }
+            }
+        }
+        let Some((subject, predicate, object, _)) = next else {
+            break;
+        };
+        current
+            .get_or_insert_with(|| (subject, BTreeMap::new()))
+            .1
+            .entry(predicate)
+            .or_default()
+            .push(object);
+    }
+    if rows > 0 {
+        writer.write(&finish_batch(&schema, &mut builders)?).await?;
+    }
+    writer.close().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_types_widen() {
+        assert_eq!(ColumnType::of("42"), ColumnType::Integer);
+        assert_eq!(ColumnType::of("4.2"), ColumnType::Double);
+        assert_eq!(ColumnType::of("99999999999999999999"), ColumnType::Double);
+        assert_eq!(ColumnType::of("false"), ColumnType::Boolean);
+        assert_eq!(ColumnType::of("http://example.com/x"), ColumnType::Text);
+        assert_eq!(
+            ColumnType::Integer.join(ColumnType::Double),
+            ColumnType::Double
+        );
+        assert_eq!(
+            ColumnType::Boolean.join(ColumnType::Integer),
+            ColumnType::Text
+        );
+
+        let mut column = ColumnBuilder::new(ColumnType::of("99999999999999999999"));
+        column.append(Some("99999999999999999999"));
+        assert_eq!(column.finish().null_count(), 0);
+
+        assert_eq!(datatype("42").as_deref(), Some(&*format!("{XSD}integer")));
+        assert_eq!(datatype("hi").as_deref(), Some(&*format!("{XSD}string")));
+        assert_eq!(datatype("_:b0"), None);
+    }
+}
@@ -0,0 +1,153 @@
+/// Prefix names for namespace IRIs, used to write compact Turtle.
+///
+/// Exports look names up in the prefixes the user supplies, then the ones
+/// declared by imported files, then the well-known vocabularies below, and
+/// only invent an alias for namespaces none of those cover.
+///
+use crate::data::{is_iri, TriplesError};
+use std::collections::BTreeMap;
+
+/// Vocabularies common enough in our data to always have their usual names.
+pub const WELL_KNOWN: &[(&str, &str)] = &[
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+    ("owl", "http://www.w3.org/2002/07/owl#"),
+    ("schema", "http://schema.org/"),
+    ("brick", "https://brickschema.org/schema/Brick#"),
+    ("qudt", "http://qudt.org/schema/qudt/"),
+    ("unit", "http://qudt.org/vocab/unit/"),
+];
+
+/// A one to one mapping of prefix names and namespace IRIs.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PrefixMap {
+    prefixes: BTreeMap<String, String>,
+}
+
+impl PrefixMap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn well_known() -> Self {
+        let mut map = Self::new();
+        for (prefix, namespace) in WELL_KNOWN {
+            map.insert(prefix, namespace);
+        }
+        map
+    }
+
+    /// add a mapping unless the prefix or the namespace is already mapped,
+    /// returning whether it was added
+    pub fn insert(&mut self, prefix: &str, namespace: &str) -> bool {
+        if self.prefixes.contains_key(prefix) || self.prefix(namespace).is_some() {
+            return false;
+        }
+        self.prefixes
+            .insert(prefix.to_string(), namespace.to_string());
+        true
+    }
+
+    /// add the mappings of a lower priority map that do not clash
+    pub fn extend(&mut self, other: &Self) {
+        for (prefix, namespace) in other.iter() {
+            self.insert(prefix, namespace);
+        }
+    }
+
+    #[must_use]
+    pub fn prefix(&self, namespace: &str) -> Option<&str> {
+        self.prefixes
+            .iter()
+            .find(|(_, ns)| ns.as_str() == namespace)
+            .map(|(prefix, _)| prefix.as_str())
+    }
+
+    #[must_use]
+    pub fn namespace(&self, prefix: &str) -> Option<&str> {
+        self.prefixes.get(prefix).map(String::as_str)
+    }
+
+    /// the mappings ordered by prefix name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.prefixes
+            .iter()
+            .map(|(prefix, namespace)| (prefix.as_str(), namespace.as_str()))
+    }
+
+    /// an IRI as `prefix:local` when its namespace has a prefix
+    #[must_use]
+    pub fn compact(&self, iri: &str) -> Option<String> {
+        let (namespace, local) = crate::iri::split(iri)?;
+        self.prefix(namespace)
+            .map(|prefix| format!("{prefix}:{local}"))
+    }
+
+    /// a `prefix:local` name as the IRI it stands for
+    #[must_use]
+    pub fn expand(&self, name: &str) -> Option<String> {
+        let (prefix, local) = name.split_once(':')?;
+        self.namespace(prefix)
+            .map(|namespace| crate::iri::join(namespace, local))
+    }
+
+    /// an IRI as it is, or a prefixed name as the IRI it stands for
+    ///
+    /// # Errors
+    ///
+    /// return `Err` if `name` is neither an IRI nor has a known prefix
+    pub fn resolve(&self, name: &str) -> Result<String, TriplesError> {
+        if is_iri(name) {
+            return Ok(name.to_string());
+        }
+        self.expand(name).ok_or_else(|| TriplesError::InvalidIRI {
+            uri: name.to_string(),
+        })
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn earlier_mappings_win() {
+        let mut map = PrefixMap::new();
+        assert!(map.insert("b", "https://brickschema.org/schema/Brick#"));
+        map.extend(&PrefixMap::well_known());
+
+        // the user's name for brick is kept and brick: is not reused for it
+        assert_eq!(
+            map.prefix("https://brickschema.org/schema/Brick#"),
+            Some("b")
+        );
+        assert_eq!(map.namespace("brick"), None);
+        assert_eq!(map.prefix("http://www.w3.org/2001/XMLSchema#"), Some("xsd"));
+
+        assert_eq!(
+            map.compact("http://www.w3.org/2001/XMLSchema#integer")
+                .as_deref(),
+            Some("xsd:integer")
+        );
+        assert_eq!(map.compact("http://example.com/x"), None);
+        assert_eq!(
+            map.expand("b:Room").as_deref(),
+            Some("https://brickschema.org/schema/Brick#Room")
+        );
+        assert_eq!(map.expand("nope:Room"), None);
+
+        assert!(!map.insert("xsd", "http://example.com/"));
+        assert_eq!(
+            map.namespace("xsd"),
+            Some("http://www.w3.org/2001/XMLSchema#")
+        );
+    }
+}
@@ -0,0 +1,429 @@
+/// a profile of what a store holds, for a first look at an unfamiliar db
+///
+/// Each predicate is profiled by its triples, distinct subjects and
+/// objects, the fewest and most objects one subject has for it, the kind
+/// of its values and a few examples.  Literal values are typed by their
+/// lexical form, widening integers to decimals and doubles, and a predicate
+/// with IRIs and literals alike has mixed values.
+///
+/// The profile renders as a text report, a draft of SHACL shapes with one
+/// node shape per class, and a VoID description of the dataset.
+///
+use crate::data::{extract_namespace_and_local_name, lexical_datatype, RdfName, Subject, Term};
+use crate::prefixes::PrefixMap;
+use crate::report::{display, table};
+use crate::shacl::SH;
+use crate::vocab::{RDF_TYPE, XSD};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+pub const VOID: &str = "http://rdfs.org/ns/void#";
+
+/// the namespace of the shapes drafted from a profile
+pub const SHAPES: &str = "https://triples.navicore.tech/shapes#";
+
+/// how many example values a predicate profile keeps
+pub const MAX_EXAMPLES: usize = 3;
+
+/// what the values of a predicate are
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueKind {
+    /// no values seen
+    #[default]
+    Unknown,
+    Iri,
+    BlankNode,
+    /// literals of the XSD datatype by local name
+    Literal(&'static str),
+    Mixed,
+}
+
+impl ValueKind {
+    #[must_use]
+    pub fn of(object: &str) -> Self {
+        match Term::from_object(object) {
+            Term::Iri(_) => Self::Iri,
+            Term::BlankNode(_) => Self::BlankNode,
+            Term::Literal { .. } => Self::Literal(lexical_datatype(object).unwrap_or("string")),
+        }
+    }
+
+    /// the narrowest kind holding values of both kinds
+    #[must_use]
+    pub fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Unknown, kind) | (kind, Self::Unknown) => kind,
+            (a, b) if a == b => a,
+            (Self::Literal(a), Self::Literal(b)) => match (a, b) {
+                ("integer" | "decimal", "integer" | "decimal") => Self::Literal("decimal"),
+                ("integer" | "decimal" | "double", "integer" | "decimal" | "double") => {
+                    Self::Literal("double")
+                }
+                _ => Self::Literal("string"),
+            },
+            _ => Self::Mixed,
+        }
+    }
+
+    /// the XSD datatype IRI of literal values
+    #[must_use]
+    pub fn datatype(self) -> Option<String> {
+        match self {
+            Self::Literal(local) => Some(format!("{XSD}{local}")),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "-"),
+            Self::Iri => write!(f, "IRI"),
+            Self::BlankNode => write!(f, "blank node"),
+            Self::Literal(local) => write!(f, "xsd:{local}"),
+            Self::Mixed => write!(f, "mixed"),
+        }
+    }
+}
+
+/// The counts, kind of values and examples of one predicate.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PredicateProfile {
+    pub predicate: String,
+    pub triples: u64,
+    pub subjects: u64,
+    pub objects: u64,
+    pub min_per_subject: u64,
+    pub max_per_subject: u64,
+    pub kind: ValueKind,
+    pub examples: Vec<String>,
+}
+
+impl PredicateProfile {
+    /// take a distinct value of the predicate into account
+    pub fn observe(&mut self, object: &str) {
+        self.kind = self.kind.join(ValueKind::of(object));
+        if self.examples.len() < MAX_EXAMPLES {
+            self.examples.push(object.to_string());
+        }
+    }
+}
+
+/// How many instances of a class have a predicate, and the fewest and most
+/// objects one of them has for it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClassPredicate {
+    pub predicate: String,
+    pub subjects: u64,
+    pub min_per_subject: u64,
+    pub max_per_subject: u64,
+}
+
+/// The instances of one class and the predicates they use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClassProfile {
+    pub class: String,
+    pub instances: u64,
+    pub predicates: Vec<ClassPredicate>,
+}
+
+/// How much of the data one namespace names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceUsage {
+    pub namespace: String,
+    pub predicates: u64,
+    pub classes: u64,
+    /// the triples of its predicates
+    pub triples: u64,
+}
+
+/// The profile of a store, predicates and classes ordered by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub predicates: Vec<PredicateProfile>,
+    pub classes: Vec<ClassProfile>,
+}
+
+fn namespace(iri: &str) -> &str {
+    extract_namespace_and_local_name(iri).map_or("", |(namespace, _)| namespace)
+}
+
+/// a local name usable in a shape IRI
+fn local_name(iri: &str) -> String {
+    let local = extract_namespace_and_local_name(iri).map_or(iri, |(_, local)| local);
+    local
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl Profile {
+    /// every triple of the store, counted once per predicate
+    #[must_use]
+    pub fn triples(&self) -> u64 {
+        self.predicates.iter().map(|p| p.triples).sum()
+    }
+
+    /// the namespaces of predicates and classes, ordered by namespace
+    #[must_use]
+    pub fn namespaces(&self) -> Vec<NamespaceUsage> {
+        fn usage<'a>(
+            namespaces: &'a mut BTreeMap<String, NamespaceUsage>,
+            iri: &str,
+        ) -> &'a mut NamespaceUsage {
+            let namespace = namespace(iri);
+            namespaces
+                .entry(namespace.to_string())
+                .or_insert_with(|| NamespaceUsage {
+                    namespace: namespace.to_string(),
+                    ..NamespaceUsage::default()
+                })
+        }
+
+        let mut namespaces = BTreeMap::new();
+        for predicate in &self.predicates {
+            let usage = usage(&mut namespaces, &predicate.predicate);
+            usage.predicates += 1;
+            usage.triples += predicate.triples;
+        }
+        for class in &self.classes {
+            usage(&mut namespaces, &class.class).classes += 1;
+        }
+        namespaces.into_values().collect()
+    }
+
+    /// the profile as text tables, names compacted by `prefixes`
+    #[must_use]
+    pub fn report(&self, prefixes: &PrefixMap) -> String {
+        let mut text = format!(
+            "triples {}, predicates {}, classes {}\n\n",
+            self.triples(),
+            self.predicates.len(),
+            self.classes.len()
+        );
+
+        let rows: Vec<Vec<String>> = self
+            .predicates
+            .iter()
+            .map(|p| {
+                vec![
+                    display(&p.predicate, prefixes),
+                    p.triples.to_string(),
+                    p.subjects.to_string(),
+                    p.objects.to_string(),
+                    p.min_per_subject.to_string(),
+                    p.max_per_subject.to_string(),
+                    p.kind.to_string(),
+                    p.examples
+                        .iter()
+                        .map(|example| display(example, prefixes))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ]
+            })
+            .collect();
+        text.push_str(&table(
+            &[
+                "predicate",
+                "triples",
+                "subjects",
+                "objects",
+                "min",
+                "max",
+                "values",
+                "examples",
+            ],
+            &rows,
+            &[false, true, true, true, true, true, false, false],
+        ));
+
+        if !self.classes.is_empty() {
+            let rows: Vec<Vec<String>> = self
+                .classes
+                .iter()
+                .map(|c| vec![display(&c.class, prefixes), c.instances.to_string()])
+                .collect();
+            text.push('\n');
+            text.push_str(&table(&["class", "instances"], &rows, &[false, true]));
+        }
+
+        let rows: Vec<Vec<String>> = self
+            .namespaces()
+            .into_iter()
+            .map(|n| {
+                let prefix = prefixes.prefix(&n.namespace).unwrap_or_default();
+                vec![
+                    n.namespace,
+                    prefix.to_string(),
+                    n.predicates.to_string(),
+                    n.classes.to_string(),
+                    n.triples.to_string(),
+                ]
+            })
+            .collect();
+        text.push('\n');
+        text.push_str(&table(
+            &["namespace", "prefix", "predicates", "classes", "triples"],
+            &rows,
+            &[false, false, true, true, true],
+        ));
+        text
+    }
+
+    /// draft SHACL shapes, a node shape targeting each class with a
+    /// property shape for each predicate its instances use.  A predicate
+    /// every instance has gets `sh:minCount 1`, one no instance repeats
+    /// `sh:maxCount 1`, and the kind of its values `sh:datatype` or
+    /// `sh:nodeKind`
+    #[must_use]
+    pub fn to_shapes(&self) -> Vec<Subject> {
+        let name = |iri: &str| RdfName::new(iri.to_string());
+        let sh = |local: &str| format!("{SH}{local}");
+        let kinds: BTreeMap<&str, ValueKind> = self
+            .predicates
+            .iter()
+            .map(|p| (p.predicate.as_str(), p.kind))
+            .collect();
+
+        let mut used = BTreeSet::new();
+        let mut unique = |local: String| {
+            let mut id = local.clone();
+            let mut n = 1;
+            while !used.insert(id.clone()) {
+                n += 1;
+                id = format!("{local}{n}");
+            }
+            format!("{SHAPES}{id}")
+        };
+
+        let mut subjects = Vec::new();
+        for class in &self.classes {
+            let class_local = local_name(&class.class);
+            let mut shape = Subject::new(name(&unique(format!("{class_local}Shape"))));
+            shape.add(name(RDF_TYPE), sh("NodeShape"));
+            shape.add(name(&sh("targetClass")), class.class.clone());
+            let mut properties = Vec::new();
+            for predicate in &class.predicates {
+                if predicate.predicate == RDF_TYPE {
+                    continue;
+                }
+                let id = unique(format!(
+                    "{class_local}-{}",
+                    local_name(&predicate.predicate)
+                ));
+                shape.add(name(&sh("property")), id.clone());
+                let mut property = Subject::new(name(&id));
+                property.add(name(&sh("path")), predicate.predicate.clone());
+                if predicate.subjects == class.instances {
+                    property.add(name(&sh("minCount")), "1".to_string());
+                }
+                if predicate.max_per_subject == 1 {
+                    property.add(name(&sh("maxCount")), "1".to_string());
+                }
+                match kinds.get(predicate.predicate.as_str()) {
+                    Some(ValueKind::Iri) => property.add(name(&sh("nodeKind")), sh("IRI")),
+                    Some(ValueKind::BlankNode) => {
+                        property.add(name(&sh("nodeKind")), sh("BlankNode"));
+                    }
+                    Some(kind @ ValueKind::Literal(_)) => {
+                        property.add(name(&sh("datatype")), kind.datatype().unwrap_or_default());
+                    }
+                    _ => {}
+                }
+                properties.push(property);
+            }
+            subjects.push(shape);
+            subjects.extend(properties);
+        }
+        subjects
+    }
+
+    /// a VoID description of the store as `_:dataset`, with a property
+    /// partition per predicate and a class partition per class
+    #[must_use]
+    pub fn to_void(&self) -> Vec<Subject> {
+        let mut dataset = void_dataset("_:dataset");
+        dataset.add(void("triples"), self.triples().to_string());
+        dataset.add(void("properties"), self.predicates.len().to_string());
+        dataset.add(void("classes"), self.classes.len().to_string());
+
+        let mut subjects = Vec::new();
+        for (n, predicate) in self.predicates.iter().enumerate() {
+            let id = format!("_:property{}", n + 1);
+            dataset.add(void("propertyPartition"), id.clone());
+            let mut partition = Subject::new(RdfName::new(id));
+            partition.add(void("property"), predicate.predicate.clone());
+            partition.add(void("triples"), predicate.triples.to_string());
+            partition.add(void("distinctSubjects"), predicate.subjects.to_string());
+            partition.add(void("distinctObjects"), predicate.objects.to_string());
+            subjects.push(partition);
+        }
+        subjects.extend(class_partitions(
+            &mut dataset,
+            self.classes
+                .iter()
+                .map(|class| (class.class.as_str(), class.instances)),
+        ));
+        subjects.insert(0, dataset);
+        subjects
+    }
+}
+
+/// the VoID term `local`
+pub(crate) fn void(local: &str) -> RdfName {
+    RdfName::new(format!("{VOID}{local}"))
+}
+
+/// a `void:Dataset` named `id`
+pub(crate) fn void_dataset(id: &str) -> Subject {
+    let mut dataset = Subject::new(RdfName::new(id.to_string()));
+    dataset.add(RdfName::new(RDF_TYPE.to_string()), format!("{VOID}Dataset"));
+    dataset
+}
+
+/// a `void:classPartition` of `dataset` per class with its count of
+/// instances
+pub(crate) fn class_partitions<'a>(
+    dataset: &mut Subject,
+    classes: impl Iterator<Item = (&'a str, u64)>,
+) -> Vec<Subject> {
+    classes
+        .enumerate()
+        .map(|(n, (class, instances))| {
+            let id = format!("_:class{}", n + 1);
+            dataset.add(void("classPartition"), id.clone());
+            let mut partition = Subject::new(RdfName::new(id));
+            partition.add(void("class"), class.to_string());
+            partition.add(void("entities"), instances.to_string());
+            partition
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_kinds_widen() {
+        let kind = |values: &[&str]| {
+            values.iter().fold(ValueKind::Unknown, |kind, value| {
+                kind.join(ValueKind::of(value))
+            })
+        };
+        assert_eq!(kind(&["1", "2"]), ValueKind::Literal("integer"));
+        assert_eq!(kind(&["1", "2.5"]), ValueKind::Literal("decimal"));
+        assert_eq!(kind(&["1", "2.5e3"]), ValueKind::Literal("double"));
+        assert_eq!(kind(&["1", "warm"]), ValueKind::Literal("string"));
+        assert_eq!(kind(&["http://example.com/a"]), ValueKind::Iri);
+        assert_eq!(kind(&["http://example.com/a", "1"]), ValueKind::Mixed);
+        assert_eq!(kind(&[]).to_string(), "-");
+    }
+}
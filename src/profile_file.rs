@@ -0,0 +1,134 @@
+/// functions in support of profiling a db
+///
+/// The report is written as text and the draft shapes and VoID
+/// description as Turtle files.
+///
+/// Counts come from aggregate queries, and the distinct objects of each
+/// predicate are streamed to type its values rather than held in memory.
+///
+use crate::db_api::DbApi;
+use crate::prefixes::PrefixMap;
+use crate::profile::{ClassPredicate, ClassProfile, Profile, SHAPES, VOID};
+use crate::shacl::SH;
+use crate::ttl_file;
+use crate::turtle_writer::{TurtleOptions, TurtleWriter};
+use futures::TryStreamExt;
+use std::collections::BTreeMap;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::debug;
+
+/// profile the predicates and classes of the db
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read
+pub async fn profile(db_api: &DbApi) -> Result<Profile, Box<dyn std::error::Error>> {
+    let mut predicates = db_api.predicate_profiles().await?;
+    let by_name: BTreeMap<String, usize> = predicates
+        .iter()
+        .enumerate()
+        .map(|(n, p)| (p.predicate.clone(), n))
+        .collect();
+    let mut objects = db_api.stream_predicate_objects();
+    while let Some((predicate, object)) = objects.try_next().await? {
+        if let Some(&n) = by_name.get(&predicate) {
+            predicates[n].observe(&object);
+        }
+    }
+
+    let mut classes: Vec<ClassProfile> = db_api
+        .class_counts()
+        .await?
+        .into_iter()
+        .map(|(class, instances)| ClassProfile {
+            class: class.to_string(),
+            instances,
+            predicates: Vec::new(),
+        })
+        .collect();
+    let by_class: BTreeMap<String, usize> = classes
+        .iter()
+        .enumerate()
+        .map(|(n, c)| (c.class.clone(), n))
+        .collect();
+    for (class, predicate, subjects, min, max) in db_api.class_predicate_counts().await? {
+        if let Some(&n) = by_class.get(&class) {
+            classes[n].predicates.push(ClassPredicate {
+                predicate,
+                subjects,
+                min_per_subject: min,
+                max_per_subject: max,
+            });
+        }
+    }
+    debug!(
+        "profiled {} predicates and {} classes",
+        predicates.len(),
+        classes.len()
+    );
+
+    Ok(Profile {
+        predicates,
+        classes,
+    })
+}
+
+/// the preferred prefixes of the db and those of the report vocabularies
+async fn report_prefixes(db_api: &DbApi) -> Result<PrefixMap, Box<dyn std::error::Error>> {
+    let mut preferred = db_api.preferred_prefixes().await?;
+    preferred.insert("sh", SH);
+    preferred.insert("void", VOID);
+    preferred.insert("shape", SHAPES);
+    Ok(preferred)
+}
+
+/// write the profile as a text report to `out`
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read or `out` written
+pub async fn write_report<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    profile: &Profile,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prefixes = report_prefixes(db_api).await?;
+    out.write_all(profile.report(&prefixes).as_bytes()).await?;
+    Ok(())
+}
+
+/// write the SHACL shapes drafted from the profile as turtle to `out`
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read or `out` written
+pub async fn write_shapes<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    profile: &Profile,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let subjects = profile.to_shapes();
+    let prefixes = ttl_file::compute_prefixes(&subjects, &report_prefixes(db_api).await?)?;
+    TurtleWriter::new(&subjects, &prefixes, TurtleOptions::default())
+        .write_async(out)
+        .await?;
+    Ok(())
+}
+
+/// write the VoID description of the profile as turtle to `out`
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read or `out` written
+pub async fn write_void<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    profile: &Profile,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let subjects = profile.to_void();
+    let prefixes = ttl_file::compute_prefixes(&subjects, &report_prefixes(db_api).await?)?;
+    TurtleWriter::new(&subjects, &prefixes, TurtleOptions::default())
+        .write_async(out)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,356 @@
+/// RDFS entailment, materialised into the inferred graph and kept current
+/// by triggers on the `triples` table
+///
+/// Once enabled every inserted triple, asserted or inferred, has what
+/// rdfs2 (domain), rdfs3 (range), rdfs5 and rdfs11 (subproperty and
+/// subclass transitivity), rdfs7 (subproperty) and rdfs9 (type
+/// propagation) entail from it inserted into `INFERRED_GRAPH`, so the
+/// closure grows with the data.  Deleting an asserted triple drops and
+/// re-derives the inferences about its subject and object, or every
+/// inference when the triple is part of the schema.  A triple is only
+/// inferred when it is not already stored.
+///
+/// The axiomatic triples, `rdfs:Resource` and `rdf:Property` typing and
+/// ranges over literals are left out, they hold of everything and select
+/// nothing.
+///
+use crate::db;
+use crate::inference::{self, INFERRED_GRAPH, RDFS_DELETE_TRIGGER};
+use crate::vocab::{RDFS_DOMAIN, RDFS_RANGE, RDFS_SUB_CLASS_OF, RDFS_SUB_PROPERTY_OF, RDF_TYPE};
+use sqlx::Pool;
+use sqlx::Sqlite;
+use tracing::debug;
+
+const INSERT_TRIGGER: &str = "trg_rdfs_insert";
+const INFERRED_INDEX: &str = "idx_rdfs_inferred";
+
+/// the `names` id of the IRI held by an `objects` id
+fn name_of(object: &str) -> String {
+    format!(
+        "(SELECT names.id FROM names JOIN objects ON names.name = objects.object \
+        WHERE objects.id = {object})"
+    )
+}
+
+/// the `objects` id of the IRI held by a `names` id
+fn object_of(name: &str) -> String {
+    format!(
+        "(SELECT objects.id FROM objects JOIN names ON objects.object = names.name \
+        WHERE names.id = {name})"
+    )
+}
+
+/// whether an `objects` id holds an IRI or blank node rather than a
+/// literal, by the node test of `db::node_object`
+fn is_resource(object: &str) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM objects WHERE id = {object} AND {})",
+        db::node_object("objects.object")
+    )
+}
+
+/// the ids of the vocabulary and graph the rules are written in
+struct Ids {
+    rdf_type: i64,
+    sub_class_of: i64,
+    sub_property_of: i64,
+    domain: i64,
+    range: i64,
+    graph: i64,
+}
+
+impl Ids {
+    async fn load(pool: &Pool<Sqlite>) -> Result<Self, sqlx::Error> {
+        let mut ids = Vec::new();
+        for name in [
+            RDF_TYPE,
+            RDFS_SUB_CLASS_OF,
+            RDFS_SUB_PROPERTY_OF,
+            RDFS_DOMAIN,
+            RDFS_RANGE,
+            INFERRED_GRAPH,
+        ] {
+            let row: (i64,) = sqlx::query_as(
+                "INSERT OR IGNORE INTO names (name) VALUES (?); \
+                SELECT id FROM names WHERE name = ?;",
+            )
+            .bind(name)
+            .bind(name)
+            .fetch_one(pool)
+            .await?;
+            ids.push(row.0);
+        }
+
+        Ok(Self {
+            rdf_type: ids[0],
+            sub_class_of: ids[1],
+            sub_property_of: ids[2],
+            domain: ids[3],
+            range: ids[4],
+            graph: ids[5],
+        })
+    }
+
+    fn schema(&self) -> String {
+        format!(
+            "{}, {}, {}, {}",
+            self.sub_class_of, self.sub_property_of, self.domain, self.range
+        )
+    }
+
+    /// insert the `s`, `p`, `o` rows of `candidates` into the inferred
+    /// graph, skipping triples already stored
+    fn derive(&self, candidates: &str) -> String {
+        format!(
+            "INSERT OR IGNORE INTO triples (subject, predicate, object, graph) \
+            SELECT DISTINCT c.s, c.p, c.o, {graph} FROM ({candidates}) AS c \
+            WHERE c.s IS NOT NULL AND c.p IS NOT NULL AND c.o IS NOT NULL \
+            AND NOT EXISTS (SELECT 1 FROM triples AS t \
+                WHERE t.subject = c.s AND t.predicate = c.p AND t.object = c.o);\n",
+            graph = self.graph
+        )
+    }
+
+    /// statements deriving what follows from the triples `n` of `from`
+    /// together with the stored schema
+    fn forward(&self, from: &str) -> String {
+        let Self {
+            rdf_type,
+            sub_class_of,
+            sub_property_of,
+            domain,
+            range,
+            ..
+        } = self;
+        let n_object_name = name_of("n.object");
+        let e_object_name = name_of("e.object");
+        let n_object_is_resource = is_resource("n.object");
+
+        // subproperties and range values become subjects, so need names
+        let mut sql = format!(
+            "INSERT OR IGNORE INTO names (name) \
+            SELECT objects.object FROM {from} JOIN objects ON objects.id = n.object \
+            WHERE n.predicate = {sub_property_of} \
+            OR ({n_object_is_resource} AND EXISTS (SELECT 1 FROM triples AS e \
+                WHERE e.predicate = {range} AND e.subject = n.predicate));\n"
+        );
+        // rdfs9 and rdfs11
+        sql.push_str(&self.derive(&format!(
+            "SELECT n.subject AS s, n.predicate AS p, e.object AS o \
+            FROM {from} JOIN triples AS e \
+                ON e.predicate = {sub_class_of} AND e.subject = {n_object_name} \
+            WHERE n.predicate IN ({rdf_type}, {sub_class_of})"
+        )));
+        // rdfs5
+        sql.push_str(&self.derive(&format!(
+            "SELECT n.subject AS s, n.predicate AS p, e.object AS o \
+            FROM {from} JOIN triples AS e \
+                ON e.predicate = {sub_property_of} AND e.subject = {n_object_name} \
+            WHERE n.predicate = {sub_property_of}"
+        )));
+        // rdfs7
+        sql.push_str(&self.derive(&format!(
+            "SELECT n.subject AS s, {e_object_name} AS p, n.object AS o \
+            FROM {from} JOIN triples AS e \
+                ON e.predicate = {sub_property_of} AND e.subject = n.predicate"
+        )));
+        // rdfs2
+        sql.push_str(&self.derive(&format!(
+            "SELECT n.subject AS s, {rdf_type} AS p, e.object AS o \
+            FROM {from} JOIN triples AS e \
+                ON e.predicate = {domain} AND e.subject = n.predicate"
+        )));
+        // rdfs3
+        sql.push_str(&self.derive(&format!(
+            "SELECT {n_object_name} AS s, {rdf_type} AS p, e.object AS o \
+            FROM {from} JOIN triples AS e \
+                ON e.predicate = {range} AND e.subject = n.predicate \
+            WHERE {n_object_is_resource}"
+        )));
+        sql
+    }
+
+    /// statements deriving what follows from the schema triples `n` of
+    /// `from` together with the stored triples `t` they describe
+    fn backward(&self, from: &str) -> String {
+        let Self {
+            rdf_type,
+            sub_class_of,
+            sub_property_of,
+            domain,
+            range,
+            ..
+        } = self;
+        let n_subject_object = object_of("n.subject");
+        let n_object_name = name_of("n.object");
+        let t_object_name = name_of("t.object");
+        let t_object_is_resource = is_resource("t.object");
+
+        let mut sql = format!(
+            "INSERT OR IGNORE INTO names (name) \
+            SELECT objects.object FROM {from} JOIN triples AS t ON t.predicate = n.subject \
+            JOIN objects ON objects.id = t.object \
+            WHERE n.predicate = {range} AND {t_object_is_resource};\n"
+        );
+        // rdfs9 and rdfs11
+        sql.push_str(&self.derive(&format!(
+            "SELECT t.subject AS s, t.predicate AS p, n.object AS o \
+            FROM {from} JOIN triples AS t \
+                ON t.predicate IN ({rdf_type}, {sub_class_of}) AND t.object = {n_subject_object} \
+            WHERE n.predicate = {sub_class_of}"
+        )));
+        // rdfs5
+        sql.push_str(&self.derive(&format!(
+            "SELECT t.subject AS s, t.predicate AS p, n.object AS o \
+            FROM {from} JOIN triples AS t \
+                ON t.predicate = {sub_property_of} AND t.object = {n_subject_object} \
+            WHERE n.predicate = {sub_property_of}"
+        )));
+        // rdfs7
+        sql.push_str(&self.derive(&format!(
+            "SELECT t.subject AS s, {n_object_name} AS p, t.object AS o \
+            FROM {from} JOIN triples AS t ON t.predicate = n.subject \
+            WHERE n.predicate = {sub_property_of}"
+        )));
+        // rdfs2
+        sql.push_str(&self.derive(&format!(
+            "SELECT t.subject AS s, {rdf_type} AS p, n.object AS o \
+            FROM {from} JOIN triples AS t ON t.predicate = n.subject \
+            WHERE n.predicate = {domain}"
+        )));
+        // rdfs3
+        sql.push_str(&self.derive(&format!(
+            "SELECT {t_object_name} AS s, {rdf_type} AS p, n.object AS o \
+            FROM {from} JOIN triples AS t ON t.predicate = n.subject \
+            WHERE n.predicate = {range} AND {t_object_is_resource}"
+        )));
+        sql
+    }
+
+    fn insert_trigger(&self) -> String {
+        let new = "(SELECT NEW.subject AS subject, NEW.predicate AS predicate, \
+            NEW.object AS object) AS n";
+        format!(
+            "CREATE TRIGGER {INSERT_TRIGGER} AFTER INSERT ON triples\nBEGIN\n{}{}END;",
+            self.forward(new),
+            self.backward(new)
+        )
+    }
+
+    /// inferences about the subject and object of a deleted triple are
+    /// dropped and derived again from what remains, all of them when a
+    /// schema triple is deleted, and then conclusions of rules resting on
+    /// the dropped ones are rechecked
+    fn delete_trigger(&self) -> String {
+        let graph = self.graph;
+        let schema = self.schema();
+        let old_object_name = name_of("OLD.object");
+        let old_subject_object = object_of("OLD.subject");
+        let affected =
+            format!("OLD.predicate IN ({schema}) OR subject IN (OLD.subject, {old_object_name})");
+        let premises = format!(
+            "(SELECT * FROM triples WHERE {affected} \
+            OR object IN (OLD.object, {old_subject_object})) AS n"
+        );
+        format!(
+            "CREATE TRIGGER {RDFS_DELETE_TRIGGER} AFTER DELETE ON triples \
+            WHEN OLD.graph IS NOT {graph}\nBEGIN\n\
+            DELETE FROM triples WHERE graph = {graph} AND ({affected});\n{}{}END;",
+            self.forward(&premises),
+            inference::retract_unsupported(&format!(
+                "OLD.predicate IN ({schema}) OR lost.subject IN (OLD.subject, {old_object_name})"
+            ))
+        )
+    }
+}
+
+/// Whether RDFS inference is enabled for the store.
+///
+/// # Errors
+///
+/// Will return `Err` if the schema cannot be read.
+pub async fn is_enabled(pool: &Pool<Sqlite>) -> Result<bool, Box<dyn std::error::Error>> {
+    let row: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM sqlite_master WHERE type = 'trigger' AND name = ?1")
+            .bind(INSERT_TRIGGER)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(row.0 > 0)
+}
+
+/// Installs the inference triggers and materialises the entailments of the
+/// stored triples, returning how many triples are inferred.  Enabling again
+/// re-derives every inference.
+///
+/// # Errors
+///
+/// Will return `Err` if the triggers cannot be installed or the
+/// entailments cannot be stored.
+pub async fn enable(pool: &Pool<Sqlite>) -> Result<u64, Box<dyn std::error::Error>> {
+    drop_triggers(pool).await?;
+    let ids = Ids::load(pool).await?;
+
+    // with the delete trigger in place the conclusions of rules resting on
+    // inferences are kept while those are derived again, and only then
+    // rechecked
+    sqlx::query(&ids.delete_trigger()).execute(pool).await?;
+    sqlx::query("DELETE FROM triples WHERE graph = ?1")
+        .bind(ids.graph)
+        .execute(pool)
+        .await?;
+    sqlx::query(&format!(
+        "CREATE UNIQUE INDEX {INFERRED_INDEX} ON triples (subject, predicate, object) \
+        WHERE graph = {}",
+        ids.graph
+    ))
+    .execute(pool)
+    .await?;
+    sqlx::query(&ids.insert_trigger()).execute(pool).await?;
+
+    // the triggers carry each inference on to what follows from it
+    sqlx::query(&ids.forward("triples AS n"))
+        .execute(pool)
+        .await?;
+    sqlx::query(&inference::retract_unsupported("TRUE"))
+        .execute(pool)
+        .await?;
+
+    let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM triples WHERE graph = ?1")
+        .bind(ids.graph)
+        .fetch_one(pool)
+        .await?;
+    debug!("rdfs inference enabled, {} triples inferred", row.0);
+
+    Ok(u64::try_from(row.0).unwrap_or(0))
+}
+
+async fn drop_triggers(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!(
+        "DROP TRIGGER IF EXISTS {INSERT_TRIGGER}; \
+        DROP TRIGGER IF EXISTS {RDFS_DELETE_TRIGGER}; \
+        DROP INDEX IF EXISTS {INFERRED_INDEX};"
+    ))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Removes the inference triggers and every inferred triple, returning how
+/// many were removed, with the conclusions of rules resting on them.
+///
+/// # Errors
+///
+/// Will return `Err` if the triggers or triples cannot be removed.
+pub async fn disable(pool: &Pool<Sqlite>) -> Result<u64, Box<dyn std::error::Error>> {
+    drop_triggers(pool).await?;
+
+    let result =
+        sqlx::query("DELETE FROM triples WHERE graph = (SELECT id FROM names WHERE name = ?1)")
+            .bind(INFERRED_GRAPH)
+            .execute(pool)
+            .await?;
+    debug!("rdfs inference disabled");
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,700 @@
+/// A streaming reader and a writer for RDF/XML
+/// <https://www.w3.org/TR/rdf-syntax-grammar/>
+///
+/// The reader keeps one frame per open element, so memory grows with the
+/// nesting depth of a document, not its length.  Like the rest of the
+/// store only lexical forms are kept, `xml:lang` and `rdf:datatype` are
+/// read but dropped.
+///
+use crate::data::{is_iri, parse_error, BlankNodes, Subject, TriplesError};
+use crate::iri;
+use crate::prefixes::PrefixMap;
+use crate::vocab::RDF;
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::ResolveResult;
+use quick_xml::reader::NsReader;
+use quick_xml::XmlVersion;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use tokio::io::AsyncBufRead;
+
+const XML: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// a subject, predicate and object, blank nodes as `_:label`
+pub type Triple = (String, String, String);
+
+fn rdf(local: &str) -> String {
+    format!("{RDF}{local}")
+}
+
+/// the open elements, innermost last
+enum Frame {
+    /// `rdf:RDF`
+    Root,
+    /// a node element, or the blank node of `rdf:parseType="Resource"`
+    Node { subject: String, li: usize },
+    /// a property element waiting for text or a node element
+    Property {
+        subject: String,
+        predicate: String,
+        object: Option<String>,
+        text: String,
+        done: bool,
+    },
+    /// `rdf:parseType="Collection"` gathering its member nodes
+    Collection {
+        subject: String,
+        predicate: String,
+        items: Vec<String>,
+    },
+    /// `rdf:parseType="Literal"` copying its content as written
+    Literal {
+        subject: String,
+        predicate: String,
+        depth: usize,
+        text: String,
+    },
+}
+
+/// what the parser knows between events
+struct State {
+    stack: Vec<(Frame, String)>,
+    base: String,
+    blank_nodes: BlankNodes,
+    triples: Vec<Triple>,
+}
+
+impl State {
+    fn base(&self) -> &str {
+        self.stack.last().map_or(&self.base, |(_, base)| base)
+    }
+
+    fn emit(&mut self, subject: &str, predicate: &str, object: String) {
+        self.triples
+            .push((subject.to_string(), predicate.to_string(), object));
+    }
+
+    fn in_literal(&self) -> bool {
+        matches!(self.stack.last(), Some((Frame::Literal { .. }, _)))
+    }
+
+    fn start(&mut self, name: String, attributes: Vec<(String, String)>, raw: &str) {
+        if let Some((Frame::Literal { depth, text, .. }, _)) = self.stack.last_mut() {
+            *depth += 1;
+            let _ = write!(text, "<{raw}>");
+            return;
+        }
+
+        let base = attributes
+            .iter()
+            .find(|(key, _)| key.strip_prefix(XML) == Some("base"))
+            .map_or_else(
+                || self.base().to_string(),
+                |(_, v)| iri::resolve(self.base(), v),
+            );
+
+        match self.stack.last() {
+            None if name == rdf("RDF") => self.stack.push((Frame::Root, base)),
+            Some((Frame::Node { .. }, _)) => self.property_element(name, &attributes, base),
+            _ => self.node_element(&name, &attributes, base),
+        }
+    }
+
+    fn node_element(&mut self, name: &str, attributes: &[(String, String)], base: String) {
+        let subject = self.subject(attributes, &base);
+        if name != rdf("Description") {
+            self.emit(&subject, &rdf("type"), name.to_string());
+        }
+        self.property_attributes(&subject, attributes, &base);
+
+        match self.stack.last_mut() {
+            Some((Frame::Property { object, .. }, _)) => *object = Some(subject.clone()),
+            Some((Frame::Collection { items, .. }, _)) => items.push(subject.clone()),
+            _ => {}
+        }
+        self.stack.push((Frame::Node { subject, li: 0 }, base));
+    }
+
+    fn subject(&mut self, attributes: &[(String, String)], base: &str) -> String {
+        for (key, value) in attributes {
+            match key.strip_prefix(RDF) {
+                Some("about") => return iri::resolve(base, value),
+                Some("ID") => return iri::resolve(base, &format!("#{value}")),
+                Some("nodeID") => return self.blank_nodes.label(value),
+                _ => {}
+            }
+        }
+        self.blank_nodes.fresh()
+    }
+
+    /// attributes other than the syntax ones are properties with literal
+    /// values, `rdf:type` excepted
+    fn property_attributes(&mut self, subject: &str, attributes: &[(String, String)], base: &str) {
+        for (key, value) in attributes {
+            if is_syntax_attribute(key) {
+                continue;
+            }
+            let object = if *key == rdf("type") {
+                iri::resolve(base, value)
+            } else {
+                value.clone()
+            };
+            self.emit(subject, key, object);
+        }
+    }
+
+    fn property_element(&mut self, name: String, attributes: &[(String, String)], base: String) {
+        let Some((Frame::Node { subject, li }, _)) = self.stack.last_mut() else {
+            return;
+        };
+        let subject = subject.clone();
+        let predicate = if name == rdf("li") {
+            *li += 1;
+            rdf(&format!("_{li}"))
+        } else {
+            name
+        };
+        let attribute = |local: &str| {
+            attributes
+                .iter()
+                .find(|(key, _)| key.strip_prefix(RDF) == Some(local))
+                .map(|(_, value)| value.as_str())
+        };
+
+        let frame = match attribute("parseType") {
+            Some("Resource") => {
+                let node = self.blank_nodes.fresh();
+                self.emit(&subject, &predicate, node.clone());
+                Frame::Node {
+                    subject: node,
+                    li: 0,
+                }
+            }
+            Some("Collection") => Frame::Collection {
+                subject,
+                predicate,
+                items: Vec::new(),
+            },
+            Some(_) => Frame::Literal {
+                subject,
+                predicate,
+                depth: 0,
+                text: String::new(),
+            },
+            None => {
+                let resource = attribute("resource").map(|r| iri::resolve(&base, r));
+                let node_id = attribute("nodeID").map(str::to_string);
+                let has_properties = attributes.iter().any(|(key, _)| !is_syntax_attribute(key));
+                if resource.is_some() || node_id.is_some() || has_properties {
+                    let object = match (resource, node_id) {
+                        (Some(resource), _) => resource,
+                        (None, Some(label)) => self.blank_nodes.label(&label),
+                        (None, None) => self.blank_nodes.fresh(),
+                    };
+                    self.property_attributes(&object, attributes, &base);
+                    self.emit(&subject, &predicate, object.clone());
+                    Frame::Property {
+                        subject,
+                        predicate,
+                        object: Some(object),
+                        text: String::new(),
+                        done: true,
+                    }
+                } else {
+                    Frame::Property {
+                        subject,
+                        predicate,
+                        object: None,
+                        text: String::new(),
+                        done: false,
+                    }
+                }
+            }
+        };
+        self.stack.push((frame, base));
+    }
+
+    /// character data, `raw` as written for literal XML content
+    fn text(&mut self, content: &str, raw: &str) {
+        match self.stack.last_mut() {
+            Some((Frame::Literal { text, .. }, _)) => text.push_str(raw),
+            Some((Frame::Property { text, .. }, _)) => text.push_str(content),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, raw_name: &str) {
+        if let Some((Frame::Literal { depth, text, .. }, _)) = self.stack.last_mut() {
+            if *depth > 0 {
+                *depth -= 1;
+                let _ = write!(text, "</{raw_name}>");
+                return;
+            }
+        }
+
+        let Some((frame, _)) = self.stack.pop() else {
+            return;
+        };
+        match frame {
+            Frame::Root | Frame::Node { .. } => {}
+            Frame::Property {
+                subject,
+                predicate,
+                object,
+                text,
+                done,
+            } => {
+                if !done {
+                    self.emit(&subject, &predicate, object.unwrap_or(text));
+                }
+            }
+            Frame::Collection {
+                subject,
+                predicate,
+                items,
+            } => {
+                let mut head = rdf("nil");
+                for item in items.into_iter().rev() {
+                    let node = self.blank_nodes.fresh();
+                    self.emit(&node, &rdf("first"), item);
+                    self.emit(&node, &rdf("rest"), head);
+                    head = node;
+                }
+                self.emit(&subject, &predicate, head);
+            }
+            Frame::Literal {
+                subject,
+                predicate,
+                text,
+                ..
+            } => self.emit(&subject, &predicate, text),
+        }
+    }
+}
+
+/// attributes that are part of the syntax rather than properties
+fn is_syntax_attribute(key: &str) -> bool {
+    key.starts_with(XML)
+        || matches!(
+            key.strip_prefix(RDF),
+            Some(
+                "about"
+                    | "ID"
+                    | "nodeID"
+                    | "resource"
+                    | "parseType"
+                    | "datatype"
+                    | "aboutEach"
+                    | "aboutEachPrefix"
+                    | "bagID"
+            )
+        )
+}
+
+/// Reads triples from an RDF/XML document as its elements arrive.
+pub struct RdfXmlParser<R> {
+    reader: NsReader<R>,
+    buf: Vec<u8>,
+    state: State,
+    done: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> RdfXmlParser<R> {
+    /// a parser resolving relative IRIs against `base` until an
+    /// `xml:base` says otherwise
+    #[must_use]
+    pub fn new(reader: R, base: &str) -> Self {
+        let mut reader = NsReader::from_reader(reader);
+        reader.config_mut().expand_empty_elements = true;
+        Self {
+            reader,
+            buf: Vec::new(),
+            state: State {
+                stack: Vec::new(),
+                base: base.to_string(),
+                blank_nodes: BlankNodes::new(),
+                triples: Vec::new(),
+            },
+            done: false,
+        }
+    }
+
+    /// the triples completed by the next elements of the document, empty
+    /// at its end
+    ///
+    /// # Errors
+    ///
+    /// return `Err` if the document is not well formed XML or uses unknown
+    /// entities or namespace prefixes
+    pub async fn next_triples(&mut self) -> Result<Vec<Triple>, TriplesError> {
+        while self.state.triples.is_empty() && !self.done {
+            self.buf.clear();
+            let event = self
+                .reader
+                .read_event_into_async(&mut self.buf)
+                .await
+                .map_err(parse_error)?;
+            match event {
+                Event::Start(e) if self.state.in_literal() => {
+                    self.state.start(String::new(), Vec::new(), &e);
+                }
+                Event::Start(e) => {
+                    let name = match self.reader.resolver().resolve_element(e.name()) {
+                        (ResolveResult::Bound(ns), local) => {
+                            format!("{}{}", ns.as_ref(), local.as_ref())
+                        }
+                        _ => {
+                            return Err(parse_error(format!(
+                                "element {} is not in a namespace",
+                                e.name().as_ref()
+                            )))
+                        }
+                    };
+                    let attributes = attributes(&self.reader, &e)?;
+                    self.state.start(name, attributes, &e);
+                }
+                Event::End(e) => self.state.end(e.name().as_ref()),
+                Event::Text(e) => {
+                    self.state.text(&e.xml_content(XmlVersion::Implicit1_0), &e);
+                }
+                Event::CData(e) => {
+                    let content = e.xml_content(XmlVersion::Implicit1_0);
+                    self.state.text(&content, &escape(content.as_ref()));
+                }
+                Event::GeneralRef(e) => {
+                    let raw = format!("&{};", &*e);
+                    if let Some(c) = e.resolve_char_ref().map_err(parse_error)? {
+                        self.state.text(&c.to_string(), &raw);
+                    } else {
+                        let name = e.xml10_content();
+                        let resolved = resolve_predefined_entity(&name)
+                            .ok_or_else(|| parse_error(format!("unknown entity &{name};")))?;
+                        self.state.text(resolved, &raw);
+                    }
+                }
+                Event::Eof => self.done = true,
+                _ => {}
+            }
+        }
+        Ok(std::mem::take(&mut self.state.triples))
+    }
+}
+
+/// the attributes of an element as namespace IRI plus local name, and
+/// value, without namespace declarations
+fn attributes<R>(
+    reader: &NsReader<R>,
+    element: &BytesStart,
+) -> Result<Vec<(String, String)>, TriplesError> {
+    let mut attributes = Vec::new();
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(parse_error)?;
+        let key = attribute.key.as_ref();
+        if key == "xmlns" || key.starts_with("xmlns:") {
+            continue;
+        }
+        let name = match reader.resolver().resolve_attribute(attribute.key) {
+            (ResolveResult::Bound(ns), local) => format!("{}{}", ns.as_ref(), local.as_ref()),
+            (ResolveResult::Unknown(prefix), _) => {
+                return Err(parse_error(format!("unknown namespace prefix {prefix}")));
+            }
+            // unqualified syntax names are read as their rdf: forms
+            (ResolveResult::Unbound, local) => rdf(local.as_ref()),
+        };
+        let value = attribute
+            .normalized_value(XmlVersion::Implicit1_0)
+            .map_err(parse_error)?;
+        attributes.push((name, value.into_owned()));
+    }
+    Ok(attributes)
+}
+
+/// a name split into a namespace and a local part that is a valid XML
+/// name, as element names must be
+fn qualified_name(iri: &str) -> Option<(&str, &str)> {
+    let (namespace, local) = iri::split(iri)?;
+    let first = local.chars().next()?;
+    (first.is_alphabetic() || first == '_').then_some((namespace, local))
+}
+
+/// the prefixes needed to write `subjects`, taken from `preferred` where it
+/// has them and invented otherwise
+///
+/// # Errors
+///
+/// return `Err` if a predicate can not be written as an XML element name
+pub fn prefixes(subjects: &[Subject], preferred: &PrefixMap) -> Result<PrefixMap, TriplesError> {
+    let mut prefixes = PrefixMap::new();
+    prefixes.insert("rdf", RDF);
+    let mut next = 0;
+    for subject in subjects {
+        for (predicate, objects) in subject.predicate_object_pairs() {
+            let predicate = predicate.to_string();
+            let mut names = vec![predicate.clone()];
+            if predicate == rdf("type") {
+                names.extend(
+                    objects
+                        .iter()
+                        .filter(|o| qualified_name(o).is_some())
+                        .cloned(),
+                );
+            }
+            for name in names {
+                let (namespace, _) = qualified_name(&name).ok_or_else(|| {
+                    parse_error(format!("{name} can not be written as an XML name"))
+                })?;
+                if prefixes.prefix(namespace).is_some() {
+                    continue;
+                }
+                if let Some(prefix) = preferred.prefix(namespace) {
+                    if prefixes.insert(prefix, namespace) {
+                        continue;
+                    }
+                }
+                loop {
+                    next += 1;
+                    if prefixes.insert(&format!("ns{next}"), namespace) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    Ok(prefixes)
+}
+
+/// the opening of a document declaring `prefixes`
+#[must_use]
+pub fn header(prefixes: &PrefixMap) -> String {
+    let mut text = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rdf:RDF");
+    for (prefix, namespace) in prefixes.iter() {
+        let _ = write!(text, "\n    xmlns:{prefix}=\"{}\"", escape(namespace));
+    }
+    text.push_str(">\n");
+    text
+}
+
+/// the closing of a document
+pub const FOOTER: &str = "</rdf:RDF>\n";
+
+fn element_name(iri: &str, prefixes: &PrefixMap) -> Option<String> {
+    let (namespace, local) = qualified_name(iri)?;
+    Some(format!("{}:{local}", prefixes.prefix(namespace)?))
+}
+
+fn node_reference(name: &str) -> String {
+    match name.strip_prefix("_:") {
+        Some(label) => format!("rdf:nodeID=\"{}\"", escape(label)),
+        None => format!("rdf:about=\"{}\"", escape(name)),
+    }
+}
+
+/// a subject as a node element, typed by its first `rdf:type` that has a
+/// prefix and with one property element per object
+///
+/// # Errors
+///
+/// return `Err` if a predicate has no prefix in `prefixes`
+pub fn node_element(subject: &Subject, prefixes: &PrefixMap) -> Result<String, TriplesError> {
+    let type_iri = rdf("type");
+    let pairs: BTreeMap<String, Vec<&String>> = subject
+        .predicate_object_pairs()
+        .map(|(predicate, objects)| {
+            let mut objects: Vec<&String> = objects.iter().collect();
+            objects.sort();
+            (predicate.to_string(), objects)
+        })
+        .collect();
+
+    let typed = pairs.get(&type_iri).and_then(|types| {
+        types
+            .iter()
+            .find_map(|t| element_name(t, prefixes).map(|name| (name, *t)))
+    });
+    let element = typed
+        .as_ref()
+        .map_or_else(|| "rdf:Description".to_string(), |(name, _)| name.clone());
+
+    let mut text = format!(
+        "    <{element} {}>\n",
+        node_reference(&subject.name().to_string())
+    );
+    for (predicate, objects) in &pairs {
+        let name = element_name(predicate, prefixes)
+            .ok_or_else(|| parse_error(format!("{predicate} can not be written as an XML name")))?;
+        for object in objects {
+            if *predicate == type_iri && typed.as_ref().is_some_and(|(_, t)| t == object) {
+                continue;
+            }
+            if let Some(label) = object.strip_prefix("_:") {
+                let label = escape(label);
+                let _ = writeln!(text, "        <{name} rdf:nodeID=\"{label}\"/>");
+            } else if is_iri(object) {
+                let _ = writeln!(
+                    text,
+                    "        <{name} rdf:resource=\"{}\"/>",
+                    escape(*object)
+                );
+            } else {
+                let _ = writeln!(text, "        <{name}>{}</{name}>", escape(*object));
+            }
+        }
+    }
+    let _ = writeln!(text, "    </{element}>");
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::RdfName;
+
+    async fn parse(document: &str) -> Vec<Triple> {
+        let mut parser = RdfXmlParser::new(document.as_bytes(), "http://example.com/doc");
+        let mut triples = Vec::new();
+        loop {
+            let next = parser.next_triples().await.unwrap();
+            if next.is_empty() {
+                break;
+            }
+            triples.extend(next);
+        }
+        triples
+    }
+
+    fn has(triples: &[Triple], s: &str, p: &str, o: &str) -> bool {
+        triples
+            .iter()
+            .any(|(ts, tp, to)| ts == s && tp == p && to == o)
+    }
+
+    const EX: &str = "http://example.com/ns#";
+
+    #[tokio::test]
+    async fn parses_nodes_properties_and_attributes() {
+        let triples = parse(
+            r##"<?xml version="1.0"?>
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:ex="http://example.com/ns#" xml:base="http://example.com/base/">
+  <ex:Room rdf:about="r1" ex:label="Room &amp; one">
+    <ex:floor rdf:resource="#f2"/>
+    <ex:note xml:lang="en">hot &#8211; &lt;dry&gt;</ex:note>
+    <ex:area rdf:datatype="http://www.w3.org/2001/XMLSchema#decimal">12.5</ex:area>
+    <ex:hasPoint>
+      <rdf:Description rdf:ID="p1" ex:kind="temp"/>
+    </ex:hasPoint>
+    <ex:near rdf:nodeID="x"/>
+  </ex:Room>
+  <rdf:Description rdf:nodeID="x" rdf:type="http://example.com/ns#Zone"/>
+</rdf:RDF>"##,
+        )
+        .await;
+
+        let r1 = "http://example.com/base/r1";
+        assert!(has(&triples, r1, &rdf("type"), &format!("{EX}Room")));
+        assert!(has(&triples, r1, &format!("{EX}label"), "Room & one"));
+        assert!(has(
+            &triples,
+            r1,
+            &format!("{EX}floor"),
+            "http://example.com/base/#f2"
+        ));
+        assert!(has(
+            &triples,
+            r1,
+            &format!("{EX}note"),
+            "hot \u{2013} <dry>"
+        ));
+        assert!(has(&triples, r1, &format!("{EX}area"), "12.5"));
+        let p1 = "http://example.com/base/#p1";
+        assert!(has(&triples, r1, &format!("{EX}hasPoint"), p1));
+        assert!(has(&triples, p1, &format!("{EX}kind"), "temp"));
+        let (_, _, x) = triples
+            .iter()
+            .find(|(_, p, _)| *p == format!("{EX}near"))
+            .unwrap();
+        assert!(x.starts_with("_:"));
+        assert!(has(&triples, x, &rdf("type"), &format!("{EX}Zone")));
+        assert_eq!(triples.len(), 9);
+    }
+
+    #[tokio::test]
+    async fn parses_parse_types_and_list_items() {
+        let triples = parse(
+            r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:ex="http://example.com/ns#">
+  <rdf:Description rdf:about="http://example.com/a">
+    <ex:address rdf:parseType="Resource">
+      <ex:city>Paris</ex:city>
+    </ex:address>
+    <ex:members rdf:parseType="Collection">
+      <rdf:Description rdf:about="http://example.com/m1"/>
+      <rdf:Description rdf:about="http://example.com/m2"/>
+    </ex:members>
+    <ex:markup rdf:parseType="Literal"><b class="x">bold &amp; <i>it</i></b></ex:markup>
+  </rdf:Description>
+  <rdf:Seq rdf:about="http://example.com/s">
+    <rdf:li>one</rdf:li>
+    <rdf:li>two</rdf:li>
+  </rdf:Seq>
+</rdf:RDF>"#,
+        )
+        .await;
+
+        let a = "http://example.com/a";
+        let (_, _, address) = triples
+            .iter()
+            .find(|(_, p, _)| *p == format!("{EX}address"))
+            .unwrap();
+        assert!(has(&triples, address, &format!("{EX}city"), "Paris"));
+
+        let (_, _, head) = triples
+            .iter()
+            .find(|(_, p, _)| *p == format!("{EX}members"))
+            .unwrap();
+        assert!(has(&triples, head, &rdf("first"), "http://example.com/m1"));
+        let (_, _, rest) = triples
+            .iter()
+            .find(|(s, p, _)| s == head && *p == rdf("rest"))
+            .unwrap();
+        assert!(has(&triples, rest, &rdf("first"), "http://example.com/m2"));
+        assert!(has(&triples, rest, &rdf("rest"), &rdf("nil")));
+
+        assert!(has(
+            &triples,
+            a,
+            &format!("{EX}markup"),
+            r#"<b class="x">bold &amp; <i>it</i></b>"#
+        ));
+        let s = "http://example.com/s";
+        assert!(has(&triples, s, &rdf("type"), &rdf("Seq")));
+        assert!(has(&triples, s, &rdf("_1"), "one"));
+        assert!(has(&triples, s, &rdf("_2"), "two"));
+    }
+
+    #[test]
+    fn writes_node_elements() {
+        let mut subject = Subject::new(RdfName::new("http://example.com/r1".to_string()));
+        subject.add(RdfName::new(rdf("type")), format!("{EX}Room"));
+        subject.add(RdfName::new(format!("{EX}label")), "A & B".to_string());
+        subject.add(RdfName::new(format!("{EX}near")), "_:b0".to_string());
+        subject.add(
+            RdfName::new(format!("{EX}floor")),
+            "http://example.com/f".to_string(),
+        );
+        let mut preferred = PrefixMap::new();
+        preferred.insert("ex", EX);
+        let prefixes = prefixes(std::slice::from_ref(&subject), &preferred).unwrap();
+
+        assert_eq!(
+            node_element(&subject, &prefixes).unwrap(),
+            "    <ex:Room rdf:about=\"http://example.com/r1\">\n\
+             \x20       <ex:floor rdf:resource=\"http://example.com/f\"/>\n\
+             \x20       <ex:label>A &amp; B</ex:label>\n\
+             \x20       <ex:near rdf:nodeID=\"b0\"/>\n\
+             \x20   </ex:Room>\n"
+        );
+        assert!(header(&prefixes).contains("xmlns:ex=\"http://example.com/ns#\""));
+    }
+}
@@ -0,0 +1,102 @@
+/// functions in support of RDF/XML file handling
+///
+/// Documents are parsed as a stream, relative IRIs resolved against the
+/// file they came from, and the db is exported with a node element per
+/// subject.
+///
+use crate::data::{RdfName, Subject};
+use crate::db_api::DbApi;
+use crate::rdfxml::{self, RdfXmlParser};
+use std::path::Path;
+use tokio::io::{AsyncBufRead, AsyncWrite, AsyncWriteExt};
+use tracing::trace;
+
+/// the base for relative IRIs of documents read from stdin without an
+/// `xml:base`
+pub const STDIN_BASE: &str = "file:///dev/stdin";
+
+/// the base for relative IRIs of a document read from `path`
+///
+/// # Errors
+///
+/// return `Err` if the path can not be made absolute
+pub fn file_base(path: &Path) -> std::io::Result<String> {
+    Ok(format!("file://{}", std::path::absolute(path)?.display()))
+}
+
+/// read RDF/XML from `reader`, stdin for the cli, and load db, triples are
+/// inserted as they are parsed with relative IRIs resolved against `base`
+///
+/// # Errors
+///
+/// return `Err` if the document can not be read, parsed or loaded
+pub async fn import_rdfxml<R: AsyncBufRead + Unpin>(
+    reader: R,
+    base: &str,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("import_rdfxml");
+    let mut parser = RdfXmlParser::new(reader, base);
+
+    let mut tx = db_api.begin_txn().await?;
+    loop {
+        let triples = parser.next_triples().await?;
+        if triples.is_empty() {
+            break;
+        }
+        // consecutive triples mostly share a subject, insert them together
+        let mut current: Option<Subject> = None;
+        for (subject, predicate, object) in triples {
+            if current
+                .as_ref()
+                .is_some_and(|c| c.name().to_string() != subject)
+            {
+                if let Some(done) = current.take() {
+                    db_api.insert_into_txn(&mut tx, &done, None).await?;
+                }
+            }
+            current
+                .get_or_insert_with(|| Subject::new(RdfName::new(subject)))
+                .add(RdfName::new(predicate), object);
+        }
+        if let Some(done) = current {
+            db_api.insert_into_txn(&mut tx, &done, None).await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// export the entire db to `out` as RDF/XML, one node element per subject,
+/// with the stored and well-known prefixes
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read, a predicate can not be written
+/// as an XML name or `out` can not be written
+pub async fn export_rdfxml<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("export_rdfxml");
+    let preferred = db_api.preferred_prefixes().await?;
+
+    let mut subjects = Vec::new();
+    for name in &db_api.get_subject_names().await? {
+        if let Some(subject) = db_api.query(name).await? {
+            subjects.push(subject);
+        }
+    }
+    let prefixes = rdfxml::prefixes(&subjects, &preferred)?;
+
+    out.write_all(rdfxml::header(&prefixes).as_bytes()).await?;
+    for subject in &subjects {
+        out.write_all(rdfxml::node_element(subject, &prefixes)?.as_bytes())
+            .await?;
+    }
+    out.write_all(rdfxml::FOOTER.as_bytes()).await?;
+    out.flush().await?;
+
+    Ok(())
+}
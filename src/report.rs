@@ -0,0 +1,56 @@
+/// text reports, the values and tables the reporting commands print
+///
+use crate::data::Term;
+use crate::prefixes::PrefixMap;
+
+/// how much of a literal value a report shows
+const MAX_VALUE_CHARS: usize = 32;
+
+/// a value as the report shows it, IRIs compacted where `prefixes` allow
+/// and literals quoted and shortened
+#[must_use]
+pub fn display(value: &str, prefixes: &PrefixMap) -> String {
+    match Term::from_object(value) {
+        Term::Iri(iri) => prefixes.compact(&iri).unwrap_or(iri),
+        Term::BlankNode(_) => value.to_string(),
+        Term::Literal { .. } => {
+            let mut shown: String = value.chars().take(MAX_VALUE_CHARS).collect();
+            if shown.len() < value.len() {
+                shown.push_str("...");
+            }
+            format!("\"{}\"", shown.replace('\n', "\\n"))
+        }
+    }
+}
+
+/// rows as columns padded to their widest cell, numbers aligned right
+#[must_use]
+pub fn table(header: &[&str], rows: &[Vec<String>], numeric: &[bool]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let mut line = String::new();
+        for (n, cell) in cells.iter().enumerate() {
+            if n > 0 {
+                line.push_str("  ");
+            }
+            if numeric[n] {
+                line.push_str(&format!("{cell:>width$}", width = widths[n]));
+            } else if n + 1 < cells.len() {
+                line.push_str(&format!("{cell:<width$}", width = widths[n]));
+            } else {
+                line.push_str(cell);
+            }
+        }
+        line.trim_end().to_string() + "\n"
+    };
+    let mut text = line(header.to_vec());
+    for row in rows {
+        text.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+    text
+}
@@ -0,0 +1,1032 @@
+/// User-defined rules in a subset of N3, evaluated by semi-naive forward
+/// chaining
+///
+/// A rule file holds `@prefix` declarations and rules of the form
+/// `{ body } => { head } .`, where body and head are triple patterns
+/// over IRIs, prefixed names, literals and `?variables`, written with `.`,
+/// `;` and `,` as in Turtle.  Every head variable must be bound by the
+/// body.  Body statements may also use the builtins
+/// `( ... ) string:concatenation ?s`, `log:equalTo`, `log:notEqualTo`,
+/// `math:greaterThan` and `math:lessThan`, whose inputs must be bound by
+/// triple patterns or earlier builtins.
+///
+/// ```text
+/// @prefix prop: <http://k8p.navicore.tech/property/> .
+/// @prefix string: <http://www.w3.org/2000/10/swap/string#> .
+///
+/// {
+///     ?metric prop:k8p_appname ?name ;
+///         prop:k8p_namespace ?ns .
+///     ("http://k8p.navicore.tech/app/" ?ns "/" ?name) string:concatenation ?app .
+/// } => {
+///     ?metric prop:belongsTo ?app .
+/// } .
+/// ```
+///
+use crate::data::{compare, parse_error, TriplesError, Violation};
+use crate::prefixes::PrefixMap;
+use crate::vocab::RDF_TYPE;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+const STRING: &str = "http://www.w3.org/2000/10/swap/string#";
+const LOG: &str = "http://www.w3.org/2000/10/swap/log#";
+const MATH: &str = "http://www.w3.org/2000/10/swap/math#";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RuleTerm {
+    Var(String),
+    /// an IRI or literal as stored, literals without quotes
+    Const(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RulePattern {
+    pub subject: RuleTerm,
+    pub predicate: RuleTerm,
+    pub object: RuleTerm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    LessThan,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Builtin {
+    Concatenation {
+        parts: Vec<RuleTerm>,
+        result: RuleTerm,
+    },
+    Compare {
+        left: RuleTerm,
+        comparison: Comparison,
+        right: RuleTerm,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// where the rule came from, recorded with its conclusions
+    pub name: String,
+    pub body: Vec<RulePattern>,
+    pub builtins: Vec<Builtin>,
+    /// empty for a constraint, written `{ body } => false .`
+    pub head: Vec<RulePattern>,
+}
+
+impl Rule {
+    /// a rule whose body must never match
+    #[must_use]
+    pub fn is_constraint(&self) -> bool {
+        self.head.is_empty()
+    }
+
+    /// the constant predicates of the body, `None` when a body predicate
+    /// is a variable and any triple may match
+    #[must_use]
+    pub fn body_predicates(&self) -> Option<Vec<String>> {
+        self.body
+            .iter()
+            .map(|pattern| match &pattern.predicate {
+                RuleTerm::Const(predicate) => Some(predicate.clone()),
+                RuleTerm::Var(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Iri(String),
+    Name(String, String),
+    Var(String),
+    Literal(String),
+    A,
+    Implies,
+    Prefix,
+    Punct(char),
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '%')
+}
+
+/// read a name, leaving a final `.` as the end of a statement
+fn take_name(chars: &[char], mut idx: usize) -> (String, usize) {
+    let start = idx;
+    while idx < chars.len() && is_name_char(chars[idx]) {
+        idx += 1;
+    }
+    while idx > start && chars[idx - 1] == '.' {
+        idx -= 1;
+    }
+    (chars[start..idx].iter().collect(), idx)
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, TriplesError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let c = chars[idx];
+        match c {
+            c if c.is_whitespace() => idx += 1,
+            '#' => {
+                while idx < chars.len() && chars[idx] != '\n' {
+                    idx += 1;
+                }
+            }
+            '<' => {
+                let end = chars[idx..]
+                    .iter()
+                    .position(|&c| c == '>')
+                    .ok_or_else(|| parse_error("unterminated IRI"))?;
+                tokens.push(Token::Iri(chars[idx + 1..idx + end].iter().collect()));
+                idx += end + 1;
+            }
+            '"' => {
+                let mut value = String::new();
+                idx += 1;
+                loop {
+                    let Some(&c) = chars.get(idx) else {
+                        return Err(parse_error("unterminated literal"));
+                    };
+                    idx += 1;
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            let escaped = chars
+                                .get(idx)
+                                .ok_or_else(|| parse_error("unterminated literal"))?;
+                            value.push(match escaped {
+                                'n' => '\n',
+                                't' => '\t',
+                                'r' => '\r',
+                                other => *other,
+                            });
+                            idx += 1;
+                        }
+                        c => value.push(c),
+                    }
+                }
+                // values are stored untyped, a language or datatype is dropped
+                if chars.get(idx) == Some(&'@') {
+                    idx = take_name(&chars, idx + 1).1;
+                } else if chars.get(idx) == Some(&'^') && chars.get(idx + 1) == Some(&'^') {
+                    idx += 2;
+                    if chars.get(idx) == Some(&'<') {
+                        idx += chars[idx..].iter().position(|&c| c == '>').unwrap_or(0) + 1;
+                    } else {
+                        let (_, end) = take_name(&chars, idx);
+                        idx = end;
+                        if chars.get(idx) == Some(&':') {
+                            idx = take_name(&chars, idx + 1).1;
+                        }
+                    }
+                }
+                tokens.push(Token::Literal(value));
+            }
+            '?' => {
+                let (name, end) = take_name(&chars, idx + 1);
+                if name.is_empty() {
+                    return Err(parse_error("variable without a name"));
+                }
+                tokens.push(Token::Var(name));
+                idx = end;
+            }
+            '=' if chars.get(idx + 1) == Some(&'>') => {
+                tokens.push(Token::Implies);
+                idx += 2;
+            }
+            '{' | '}' | '(' | ')' | '.' | ';' | ',' => {
+                tokens.push(Token::Punct(c));
+                idx += 1;
+            }
+            '@' => {
+                let (word, end) = take_name(&chars, idx + 1);
+                if word != "prefix" {
+                    return Err(parse_error(format!("unsupported directive @{word}")));
+                }
+                tokens.push(Token::Prefix);
+                idx = end;
+            }
+            c if c == '-' || c == '+' || c.is_ascii_digit() => {
+                let (number, end) = take_name(&chars, idx + 1);
+                tokens.push(Token::Literal(format!("{c}{number}")));
+                idx = end;
+            }
+            _ => {
+                let (prefix, end) = take_name(&chars, idx);
+                if chars.get(end) == Some(&':') {
+                    let (local, end) = take_name(&chars, end + 1);
+                    tokens.push(Token::Name(prefix, local));
+                    idx = end;
+                } else if prefix == "a" {
+                    tokens.push(Token::A);
+                    idx = end;
+                } else if prefix.eq_ignore_ascii_case("prefix") {
+                    tokens.push(Token::Prefix);
+                    idx = end;
+                } else if prefix == "true" || prefix == "false" {
+                    tokens.push(Token::Literal(prefix));
+                    idx = end;
+                } else {
+                    return Err(parse_error(format!("unexpected text at '{c}'")));
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// a subject, predicate or object as written, lists only as subjects
+#[derive(Debug, Clone)]
+enum Node {
+    Term(RuleTerm),
+    List(Vec<RuleTerm>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    idx: usize,
+    /// the prefixes the file declares, which take precedence over
+    /// `defaults`
+    prefixes: PrefixMap,
+    /// the well-known vocabulary names, for files that use them undeclared
+    defaults: PrefixMap,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.idx)
+    }
+
+    fn next(&mut self) -> Result<Token, TriplesError> {
+        let token = self
+            .tokens
+            .get(self.idx)
+            .cloned()
+            .ok_or_else(|| parse_error("unexpected end of rules"))?;
+        self.idx += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), TriplesError> {
+        match self.next()? {
+            Token::Punct(c) if c == punct => Ok(()),
+            token => Err(parse_error(format!("expected '{punct}', found {token:?}"))),
+        }
+    }
+
+    fn prefix(&mut self) -> Result<(), TriplesError> {
+        let Token::Name(prefix, local) = self.next()? else {
+            return Err(parse_error("expected a prefix name"));
+        };
+        if !local.is_empty() {
+            return Err(parse_error(format!("bad prefix name {prefix}:{local}")));
+        }
+        let Token::Iri(namespace) = self.next()? else {
+            return Err(parse_error("expected a prefix IRI"));
+        };
+        if self.prefixes.namespace(&prefix) != Some(namespace.as_str())
+            && !self.prefixes.insert(&prefix, &namespace)
+        {
+            return Err(parse_error(format!(
+                "prefix {prefix}: <{namespace}> clashes with an earlier declaration"
+            )));
+        }
+        if self.peek() == Some(&Token::Punct('.')) {
+            self.idx += 1;
+        }
+        Ok(())
+    }
+
+    fn term(&mut self) -> Result<RuleTerm, TriplesError> {
+        match self.next()? {
+            Token::Iri(iri) | Token::Literal(iri) => Ok(RuleTerm::Const(iri)),
+            Token::Var(name) => Ok(RuleTerm::Var(name)),
+            Token::A => Ok(RuleTerm::Const(RDF_TYPE.to_string())),
+            Token::Name(prefix, local) => {
+                let name = format!("{prefix}:{local}");
+                match self.prefixes.namespace(&prefix) {
+                    Some(_) => self.prefixes.expand(&name),
+                    None => self.defaults.expand(&name),
+                }
+                .map(RuleTerm::Const)
+                .ok_or_else(|| parse_error(format!("undeclared prefix {prefix}:")))
+            }
+            token => Err(parse_error(format!("expected a term, found {token:?}"))),
+        }
+    }
+
+    fn node(&mut self) -> Result<Node, TriplesError> {
+        if self.peek() == Some(&Token::Punct('(')) {
+            self.idx += 1;
+            let mut items = Vec::new();
+            while self.peek() != Some(&Token::Punct(')')) {
+                items.push(self.term()?);
+            }
+            self.idx += 1;
+            return Ok(Node::List(items));
+        }
+        Ok(Node::Term(self.term()?))
+    }
+
+    /// the statements between `{` and `}`
+    fn formula(&mut self) -> Result<Vec<(Node, RuleTerm, RuleTerm)>, TriplesError> {
+        self.expect('{')?;
+        let mut statements = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Token::Punct('}')) => {
+                    self.idx += 1;
+                    return Ok(statements);
+                }
+                Some(Token::Punct('.')) => self.idx += 1,
+                _ => {
+                    let subject = self.node()?;
+                    loop {
+                        let predicate = self.term()?;
+                        loop {
+                            let object = self.term()?;
+                            statements.push((subject.clone(), predicate.clone(), object));
+                            if self.peek() != Some(&Token::Punct(',')) {
+                                break;
+                            }
+                            self.idx += 1;
+                        }
+                        if self.peek() != Some(&Token::Punct(';')) {
+                            break;
+                        }
+                        self.idx += 1;
+                        if matches!(self.peek(), Some(Token::Punct('.' | '}'))) {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn rule(&mut self, name: String) -> Result<Rule, TriplesError> {
+        let mut body = Vec::new();
+        let mut builtins = Vec::new();
+        for (subject, predicate, object) in self.formula()? {
+            match (subject, predicate) {
+                (Node::List(parts), RuleTerm::Const(p))
+                    if p == format!("{STRING}concatenation") =>
+                {
+                    builtins.push(Builtin::Concatenation {
+                        parts,
+                        result: object,
+                    });
+                }
+                (Node::Term(left), RuleTerm::Const(p)) if comparison(&p).is_some() => {
+                    builtins.push(Builtin::Compare {
+                        left,
+                        comparison: comparison(&p).unwrap_or(Comparison::Equal),
+                        right: object,
+                    });
+                }
+                (Node::Term(subject), predicate) => body.push(RulePattern {
+                    subject,
+                    predicate,
+                    object,
+                }),
+                (Node::List(_), _) => {
+                    return Err(parse_error(format!(
+                        "{name}: lists are only supported by string:concatenation"
+                    )))
+                }
+            }
+        }
+        if self.next()? != Token::Implies {
+            return Err(parse_error(format!("{name}: expected =>")));
+        }
+        let mut head = Vec::new();
+        if self.peek() == Some(&Token::Literal("false".to_string())) {
+            self.idx += 1;
+        } else {
+            for (subject, predicate, object) in self.formula()? {
+                let Node::Term(subject) = subject else {
+                    return Err(parse_error(format!("{name}: lists can not be concluded")));
+                };
+                head.push(RulePattern {
+                    subject,
+                    predicate,
+                    object,
+                });
+            }
+            if head.is_empty() {
+                return Err(parse_error(format!(
+                    "{name}: concludes nothing, write `=> false` for a constraint"
+                )));
+            }
+        }
+        self.expect('.')?;
+
+        let rule = Rule {
+            name,
+            body,
+            builtins,
+            head,
+        };
+        check_bound(&rule)?;
+        Ok(rule)
+    }
+}
+
+fn comparison(predicate: &str) -> Option<Comparison> {
+    match predicate {
+        p if p == format!("{LOG}equalTo") => Some(Comparison::Equal),
+        p if p == format!("{LOG}notEqualTo") => Some(Comparison::NotEqual),
+        p if p == format!("{MATH}greaterThan") => Some(Comparison::GreaterThan),
+        p if p == format!("{MATH}lessThan") => Some(Comparison::LessThan),
+        _ => None,
+    }
+}
+
+/// builtin inputs and head variables must be bound before they are used
+fn check_bound(rule: &Rule) -> Result<(), TriplesError> {
+    let mut bound: HashSet<&str> = HashSet::new();
+    for pattern in &rule.body {
+        for term in [&pattern.subject, &pattern.predicate, &pattern.object] {
+            if let RuleTerm::Var(name) = term {
+                bound.insert(name);
+            }
+        }
+    }
+    let unbound = |term: &RuleTerm, bound: &HashSet<&str>| match term {
+        RuleTerm::Var(name) if !bound.contains(name.as_str()) => Some(name.clone()),
+        _ => None,
+    };
+    for builtin in &rule.builtins {
+        let inputs: Vec<&RuleTerm> = match builtin {
+            Builtin::Concatenation { parts, .. } => parts.iter().collect(),
+            Builtin::Compare { left, right, .. } => vec![left, right],
+        };
+        if let Some(name) = inputs.into_iter().find_map(|t| unbound(t, &bound)) {
+            return Err(parse_error(format!(
+                "{}: builtin input ?{name} is not bound",
+                rule.name
+            )));
+        }
+        if let Builtin::Concatenation {
+            result: RuleTerm::Var(name),
+            ..
+        } = builtin
+        {
+            bound.insert(name);
+        }
+    }
+    for pattern in &rule.head {
+        for term in [&pattern.subject, &pattern.predicate, &pattern.object] {
+            if let Some(name) = unbound(term, &bound) {
+                return Err(parse_error(format!(
+                    "{}: head variable ?{name} is not bound",
+                    rule.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// parse the rules of a file, named `source#1`, `source#2`, ... in order
+///
+/// # Errors
+///
+/// return `Err` if the text is not in the supported N3 subset, or a rule
+/// uses a variable before it is bound
+pub fn parse_rules(text: &str, source: &str) -> Result<Vec<Rule>, TriplesError> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        idx: 0,
+        prefixes: PrefixMap::new(),
+        defaults: PrefixMap::well_known(),
+    };
+    let mut rules = Vec::new();
+    while let Some(token) = parser.peek() {
+        if *token == Token::Prefix {
+            parser.idx += 1;
+            parser.prefix()?;
+        } else {
+            let name = format!("{source}#{}", rules.len() + 1);
+            rules.push(parser.rule(name)?);
+        }
+    }
+    Ok(rules)
+}
+
+pub type Triple = (String, String, String);
+
+/// A new triple a round of evaluation concluded, or one concluded before
+/// that has gained derivations, each a rule name and the ids of the
+/// triples the rule matched.  A later derivation is only reported when
+/// none of its premises rests on the conclusion, so following derivations
+/// back always ends at stored triples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conclusion {
+    pub triple: Triple,
+    /// the stored id of a triple concluded in an earlier round
+    pub id: Option<i64>,
+    pub derivations: Vec<(String, Vec<i64>)>,
+}
+
+type Bindings = HashMap<String, String>;
+
+/// Facts indexed for matching, and the rules to evaluate over them.  Facts
+/// added since the last round are the delta of the next, and every match
+/// in a round uses at least one of them.
+#[derive(Debug, Default)]
+pub struct Reasoner {
+    rules: Vec<Rule>,
+    facts: Vec<(Triple, i64)>,
+    known: HashMap<Triple, usize>,
+    concluded: HashSet<usize>,
+    /// the conclusions with a recorded derivation from each fact
+    dependents: HashMap<i64, Vec<i64>>,
+    /// the premises of new conclusions, until they are added as facts
+    pending: HashMap<Triple, Vec<i64>>,
+    by_predicate: HashMap<String, Vec<usize>>,
+    by_subject: HashMap<(String, String), Vec<usize>>,
+    by_object: HashMap<(String, String), Vec<usize>>,
+    delta_start: usize,
+}
+
+impl Reasoner {
+    #[must_use]
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self {
+            rules,
+            ..Self::default()
+        }
+    }
+
+    /// add a stored triple, `concluded` when it is a conclusion of the
+    /// rules
+    pub fn add_fact(&mut self, triple: Triple, id: i64, concluded: bool) {
+        if self.known.contains_key(&triple) {
+            return;
+        }
+        let idx = self.facts.len();
+        let (subject, predicate, object) = &triple;
+        self.by_predicate
+            .entry(predicate.clone())
+            .or_default()
+            .push(idx);
+        self.by_subject
+            .entry((predicate.clone(), subject.clone()))
+            .or_default()
+            .push(idx);
+        self.by_object
+            .entry((predicate.clone(), object.clone()))
+            .or_default()
+            .push(idx);
+        self.known.insert(triple.clone(), idx);
+        if concluded {
+            self.concluded.insert(idx);
+            if let Some(premises) = self.pending.remove(&triple) {
+                self.add_dependents(id, &premises);
+            }
+        }
+        self.facts.push((triple, id));
+    }
+
+    /// the facts that may match `pattern` under `bindings`
+    fn candidates(&self, pattern: &RulePattern, bindings: &Bindings) -> Vec<usize> {
+        let value = |term: &RuleTerm| match term {
+            RuleTerm::Const(value) => Some(value.clone()),
+            RuleTerm::Var(name) => bindings.get(name).cloned(),
+        };
+        let lookup = |index: &HashMap<(String, String), Vec<usize>>, key| {
+            index.get(&key).cloned().unwrap_or_default()
+        };
+        match (
+            value(&pattern.subject),
+            value(&pattern.predicate),
+            value(&pattern.object),
+        ) {
+            (Some(s), Some(p), _) => lookup(&self.by_subject, (p, s)),
+            (_, Some(p), Some(o)) => lookup(&self.by_object, (p, o)),
+            (_, Some(p), None) => self.by_predicate.get(&p).cloned().unwrap_or_default(),
+            (_, None, _) => (0..self.facts.len()).collect(),
+        }
+    }
+
+    fn matches(&self, pattern: &RulePattern, idx: usize, bindings: &Bindings) -> Option<Bindings> {
+        let ((subject, predicate, object), _) = &self.facts[idx];
+        let mut bindings = bindings.clone();
+        for (term, value) in [
+            (&pattern.subject, subject),
+            (&pattern.predicate, predicate),
+            (&pattern.object, object),
+        ] {
+            match term {
+                RuleTerm::Const(constant) if constant != value => return None,
+                RuleTerm::Var(name) => match bindings.get(name) {
+                    Some(bound) if bound != value => return None,
+                    Some(_) => {}
+                    None => {
+                        bindings.insert(name.clone(), value.clone());
+                    }
+                },
+                RuleTerm::Const(_) => {}
+            }
+        }
+        Some(bindings)
+    }
+
+    /// bindings and premises of `rule` matching a delta fact at `delta`,
+    /// earlier patterns old facts and later patterns any fact, so each
+    /// match is found in one round only
+    fn solve(&self, rule: &Rule, delta: usize, old: usize) -> Vec<(Bindings, Vec<i64>)> {
+        let mut order: Vec<usize> = vec![delta];
+        order.extend((0..rule.body.len()).filter(|&i| i != delta));
+
+        let mut partial = vec![(Bindings::new(), Vec::new())];
+        for position in order {
+            let pattern = &rule.body[position];
+            let mut next = Vec::new();
+            for (bindings, premises) in &partial {
+                let candidates: Vec<usize> = if position == delta {
+                    (old..self.facts.len()).collect()
+                } else {
+                    self.candidates(pattern, bindings)
+                };
+                for idx in candidates {
+                    if (position < delta && idx >= old) || (position == delta && idx < old) {
+                        continue;
+                    }
+                    if let Some(bindings) = self.matches(pattern, idx, bindings) {
+                        let mut premises = premises.clone();
+                        premises.push(self.facts[idx].1);
+                        next.push((bindings, premises));
+                    }
+                }
+            }
+            partial = next;
+        }
+        partial
+    }
+
+    /// evaluate one round, returning what the facts added since the last
+    /// round lead to; the caller stores the new triples and adds them back
+    /// as facts for the next round
+    pub fn step(&mut self) -> Vec<Conclusion> {
+        let old = self.delta_start;
+        self.delta_start = self.facts.len();
+        if old == self.facts.len() {
+            return Vec::new();
+        }
+
+        let mut matches = Vec::new();
+        for rule in self.rules.iter().filter(|rule| !rule.is_constraint()) {
+            for delta in 0..rule.body.len() {
+                for (bindings, mut premises) in self.solve(rule, delta, old) {
+                    let Some(bindings) = apply_builtins(&rule.builtins, bindings) else {
+                        continue;
+                    };
+                    premises.sort_unstable();
+                    premises.dedup();
+                    for pattern in &rule.head {
+                        if let Some(triple) = instantiate(pattern, &bindings) {
+                            matches.push((rule.name.clone(), triple, premises.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut conclusions: Vec<Conclusion> = Vec::new();
+        let mut positions: HashMap<Triple, usize> = HashMap::new();
+        for (rule, triple, premises) in matches {
+            let id = match self.known.get(&triple) {
+                Some(idx) if self.concluded.contains(idx) => {
+                    let id = self.facts[*idx].1;
+                    if premises.iter().any(|premise| self.rests_on(*premise, id)) {
+                        continue;
+                    }
+                    self.add_dependents(id, &premises);
+                    Some(id)
+                }
+                Some(_) => continue,
+                None => {
+                    self.pending
+                        .entry(triple.clone())
+                        .or_default()
+                        .extend(&premises);
+                    None
+                }
+            };
+            let position = *positions.entry(triple.clone()).or_insert_with(|| {
+                conclusions.push(Conclusion {
+                    triple,
+                    id,
+                    derivations: Vec::new(),
+                });
+                conclusions.len() - 1
+            });
+            conclusions[position].derivations.push((rule, premises));
+        }
+        conclusions
+    }
+
+    fn add_dependents(&mut self, id: i64, premises: &[i64]) {
+        for premise in premises {
+            self.dependents.entry(*premise).or_default().push(id);
+        }
+    }
+
+    /// whether the fact `premise` is the conclusion `id` or follows from it
+    /// by the derivations recorded so far
+    fn rests_on(&self, premise: i64, id: i64) -> bool {
+        let mut seen = HashSet::from([id]);
+        let mut queue = vec![id];
+        while let Some(next) = queue.pop() {
+            if next == premise {
+                return true;
+            }
+            for dependent in self.dependents.get(&next).into_iter().flatten() {
+                if seen.insert(*dependent) {
+                    queue.push(*dependent);
+                }
+            }
+        }
+        false
+    }
+
+    /// every match of a constraint among the facts, by rule and variables
+    #[must_use]
+    pub fn violations(&self) -> Vec<Violation> {
+        let mut violations = BTreeSet::new();
+        for rule in self.rules.iter().filter(|rule| rule.is_constraint()) {
+            for (bindings, _) in self.solve(rule, 0, 0) {
+                if let Some(bindings) = apply_builtins(&rule.builtins, bindings) {
+                    violations.insert(Violation {
+                        rule: rule.name.clone(),
+                        bindings: bindings.into_iter().collect(),
+                    });
+                }
+            }
+        }
+        violations.into_iter().collect()
+    }
+}
+
+fn resolve<'a>(term: &'a RuleTerm, bindings: &'a Bindings) -> Option<&'a str> {
+    match term {
+        RuleTerm::Const(value) => Some(value),
+        RuleTerm::Var(name) => bindings.get(name).map(String::as_str),
+    }
+}
+
+fn instantiate(pattern: &RulePattern, bindings: &Bindings) -> Option<Triple> {
+    Some((
+        resolve(&pattern.subject, bindings)?.to_string(),
+        resolve(&pattern.predicate, bindings)?.to_string(),
+        resolve(&pattern.object, bindings)?.to_string(),
+    ))
+}
+
+fn apply_builtins(builtins: &[Builtin], mut bindings: Bindings) -> Option<Bindings> {
+    for builtin in builtins {
+        match builtin {
+            Builtin::Concatenation { parts, result } => {
+                let value: String = parts
+                    .iter()
+                    .map(|part| resolve(part, &bindings))
+                    .collect::<Option<_>>()?;
+                match result {
+                    RuleTerm::Var(name) if !bindings.contains_key(name) => {
+                        bindings.insert(name.clone(), value);
+                    }
+                    term => {
+                        if resolve(term, &bindings)? != value {
+                            return None;
+                        }
+                    }
+                }
+            }
+            Builtin::Compare {
+                left,
+                comparison,
+                right,
+            } => {
+                let ordering = compare(resolve(left, &bindings)?, resolve(right, &bindings)?);
+                let holds = match comparison {
+                    Comparison::Equal => ordering.is_eq(),
+                    Comparison::NotEqual => ordering.is_ne(),
+                    Comparison::GreaterThan => ordering.is_gt(),
+                    Comparison::LessThan => ordering.is_lt(),
+                };
+                if !holds {
+                    return None;
+                }
+            }
+        }
+    }
+    Some(bindings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+        @prefix ex: <http://example.com/> .
+        @prefix string: <http://www.w3.org/2000/10/swap/string#> .
+        @prefix math: <http://www.w3.org/2000/10/swap/math#> .
+
+        # pods belong to their namespaced app
+        {
+            ?pod ex:appname ?name ;
+                ex:namespace ?ns .
+            ("http://example.com/app/" ?ns "/" ?name) string:concatenation ?app .
+        } => {
+            ?pod ex:belongsTo ?app .
+        } .
+
+        { ?a ex:partOf ?b . ?b ex:partOf ?c } => { ?a ex:partOf ?c } .
+
+        { ?pod ex:restarts ?n . ?n math:greaterThan 3 } => { ?pod a ex:Unstable } .
+    "#;
+
+    fn triple(s: &str, p: &str, o: &str) -> Triple {
+        (
+            format!("http://example.com/{s}"),
+            format!("http://example.com/{p}"),
+            o.to_string(),
+        )
+    }
+
+    #[test]
+    fn parses_n3_rules() {
+        let rules = parse_rules(RULES, "test.n3").unwrap();
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[0].name, "test.n3#1");
+        assert_eq!(rules[0].body.len(), 2);
+        assert_eq!(
+            rules[0].builtins[0],
+            Builtin::Concatenation {
+                parts: vec![
+                    RuleTerm::Const("http://example.com/app/".to_string()),
+                    RuleTerm::Var("ns".to_string()),
+                    RuleTerm::Const("/".to_string()),
+                    RuleTerm::Var("name".to_string()),
+                ],
+                result: RuleTerm::Var("app".to_string()),
+            }
+        );
+        assert_eq!(
+            rules[2].head[0].predicate,
+            RuleTerm::Const(RDF_TYPE.to_string())
+        );
+        assert_eq!(
+            rules[1].body_predicates(),
+            Some(vec![
+                "http://example.com/partOf".to_string(),
+                "http://example.com/partOf".to_string()
+            ])
+        );
+
+        assert!(parse_rules("{ ?a ?p ?b } => { ?a ?p ?c } .", "bad").is_err());
+        assert!(parse_rules(
+            "@prefix ex: <http://example.com/> . @prefix ex: <http://example.org/> .",
+            "bad"
+        )
+        .is_err());
+        assert!(parse_rules("{ ?a ex:p ?b } => { ?a ex:p ?b } .", "bad").is_err());
+    }
+
+    #[test]
+    fn declared_prefixes_override_well_known_ones() {
+        let rules = parse_rules(
+            r"
+            @prefix schema: <https://schema.org/> .
+            @prefix b: <https://brickschema.org/schema/Brick#> .
+            { ?x a b:AHU } => { ?x a schema:Thing } .
+            { ?x a brick:VAV } => { ?x a schema:Place } .
+            ",
+            "test.n3",
+        )
+        .unwrap();
+        assert_eq!(
+            rules[0].body[0].object,
+            RuleTerm::Const("https://brickschema.org/schema/Brick#AHU".to_string())
+        );
+        assert_eq!(
+            rules[0].head[0].object,
+            RuleTerm::Const("https://schema.org/Thing".to_string())
+        );
+        // undeclared prefixes still fall back to the well-known names
+        assert_eq!(
+            rules[1].body[0].object,
+            RuleTerm::Const("https://brickschema.org/schema/Brick#VAV".to_string())
+        );
+    }
+
+    #[test]
+    fn chains_semi_naively() {
+        let mut reasoner = Reasoner::new(parse_rules(RULES, "test.n3").unwrap());
+        let facts = [
+            triple("pod1", "appname", "web"),
+            triple("pod1", "namespace", "prod"),
+            triple("pod1", "restarts", "5"),
+            triple("pod2", "restarts", "1"),
+            triple("a", "partOf", "http://example.com/b"),
+            triple("b", "partOf", "http://example.com/c"),
+            triple("c", "partOf", "http://example.com/d"),
+        ];
+        for (id, fact) in facts.into_iter().enumerate() {
+            reasoner.add_fact(fact, i64::try_from(id).unwrap(), false);
+        }
+
+        let mut rounds = Vec::new();
+        let mut next_id = 100;
+        loop {
+            let conclusions = reasoner.step();
+            if conclusions.is_empty() {
+                break;
+            }
+            for conclusion in &conclusions {
+                if conclusion.id.is_none() {
+                    reasoner.add_fact(conclusion.triple.clone(), next_id, true);
+                    next_id += 1;
+                }
+            }
+            rounds.push(conclusions);
+        }
+
+        let first: Vec<&Triple> = rounds[0].iter().map(|c| &c.triple).collect();
+        assert!(first.contains(&&triple(
+            "pod1",
+            "belongsTo",
+            "http://example.com/app/prod/web"
+        )));
+        assert!(first.contains(&&(
+            "http://example.com/pod1".to_string(),
+            RDF_TYPE.to_string(),
+            "http://example.com/Unstable".to_string()
+        )));
+        assert!(!first.iter().any(|(s, _, _)| s == "http://example.com/pod2"));
+        let belongs = rounds[0]
+            .iter()
+            .find(|c| c.triple.1 == "http://example.com/belongsTo")
+            .unwrap();
+        assert_eq!(belongs.derivations, [("test.n3#1".to_string(), vec![0, 1])]);
+
+        // a partOf d takes a second round, by two derivations
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[1].len(), 1);
+        assert_eq!(
+            rounds[1][0].triple,
+            triple("a", "partOf", "http://example.com/d")
+        );
+        assert_eq!(rounds[1][0].derivations.len(), 2);
+    }
+
+    #[test]
+    fn skips_derivations_resting_on_the_conclusion() {
+        let rules = r"
+            @prefix ex: <http://example.com/> .
+            { ?a ex:near ?b . } => { ?b ex:near ?a . } .
+            { ?a ex:near ?b . ?b ex:near ?c . } => { ?a ex:near ?c . } .
+        ";
+        let mut reasoner = Reasoner::new(parse_rules(rules, "test.n3").unwrap());
+        reasoner.add_fact(triple("x", "near", "http://example.com/y"), 0, false);
+
+        let mut derivations: HashMap<Triple, Vec<Vec<i64>>> = HashMap::new();
+        let mut next_id = 100;
+        loop {
+            let conclusions = reasoner.step();
+            if conclusions.is_empty() {
+                break;
+            }
+            for conclusion in conclusions {
+                if conclusion.id.is_none() {
+                    reasoner.add_fact(conclusion.triple.clone(), next_id, true);
+                    next_id += 1;
+                }
+                derivations
+                    .entry(conclusion.triple)
+                    .or_default()
+                    .extend(conclusion.derivations.into_iter().map(|(_, p)| p));
+            }
+        }
+
+        // y near x, x near x and y near y all rest on x near y alone
+        assert_eq!(derivations.len(), 3);
+        assert_eq!(
+            derivations[&triple("y", "near", "http://example.com/x")],
+            [vec![0]]
+        );
+        assert_eq!(
+            derivations[&triple("x", "near", "http://example.com/x")],
+            [vec![0, 100]]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+/// functions in support of user-defined rules
+///
+/// Rules are read from files, the store is both what they match and where
+/// their conclusions go.
+///
+/// Conclusions are stored in a graph of their own with how they were
+/// derived, and are retracted when no triple is left with the content of
+/// a premise, unless another derivation still holds.  Conclusions resting
+/// on RDFS inferences are kept while those are derived again after a
+/// delete, and retracted only if they no longer follow.
+///
+/// A rule written `{ body } => false .` is a constraint, reported as
+/// violated once nothing more follows rather than concluding anything.
+///
+use crate::data::TriplesError;
+use crate::db_api::DbApi;
+use crate::rules::{parse_rules, Reasoner, Rule, RuleTerm};
+use std::collections::BTreeSet;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+use tracing::debug;
+
+/// read and parse the rules of each file in order
+///
+/// # Errors
+///
+/// return `Err` if a file can not be read or holds a rule that can not be
+/// parsed
+pub async fn read_rules(
+    paths: &[impl AsRef<Path>],
+) -> Result<Vec<Rule>, Box<dyn std::error::Error>> {
+    let mut rules = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let mut text = String::new();
+        crate::io_file::open(path)
+            .await?
+            .read_to_string(&mut text)
+            .await?;
+        rules.extend(parse_rules(&text, &path.display().to_string())?);
+    }
+    Ok(rules)
+}
+
+/// the predicates rules can match or conclude, `None` when that may be any
+fn relevant_predicates(rules: &[Rule]) -> Option<Vec<String>> {
+    let mut predicates = BTreeSet::new();
+    for pattern in rules
+        .iter()
+        .flat_map(|rule| rule.body.iter().chain(&rule.head))
+    {
+        match &pattern.predicate {
+            RuleTerm::Const(predicate) => predicates.insert(predicate.clone()),
+            RuleTerm::Var(_) => return None,
+        };
+    }
+    Some(predicates.into_iter().collect())
+}
+
+/// replace the conclusions of earlier runs in `graph` by what `rules`
+/// derive from the store until nothing new follows, returning how many
+/// triples were concluded
+///
+/// # Errors
+///
+/// return `Err` if the store can not be read or the conclusions stored, or
+/// `TriplesError::Inconsistent` when a constraint matches, leaving the
+/// store as it was
+pub async fn infer(
+    rules: Vec<Rule>,
+    graph: &str,
+    db_api: &DbApi,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut tx = db_api.begin_txn().await?;
+
+    let retracted = db_api.clear_conclusions_txn(&mut tx, graph).await?;
+    debug!("retracted {retracted} earlier conclusions from {graph}");
+
+    let predicates = relevant_predicates(&rules);
+    let mut reasoner = Reasoner::new(rules);
+    for (id, subject, predicate, object) in db_api
+        .identified_triples_txn(&mut tx, predicates.as_deref())
+        .await?
+    {
+        reasoner.add_fact((subject, predicate, object), id, false);
+    }
+
+    let mut concluded = 0;
+    let mut round = 0;
+    loop {
+        let conclusions = reasoner.step();
+        if conclusions.is_empty() {
+            break;
+        }
+        round += 1;
+        debug!("round {round}: {} conclusions", conclusions.len());
+        for conclusion in conclusions {
+            let id = match conclusion.id {
+                Some(id) => id,
+                None => {
+                    let (subject, predicate, object) = &conclusion.triple;
+                    let id = db_api
+                        .insert_conclusion_txn(&mut tx, graph, subject, predicate, object)
+                        .await?;
+                    reasoner.add_fact(conclusion.triple, id, true);
+                    concluded += 1;
+                    id
+                }
+            };
+            for (rule, premises) in &conclusion.derivations {
+                db_api
+                    .insert_derivation_txn(&mut tx, id, rule, premises)
+                    .await?;
+            }
+        }
+    }
+
+    let violations = reasoner.violations();
+    if !violations.is_empty() {
+        tx.rollback().await?;
+        return Err(Box::new(TriplesError::Inconsistent { violations }));
+    }
+
+    tx.commit().await?;
+
+    Ok(concluded)
+}
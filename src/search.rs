@@ -0,0 +1,87 @@
+/// full-text search of the literal objects
+///
+/// Literals are indexed by `SQLite` FTS5 as they are stored.  Search text
+/// is a list of words that must all appear in a literal, in any order and
+/// case, a word ending in `*` matching any word it begins.  Matches are
+/// ranked by BM25, rarer words and shorter literals ranking higher.
+///
+/// SPARQL queries reach the index through the `TEXT_MATCH` extension
+/// function, `FILTER(<https://triples.navicore.tech/function#textMatch>(?o, "words"))`.
+///
+use crate::data::TriplesError;
+use crate::prefixes::PrefixMap;
+use crate::report::{display, table};
+
+/// The SPARQL extension function true of literals matching search text.
+pub const TEXT_MATCH: &str = "https://triples.navicore.tech/function#textMatch";
+
+/// A subject whose literal matched, with its best matching literal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    /// higher for better matches
+    pub score: f64,
+}
+
+/// the FTS5 query matching literals with every word of `text`, each word
+/// quoted so punctuation is never read as query syntax
+///
+/// # Errors
+///
+/// return `Err` if `text` has no words
+pub fn match_expression(text: &str) -> Result<String, TriplesError> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| match word.strip_suffix('*') {
+            Some(prefix) if !prefix.is_empty() => format!("\"{}\"*", prefix.replace('"', "\"\"")),
+            _ => format!("\"{}\"", word.replace('"', "\"\"")),
+        })
+        .collect();
+    if words.is_empty() {
+        return Err(TriplesError::ParseError {
+            reason: "nothing to search for".to_string(),
+        });
+    }
+    Ok(words.join(" "))
+}
+
+/// the hits as a text table, names compacted by `prefixes`
+#[must_use]
+pub fn report(hits: &[SearchHit], prefixes: &PrefixMap) -> String {
+    let rows: Vec<Vec<String>> = hits
+        .iter()
+        .map(|hit| {
+            vec![
+                format!("{:.3}", hit.score),
+                display(&hit.subject, prefixes),
+                display(&hit.predicate, prefixes),
+                display(&hit.object, prefixes),
+            ]
+        })
+        .collect();
+    table(
+        &["score", "subject", "predicate", "match"],
+        &rows,
+        &[true, false, false, false],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_are_quoted() {
+        assert_eq!(
+            match_expression("peak  live-thread cou*").unwrap(),
+            "\"peak\" \"live-thread\" \"cou\"*"
+        );
+        assert_eq!(
+            match_expression("say \"hi\"").unwrap(),
+            "\"say\" \"\"\"hi\"\"\""
+        );
+        assert!(match_expression("  ").is_err());
+    }
+}
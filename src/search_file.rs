@@ -0,0 +1,34 @@
+/// functions in support of searching the literals of the db
+///
+/// The hits are written as a text table, best first.
+///
+use crate::db_api::DbApi;
+use crate::search;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// write the `limit` subjects best matching `text` to `out`, of the
+/// literals of `predicates`, IRIs or prefixed names, only unless empty
+///
+/// # Errors
+///
+/// return `Err` if `text` has no words, a predicate can not be expanded,
+/// or on db read or write errors
+pub async fn write_hits<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    text: &str,
+    predicates: &[String],
+    limit: usize,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prefixes = db_api.preferred_prefixes().await?;
+    let predicates = predicates
+        .iter()
+        .map(|p| prefixes.resolve(p))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut hits = db_api.search(text, &predicates).await?;
+    hits.truncate(limit);
+    out.write_all(search::report(&hits, &prefixes).as_bytes())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,778 @@
+/// SHACL Core validation of a data graph against a shapes graph
+///
+/// Node shapes and property shapes are read from the shapes graph with
+/// their `sh:targetClass`, `sh:targetNode`, `sh:targetSubjectsOf` and
+/// `sh:targetObjectsOf` targets, classes that are shapes included.  Paths
+/// are a predicate or `[ sh:inversePath p ]`.  The constraints checked are
+/// `sh:class`, `sh:datatype`, `sh:nodeKind`, `sh:minCount`, `sh:maxCount`,
+/// the value ranges, `sh:minLength`, `sh:maxLength`, `sh:pattern` with
+/// `sh:flags`, `sh:in`, `sh:hasValue`, `sh:node` and `sh:closed` with
+/// `sh:ignoredProperties`.
+///
+/// The store keeps literals without their datatype, so `sh:datatype` holds
+/// of a literal whose lexical form is valid for the datatype.
+///
+use crate::data::{compare, RdfName, Subject, Term, TriplesError};
+use crate::vocab::{RDFS_CLASS, RDFS_SUB_CLASS_OF, RDF_FIRST, RDF_NIL, RDF_REST, RDF_TYPE, XSD};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const SH: &str = "http://www.w3.org/ns/shacl#";
+
+/// how deep `sh:node` may nest before a shape is taken to refer to itself
+const MAX_DEPTH: usize = 32;
+
+fn sh(name: &str) -> String {
+    format!("{SH}{name}")
+}
+
+/// Triples indexed by subject and by object, for the data graph and the
+/// shapes graph alike.
+#[derive(Debug, Default, Clone)]
+pub struct Graph {
+    forward: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+    backward: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl Graph {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, subject: &str, predicate: &str, object: &str) {
+        self.forward
+            .entry(subject.to_string())
+            .or_default()
+            .entry(predicate.to_string())
+            .or_default()
+            .insert(object.to_string());
+        self.backward
+            .entry(object.to_string())
+            .or_default()
+            .entry(predicate.to_string())
+            .or_default()
+            .insert(subject.to_string());
+    }
+
+    pub fn add_subject(&mut self, subject: &Subject) {
+        let name = subject.name().to_string();
+        for (predicate, objects) in subject.predicate_object_pairs() {
+            for object in objects {
+                self.add(&name, &predicate.to_string(), object);
+            }
+        }
+    }
+
+    fn objects(&self, subject: &str, predicate: &str) -> Vec<&str> {
+        self.forward
+            .get(subject)
+            .and_then(|predicates| predicates.get(predicate))
+            .map(|objects| objects.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    fn object(&self, subject: &str, predicate: &str) -> Option<&str> {
+        self.objects(subject, predicate).into_iter().next()
+    }
+
+    fn subjects(&self, predicate: &str, object: &str) -> Vec<&str> {
+        self.backward
+            .get(object)
+            .and_then(|predicates| predicates.get(predicate))
+            .map(|subjects| subjects.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    fn predicates(&self, subject: &str) -> Vec<&str> {
+        self.forward
+            .get(subject)
+            .map(|predicates| predicates.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// every subject and object of `predicate`
+    fn uses(&self, predicate: &str, as_subject: bool) -> BTreeSet<&str> {
+        let index = if as_subject {
+            &self.forward
+        } else {
+            &self.backward
+        };
+        index
+            .iter()
+            .filter(|(_, predicates)| predicates.contains_key(predicate))
+            .map(|(node, _)| node.as_str())
+            .collect()
+    }
+
+    /// `class` and its subclasses
+    fn subclasses<'a>(&'a self, class: &'a str) -> BTreeSet<&'a str> {
+        let mut classes = BTreeSet::from([class]);
+        let mut pending = vec![class];
+        while let Some(class) = pending.pop() {
+            for subclass in self.subjects(RDFS_SUB_CLASS_OF, class) {
+                if classes.insert(subclass) {
+                    pending.push(subclass);
+                }
+            }
+        }
+        classes
+    }
+
+    /// the SHACL instances of `class`, typed by it or one of its subclasses
+    fn instances(&self, class: &str) -> BTreeSet<&str> {
+        self.subclasses(class)
+            .into_iter()
+            .flat_map(|class| self.subjects(RDF_TYPE, class))
+            .collect()
+    }
+
+    fn is_instance(&self, node: &str, class: &str) -> bool {
+        let classes = self.subclasses(class);
+        self.objects(node, RDF_TYPE)
+            .iter()
+            .any(|class| classes.contains(class))
+    }
+
+    /// the members of the RDF list starting at `head`
+    fn list(&self, head: &str) -> Vec<String> {
+        let mut members = Vec::new();
+        let mut node = head;
+        let mut seen = BTreeSet::new();
+        while node != RDF_NIL && seen.insert(node) {
+            members.extend(
+                self.objects(node, RDF_FIRST)
+                    .iter()
+                    .map(ToString::to_string),
+            );
+            match self.object(node, RDF_REST) {
+                Some(rest) => node = rest,
+                None => break,
+            }
+        }
+        members
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Path {
+    Predicate(String),
+    Inverse(String),
+}
+
+impl Path {
+    fn values<'a>(&self, graph: &'a Graph, focus: &str) -> Vec<&'a str> {
+        match self {
+            Self::Predicate(predicate) => graph.objects(focus, predicate),
+            Self::Inverse(predicate) => graph.subjects(predicate, focus),
+        }
+    }
+
+    fn predicate(&self) -> &str {
+        match self {
+            Self::Predicate(predicate) | Self::Inverse(predicate) => predicate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Class(String),
+    Node(String),
+    SubjectsOf(String),
+    ObjectsOf(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Constraint {
+    Class(String),
+    Datatype(String),
+    NodeKind(String),
+    MinCount(usize),
+    MaxCount(usize),
+    MinInclusive(String),
+    MaxInclusive(String),
+    MinExclusive(String),
+    MaxExclusive(String),
+    MinLength(usize),
+    MaxLength(usize),
+    Pattern(Regex),
+    In(Vec<String>),
+    HasValue(String),
+    Node(String),
+    Closed(BTreeSet<String>),
+}
+
+impl Constraint {
+    /// the local name of the constraint component in the `sh:` namespace
+    const fn component(&self) -> &'static str {
+        match self {
+            Self::Class(_) => "ClassConstraintComponent",
+            Self::Datatype(_) => "DatatypeConstraintComponent",
+            Self::NodeKind(_) => "NodeKindConstraintComponent",
+            Self::MinCount(_) => "MinCountConstraintComponent",
+            Self::MaxCount(_) => "MaxCountConstraintComponent",
+            Self::MinInclusive(_) => "MinInclusiveConstraintComponent",
+            Self::MaxInclusive(_) => "MaxInclusiveConstraintComponent",
+            Self::MinExclusive(_) => "MinExclusiveConstraintComponent",
+            Self::MaxExclusive(_) => "MaxExclusiveConstraintComponent",
+            Self::MinLength(_) => "MinLengthConstraintComponent",
+            Self::MaxLength(_) => "MaxLengthConstraintComponent",
+            Self::Pattern(_) => "PatternConstraintComponent",
+            Self::In(_) => "InConstraintComponent",
+            Self::HasValue(_) => "HasValueConstraintComponent",
+            Self::Node(_) => "NodeConstraintComponent",
+            Self::Closed(_) => "ClosedConstraintComponent",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Shape {
+    pub id: String,
+    pub targets: Vec<Target>,
+    /// `None` for a node shape
+    pub path: Option<Path>,
+    pub constraints: Vec<Constraint>,
+    /// the property shapes of `sh:property`
+    pub properties: Vec<String>,
+    pub severity: String,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationResult {
+    pub focus_node: String,
+    pub path: Option<Path>,
+    pub value: Option<String>,
+    pub source_shape: String,
+    /// the constraint component IRI
+    pub component: String,
+    pub severity: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub results: Vec<ValidationResult>,
+}
+
+impl ValidationReport {
+    #[must_use]
+    pub fn conforms(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// the results of `sh:Violation` severity
+    #[must_use]
+    pub fn violations(&self) -> usize {
+        let violation = sh("Violation");
+        self.results
+            .iter()
+            .filter(|result| result.severity == violation)
+            .count()
+    }
+
+    /// the report as a `sh:ValidationReport` graph with one
+    /// `sh:ValidationResult` blank node per result
+    #[must_use]
+    pub fn to_subjects(&self) -> Vec<Subject> {
+        let name = |local: &str| RdfName::new(sh(local));
+        let mut report = Subject::new(RdfName::new("_:report".to_string()));
+        report.add(RdfName::new(RDF_TYPE.to_string()), sh("ValidationReport"));
+        report.add(name("conforms"), self.conforms().to_string());
+
+        let mut subjects = Vec::new();
+        for (n, result) in self.results.iter().enumerate() {
+            let id = format!("_:result{}", n + 1);
+            report.add(name("result"), id.clone());
+            let mut subject = Subject::new(RdfName::new(id));
+            subject.add(RdfName::new(RDF_TYPE.to_string()), sh("ValidationResult"));
+            subject.add(name("focusNode"), result.focus_node.clone());
+            match &result.path {
+                Some(Path::Predicate(predicate)) => {
+                    subject.add(name("resultPath"), predicate.clone());
+                }
+                Some(Path::Inverse(predicate)) => {
+                    let path_id = format!("_:path{}", n + 1);
+                    subject.add(name("resultPath"), path_id.clone());
+                    let mut path = Subject::new(RdfName::new(path_id));
+                    path.add(name("inversePath"), predicate.clone());
+                    subjects.push(path);
+                }
+                None => {}
+            }
+            if let Some(value) = &result.value {
+                subject.add(name("value"), value.clone());
+            }
+            subject.add(name("sourceShape"), result.source_shape.clone());
+            subject.add(name("sourceConstraintComponent"), result.component.clone());
+            subject.add(name("resultSeverity"), result.severity.clone());
+            subject.add(name("resultMessage"), result.message.clone());
+            subjects.push(subject);
+        }
+        subjects.insert(0, report);
+        subjects
+    }
+}
+
+fn shape_error(shape: &str, reason: &str) -> TriplesError {
+    TriplesError::ParseError {
+        reason: format!("shape {shape}: {reason}"),
+    }
+}
+
+fn count(shape: &str, value: &str) -> Result<usize, TriplesError> {
+    value
+        .parse()
+        .map_err(|_| shape_error(shape, &format!("{value} is not a count")))
+}
+
+/// The shapes of a shapes graph, by id.
+#[derive(Debug, Clone, Default)]
+pub struct Shapes {
+    shapes: BTreeMap<String, Shape>,
+}
+
+impl Shapes {
+    /// read the shapes of `graph`, those declared `sh:NodeShape` or
+    /// `sh:PropertyShape`, with a target or path, or used by another shape
+    ///
+    /// # Errors
+    ///
+    /// return `Err` if a shape has a malformed constraint
+    pub fn parse(graph: &Graph) -> Result<Self, TriplesError> {
+        let mut ids: BTreeSet<&str> = BTreeSet::new();
+        for class in ["NodeShape", "PropertyShape"] {
+            ids.extend(graph.subjects(RDF_TYPE, &sh(class)));
+        }
+        for predicate in [
+            "path",
+            "targetClass",
+            "targetNode",
+            "targetSubjectsOf",
+            "targetObjectsOf",
+        ] {
+            ids.extend(graph.uses(&sh(predicate), true));
+        }
+        for predicate in ["property", "node"] {
+            ids.extend(graph.uses(&sh(predicate), false));
+        }
+
+        let mut shapes = BTreeMap::new();
+        for id in ids {
+            if graph.object(id, &sh("deactivated")) == Some("true") {
+                continue;
+            }
+            shapes.insert(id.to_string(), Self::shape(graph, id)?);
+        }
+        Ok(Self { shapes })
+    }
+
+    fn shape(graph: &Graph, id: &str) -> Result<Shape, TriplesError> {
+        let values = |local: &str| graph.objects(id, &sh(local));
+
+        let mut targets: Vec<Target> = Vec::new();
+        if graph.objects(id, RDF_TYPE).contains(&RDFS_CLASS) {
+            targets.push(Target::Class(id.to_string()));
+        }
+        for (local, target) in [
+            ("targetClass", Target::Class as fn(String) -> Target),
+            ("targetNode", Target::Node),
+            ("targetSubjectsOf", Target::SubjectsOf),
+            ("targetObjectsOf", Target::ObjectsOf),
+        ] {
+            targets.extend(values(local).into_iter().map(|v| target(v.to_string())));
+        }
+
+        let path = match graph.object(id, &sh("path")) {
+            None => None,
+            Some(path) if path.starts_with("_:") => {
+                let inverse = graph.object(path, &sh("inversePath")).ok_or_else(|| {
+                    shape_error(id, "only predicate and inverse paths are supported")
+                })?;
+                Some(Path::Inverse(inverse.to_string()))
+            }
+            Some(path) => Some(Path::Predicate(path.to_string())),
+        };
+
+        let mut constraints = Vec::new();
+        for (local, constraint) in [
+            ("class", Constraint::Class as fn(String) -> Constraint),
+            ("datatype", Constraint::Datatype),
+            ("nodeKind", Constraint::NodeKind),
+            ("minInclusive", Constraint::MinInclusive),
+            ("maxInclusive", Constraint::MaxInclusive),
+            ("minExclusive", Constraint::MinExclusive),
+            ("maxExclusive", Constraint::MaxExclusive),
+            ("hasValue", Constraint::HasValue),
+            ("node", Constraint::Node),
+        ] {
+            constraints.extend(values(local).into_iter().map(|v| constraint(v.to_string())));
+        }
+        for (local, constraint) in [
+            ("minCount", Constraint::MinCount as fn(usize) -> Constraint),
+            ("maxCount", Constraint::MaxCount),
+            ("minLength", Constraint::MinLength),
+            ("maxLength", Constraint::MaxLength),
+        ] {
+            for value in values(local) {
+                constraints.push(constraint(count(id, value)?));
+            }
+        }
+        for pattern in values("pattern") {
+            let flags = graph.object(id, &sh("flags")).unwrap_or_default();
+            let pattern = if flags.contains('i') {
+                format!("(?i){pattern}")
+            } else {
+                pattern.to_string()
+            };
+            let regex = Regex::new(&pattern)
+                .map_err(|e| shape_error(id, &format!("bad sh:pattern: {e}")))?;
+            constraints.push(Constraint::Pattern(regex));
+        }
+        for list in values("in") {
+            constraints.push(Constraint::In(graph.list(list)));
+        }
+
+        let properties: Vec<String> = values("property")
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        if graph.object(id, &sh("closed")) == Some("true") {
+            let mut allowed: BTreeSet<String> = values("ignoredProperties")
+                .into_iter()
+                .flat_map(|list| graph.list(list))
+                .collect();
+            for property in &properties {
+                if let Some(predicate) = graph.object(property, &sh("path")) {
+                    allowed.insert(predicate.to_string());
+                }
+            }
+            constraints.push(Constraint::Closed(allowed));
+        }
+
+        Ok(Shape {
+            id: id.to_string(),
+            targets,
+            path,
+            constraints,
+            properties,
+            severity: graph
+                .object(id, &sh("severity"))
+                .map_or_else(|| sh("Violation"), ToString::to_string),
+            message: graph.object(id, &sh("message")).map(ToString::to_string),
+        })
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// the predicates validation reads, `None` when a closed shape needs
+    /// every predicate of its focus nodes
+    #[must_use]
+    pub fn predicates(&self) -> Option<Vec<String>> {
+        let mut predicates: BTreeSet<String> = [RDF_TYPE, RDFS_SUB_CLASS_OF]
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        for shape in self.shapes.values() {
+            if let Some(path) = &shape.path {
+                predicates.insert(path.predicate().to_string());
+            }
+            for target in &shape.targets {
+                if let Target::SubjectsOf(p) | Target::ObjectsOf(p) = target {
+                    predicates.insert(p.clone());
+                }
+            }
+            if shape
+                .constraints
+                .iter()
+                .any(|c| matches!(c, Constraint::Closed(_)))
+            {
+                return None;
+            }
+        }
+        Some(predicates.into_iter().collect())
+    }
+
+    /// validate the focus nodes of every shape's targets in `data`, only
+    /// those in `focus` when given
+    #[must_use]
+    pub fn validate(&self, data: &Graph, focus: Option<&BTreeSet<String>>) -> ValidationReport {
+        let mut results = Vec::new();
+        for shape in self.shapes.values() {
+            let mut nodes: BTreeSet<&str> = BTreeSet::new();
+            for target in &shape.targets {
+                match target {
+                    Target::Class(class) => nodes.extend(data.instances(class)),
+                    Target::Node(node) => {
+                        nodes.insert(node);
+                    }
+                    Target::SubjectsOf(predicate) => nodes.extend(data.uses(predicate, true)),
+                    Target::ObjectsOf(predicate) => nodes.extend(data.uses(predicate, false)),
+                }
+            }
+            for node in nodes {
+                if focus.is_none_or(|focus| focus.contains(node)) {
+                    self.validate_shape(shape, node, data, 0, &mut results);
+                }
+            }
+        }
+        ValidationReport { results }
+    }
+
+    fn validate_shape(
+        &self,
+        shape: &Shape,
+        focus: &str,
+        data: &Graph,
+        depth: usize,
+        results: &mut Vec<ValidationResult>,
+    ) {
+        let values = shape
+            .path
+            .as_ref()
+            .map_or_else(|| vec![focus], |path| path.values(data, focus));
+        let mut report = |constraint: &Constraint, value: Option<&str>, message: String| {
+            results.push(ValidationResult {
+                focus_node: focus.to_string(),
+                path: shape.path.clone(),
+                value: value.map(ToString::to_string),
+                source_shape: shape.id.clone(),
+                component: sh(constraint.component()),
+                severity: shape.severity.clone(),
+                message: shape.message.clone().unwrap_or(message),
+            });
+        };
+
+        for constraint in &shape.constraints {
+            match constraint {
+                Constraint::MinCount(min) if values.len() < *min => report(
+                    constraint,
+                    None,
+                    format!("fewer than {min} values, found {}", values.len()),
+                ),
+                Constraint::MaxCount(max) if values.len() > *max => report(
+                    constraint,
+                    None,
+                    format!("more than {max} values, found {}", values.len()),
+                ),
+                Constraint::MinCount(_) | Constraint::MaxCount(_) => {}
+                Constraint::Closed(allowed) => {
+                    for predicate in data.predicates(focus) {
+                        if predicate != RDF_TYPE && !allowed.contains(predicate) {
+                            for value in data.objects(focus, predicate) {
+                                report(
+                                    constraint,
+                                    Some(value),
+                                    format!("{predicate} is not allowed by the closed shape"),
+                                );
+                            }
+                        }
+                    }
+                }
+                Constraint::HasValue(expected) => {
+                    if !values.contains(&expected.as_str()) {
+                        report(constraint, None, format!("missing the value {expected}"));
+                    }
+                }
+                Constraint::Node(node_shape) => {
+                    let Some(node_shape) = self.shapes.get(node_shape) else {
+                        continue;
+                    };
+                    for value in &values {
+                        let mut nested = Vec::new();
+                        if depth < MAX_DEPTH {
+                            self.validate_shape(node_shape, value, data, depth + 1, &mut nested);
+                        }
+                        if !nested.is_empty() {
+                            report(
+                                constraint,
+                                Some(value),
+                                format!("does not conform to {}", node_shape.id),
+                            );
+                        }
+                    }
+                }
+                _ => {
+                    for value in &values {
+                        if let Some(message) = value_violation(constraint, value, data) {
+                            report(constraint, Some(value), message);
+                        }
+                    }
+                }
+            }
+        }
+
+        for property in &shape.properties {
+            if let Some(property) = self.shapes.get(property) {
+                self.validate_shape(property, focus, data, depth, results);
+            }
+        }
+    }
+}
+
+/// whether `value` is a valid lexical form of the XSD `datatype`
+fn has_datatype(value: &str, datatype: &str) -> bool {
+    let Some(local) = datatype.strip_prefix(XSD) else {
+        return false;
+    };
+    match local {
+        "boolean" => matches!(value, "true" | "false" | "1" | "0"),
+        "integer" | "long" | "int" | "short" | "byte" => value.parse::<i64>().is_ok(),
+        "nonNegativeInteger" | "unsignedLong" | "unsignedInt" => value.parse::<u64>().is_ok(),
+        "positiveInteger" => value.parse::<u64>().is_ok_and(|n| n > 0),
+        "decimal" => !value.contains(['e', 'E']) && value.parse::<f64>().is_ok(),
+        "double" | "float" => value.parse::<f64>().is_ok(),
+        "dateTime" => {
+            chrono::DateTime::parse_from_rfc3339(value).is_ok()
+                || chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+        }
+        "date" => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        "string" | "anyURI" | "normalizedString" | "token" => true,
+        _ => false,
+    }
+}
+
+/// why `value` fails a constraint on single values, `None` when it holds
+fn value_violation(constraint: &Constraint, value: &str, data: &Graph) -> Option<String> {
+    let term = Term::from_object(value);
+    let is_literal = matches!(term, Term::Literal { .. });
+    let holds = match constraint {
+        Constraint::Class(class) => data.is_instance(value, class),
+        Constraint::Datatype(datatype) => is_literal && has_datatype(value, datatype),
+        Constraint::NodeKind(kind) => {
+            let kind = kind.strip_prefix(SH).unwrap_or(kind);
+            match term {
+                Term::Iri(_) => kind.contains("IRI"),
+                Term::BlankNode(_) => kind.contains("BlankNode"),
+                Term::Literal { .. } => kind.contains("Literal"),
+            }
+        }
+        Constraint::MinInclusive(min) => is_literal && compare(value, min).is_ge(),
+        Constraint::MaxInclusive(max) => is_literal && compare(value, max).is_le(),
+        Constraint::MinExclusive(min) => is_literal && compare(value, min).is_gt(),
+        Constraint::MaxExclusive(max) => is_literal && compare(value, max).is_lt(),
+        Constraint::MinLength(min) => {
+            !matches!(term, Term::BlankNode(_)) && value.chars().count() >= *min
+        }
+        Constraint::MaxLength(max) => {
+            !matches!(term, Term::BlankNode(_)) && value.chars().count() <= *max
+        }
+        Constraint::Pattern(regex) => !matches!(term, Term::BlankNode(_)) && regex.is_match(value),
+        Constraint::In(members) => members.iter().any(|member| member == value),
+        Constraint::MinCount(_)
+        | Constraint::MaxCount(_)
+        | Constraint::HasValue(_)
+        | Constraint::Node(_)
+        | Constraint::Closed(_) => true,
+    };
+    if holds {
+        return None;
+    }
+    Some(match constraint {
+        Constraint::Class(class) => format!("{value} is not a {class}"),
+        Constraint::Datatype(datatype) => format!("{value} is not a valid {datatype}"),
+        Constraint::NodeKind(kind) => format!("{value} is not of kind {kind}"),
+        Constraint::MinInclusive(min) => format!("{value} is less than {min}"),
+        Constraint::MaxInclusive(max) => format!("{value} is more than {max}"),
+        Constraint::MinExclusive(min) => format!("{value} is not more than {min}"),
+        Constraint::MaxExclusive(max) => format!("{value} is not less than {max}"),
+        Constraint::MinLength(min) => format!("{value} is shorter than {min}"),
+        Constraint::MaxLength(max) => format!("{value} is longer than {max}"),
+        Constraint::Pattern(regex) => format!("{value} does not match {regex}"),
+        Constraint::In(_) => format!("{value} is not an allowed value"),
+        _ => format!("{value} fails {}", constraint.component()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EX: &str = "http://example.com/";
+
+    fn ex(local: &str) -> String {
+        format!("{EX}{local}")
+    }
+
+    fn shapes() -> Shapes {
+        let mut graph = Graph::new();
+        graph.add(&ex("PointShape"), RDF_TYPE, &sh("NodeShape"));
+        graph.add(&ex("PointShape"), &sh("targetClass"), &ex("Point"));
+        graph.add(&ex("PointShape"), &sh("property"), "_:unit");
+        graph.add(&ex("PointShape"), &sh("property"), "_:value");
+        graph.add("_:unit", &sh("path"), &ex("unit"));
+        graph.add("_:unit", &sh("minCount"), "1");
+        graph.add("_:unit", &sh("in"), "_:l1");
+        graph.add("_:l1", RDF_FIRST, "degC");
+        graph.add("_:l1", RDF_REST, "_:l2");
+        graph.add("_:l2", RDF_FIRST, "degF");
+        graph.add("_:l2", RDF_REST, RDF_NIL);
+        graph.add("_:value", &sh("path"), &ex("value"));
+        graph.add("_:value", &sh("maxCount"), "1");
+        graph.add("_:value", &sh("datatype"), &format!("{XSD}decimal"));
+        graph.add("_:value", &sh("maxInclusive"), "150");
+        Shapes::parse(&graph).unwrap()
+    }
+
+    #[test]
+    fn validates_property_shapes() {
+        let mut data = Graph::new();
+        data.add(&ex("SubPoint"), RDFS_SUB_CLASS_OF, &ex("Point"));
+        data.add(&ex("ok"), RDF_TYPE, &ex("Point"));
+        data.add(&ex("ok"), &ex("unit"), "degC");
+        data.add(&ex("ok"), &ex("value"), "72.5");
+        data.add(&ex("bad"), RDF_TYPE, &ex("SubPoint"));
+        data.add(&ex("bad"), &ex("unit"), "kelvin");
+        data.add(&ex("bad"), &ex("value"), "hot");
+        data.add(&ex("bad"), &ex("value"), "200");
+        data.add(&ex("none"), RDF_TYPE, &ex("Point"));
+
+        let report = shapes().validate(&data, None);
+        let mut found: Vec<(&str, &str, Option<&str>)> = report
+            .results
+            .iter()
+            .map(|r| {
+                (
+                    r.focus_node.trim_start_matches(EX),
+                    r.component.trim_start_matches(SH),
+                    r.value.as_deref(),
+                )
+            })
+            .collect();
+        found.sort_unstable();
+        assert_eq!(
+            found,
+            [
+                ("bad", "DatatypeConstraintComponent", Some("hot")),
+                ("bad", "InConstraintComponent", Some("kelvin")),
+                ("bad", "MaxCountConstraintComponent", None),
+                ("bad", "MaxInclusiveConstraintComponent", Some("200")),
+                ("bad", "MaxInclusiveConstraintComponent", Some("hot")),
+                ("none", "MinCountConstraintComponent", None),
+            ]
+        );
+        assert_eq!(report.violations(), 6);
+
+        let focus = BTreeSet::from([ex("ok")]);
+        assert!(shapes().validate(&data, Some(&focus)).conforms());
+    }
+
+    #[test]
+    fn reports_as_a_graph() {
+        let mut data = Graph::new();
+        data.add(&ex("none"), RDF_TYPE, &ex("Point"));
+        let subjects = shapes().validate(&data, None).to_subjects();
+        assert_eq!(subjects.len(), 2);
+        let conforms = subjects[0].get(&RdfName::new(sh("conforms"))).unwrap();
+        assert!(conforms.contains("false"));
+        let component = subjects[1]
+            .get(&RdfName::new(sh("sourceConstraintComponent")))
+            .unwrap();
+        assert!(component.contains(&sh("MinCountConstraintComponent")));
+    }
+}
@@ -0,0 +1,130 @@
+/// functions in support of SHACL validation
+///
+/// Shapes are read from Turtle files and the validation report is written
+/// as Turtle.
+///
+/// A batch is validated together with the store it is about to join, so
+/// `sh:class` and paths may reach stored nodes, but only the subjects of
+/// the batch are focus nodes.  Violations already in the store do not hold
+/// up an import, and results of a lesser `sh:severity` do not either.
+///
+use crate::data::{Subject, TriplesError};
+use crate::db_api::DbApi;
+use crate::io_file;
+use crate::prefixes::PrefixMap;
+use crate::shacl::{Graph, Shapes, ValidationReport, SH};
+use crate::ttl_file;
+use crate::turtle_writer::{TurtleOptions, TurtleWriter};
+use std::collections::BTreeSet;
+use std::path::Path;
+use tokio::io::AsyncWrite;
+use tracing::debug;
+
+/// read a shapes graph from a turtle file
+///
+/// # Errors
+///
+/// return `Err` if the file can not be read or parsed, or a shape is
+/// malformed
+pub async fn read_shapes(path: &Path) -> Result<Shapes, Box<dyn std::error::Error>> {
+    let (subjects, _) = ttl_file::read_turtle(io_file::open(path).await?).await?;
+    let mut graph = Graph::new();
+    for subject in &subjects {
+        graph.add_subject(subject);
+    }
+    Ok(Shapes::parse(&graph)?)
+}
+
+/// the stored triples the shapes read
+async fn stored_graph(
+    shapes: &Shapes,
+    db_api: &DbApi,
+) -> Result<Graph, Box<dyn std::error::Error>> {
+    let predicates = shapes.predicates();
+    let mut graph = Graph::new();
+    for (_, subject, predicate, object) in db_api.identified_triples(predicates.as_deref()).await? {
+        graph.add(&subject, &predicate, &object);
+    }
+    Ok(graph)
+}
+
+/// validate the store against `shapes`
+///
+/// # Errors
+///
+/// return `Err` if the store can not be read
+pub async fn validate(
+    shapes: &Shapes,
+    db_api: &DbApi,
+) -> Result<ValidationReport, Box<dyn std::error::Error>> {
+    let graph = stored_graph(shapes, db_api).await?;
+    Ok(shapes.validate(&graph, None))
+}
+
+/// validate the subjects of a batch, before import, against `shapes`
+///
+/// # Errors
+///
+/// return `Err` if the store can not be read
+pub async fn validate_batch(
+    shapes: &Shapes,
+    batch: &[Subject],
+    db_api: &DbApi,
+) -> Result<ValidationReport, Box<dyn std::error::Error>> {
+    let mut graph = stored_graph(shapes, db_api).await?;
+    let mut focus = BTreeSet::new();
+    for subject in batch {
+        graph.add_subject(subject);
+        focus.insert(subject.name().to_string());
+    }
+    let report = shapes.validate(&graph, Some(&focus));
+    debug!(
+        "validated {} subjects, {} results",
+        batch.len(),
+        report.results.len()
+    );
+    Ok(report)
+}
+
+/// write a validation report graph as turtle to `out`
+///
+/// # Errors
+///
+/// return `Err` if `out` can not be written
+pub async fn write_report<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    report: &ValidationReport,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let subjects = report.to_subjects();
+    let mut preferred = PrefixMap::new();
+    preferred.insert("sh", SH);
+    preferred.extend(&PrefixMap::well_known());
+    let prefixes = ttl_file::compute_prefixes(&subjects, &preferred)?;
+    TurtleWriter::new(&subjects, &prefixes, TurtleOptions::default())
+        .write_async(out)
+        .await?;
+    Ok(())
+}
+
+/// import a batch read by `ttl_file::read_turtle` if it conforms to
+/// `shapes`, else write the report to `out` and import none of it
+///
+/// # Errors
+///
+/// return `TriplesError::ShapeViolations` if the batch is rejected, or
+/// `Err` if it can not be validated or loaded
+pub async fn import_validated<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    subjects: &[Subject],
+    prefixes: &[(String, String)],
+    shapes: &Shapes,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = validate_batch(shapes, subjects, db_api).await?;
+    let count = report.violations();
+    if count > 0 {
+        write_report(out, &report).await?;
+        return Err(Box::new(TriplesError::ShapeViolations { count }));
+    }
+    ttl_file::import_subjects(subjects, prefixes, db_api).await
+}
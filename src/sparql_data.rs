@@ -1,25 +1,188 @@
+use crate::data::Term;
+use std::fmt;
+
 // A simple struct representation for a SPARQL query
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SparqlQuery {
     pub select_clause: SelectClause,
     pub triples_block: Vec<TriplePattern>,
+    pub filters: Vec<Filter>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+/// An empty `variables` list is `SELECT *`.
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SelectClause {
     pub distinct: bool,
     pub variables: Vec<Variable>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Variable {
     IRI(String),
     Var(String),
+    Literal(String),
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TriplePattern {
     pub subject: Variable,
-    pub predicate: String,
+    pub predicate: PropertyPath,
     pub object: Variable,
 }
+
+/// The predicate position of a triple pattern, see
+/// <https://www.w3.org/TR/sparql11-query/#propertypaths>
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PropertyPath {
+    /// a plain predicate IRI
+    Iri(String),
+    /// `?p` - not a path, but it shares the predicate slot
+    Var(String),
+    /// `^p`
+    Inverse(Box<PropertyPath>),
+    /// `p1/p2`
+    Sequence(Vec<PropertyPath>),
+    /// `p1|p2`
+    Alternative(Vec<PropertyPath>),
+    /// `p?`
+    ZeroOrOne(Box<PropertyPath>),
+    /// `p*`
+    ZeroOrMore(Box<PropertyPath>),
+    /// `p+`
+    OneOrMore(Box<PropertyPath>),
+    /// `!p` or `!(p1|^p2)`
+    NegatedSet(Vec<NegatedIri>),
+}
+
+/// A `FILTER` constraint, of the extension functions the evaluator
+/// supports.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Filter {
+    /// `search::TEXT_MATCH` of a variable and search text, true when the
+    /// variable is bound to a literal with every word of the text
+    TextMatch { variable: String, text: String },
+}
+
+/// A member of a negated property set, `p` or `^p`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NegatedIri {
+    Forward(String),
+    Inverse(String),
+}
+
+/// An ASK query, true if the triple patterns have any solution.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AskQuery {
+    pub triples_block: Vec<TriplePattern>,
+    pub filters: Vec<Filter>,
+}
+
+/// The query forms the evaluator supports.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum QueryForm {
+    Select(SparqlQuery),
+    Ask(AskQuery),
+}
+
+/// The variables and rows produced by evaluating a `SparqlQuery`.  Unbound
+/// values are `None`.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Solutions {
+    pub variables: Vec<String>,
+    pub rows: Vec<Vec<Option<Term>>>,
+}
+
+/// The result of evaluating any `QueryForm`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum QueryResults {
+    Solutions(Solutions),
+    Boolean(bool),
+}
+
+/// A ground triple of `INSERT DATA` or `DELETE DATA`, in the named graph
+/// when `graph` is set and the default graph otherwise.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Quad {
+    pub graph: Option<String>,
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+}
+
+/// The graphs a `CLEAR` or `DROP` applies to.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum GraphTarget {
+    Default,
+    Named(String),
+    All,
+}
+
+/// One operation of a SPARQL 1.1 Update request, see
+/// <https://www.w3.org/TR/sparql11-update/#graphUpdate>
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum UpdateOperation {
+    InsertData(Vec<Quad>),
+    DeleteData(Vec<Quad>),
+    Clear(GraphTarget),
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::IRI(iri) => write!(f, "<{iri}>"),
+            Self::Var(name) => write!(f, "?{name}"),
+            Self::Literal(value) => write!(f, "\"{value}\""),
+        }
+    }
+}
+
+impl fmt::Display for TriplePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.subject, self.predicate, self.object)
+    }
+}
+
+impl fmt::Display for NegatedIri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Forward(iri) => write!(f, "<{iri}>"),
+            Self::Inverse(iri) => write!(f, "^<{iri}>"),
+        }
+    }
+}
+
+fn write_joined(f: &mut fmt::Formatter, paths: &[PropertyPath], sep: &str) -> fmt::Result {
+    write!(f, "(")?;
+    for (idx, path) in paths.iter().enumerate() {
+        if idx > 0 {
+            write!(f, "{sep}")?;
+        }
+        write!(f, "{path}")?;
+    }
+    write!(f, ")")
+}
+
+impl fmt::Display for PropertyPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Iri(iri) => write!(f, "<{iri}>"),
+            Self::Var(name) => write!(f, "?{name}"),
+            Self::Inverse(inner) => write!(f, "^{inner}"),
+            Self::Sequence(steps) => write_joined(f, steps, "/"),
+            Self::Alternative(alternatives) => write_joined(f, alternatives, "|"),
+            Self::ZeroOrOne(inner) => write!(f, "{inner}?"),
+            Self::ZeroOrMore(inner) => write!(f, "{inner}*"),
+            Self::OneOrMore(inner) => write!(f, "{inner}+"),
+            Self::NegatedSet(set) => {
+                write!(f, "!(")?;
+                for (idx, iri) in set.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, "|")?;
+                    }
+                    write!(f, "{iri}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+/// functions in support of running SPARQL queries from the cli
+///
+/// A query is read from stdin, and its results written to stdout in the
+/// format asked for, or the plan and SQL it translates to explained.
+///
+use crate::db_api::DbApi;
+use crate::sparql_results::{self, ResultFormat};
+use crate::sparql_sql;
+use std::io::Write;
+use tokio::io::{stdin, AsyncReadExt};
+use tracing::trace;
+
+/// read a SPARQL query from stdin
+///
+/// # Errors
+///
+/// return `Err` if stdin can not be read
+pub async fn read_query() -> Result<String, Box<dyn std::error::Error>> {
+    let mut text = String::new();
+    stdin().read_to_string(&mut text).await?;
+    Ok(text)
+}
+
+/// evaluate a SPARQL SELECT or ASK query and write the results to stdout
+///
+/// # Errors
+///
+/// return `Err` if the query can not be parsed or evaluated
+pub async fn query(
+    text: &str,
+    format: ResultFormat,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("query");
+    let form = sparql_sql::parse_form(text)?;
+    let results = db_api.evaluate(&form).await?;
+
+    let mut out = std::io::stdout().lock();
+    sparql_results::write(&results, format, &mut out)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+/// print the plan chosen for a SPARQL query and the SQL it translates to
+///
+/// # Errors
+///
+/// return `Err` if the query can not be parsed or planned
+pub async fn explain(text: &str, db_api: &DbApi) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("explain");
+    let query = sparql_sql::parse(text)?;
+    let explanation = db_api.explain(&query).await?;
+
+    println!("plan:");
+    println!("{}", explanation.plan.describe(&query));
+    println!();
+    println!("sql:");
+    println!("{}", explanation.sql.sql);
+    println!();
+    println!("params:");
+    for (idx, param) in explanation.sql.params.iter().enumerate() {
+        println!("?{} = {param}", idx + 1);
+    }
+    println!();
+    println!("database plan:");
+    for detail in &explanation.database_plan {
+        println!("{detail}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,748 @@
+/// Writers and readers for the standard SPARQL 1.1 query result documents:
+///
+/// * JSON <https://www.w3.org/TR/sparql11-results-json/>
+/// * XML <https://www.w3.org/TR/rdf-sparql-XMLres/>
+/// * CSV and TSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>
+///
+/// CSV has no standard form for ASK results, like other engines the value
+/// is written under an `_askResult` header.
+///
+use crate::csv::sanitize_csv_field;
+use crate::data::{is_iri, lexical_datatype, parse_error, Term, TriplesError};
+use crate::ntriples;
+use crate::sparql_data::{QueryResults, Solutions};
+use crate::vocab::XSD;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use quick_xml::XmlVersion;
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+const ASK_RESULT: &str = "_askResult";
+
+/// The supported result document formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultFormat {
+    Json,
+    Xml,
+    Csv,
+    Tsv,
+}
+
+impl ResultFormat {
+    #[must_use]
+    pub const fn media_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/sparql-results+json",
+            Self::Xml => "application/sparql-results+xml",
+            Self::Csv => "text/csv",
+            Self::Tsv => "text/tab-separated-values",
+        }
+    }
+
+    /// the format for a media type, parameters like `; charset=utf-8` are
+    /// ignored
+    #[must_use]
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or_default().trim();
+        match essence.to_ascii_lowercase().as_str() {
+            "application/sparql-results+json" | "application/json" => Some(Self::Json),
+            "application/sparql-results+xml" | "application/xml" | "text/xml" => Some(Self::Xml),
+            "text/csv" => Some(Self::Csv),
+            "text/tab-separated-values" => Some(Self::Tsv),
+            _ => None,
+        }
+    }
+
+    /// pick the most preferred supported format from an HTTP `Accept`
+    /// header, JSON when there is no header or any type is acceptable
+    #[must_use]
+    pub fn negotiate(accept: Option<&str>) -> Option<Self> {
+        let Some(accept) = accept.filter(|a| !a.trim().is_empty()) else {
+            return Some(Self::Json);
+        };
+
+        media_ranges(accept)
+            .into_iter()
+            .find_map(|media_type| match media_type {
+                "*/*" | "application/*" => Some(Self::Json),
+                "text/*" => Some(Self::Tsv),
+                _ => Self::from_media_type(media_type),
+            })
+    }
+}
+
+/// the media ranges of an HTTP `Accept` header, most preferred first,
+/// leaving out those with a quality of zero
+#[must_use]
+pub fn media_ranges(accept: &str) -> Vec<&str> {
+    let mut ranges: Vec<(f32, &str)> = accept
+        .split(',')
+        .map(|range| {
+            let mut parts = range.split(';');
+            let media_type = parts.next().unwrap_or_default().trim();
+            let quality = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality, media_type)
+        })
+        .filter(|(quality, _)| *quality > 0.0)
+        .collect();
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranges
+        .into_iter()
+        .map(|(_, media_type)| media_type)
+        .collect()
+}
+
+impl fmt::Display for ResultFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Xml => write!(f, "xml"),
+            Self::Csv => write!(f, "csv"),
+            Self::Tsv => write!(f, "tsv"),
+        }
+    }
+}
+
+impl FromStr for ResultFormat {
+    type Err = TriplesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "xml" => Ok(Self::Xml),
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            _ => Self::from_media_type(s).ok_or_else(|| TriplesError::ParseError {
+                reason: format!("unknown result format {s}"),
+            }),
+        }
+    }
+}
+
+/// write results in the given format
+///
+/// # Errors
+///
+/// return `Err` if the writer fails
+pub fn write<W: Write>(results: &QueryResults, format: ResultFormat, w: &mut W) -> io::Result<()> {
+    match format {
+        ResultFormat::Json => write_json(results, w),
+        ResultFormat::Xml => write_xml(results, w),
+        ResultFormat::Csv => write_csv(results, w),
+        ResultFormat::Tsv => write_tsv(results, w),
+    }
+}
+
+/// read results in the given format
+///
+/// # Errors
+///
+/// return `Err` if the text is not a valid document of that format
+pub fn read(text: &str, format: ResultFormat) -> Result<QueryResults, TriplesError> {
+    match format {
+        ResultFormat::Json => read_json(text),
+        ResultFormat::Xml => read_xml(text),
+        ResultFormat::Csv => read_csv(text),
+        ResultFormat::Tsv => read_tsv(text),
+    }
+}
+
+fn json_term(term: &Term) -> Value {
+    match term {
+        Term::Iri(iri) => json!({"type": "uri", "value": iri}),
+        Term::BlankNode(label) => json!({"type": "bnode", "value": label}),
+        Term::Literal {
+            value,
+            datatype,
+            language,
+        } => {
+            let mut object = Map::new();
+            object.insert("type".to_string(), json!("literal"));
+            object.insert("value".to_string(), json!(value));
+            if let Some(language) = language {
+                object.insert("xml:lang".to_string(), json!(language));
+            } else if let Some(datatype) = datatype {
+                object.insert("datatype".to_string(), json!(datatype));
+            }
+            Value::Object(object)
+        }
+    }
+}
+
+/// # Errors
+///
+/// return `Err` if the writer fails
+pub fn write_json<W: Write>(results: &QueryResults, w: &mut W) -> io::Result<()> {
+    let document = match results {
+        QueryResults::Boolean(value) => json!({"head": {}, "boolean": value}),
+        QueryResults::Solutions(solutions) => {
+            let bindings: Vec<Value> = solutions
+                .rows
+                .iter()
+                .map(|row| {
+                    let mut binding = Map::new();
+                    for (variable, term) in solutions.variables.iter().zip(row) {
+                        if let Some(term) = term {
+                            binding.insert(variable.clone(), json_term(term));
+                        }
+                    }
+                    Value::Object(binding)
+                })
+                .collect();
+            json!({
+                "head": {"vars": solutions.variables},
+                "results": {"bindings": bindings}
+            })
+        }
+    };
+    serde_json::to_writer(&mut *w, &document)?;
+    writeln!(w)
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// # Errors
+///
+/// return `Err` if the writer fails
+pub fn write_xml<W: Write>(results: &QueryResults, w: &mut W) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0"?>"#)?;
+    writeln!(
+        w,
+        r#"<sparql xmlns="http://www.w3.org/2005/sparql-results#">"#
+    )?;
+    match results {
+        QueryResults::Boolean(value) => {
+            writeln!(w, "  <head/>")?;
+            writeln!(w, "  <boolean>{value}</boolean>")?;
+        }
+        QueryResults::Solutions(solutions) => {
+            writeln!(w, "  <head>")?;
+            for variable in &solutions.variables {
+                writeln!(w, r#"    <variable name="{}"/>"#, escape_xml(variable))?;
+            }
+            writeln!(w, "  </head>")?;
+            writeln!(w, "  <results>")?;
+            for row in &solutions.rows {
+                writeln!(w, "    <result>")?;
+                for (variable, term) in solutions.variables.iter().zip(row) {
+                    let Some(term) = term else { continue };
+                    let value = match term {
+                        Term::Iri(iri) => format!("<uri>{}</uri>", escape_xml(iri)),
+                        Term::BlankNode(label) => format!("<bnode>{}</bnode>", escape_xml(label)),
+                        Term::Literal {
+                            value,
+                            datatype,
+                            language,
+                        } => {
+                            let attribute = if let Some(language) = language {
+                                format!(r#" xml:lang="{}""#, escape_xml(language))
+                            } else if let Some(datatype) = datatype {
+                                format!(r#" datatype="{}""#, escape_xml(datatype))
+                            } else {
+                                String::new()
+                            };
+                            format!("<literal{attribute}>{}</literal>", escape_xml(value))
+                        }
+                    };
+                    writeln!(
+                        w,
+                        r#"      <binding name="{}">{value}</binding>"#,
+                        escape_xml(variable)
+                    )?;
+                }
+                writeln!(w, "    </result>")?;
+            }
+            writeln!(w, "  </results>")?;
+        }
+    }
+    writeln!(w, "</sparql>")
+}
+
+/// # Errors
+///
+/// return `Err` if the writer fails
+pub fn write_csv<W: Write>(results: &QueryResults, w: &mut W) -> io::Result<()> {
+    match results {
+        QueryResults::Boolean(value) => write!(w, "{ASK_RESULT}\r\n{value}\r\n"),
+        QueryResults::Solutions(solutions) => {
+            let header: Vec<String> = solutions
+                .variables
+                .iter()
+                .map(|v| sanitize_csv_field(v))
+                .collect();
+            write!(w, "{}\r\n", header.join(","))?;
+            for row in &solutions.rows {
+                let values: Vec<String> = row
+                    .iter()
+                    .map(|term| match term {
+                        None => String::new(),
+                        Some(Term::BlankNode(label)) => format!("_:{label}"),
+                        Some(term) => sanitize_csv_field(term.value()),
+                    })
+                    .collect();
+                write!(w, "{}\r\n", values.join(","))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// # Errors
+///
+/// return `Err` if the writer fails
+pub fn write_tsv<W: Write>(results: &QueryResults, w: &mut W) -> io::Result<()> {
+    match results {
+        QueryResults::Boolean(value) => writeln!(w, "?{ASK_RESULT}\n{value}"),
+        QueryResults::Solutions(solutions) => {
+            let header: Vec<String> = solutions
+                .variables
+                .iter()
+                .map(|v| format!("?{v}"))
+                .collect();
+            writeln!(w, "{}", header.join("\t"))?;
+            for row in &solutions.rows {
+                let values: Vec<String> = row
+                    .iter()
+                    .map(|term| term.as_ref().map(ToString::to_string).unwrap_or_default())
+                    .collect();
+                writeln!(w, "{}", values.join("\t"))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn term_from_json(value: &Value) -> Result<Term, TriplesError> {
+    let field = |name: &str| value.get(name).and_then(Value::as_str).map(str::to_string);
+    let lexical = field("value").ok_or_else(|| parse_error("binding without value"))?;
+    match field("type").as_deref() {
+        Some("uri") => Ok(Term::Iri(lexical)),
+        Some("bnode") => Ok(Term::BlankNode(lexical)),
+        Some("literal" | "typed-literal") => Ok(Term::Literal {
+            value: lexical,
+            datatype: field("datatype"),
+            language: field("xml:lang"),
+        }),
+        other => Err(parse_error(format!("unknown binding type {other:?}"))),
+    }
+}
+
+/// # Errors
+///
+/// return `Err` if the text is not a SPARQL JSON results document
+pub fn read_json(text: &str) -> Result<QueryResults, TriplesError> {
+    let document: Value = serde_json::from_str(text).map_err(parse_error)?;
+
+    if let Some(value) = document.get("boolean") {
+        return value
+            .as_bool()
+            .map(QueryResults::Boolean)
+            .ok_or_else(|| parse_error("boolean result is not true or false"));
+    }
+
+    let variables: Vec<String> = document
+        .pointer("/head/vars")
+        .and_then(Value::as_array)
+        .ok_or_else(|| parse_error("missing head vars"))?
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+
+    let bindings = document
+        .pointer("/results/bindings")
+        .and_then(Value::as_array)
+        .ok_or_else(|| parse_error("missing results bindings"))?;
+
+    let mut rows = Vec::with_capacity(bindings.len());
+    for binding in bindings {
+        let mut row = Vec::with_capacity(variables.len());
+        for variable in &variables {
+            row.push(binding.get(variable).map(term_from_json).transpose()?);
+        }
+        rows.push(row);
+    }
+
+    Ok(QueryResults::Solutions(Solutions { variables, rows }))
+}
+
+/// # Errors
+///
+/// return `Err` if the text is not a SPARQL XML results document
+pub fn read_xml(text: &str) -> Result<QueryResults, TriplesError> {
+    let mut reader = Reader::from_str(text);
+
+    let mut variables: Vec<String> = Vec::new();
+    let mut rows: Vec<Vec<Option<Term>>> = Vec::new();
+    let mut row: Vec<Option<Term>> = Vec::new();
+    let mut binding: Option<usize> = None;
+    let mut term: Option<(String, Option<String>, Option<String>)> = None;
+    let mut content = String::new();
+    let mut boolean: Option<bool> = None;
+
+    loop {
+        let event = reader.read_event().map_err(parse_error)?;
+        if let Event::Start(element) | Event::Empty(element) = &event {
+            let attribute = |name: &str| -> Result<Option<String>, TriplesError> {
+                element
+                    .try_get_attribute(name)
+                    .map_err(parse_error)?
+                    .map(|a| {
+                        a.normalized_value(XmlVersion::Implicit1_0)
+                            .map(|v| v.to_string())
+                    })
+                    .transpose()
+                    .map_err(parse_error)
+            };
+            match element.local_name().as_ref() {
+                "variable" => variables.extend(attribute("name")?),
+                "result" => row = vec![None; variables.len()],
+                "binding" => {
+                    let name = attribute("name")?.unwrap_or_default();
+                    binding = variables.iter().position(|v| *v == name);
+                }
+                kind @ ("uri" | "bnode" | "literal") => {
+                    term = Some((
+                        kind.to_string(),
+                        attribute("xml:lang")?,
+                        attribute("datatype")?,
+                    ));
+                    content.clear();
+                }
+                _ => {}
+            }
+        }
+
+        let ended = match &event {
+            Event::End(e) => Some(e.local_name().as_ref().to_string()),
+            Event::Empty(e) => Some(e.local_name().as_ref().to_string()),
+            _ => None,
+        };
+
+        match &event {
+            Event::Text(e) => content.push_str(&e.xml_content(XmlVersion::Implicit1_0)),
+            Event::CData(e) => content.push_str(&e.xml_content(XmlVersion::Implicit1_0)),
+            Event::GeneralRef(e) => {
+                if let Some(c) = e.resolve_char_ref().map_err(parse_error)? {
+                    content.push(c);
+                } else {
+                    let name = e.xml10_content();
+                    let resolved = quick_xml::escape::resolve_predefined_entity(&name)
+                        .ok_or_else(|| parse_error(format!("unknown entity &{name};")))?;
+                    content.push_str(resolved);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        match ended.as_deref() {
+            Some("uri" | "bnode" | "literal") => {
+                if let (Some((kind, language, datatype)), Some(idx)) = (term.take(), binding) {
+                    let value = std::mem::take(&mut content);
+                    let parsed = match kind.as_str() {
+                        "uri" => Term::Iri(value),
+                        "bnode" => Term::BlankNode(value),
+                        _ => Term::Literal {
+                            value,
+                            datatype,
+                            language,
+                        },
+                    };
+                    if let Some(slot) = row.get_mut(idx) {
+                        *slot = Some(parsed);
+                    }
+                }
+            }
+            Some("binding") => binding = None,
+            Some("result") => rows.push(std::mem::take(&mut row)),
+            Some("boolean") => {
+                boolean = Some(content.trim() == "true");
+            }
+            Some("head") => content.clear(),
+            _ => {}
+        }
+    }
+
+    Ok(boolean.map_or(
+        QueryResults::Solutions(Solutions { variables, rows }),
+        QueryResults::Boolean,
+    ))
+}
+
+/// split RFC 4180 records, quoted fields may contain separators and newlines
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn boolean_result(header: &[String], records: &[Vec<String>]) -> Option<bool> {
+    if header.len() == 1 && header[0].trim_start_matches('?') == ASK_RESULT {
+        records
+            .first()
+            .and_then(|r| r.first())
+            .map(|v| v.trim() == "true")
+    } else {
+        None
+    }
+}
+
+/// CSV drops the kind of each term, IRIs are recognised by their scheme
+///
+/// # Errors
+///
+/// return `Err` if the text has no header row
+pub fn read_csv(text: &str) -> Result<QueryResults, TriplesError> {
+    let mut records = csv_records(text).into_iter();
+    let variables = records
+        .next()
+        .ok_or_else(|| parse_error("missing csv header"))?;
+    let records: Vec<Vec<String>> = records.collect();
+
+    if let Some(value) = boolean_result(&variables, &records) {
+        return Ok(QueryResults::Boolean(value));
+    }
+
+    let rows = records
+        .into_iter()
+        .map(|record| {
+            (0..variables.len())
+                .map(|idx| {
+                    record.get(idx).filter(|v| !v.is_empty()).map(|v| {
+                        if let Some(label) = v.strip_prefix("_:") {
+                            Term::BlankNode(label.to_string())
+                        } else if is_iri(v) {
+                            Term::Iri(v.clone())
+                        } else {
+                            Term::literal(v.clone())
+                        }
+                    })
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(QueryResults::Solutions(Solutions { variables, rows }))
+}
+
+/// one TSV cell, encoded as in Turtle
+fn term_from_tsv(cell: &str) -> Result<Option<Term>, TriplesError> {
+    let cell = cell.trim();
+    if cell.is_empty() {
+        return Ok(None);
+    }
+    if cell.starts_with(['<', '"']) || cell.starts_with("_:") {
+        let (term, rest) = ntriples::term(cell)?;
+        if !rest.trim().is_empty() {
+            return Err(parse_error(format!(
+                "unexpected {rest} after tsv term {cell}"
+            )));
+        }
+        return Ok(Some(term));
+    }
+
+    // unquoted numbers and booleans are Turtle shorthand for typed literals
+    let datatype = lexical_datatype(cell)
+        .ok_or_else(|| parse_error(format!("unrecognised tsv term {cell}")))?;
+    Ok(Some(Term::Literal {
+        value: cell.to_string(),
+        datatype: Some(format!("{XSD}{datatype}")),
+        language: None,
+    }))
+}
+
+/// # Errors
+///
+/// return `Err` if the text is not a SPARQL TSV results document
+pub fn read_tsv(text: &str) -> Result<QueryResults, TriplesError> {
+    let mut lines = text.lines();
+    let header: Vec<String> = lines
+        .next()
+        .ok_or_else(|| parse_error("missing tsv header"))?
+        .split('\t')
+        .map(|v| v.trim().trim_start_matches(['?', '$']).to_string())
+        .collect();
+    let lines: Vec<&str> = lines.filter(|l| !l.is_empty()).collect();
+
+    if header.len() == 1 && header[0] == ASK_RESULT {
+        let value = lines.first().is_some_and(|v| v.trim() == "true");
+        return Ok(QueryResults::Boolean(value));
+    }
+
+    let mut rows = Vec::with_capacity(lines.len());
+    for line in lines {
+        let cells: Vec<&str> = line.split('\t').collect();
+        let mut row = Vec::with_capacity(header.len());
+        for idx in 0..header.len() {
+            row.push(match cells.get(idx) {
+                Some(cell) => term_from_tsv(cell)?,
+                None => None,
+            });
+        }
+        rows.push(row);
+    }
+
+    Ok(QueryResults::Solutions(Solutions {
+        variables: header,
+        rows,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solutions() -> QueryResults {
+        QueryResults::Solutions(Solutions {
+            variables: vec!["s".to_string(), "label".to_string(), "b".to_string()],
+            rows: vec![
+                vec![
+                    Some(Term::Iri("http://example.com/a".to_string())),
+                    Some(Term::Literal {
+                        value: "say \"hi\", <twice>\nplease".to_string(),
+                        datatype: None,
+                        language: Some("en".to_string()),
+                    }),
+                    Some(Term::BlankNode("b0".to_string())),
+                ],
+                vec![
+                    Some(Term::Iri("http://example.com/b".to_string())),
+                    Some(Term::Literal {
+                        value: "42".to_string(),
+                        datatype: Some(format!("{XSD}integer")),
+                        language: None,
+                    }),
+                    None,
+                ],
+            ],
+        })
+    }
+
+    fn round_trip(results: &QueryResults, format: ResultFormat) -> QueryResults {
+        let mut buffer = Vec::new();
+        write(results, format, &mut buffer).unwrap();
+        read(&String::from_utf8(buffer).unwrap(), format).unwrap()
+    }
+
+    #[test]
+    fn json_xml_tsv_round_trip() {
+        for format in [ResultFormat::Json, ResultFormat::Xml, ResultFormat::Tsv] {
+            assert_eq!(round_trip(&solutions(), format), solutions(), "{format}");
+            for value in [true, false] {
+                let boolean = QueryResults::Boolean(value);
+                assert_eq!(round_trip(&boolean, format), boolean, "{format}");
+            }
+        }
+    }
+
+    #[test]
+    fn csv_round_trip_keeps_values() {
+        let QueryResults::Solutions(read) = round_trip(&solutions(), ResultFormat::Csv) else {
+            panic!("expected solutions");
+        };
+        assert_eq!(read.variables, vec!["s", "label", "b"]);
+        assert_eq!(
+            read.rows[0][0],
+            Some(Term::Iri("http://example.com/a".to_string()))
+        );
+        assert_eq!(
+            read.rows[0][1],
+            Some(Term::literal("say \"hi\", <twice>\nplease"))
+        );
+        assert_eq!(read.rows[0][2], Some(Term::BlankNode("b0".to_string())));
+        assert_eq!(read.rows[1][2], None);
+
+        let boolean = QueryResults::Boolean(true);
+        assert_eq!(round_trip(&boolean, ResultFormat::Csv), boolean);
+    }
+
+    #[test]
+    fn reads_other_engines_tsv_shorthand() {
+        let text = "?x\t?n\n<http://example.com/a>\t12\n<http://example.com/b>\t1.5e3\n";
+        let QueryResults::Solutions(read) = read_tsv(text).unwrap() else {
+            panic!("expected solutions");
+        };
+        assert_eq!(
+            read.rows[0][1],
+            Some(Term::Literal {
+                value: "12".to_string(),
+                datatype: Some(format!("{XSD}integer")),
+                language: None,
+            })
+        );
+        assert_eq!(read.rows[1][1].as_ref().map(Term::value), Some("1.5e3"));
+    }
+
+    #[test]
+    fn media_types() {
+        assert_eq!(
+            ResultFormat::from_media_type("application/sparql-results+json; charset=utf-8"),
+            Some(ResultFormat::Json)
+        );
+        assert_eq!("tsv".parse::<ResultFormat>().unwrap(), ResultFormat::Tsv);
+        assert!("yaml".parse::<ResultFormat>().is_err());
+    }
+
+    #[test]
+    fn negotiation() {
+        assert_eq!(ResultFormat::negotiate(None), Some(ResultFormat::Json));
+        assert_eq!(
+            ResultFormat::negotiate(Some(
+                "text/html, text/csv;q=0.5, application/sparql-results+xml;q=0.9"
+            )),
+            Some(ResultFormat::Xml)
+        );
+        assert_eq!(
+            ResultFormat::negotiate(Some("text/html, */*;q=0.1")),
+            Some(ResultFormat::Json)
+        );
+        assert_eq!(ResultFormat::negotiate(Some("text/html")), None);
+        assert_eq!(
+            ResultFormat::negotiate(Some("text/csv;q=0, text/tab-separated-values")),
+            Some(ResultFormat::Tsv)
+        );
+    }
+}
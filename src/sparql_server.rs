@@ -0,0 +1,377 @@
+/// a local SPARQL 1.1 Protocol endpoint over the store
+///
+/// * `/sparql` answers queries via GET or POST and updates via POST, see
+///   <https://www.w3.org/TR/sparql11-protocol/>
+/// * `/graphs?default` and `/graphs?graph=<iri>` read and replace graphs
+///   with Turtle or N-Triples, see
+///   <https://www.w3.org/TR/sparql11-http-rdf-update/>
+///
+/// Queries see the union of all graphs as their default graph.
+///
+use crate::data::{RdfName, Subject, TriplesError};
+use crate::db_api::DbApi;
+use crate::ntriples;
+use crate::sparql_data::GraphTarget;
+use crate::sparql_results::{self, ResultFormat};
+use crate::sparql_sql;
+use crate::ttl_file;
+use crate::turtle_stream::TurtleStream;
+use crate::turtle_writer::{self, TurtleOptions, TurtleWriter};
+use axum::body::Bytes;
+use axum::extract::{RawQuery, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use sqlx::{Sqlite, Transaction};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing::{debug, info, trace};
+
+const FORM: &str = "application/x-www-form-urlencoded";
+const SPARQL_QUERY: &str = "application/sparql-query";
+const SPARQL_UPDATE: &str = "application/sparql-update";
+const TURTLE: &str = "text/turtle";
+const N_TRIPLES: &str = "application/n-triples";
+
+/// the routes of the endpoint, sharing one `DbApi` pool
+pub fn router(db_api: Arc<DbApi>) -> Router {
+    Router::new()
+        .route("/sparql", get(query_get).post(sparql_post))
+        .route(
+            "/graphs",
+            get(graph_get)
+                .put(graph_put)
+                .post(graph_post)
+                .delete(graph_delete),
+        )
+        .with_state(db_api)
+}
+
+/// listen on `bind` until ctrl-c or SIGTERM, letting in flight requests
+/// finish before returning
+///
+/// # Errors
+///
+/// return `Err` if the address can not be bound or the server fails
+pub async fn serve(bind: &str, db_api: DbApi) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(bind).await?;
+    info!("serving SPARQL on http://{}/sparql", listener.local_addr()?);
+
+    axum::serve(listener, router(Arc::new(db_api)))
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    info!("server stopped");
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+    debug!("shutdown signal received");
+}
+
+/// an error status and the plain text message sent with it
+type Rejection = (StatusCode, String);
+
+fn bad_request(message: impl ToString) -> Rejection {
+    (StatusCode::BAD_REQUEST, message.to_string())
+}
+
+fn internal_error(message: impl ToString) -> Rejection {
+    (StatusCode::INTERNAL_SERVER_ERROR, message.to_string())
+}
+
+/// a query the store can not translate is the client's error, a failure
+/// of the store itself the server's
+fn query_error(error: Box<dyn std::error::Error>) -> Rejection {
+    if error.downcast_ref::<TriplesError>().is_some() {
+        bad_request(error)
+    } else {
+        internal_error(error)
+    }
+}
+
+fn param(query: Option<&str>, name: &str) -> Option<String> {
+    form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+fn has_param(query: Option<&str>, name: &str) -> bool {
+    form_urlencoded::parse(query.unwrap_or_default().as_bytes()).any(|(key, _)| key == name)
+}
+
+/// the media type of a request without its parameters
+fn content_type(headers: &HeaderMap) -> String {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn unsupported_media_type(media_type: &str) -> Rejection {
+    (
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        format!("unsupported content type {media_type}"),
+    )
+}
+
+async fn run_query(db_api: &DbApi, text: &str, headers: &HeaderMap) -> Result<Response, Rejection> {
+    trace!("run_query");
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let format = ResultFormat::negotiate(accept).ok_or_else(|| {
+        (
+            StatusCode::NOT_ACCEPTABLE,
+            "no supported result format".to_string(),
+        )
+    })?;
+
+    let form = sparql_sql::parse_form(text).map_err(bad_request)?;
+    let results = db_api.evaluate(&form).await.map_err(query_error)?;
+
+    let mut body = Vec::new();
+    sparql_results::write(&results, format, &mut body).map_err(internal_error)?;
+    Ok(([(header::CONTENT_TYPE, format.media_type())], body).into_response())
+}
+
+async fn run_update(db_api: &DbApi, text: &str) -> Result<Response, Rejection> {
+    trace!("run_update");
+    let operations = sparql_sql::parse_update(text).map_err(bad_request)?;
+    db_api.update(&operations).await.map_err(query_error)?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn query_get(
+    State(db_api): State<Arc<DbApi>>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Result<Response, Rejection> {
+    let text =
+        param(query.as_deref(), "query").ok_or_else(|| bad_request("missing query parameter"))?;
+    run_query(&db_api, &text, &headers).await
+}
+
+async fn sparql_post(
+    State(db_api): State<Arc<DbApi>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, Rejection> {
+    let body = std::str::from_utf8(&body).map_err(|_| bad_request("request body is not utf-8"))?;
+
+    match content_type(&headers).as_str() {
+        FORM => {
+            if let Some(text) = param(Some(body), "query") {
+                run_query(&db_api, &text, &headers).await
+            } else if let Some(text) = param(Some(body), "update") {
+                run_update(&db_api, &text).await
+            } else {
+                Err(bad_request("missing query or update parameter"))
+            }
+        }
+        SPARQL_QUERY => run_query(&db_api, body, &headers).await,
+        SPARQL_UPDATE => run_update(&db_api, body).await,
+        other => Err(unsupported_media_type(other)),
+    }
+}
+
+/// the graph named by `?graph=<iri>`, `None` for `?default`
+fn graph_param(query: Option<&str>) -> Result<Option<RdfName>, Rejection> {
+    if let Some(graph) = param(query, "graph") {
+        Ok(Some(RdfName::new(graph)))
+    } else if has_param(query, "default") {
+        Ok(None)
+    } else {
+        Err(bad_request("expected a graph or default parameter"))
+    }
+}
+
+fn graph_target(graph: Option<&RdfName>) -> GraphTarget {
+    graph.map_or(GraphTarget::Default, |g| GraphTarget::Named(g.to_string()))
+}
+
+fn no_such_graph() -> Rejection {
+    (StatusCode::NOT_FOUND, "no such graph".to_string())
+}
+
+/// whether a graph is sent as Turtle rather than N-Triples, picked from
+/// an HTTP `Accept` header, N-Triples when there is no header or any type
+/// is acceptable
+fn negotiate_graph(accept: Option<&str>) -> Option<bool> {
+    let Some(accept) = accept.filter(|a| !a.trim().is_empty()) else {
+        return Some(false);
+    };
+
+    sparql_results::media_ranges(accept)
+        .into_iter()
+        .find_map(|media_type| match media_type {
+            TURTLE | "text/*" => Some(true),
+            N_TRIPLES | "application/*" | "*/*" => Some(false),
+            _ => None,
+        })
+}
+
+async fn turtle(db_api: &DbApi, subjects: &[Subject]) -> Result<String, Rejection> {
+    let preferred = db_api.preferred_prefixes().await.map_err(internal_error)?;
+    let prefixes = ttl_file::compute_prefixes(subjects, &preferred).map_err(internal_error)?;
+
+    // the default layout is one `parse_graph` reads back, so a fetched
+    // graph can be PUT unchanged
+    let mut body = Vec::new();
+    TurtleWriter::new(subjects, &prefixes, TurtleOptions::default())
+        .write(&mut body)
+        .map_err(internal_error)?;
+    String::from_utf8(body).map_err(internal_error)
+}
+
+async fn graph_get(
+    State(db_api): State<Arc<DbApi>>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Result<Response, Rejection> {
+    let graph = graph_param(query.as_deref())?;
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let as_turtle = negotiate_graph(accept).ok_or_else(|| {
+        (
+            StatusCode::NOT_ACCEPTABLE,
+            "no supported graph format".to_string(),
+        )
+    })?;
+
+    let subjects = db_api
+        .graph_subjects(graph.as_ref())
+        .await
+        .map_err(internal_error)?;
+    if subjects.is_empty() && graph.is_some() {
+        return Err(no_such_graph());
+    }
+
+    if as_turtle {
+        let body = turtle(&db_api, &subjects).await?;
+        Ok(([(header::CONTENT_TYPE, TURTLE)], body).into_response())
+    } else {
+        let body: String = subjects.iter().map(turtle_writer::ntriples).collect();
+        Ok(([(header::CONTENT_TYPE, N_TRIPLES)], body).into_response())
+    }
+}
+
+/// parse a Turtle or N-Triples request body before touching the store, so
+/// a bad `PUT` leaves the graph as it was
+fn parse_graph(headers: &HeaderMap, body: &Bytes) -> Result<Vec<Subject>, Rejection> {
+    let media_type = content_type(headers);
+    if media_type != TURTLE && media_type != N_TRIPLES {
+        return Err(unsupported_media_type(&media_type));
+    }
+    let text = std::str::from_utf8(body).map_err(|_| bad_request("request body is not utf-8"))?;
+
+    let mut subjects = Vec::new();
+    if media_type == N_TRIPLES {
+        for line in text.lines() {
+            if let Some(subject) = ntriples::parse_line(line).map_err(bad_request)? {
+                subjects.push(subject);
+            }
+        }
+        return Ok(subjects);
+    }
+    let mut stream = TurtleStream::new();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        if let Some(subject) = stream.load(line).map_err(bad_request)? {
+            subjects.push(subject);
+        }
+    }
+    Ok(subjects)
+}
+
+async fn insert_graph(
+    db_api: &DbApi,
+    tx: &mut Transaction<'_, Sqlite>,
+    graph: Option<&RdfName>,
+    subjects: &[Subject],
+) -> Result<(), Rejection> {
+    for subject in subjects {
+        db_api
+            .insert_into_txn(tx, subject, graph)
+            .await
+            .map_err(internal_error)?;
+    }
+    Ok(())
+}
+
+/// replace the graph in one transaction, so a failed `PUT` leaves the
+/// graph as it was too
+async fn graph_put(
+    State(db_api): State<Arc<DbApi>>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, Rejection> {
+    let graph = graph_param(query.as_deref())?;
+    let subjects = parse_graph(&headers, &body)?;
+
+    let mut tx = db_api.begin_txn().await.map_err(internal_error)?;
+    let cleared = db_api
+        .clear_txn(&mut tx, &graph_target(graph.as_ref()))
+        .await
+        .map_err(internal_error)?;
+    insert_graph(&db_api, &mut tx, graph.as_ref(), &subjects).await?;
+    tx.commit().await.map_err(internal_error)?;
+
+    if cleared == 0 {
+        Ok(StatusCode::CREATED)
+    } else {
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+async fn graph_post(
+    State(db_api): State<Arc<DbApi>>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, Rejection> {
+    let graph = graph_param(query.as_deref())?;
+    let subjects = parse_graph(&headers, &body)?;
+
+    let mut tx = db_api.begin_txn().await.map_err(internal_error)?;
+    insert_graph(&db_api, &mut tx, graph.as_ref(), &subjects).await?;
+    tx.commit().await.map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn graph_delete(
+    State(db_api): State<Arc<DbApi>>,
+    RawQuery(query): RawQuery,
+) -> Result<StatusCode, Rejection> {
+    let graph = graph_param(query.as_deref())?;
+    let cleared = db_api
+        .clear(&graph_target(graph.as_ref()))
+        .await
+        .map_err(internal_error)?;
+    if cleared == 0 && graph.is_some() {
+        return Err(no_such_graph());
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,560 @@
+/// Translation of parsed SPARQL queries into SQL over the `names`,
+/// `objects` and `triples` tables.
+///
+/// Every triple pattern becomes a derived table with text columns `s` and
+/// `o` (and `p` when the predicate is a variable) so that IRIs stored in
+/// `names` join naturally with IRI valued objects stored in `objects`.
+/// Recursive property paths compile to `WITH RECURSIVE` common table
+/// expressions, which both `SQLite` and Postgres evaluate natively.
+///
+use crate::data::TriplesError;
+use crate::search;
+use crate::sparql::{FormParser, QueryParser, UpdateParser};
+use crate::sparql_data::{
+    Filter, NegatedIri, PropertyPath, QueryForm, SparqlQuery, TriplePattern, UpdateOperation,
+    Variable,
+};
+use crate::sparql_plan::Plan;
+use std::collections::HashMap;
+
+const EDGES: &str = "SELECT subjects.name AS s, objects.object AS o \
+    FROM triples \
+    JOIN names AS subjects ON triples.subject = subjects.id \
+    JOIN names AS predicates ON triples.predicate = predicates.id \
+    JOIN objects ON triples.object = objects.id";
+
+const EDGES_WITH_PREDICATE: &str =
+    "SELECT subjects.name AS s, predicates.name AS p, objects.object AS o \
+    FROM triples \
+    JOIN names AS subjects ON triples.subject = subjects.id \
+    JOIN names AS predicates ON triples.predicate = predicates.id \
+    JOIN objects ON triples.object = objects.id";
+
+const NODES: &str = "SELECT name AS s, name AS o FROM names \
+    WHERE id IN (SELECT subject FROM triples) \
+    UNION SELECT object AS s, object AS o FROM objects";
+
+/// A SQL statement ready to be bound and executed.  `params` are bound to
+/// the positional `?` placeholders in order and each result column
+/// corresponds to the variable at the same index in `variables`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SqlQuery {
+    pub sql: String,
+    pub params: Vec<String>,
+    pub variables: Vec<String>,
+}
+
+/// parse SPARQL query text
+///
+/// # Errors
+///
+/// return `Err` if the text is not a supported SPARQL query
+pub fn parse(text: &str) -> Result<SparqlQuery, TriplesError> {
+    QueryParser::new()
+        .parse(text)
+        .map_err(|e| TriplesError::ParseError {
+            reason: e.to_string(),
+        })
+}
+
+/// parse SPARQL query text of any supported form
+///
+/// # Errors
+///
+/// return `Err` if the text is not a supported SPARQL query
+pub fn parse_form(text: &str) -> Result<QueryForm, TriplesError> {
+    FormParser::new()
+        .parse(text)
+        .map_err(|e| TriplesError::ParseError {
+            reason: e.to_string(),
+        })
+}
+
+/// parse the text of a SPARQL 1.1 Update request
+///
+/// # Errors
+///
+/// return `Err` if the text is not a supported update
+pub fn parse_update(text: &str) -> Result<Vec<UpdateOperation>, TriplesError> {
+    UpdateParser::new()
+        .parse(text)
+        .map_err(|e| TriplesError::ParseError {
+            reason: e.to_string(),
+        })
+}
+
+/// translate a parsed query into one SQL statement, joining the triple
+/// patterns in the order they were written
+///
+/// # Errors
+///
+/// return `Err` if the query uses a construct that can not be translated
+pub fn compile(query: &SparqlQuery) -> Result<SqlQuery, TriplesError> {
+    compile_plan(query, &Plan::naive(query))
+}
+
+/// translate a parsed query into one SQL statement, joining the triple
+/// patterns in plan order.  `CROSS JOIN` keeps `SQLite` from reordering
+/// them again.
+///
+/// # Errors
+///
+/// return `Err` if the query uses a construct that can not be translated
+pub fn compile_plan(query: &SparqlQuery, plan: &Plan) -> Result<SqlQuery, TriplesError> {
+    let mut compiler = Compiler::default();
+
+    let mut from = Vec::new();
+    let mut from_params = Vec::new();
+    let mut conditions = Vec::new();
+    let mut where_params = Vec::new();
+    let mut bindings: HashMap<String, String> = HashMap::new();
+
+    for (position, step) in plan.steps.iter().enumerate() {
+        let pattern =
+            query
+                .triples_block
+                .get(step.pattern)
+                .ok_or_else(|| TriplesError::ParseError {
+                    reason: format!("plan refers to missing pattern {}", step.pattern),
+                })?;
+        let alias = format!("t{position}");
+        let (sql, anchored) = compiler.pattern(pattern)?;
+        from.push(format!("({sql}) AS {alias}"));
+        from_params.append(&mut compiler.params);
+
+        let predicate = match &pattern.predicate {
+            PropertyPath::Var(name) => Some(Variable::Var(name.clone())),
+            _ => None,
+        };
+        let positions = [
+            (Some(&pattern.subject), format!("{alias}.s")),
+            (predicate.as_ref(), format!("{alias}.p")),
+            (Some(&pattern.object), format!("{alias}.o")),
+        ];
+
+        for (term, column) in positions {
+            match term {
+                Some(Variable::Var(name)) => {
+                    bind_variable(name, column, &mut bindings, &mut conditions);
+                }
+                Some(Variable::IRI(value) | Variable::Literal(value)) if !anchored => {
+                    conditions.push(format!("{column} = ?"));
+                    where_params.push(value.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for filter in &query.filters {
+        match filter {
+            Filter::TextMatch { variable, text } => {
+                let column = bindings
+                    .get(variable)
+                    .ok_or_else(|| TriplesError::ParseError {
+                        reason: format!("FILTER variable ?{variable} is not in a triple pattern"),
+                    })?;
+                conditions.push(format!(
+                    "{column} IN (SELECT objects.object FROM objects_fts \
+                    JOIN objects ON objects.id = objects_fts.rowid WHERE objects_fts MATCH ?)"
+                ));
+                where_params.push(search::match_expression(text)?);
+            }
+        }
+    }
+
+    let variables: Vec<String> = if query.select_clause.variables.is_empty() {
+        pattern_variables(query)
+    } else {
+        query
+            .select_clause
+            .variables
+            .iter()
+            .map(|v| match v {
+                Variable::Var(name) => Ok(name.clone()),
+                Variable::IRI(value) | Variable::Literal(value) => Err(TriplesError::ParseError {
+                    reason: format!("can not project constant {value}"),
+                }),
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    let columns = if variables.is_empty() {
+        "1".to_string()
+    } else {
+        variables
+            .iter()
+            .map(|name| {
+                let column = bindings.get(name).map_or("NULL", String::as_str);
+                format!("{column} AS \"{name}\"")
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut sql = format!(
+        "SELECT {}{columns} FROM {}",
+        if query.select_clause.distinct {
+            "DISTINCT "
+        } else {
+            ""
+        },
+        from.join(" CROSS JOIN ")
+    );
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+
+    from_params.append(&mut where_params);
+
+    Ok(SqlQuery {
+        sql,
+        params: from_params,
+        variables,
+    })
+}
+
+/// the variables of the query in the order they are first written
+fn pattern_variables(query: &SparqlQuery) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    for pattern in &query.triples_block {
+        let predicate = match &pattern.predicate {
+            PropertyPath::Var(name) => Some(name),
+            _ => None,
+        };
+        let names = [&pattern.subject, &pattern.object]
+            .into_iter()
+            .filter_map(|v| match v {
+                Variable::Var(name) => Some(name),
+                Variable::IRI(_) | Variable::Literal(_) => None,
+            });
+        let mut names: Vec<&String> = names.collect();
+        if let Some(predicate) = predicate {
+            names.insert(1, predicate);
+        }
+        for name in names {
+            if !variables.contains(name) {
+                variables.push(name.clone());
+            }
+        }
+    }
+    variables
+}
+
+fn bind_variable(
+    name: &str,
+    column: String,
+    bindings: &mut HashMap<String, String>,
+    conditions: &mut Vec<String>,
+) {
+    if let Some(bound) = bindings.get(name) {
+        conditions.push(format!("{column} = {bound}"));
+    } else {
+        bindings.insert(name.to_string(), column);
+    }
+}
+
+const fn constant(term: &Variable) -> Option<&String> {
+    match term {
+        Variable::IRI(value) | Variable::Literal(value) => Some(value),
+        Variable::Var(_) => None,
+    }
+}
+
+#[derive(Default)]
+struct Compiler {
+    params: Vec<String>,
+    aliases: usize,
+}
+
+impl Compiler {
+    fn next_alias(&mut self, stem: &str) -> String {
+        self.aliases += 1;
+        format!("{stem}{}", self.aliases)
+    }
+
+    /// returns the SELECT for one triple pattern and whether its constant
+    /// subject and object were pushed down into it
+    fn pattern(&mut self, pattern: &TriplePattern) -> Result<(String, bool), TriplesError> {
+        let subject = constant(&pattern.subject);
+        let object = constant(&pattern.object);
+        match &pattern.predicate {
+            PropertyPath::Var(_) => Ok((
+                self.edges(EDGES_WITH_PREDICATE, None, subject, object),
+                true,
+            )),
+            PropertyPath::Iri(iri) => Ok((self.edges(EDGES, Some(iri), subject, object), true)),
+            PropertyPath::OneOrMore(inner) | PropertyPath::ZeroOrMore(inner)
+                if subject.is_some() || object.is_some() =>
+            {
+                let zero_length = matches!(pattern.predicate, PropertyPath::ZeroOrMore(_));
+                Ok((
+                    self.anchored_closure(inner, subject, object, zero_length)?,
+                    true,
+                ))
+            }
+            path => Ok((self.path(path)?, false)),
+        }
+    }
+
+    fn edges(
+        &mut self,
+        select: &str,
+        predicate: Option<&String>,
+        subject: Option<&String>,
+        object: Option<&String>,
+    ) -> String {
+        let mut conditions = Vec::new();
+        for (column, value) in [
+            ("predicates.name", predicate),
+            ("subjects.name", subject),
+            ("objects.object", object),
+        ] {
+            if let Some(value) = value {
+                conditions.push(format!("{column} = ?"));
+                self.params.push(value.clone());
+            }
+        }
+        if conditions.is_empty() {
+            select.to_string()
+        } else {
+            format!("{select} WHERE {}", conditions.join(" AND "))
+        }
+    }
+
+    /// returns a SELECT producing the `s` and `o` columns of every pair of
+    /// nodes connected by `path`
+    fn path(&mut self, path: &PropertyPath) -> Result<String, TriplesError> {
+        match path {
+            PropertyPath::Iri(iri) => {
+                self.params.push(iri.clone());
+                Ok(format!("{EDGES} WHERE predicates.name = ?"))
+            }
+            PropertyPath::Var(name) => Err(TriplesError::ParseError {
+                reason: format!("variable ?{name} can not be used inside a property path"),
+            }),
+            PropertyPath::Inverse(inner) => {
+                let inner = self.path(inner)?;
+                let alias = self.next_alias("inv");
+                Ok(format!("SELECT o AS s, s AS o FROM ({inner}) AS {alias}"))
+            }
+            PropertyPath::Sequence(steps) => {
+                let mut steps = steps.iter();
+                let first = steps.next().ok_or_else(|| TriplesError::ParseError {
+                    reason: "empty path sequence".to_string(),
+                })?;
+                let mut sql = self.path(first)?;
+                for step in steps {
+                    let next = self.path(step)?;
+                    let left = self.next_alias("seq");
+                    let right = self.next_alias("seq");
+                    sql = format!(
+                        "SELECT {left}.s AS s, {right}.o AS o FROM ({sql}) AS {left} \
+                         JOIN ({next}) AS {right} ON {left}.o = {right}.s"
+                    );
+                }
+                Ok(sql)
+            }
+            PropertyPath::Alternative(alternatives) => {
+                let mut parts = Vec::new();
+                for alternative in alternatives {
+                    let sql = self.path(alternative)?;
+                    let alias = self.next_alias("alt");
+                    parts.push(format!("SELECT s, o FROM ({sql}) AS {alias}"));
+                }
+                Ok(parts.join(" UNION "))
+            }
+            PropertyPath::OneOrMore(inner) => self.closure(inner),
+            PropertyPath::ZeroOrMore(inner) => {
+                let closure = self.closure(inner)?;
+                let alias = self.next_alias("star");
+                Ok(format!(
+                    "SELECT s, o FROM ({closure}) AS {alias} UNION {NODES}"
+                ))
+            }
+            PropertyPath::ZeroOrOne(inner) => {
+                let inner = self.path(inner)?;
+                let alias = self.next_alias("opt");
+                Ok(format!(
+                    "SELECT s, o FROM ({inner}) AS {alias} UNION {NODES}"
+                ))
+            }
+            PropertyPath::NegatedSet(set) => Ok(self.negated_set(set)),
+        }
+    }
+
+    /// transitive closure of `inner`, UNION (not UNION ALL) keeps cycles
+    /// from recursing forever
+    fn closure(&mut self, inner: &PropertyPath) -> Result<String, TriplesError> {
+        let inner = self.path(inner)?;
+        let step = self.next_alias("step");
+        let reach = self.next_alias("reach");
+        Ok(format!(
+            "WITH RECURSIVE {step}(s, o) AS ({inner}), \
+             {reach}(s, o) AS (SELECT s, o FROM {step} \
+             UNION SELECT {reach}.s, {step}.o FROM {reach} JOIN {step} ON {step}.s = {reach}.o) \
+             SELECT s, o FROM {reach}"
+        ))
+    }
+
+    /// transitive closure of `inner` grown only from a known subject, or
+    /// backwards from a known object, instead of from every node
+    fn anchored_closure(
+        &mut self,
+        inner: &PropertyPath,
+        subject: Option<&String>,
+        object: Option<&String>,
+        zero_length: bool,
+    ) -> Result<String, TriplesError> {
+        let inner = self.path(inner)?;
+        let step = self.next_alias("step");
+        let reach = self.next_alias("reach");
+        let mut sql = if let Some(subject) = subject {
+            self.params.push(subject.clone());
+            format!(
+                "WITH RECURSIVE {step}(s, o) AS ({inner}), \
+                 {reach}(s, o) AS (SELECT s, o FROM {step} WHERE s = ? \
+                 UNION SELECT {reach}.s, {step}.o FROM {reach} JOIN {step} ON {step}.s = {reach}.o) \
+                 SELECT s, o FROM {reach}"
+            )
+        } else {
+            self.params.extend(object.cloned());
+            format!(
+                "WITH RECURSIVE {step}(s, o) AS ({inner}), \
+                 {reach}(s, o) AS (SELECT s, o FROM {step} WHERE o = ? \
+                 UNION SELECT {step}.s, {reach}.o FROM {reach} JOIN {step} ON {step}.o = {reach}.s) \
+                 SELECT s, o FROM {reach}"
+            )
+        };
+        if zero_length {
+            let anchor = subject.or(object).cloned().unwrap_or_default();
+            self.params.push(anchor.clone());
+            self.params.push(anchor);
+            let alias = self.next_alias("star");
+            sql = format!("SELECT s, o FROM ({sql}) AS {alias} UNION SELECT ? AS s, ? AS o");
+        }
+        if let (Some(_), Some(object)) = (subject, object) {
+            self.params.push(object.clone());
+            let alias = self.next_alias("anchored");
+            sql = format!("SELECT s, o FROM ({sql}) AS {alias} WHERE o = ?");
+        }
+        Ok(sql)
+    }
+
+    fn negated_set(&mut self, set: &[NegatedIri]) -> String {
+        let forward: Vec<&String> = set
+            .iter()
+            .filter_map(|i| match i {
+                NegatedIri::Forward(iri) => Some(iri),
+                NegatedIri::Inverse(_) => None,
+            })
+            .collect();
+        let inverse: Vec<&String> = set
+            .iter()
+            .filter_map(|i| match i {
+                NegatedIri::Inverse(iri) => Some(iri),
+                NegatedIri::Forward(_) => None,
+            })
+            .collect();
+
+        let mut parts = Vec::new();
+        if !forward.is_empty() {
+            let placeholders = vec!["?"; forward.len()].join(", ");
+            self.params.extend(forward.into_iter().cloned());
+            parts.push(format!(
+                "{EDGES} WHERE predicates.name NOT IN ({placeholders})"
+            ));
+        }
+        if !inverse.is_empty() {
+            let placeholders = vec!["?"; inverse.len()].join(", ");
+            self.params.extend(inverse.into_iter().cloned());
+            let alias = self.next_alias("inv");
+            parts.push(format!(
+                "SELECT o AS s, s AS o FROM ({EDGES} WHERE predicates.name NOT IN ({placeholders})) AS {alias}"
+            ));
+        }
+        parts.join(" UNION ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compile_simple_pattern() {
+        let query = parse("SELECT ?s WHERE { ?s <http://example.com/p> \"x\" }").unwrap();
+        let sql = compile(&query).unwrap();
+        assert_eq!(sql.variables, vec!["s".to_string()]);
+        assert_eq!(
+            sql.params,
+            vec!["http://example.com/p".to_string(), "x".to_string()]
+        );
+        assert!(sql
+            .sql
+            .contains("WHERE predicates.name = ? AND objects.object = ?"));
+    }
+
+    #[test]
+    fn compile_shared_variable_joins() {
+        let query =
+            parse("SELECT * WHERE { ?s <http://example.com/p> ?o . ?o <http://example.com/q> ?x }")
+                .unwrap();
+        let sql = compile(&query).unwrap();
+        assert_eq!(sql.variables, vec!["s", "o", "x"]);
+        assert!(sql.sql.contains("t1.s = t0.o"));
+    }
+
+    #[test]
+    fn compile_recursive_path() {
+        let query =
+            parse("SELECT ?x WHERE { <http://example.com/a> <http://example.com/p>+ ?x }").unwrap();
+        let sql = compile(&query).unwrap();
+        assert!(sql.sql.contains("WITH RECURSIVE"));
+        assert_eq!(sql.params.len(), 2);
+    }
+
+    #[test]
+    fn compile_text_match_filter() {
+        let query = parse(
+            "SELECT ?s WHERE { ?s <http://example.com/p> ?o \
+            FILTER(<https://triples.navicore.tech/function#textMatch>(?o, \"live thread\")) }",
+        )
+        .unwrap();
+        let sql = compile(&query).unwrap();
+        assert!(sql
+            .sql
+            .contains("t0.o IN (SELECT objects.object FROM objects_fts"));
+        assert_eq!(sql.params.last().unwrap(), "\"live\" \"thread\"");
+
+        let unbound = parse(
+            "SELECT ?s WHERE { ?s <http://example.com/p> ?o \
+            FILTER <https://triples.navicore.tech/function#textMatch>(?x, \"live\") }",
+        )
+        .unwrap();
+        assert!(compile(&unbound).is_err());
+        assert!(parse(
+            "SELECT ?s WHERE { ?s <http://example.com/p> ?o \
+            FILTER(<http://example.com/unknown>(?o, \"live\")) }"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn variable_inside_path_is_rejected() {
+        let query = SparqlQuery {
+            select_clause: crate::sparql_data::SelectClause {
+                distinct: false,
+                variables: vec![],
+            },
+            triples_block: vec![TriplePattern {
+                subject: Variable::Var("s".to_string()),
+                predicate: PropertyPath::OneOrMore(Box::new(PropertyPath::Var("p".to_string()))),
+                object: Variable::Var("o".to_string()),
+            }],
+            filters: vec![],
+        };
+        assert!(compile(&query).is_err());
+    }
+}
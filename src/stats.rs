@@ -0,0 +1,195 @@
+/// counts of what a store holds, cheap enough to take of a large db
+///
+/// Every count comes from an aggregate query rather than listing names.
+/// Triples are counted as stored, so a triple asserted in two graphs
+/// counts in both, and the sizes of tables and indexes are the pages
+/// SQLite has allocated to them.
+///
+/// The statistics render as a text report or as a VoID description, with
+/// a `void:subset` per graph and a `void:classPartition` per class.
+///
+use crate::data::{RdfName, Subject};
+use crate::prefixes::PrefixMap;
+use crate::profile::{class_partitions, void, void_dataset};
+use crate::report::{display, table};
+
+pub const SD: &str = "http://www.w3.org/ns/sparql-service-description#";
+
+/// The counts of the triples of one graph, `None` for the default graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphStats {
+    pub graph: Option<String>,
+    pub triples: u64,
+    pub subjects: u64,
+    pub predicates: u64,
+    pub objects: u64,
+}
+
+/// The bytes SQLite has allocated to one table or index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    pub name: String,
+    /// `table` or `index`
+    pub kind: String,
+    pub bytes: u64,
+}
+
+/// The counts and sizes of a store.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub triples: u64,
+    pub subjects: u64,
+    pub predicates: u64,
+    pub objects: u64,
+    /// the default graph first, then named graphs by name
+    pub graphs: Vec<GraphStats>,
+    /// each class with its instances, by class name
+    pub classes: Vec<(String, u64)>,
+    pub file_size: u64,
+    /// tables and indexes, largest first, empty when SQLite can not tell
+    pub storage: Vec<StorageStats>,
+}
+
+/// a byte count in binary units, the largest that keeps at least ten of
+/// them
+#[must_use]
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes;
+    let mut unit = 0;
+    while size >= 1024 * 10 && unit + 1 < UNITS.len() {
+        size /= 1024;
+        unit += 1;
+    }
+    format!("{size} {}", UNITS[unit])
+}
+
+/// describe `dataset` with its triples, distinct
+/// subjects, properties and distinct objects
+fn describe(dataset: &mut Subject, counts: [u64; 4]) {
+    for (local, count) in [
+        "triples",
+        "distinctSubjects",
+        "properties",
+        "distinctObjects",
+    ]
+    .iter()
+    .zip(counts)
+    {
+        dataset.add(void(local), count.to_string());
+    }
+}
+
+impl Stats {
+    /// the statistics as text tables, names compacted by `prefixes`
+    #[must_use]
+    pub fn report(&self, prefixes: &PrefixMap) -> String {
+        let mut text = format!(
+            "triples {}, subjects {}, predicates {}, objects {}\nfile {}\n\n",
+            self.triples,
+            self.subjects,
+            self.predicates,
+            self.objects,
+            human_bytes(self.file_size)
+        );
+
+        let rows: Vec<Vec<String>> = self
+            .graphs
+            .iter()
+            .map(|g| {
+                vec![
+                    g.graph
+                        .as_deref()
+                        .map_or_else(|| "(default)".to_string(), |g| display(g, prefixes)),
+                    g.triples.to_string(),
+                    g.subjects.to_string(),
+                    g.predicates.to_string(),
+                    g.objects.to_string(),
+                ]
+            })
+            .collect();
+        text.push_str(&table(
+            &["graph", "triples", "subjects", "predicates", "objects"],
+            &rows,
+            &[false, true, true, true, true],
+        ));
+
+        if !self.classes.is_empty() {
+            let rows: Vec<Vec<String>> = self
+                .classes
+                .iter()
+                .map(|(class, instances)| vec![display(class, prefixes), instances.to_string()])
+                .collect();
+            text.push('\n');
+            text.push_str(&table(&["class", "instances"], &rows, &[false, true]));
+        }
+
+        if !self.storage.is_empty() {
+            let rows: Vec<Vec<String>> = self
+                .storage
+                .iter()
+                .map(|s| vec![s.name.clone(), s.kind.clone(), human_bytes(s.bytes)])
+                .collect();
+            text.push('\n');
+            text.push_str(&table(
+                &["storage", "kind", "size"],
+                &rows,
+                &[false, false, true],
+            ));
+        }
+        text
+    }
+
+    /// a VoID description of the store as `_:dataset`, each graph a
+    /// `void:subset` named by `sd:name` and each class a partition
+    #[must_use]
+    pub fn to_void(&self) -> Vec<Subject> {
+        let mut dataset = void_dataset("_:dataset");
+        describe(
+            &mut dataset,
+            [self.triples, self.subjects, self.predicates, self.objects],
+        );
+        dataset.add(void("classes"), self.classes.len().to_string());
+
+        let mut subjects = Vec::new();
+        for (n, graph) in self.graphs.iter().enumerate() {
+            let id = format!("_:graph{}", n + 1);
+            dataset.add(void("subset"), id.clone());
+            let mut subset = void_dataset(&id);
+            describe(
+                &mut subset,
+                [
+                    graph.triples,
+                    graph.subjects,
+                    graph.predicates,
+                    graph.objects,
+                ],
+            );
+            if let Some(name) = &graph.graph {
+                subset.add(RdfName::new(format!("{SD}name")), name.clone());
+            }
+            subjects.push(subset);
+        }
+        subjects.extend(class_partitions(
+            &mut dataset,
+            self.classes
+                .iter()
+                .map(|(class, instances)| (class.as_str(), *instances)),
+        ));
+        subjects.insert(0, dataset);
+        subjects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_are_human() {
+        assert_eq!(human_bytes(512), "512 B");
+        assert_eq!(human_bytes(4096), "4096 B");
+        assert_eq!(human_bytes(64 * 1024), "64 KiB");
+        assert_eq!(human_bytes(300 * 1024 * 1024), "300 MiB");
+    }
+}
@@ -0,0 +1,53 @@
+/// functions in support of reporting db statistics
+///
+/// The statistics are written as text or as a VoID description in
+/// Turtle.
+///
+use crate::db_api::DbApi;
+use crate::prefixes::PrefixMap;
+use crate::profile::VOID;
+use crate::stats::{Stats, SD};
+use crate::ttl_file;
+use crate::turtle_writer::{TurtleOptions, TurtleWriter};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// the preferred prefixes of the db and those of the report vocabularies
+async fn report_prefixes(db_api: &DbApi) -> Result<PrefixMap, Box<dyn std::error::Error>> {
+    let mut preferred = db_api.preferred_prefixes().await?;
+    preferred.insert("void", VOID);
+    preferred.insert("sd", SD);
+    Ok(preferred)
+}
+
+/// write the statistics as a text report to `out`
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read or `out` written
+pub async fn write_report<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    stats: &Stats,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prefixes = report_prefixes(db_api).await?;
+    out.write_all(stats.report(&prefixes).as_bytes()).await?;
+    Ok(())
+}
+
+/// write the statistics as a VoID description in turtle to `out`
+///
+/// # Errors
+///
+/// return `Err` if the db can not be read or `out` written
+pub async fn write_void<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    stats: &Stats,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let subjects = stats.to_void();
+    let prefixes = ttl_file::compute_prefixes(&subjects, &report_prefixes(db_api).await?)?;
+    TurtleWriter::new(&subjects, &prefixes, TurtleOptions::default())
+        .write_async(out)
+        .await?;
+    Ok(())
+}
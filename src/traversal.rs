@@ -0,0 +1,162 @@
+/// traversal of the graph the stored triples form, the nodes being
+/// subjects and the IRI and blank node objects, the edges the triples
+/// between them
+///
+/// Edges are followed from subject to object, against that direction, or
+/// both ways.  Literal objects are not nodes, so subjects sharing a
+/// literal value are not neighbours.
+///
+use crate::data::TriplesError;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Which way edges are followed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// subject to object
+    #[default]
+    Outgoing,
+    /// object to subject
+    Incoming,
+    Both,
+}
+
+impl Direction {
+    #[must_use]
+    pub const fn outgoing(self) -> bool {
+        matches!(self, Self::Outgoing | Self::Both)
+    }
+
+    #[must_use]
+    pub const fn incoming(self) -> bool {
+        matches!(self, Self::Incoming | Self::Both)
+    }
+}
+
+impl FromStr for Direction {
+    type Err = TriplesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "out" | "outgoing" => Ok(Self::Outgoing),
+            "in" | "incoming" => Ok(Self::Incoming),
+            "both" => Ok(Self::Both),
+            _ => Err(TriplesError::ParseError {
+                reason: format!("unknown direction {s}, expected out, in or both"),
+            }),
+        }
+    }
+}
+
+/// A triple followed in a traversal, `hops` edges from where it started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    /// whether it was followed from subject to object
+    pub forward: bool,
+    pub hops: usize,
+}
+
+impl Step {
+    /// the node the step was taken from
+    #[must_use]
+    pub fn from(&self) -> &str {
+        if self.forward {
+            &self.subject
+        } else {
+            &self.object
+        }
+    }
+
+    /// the node the step reached
+    #[must_use]
+    pub fn to(&self) -> &str {
+        if self.forward {
+            &self.object
+        } else {
+            &self.subject
+        }
+    }
+}
+
+/// N-Triples like, `^` marking a step taken against the edge
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mark = if self.forward { "" } else { "^" };
+        write!(
+            f,
+            "<{}> {mark}<{}> <{}>",
+            self.subject, self.predicate, self.object
+        )
+    }
+}
+
+/// the fewest hops each node is reached in, `start` in none
+fn distances<'a>(start: &'a str, steps: &'a [Step]) -> HashMap<&'a str, usize> {
+    let mut distances = HashMap::from([(start, 0)]);
+    for step in steps {
+        distances
+            .entry(step.to())
+            .and_modify(|hops| *hops = (*hops).min(step.hops))
+            .or_insert(step.hops);
+    }
+    distances
+}
+
+/// a shortest path from `start` to `end` through `steps`, each step taken
+/// the fewest hops it can be from `start`, as `DbApi::neighbourhood` finds
+/// them.  `None` when `end` is not reached
+#[must_use]
+pub fn shortest_path(start: &str, end: &str, steps: &[Step]) -> Option<Vec<Step>> {
+    let distances = distances(start, steps);
+    let mut node = end;
+    let mut hops = *distances.get(end)?;
+    let mut path = Vec::with_capacity(hops);
+    while hops > 0 {
+        let step = steps.iter().find(|step| {
+            step.hops == hops
+                && step.to() == node
+                && distances.get(step.from()) == Some(&(hops - 1))
+        })?;
+        path.push(step.clone());
+        node = step.from();
+        hops -= 1;
+    }
+    path.reverse();
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(subject: &str, object: &str, forward: bool, hops: usize) -> Step {
+        Step {
+            subject: subject.to_string(),
+            predicate: "p".to_string(),
+            object: object.to_string(),
+            forward,
+            hops,
+        }
+    }
+
+    #[test]
+    fn finds_shortest_path() {
+        // a -> b -> c -> d and a -> e <- d
+        let steps = [
+            step("a", "b", true, 1),
+            step("a", "e", true, 1),
+            step("b", "c", true, 2),
+            step("d", "e", false, 2),
+            step("c", "d", true, 3),
+        ];
+        let path = shortest_path("a", "d", &steps).unwrap();
+        let nodes: Vec<&str> = path.iter().map(Step::to).collect();
+        assert_eq!(nodes, ["e", "d"]);
+        assert_eq!(shortest_path("a", "a", &steps), Some(Vec::new()));
+        assert_eq!(shortest_path("a", "z", &steps), None);
+    }
+}
@@ -0,0 +1,72 @@
+/// functions in support of finding paths through the db
+///
+/// A path is written one triple per line from the start, `^` marking a
+/// predicate followed from object to subject.
+///
+use crate::data::{Term, TriplesError};
+use crate::db_api::DbApi;
+use crate::prefixes::PrefixMap;
+use crate::traversal::{Direction, Step};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// how a path may go
+#[derive(Debug, Clone, Default)]
+pub struct PathOptions {
+    pub max_hops: usize,
+    pub direction: Direction,
+    /// predicate IRIs or prefixed names to follow, all when empty
+    pub predicates: Vec<String>,
+}
+
+/// a name as a prefixed name where `prefixes` allow, else in full
+fn compact(name: &str, prefixes: &PrefixMap) -> String {
+    match Term::from_object(name) {
+        Term::Iri(iri) => prefixes.compact(&iri).unwrap_or_else(|| format!("<{iri}>")),
+        _ => name.to_string(),
+    }
+}
+
+fn line(step: &Step, prefixes: &PrefixMap) -> String {
+    let mark = if step.forward { "" } else { "^" };
+    format!(
+        "{} {mark}{} {}\n",
+        compact(&step.subject, prefixes),
+        compact(&step.predicate, prefixes),
+        compact(&step.object, prefixes)
+    )
+}
+
+/// write a shortest path from `from` to `to` to `out`, the names given as
+/// IRIs or prefixed names
+///
+/// # Errors
+///
+/// return `TriplesError::NoPath` if there is no path within
+/// `options.max_hops`, or `Err` if a name can not be expanded or on db
+/// read or write errors
+pub async fn write_path<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    from: &str,
+    to: &str,
+    options: &PathOptions,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prefixes = db_api.preferred_prefixes().await?;
+    let predicates = options
+        .predicates
+        .iter()
+        .map(|p| prefixes.resolve(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (from, to) = (prefixes.resolve(from)?, prefixes.resolve(to)?);
+
+    let Some(path) = db_api
+        .shortest_path(&from, &to, options.max_hops, options.direction, &predicates)
+        .await?
+    else {
+        return Err(TriplesError::NoPath { from, to }.into());
+    };
+    for step in &path {
+        out.write_all(line(step, &prefixes).as_bytes()).await?;
+    }
+    Ok(())
+}
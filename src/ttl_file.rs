@@ -1,32 +1,74 @@
-use crate::data::TriplesError;
-/// functions in support of ttl file handling
+/// functions in support of Turtle and N-Triples file handling
 ///
-/// Prefer to process data via stdin and stdout to enable *nix style
-/// command pipelining.
+/// Turtle is read line by line with `TurtleStream` and N-Triples with its
+/// own reader, each loaded in one transaction.  Exports write the whole db
+/// with the prefixes `compute_prefixes` picks, canonically ordered and
+/// labeled on request.
 ///
-use crate::data::{extract_namespace_and_local_name, RdfName, Subject};
+use crate::data::{is_iri, RdfName, Subject, TriplesError};
 use crate::db_api::DbApi;
+use crate::iri;
+use crate::ntriples;
+use crate::prefixes::PrefixMap;
 use crate::turtle_stream::TurtleStream;
-use std::collections::{HashMap, HashSet};
-use tokio::io::{stdin, AsyncBufReadExt, BufReader}; // assuming you have a Result type alias, adjust as necessary
-use tracing::{error, trace};
-
-/// read ttl from stdin and load db
+use crate::turtle_writer::{self, TurtleOptions, TurtleWriter};
+use crate::vocab::RDF_TYPE;
+use sqlx::{Sqlite, Transaction};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::trace;
+
+/// read ttl from `reader`, stdin for the cli, and load db
 ///
 /// # Errors
 ///
 /// return `Err` if any entry can not be loaded
-pub async fn import_turtle(db_api: &DbApi) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn import_turtle<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
     trace!("import_turtle");
     let mut stream = TurtleStream::new();
-    let stdin = stdin();
-    let mut reader = BufReader::new(stdin);
 
-    let tx = db_api.begin_txn().await?;
+    let mut tx = db_api.begin_txn().await?;
+
+    let mut line = String::new();
+    while reader.read_line(&mut line).await? != 0 {
+        import_line(&line, &mut stream, &mut tx, db_api).await?;
+        line.clear();
+    }
+
+    for (prefix, namespace) in stream.prefixes() {
+        db_api
+            .insert_namespace_txn(&mut tx, &prefix, &namespace)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// read N-Triples from `reader` and load db, one triple per line
+///
+/// Unlike the turtle line grammar this reads every N-Triples term, typed
+/// and language tagged literals are stored by their lexical form.
+///
+/// # Errors
+///
+/// return `Err` if any line can not be parsed or loaded
+pub async fn import_ntriples<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("import_ntriples");
+    let mut tx = db_api.begin_txn().await?;
 
     let mut line = String::new();
     while reader.read_line(&mut line).await? != 0 {
-        import_line(&line, &mut stream, db_api).await?;
+        if let Some(subject) = ntriples::parse_line(&line)? {
+            db_api.insert_into_txn(&mut tx, &subject, None).await?;
+        }
         line.clear();
     }
 
@@ -35,46 +77,224 @@ pub async fn import_turtle(db_api: &DbApi) -> Result<(), Box<dyn std::error::Err
     Ok(())
 }
 
-/// export ttl to stdout of entire db
+/// read ttl from `reader` without loading the db, returning its subjects
+/// and the prefixes it declares
+///
+/// # Errors
+///
+/// return `Err` if the turtle can not be read or parsed
+pub async fn read_turtle<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+) -> Result<(Vec<Subject>, Vec<(String, String)>), Box<dyn std::error::Error>> {
+    trace!("read_turtle");
+    let mut stream = TurtleStream::new();
+    let mut subjects = Vec::new();
+
+    let mut line = String::new();
+    while reader.read_line(&mut line).await? != 0 {
+        if !line.trim().is_empty() {
+            subjects.extend(stream.load(&line)?);
+        }
+        line.clear();
+    }
+
+    Ok((subjects, stream.prefixes()))
+}
+
+/// load subjects and prefixes read by `read_turtle` in one transaction
+///
+/// # Errors
+///
+/// return `Err` if any entry can not be loaded
+pub async fn import_subjects(
+    subjects: &[Subject],
+    prefixes: &[(String, String)],
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = db_api.begin_txn().await?;
+    for subject in subjects {
+        db_api.insert_into_txn(&mut tx, subject, None).await?;
+    }
+    for (prefix, namespace) in prefixes {
+        db_api
+            .insert_namespace_txn(&mut tx, prefix, namespace)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// read the `@prefix` declarations of a turtle file
+///
+/// # Errors
+///
+/// return `Err` if the file can not be read or parsed
+pub fn read_prefixes(path: &str) -> Result<PrefixMap, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path)?;
+    let mut stream = TurtleStream::new();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        stream.load(line)?;
+    }
+
+    let mut prefixes = PrefixMap::new();
+    for (prefix, namespace) in stream.prefixes() {
+        prefixes.insert(&prefix, &namespace);
+    }
+    Ok(prefixes)
+}
+
+/// export ttl of entire db to `out`, stdout for the cli
+///
+/// The layout follows `options`, see `TurtleWriter`.
+///
+/// Namespaces are written with the prefixes in `prefix_file` if given, else
+/// those declared by imported files, else the well-known vocabulary names.
+///
+/// In `canonical` mode prefixes, subjects, predicates (`rdf:type` first) and
+/// objects are sorted and blank nodes are relabeled by their content, so an
+/// unchanged store always exports byte identical turtle.
 ///
 /// # Errors
 ///
 /// Will return `Err` if any entry can not be marshaled out as valid turtle
-pub async fn export_turtle(db_api: &DbApi) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn export_turtle<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    canonical: bool,
+    prefix_file: Option<&str>,
+    options: TurtleOptions,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
     trace!("export_turtle");
-    let subject_names = db_api.get_subject_names().await?;
-    let prefixes = compute_prefixes(&subject_names, db_api).await?;
-
-    print_prefixes(&prefixes);
+    let mut preferred = match prefix_file {
+        Some(path) => read_prefixes(path)?,
+        None => PrefixMap::new(),
+    };
+    preferred.extend(&db_api.preferred_prefixes().await?);
 
+    let subject_names = db_api.get_subject_names().await?;
+    let mut subjects = Vec::with_capacity(subject_names.len());
     for name in &subject_names {
         if let Some(subject) = db_api.query(name).await? {
-            let name_string = subject.name().to_string();
-            let (ns, local_name) = extract_namespace_and_local_name(&name_string)?;
-
-            if ns.is_empty() && local_name.contains(":/") {
-                println!("<{local_name}>");
-            } else if ns.is_empty() {
-                println!("{local_name}");
-            } else if let Some(prefix) = prefixes.get(ns) {
-                println!("{prefix}:{local_name}");
-            } else {
-                let e = TriplesError::UnresolvableURIPrefix {
-                    prefix_name: ns.to_string(),
-                    name: local_name.to_string(),
-                };
-                error!("export_turtle no prefix: {e:?}");
-                return Err(Box::new(e));
-            };
-
-            let pairs: Vec<_> = subject.predicate_object_pairs().collect();
-            print_predicate_object_pairs(&pairs, &prefixes)?;
+            subjects.push(subject);
         }
     }
+    if canonical {
+        subjects = canonical_subjects(subjects);
+    }
+
+    let prefixes = compute_prefixes(&subjects, &preferred)?;
+
+    let options = TurtleOptions {
+        sorted: options.sorted || canonical,
+        ..options
+    };
+    TurtleWriter::new(&subjects, &prefixes, options)
+        .write_async(out)
+        .await?;
 
     Ok(())
 }
 
+/// export N-Triples of entire db to `out`, one triple per line
+///
+/// # Errors
+///
+/// Will return `Err` if the db can not be read or `out` written
+pub async fn export_ntriples<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    db_api: &DbApi,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace!("export_ntriples");
+    for name in &db_api.get_subject_names().await? {
+        if let Some(subject) = db_api.query(name).await? {
+            out.write_all(turtle_writer::ntriples(&subject).as_bytes())
+                .await?;
+        }
+    }
+    out.flush().await?;
+    Ok(())
+}
+
+fn is_blank(name: &str) -> bool {
+    name.starts_with("_:")
+}
+
+/// the content of every blank node with other blank node labels masked,
+/// so two stores holding the same graph under different labels agree on
+/// it, gathered in one pass over `subjects`
+fn blank_node_signatures(subjects: &[Subject]) -> BTreeMap<String, Vec<String>> {
+    let mask = |name: &str| {
+        if is_blank(name) {
+            "_:".to_string()
+        } else {
+            name.to_string()
+        }
+    };
+
+    let mut signatures: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for subject in subjects {
+        let subject_name = subject.name().to_string();
+        let subject_blank = is_blank(&subject_name);
+        if subject_blank {
+            signatures.entry(subject_name.clone()).or_default();
+        }
+        for (predicate, objects) in subject.predicate_object_pairs() {
+            for object in objects {
+                if subject_blank {
+                    signatures
+                        .entry(subject_name.clone())
+                        .or_default()
+                        .push(format!("{predicate} {}", mask(object)));
+                }
+                if is_blank(object) {
+                    signatures
+                        .entry(object.clone())
+                        .or_default()
+                        .push(format!("^{predicate} {}", mask(&subject_name)));
+                }
+            }
+        }
+    }
+    for signature in signatures.values_mut() {
+        signature.sort();
+    }
+    signatures
+}
+
+/// sort subjects, named ones first, and relabel blank nodes `_:b0`,
+/// `_:b1`, ... in order of their content
+fn canonical_subjects(subjects: Vec<Subject>) -> Vec<Subject> {
+    let mut signed: Vec<(Vec<String>, String)> = blank_node_signatures(&subjects)
+        .into_iter()
+        .map(|(label, signature)| (signature, label))
+        .collect();
+    signed.sort();
+    let relabel: HashMap<String, usize> = signed
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (_, label))| (label, idx))
+        .collect();
+    let rename = |name: String| match relabel.get(&name) {
+        Some(idx) => format!("_:b{idx}"),
+        None => name,
+    };
+
+    let mut ordered: BTreeMap<(Option<usize>, String), Subject> = BTreeMap::new();
+    for subject in subjects {
+        let subject_name = subject.name().to_string();
+        let key = (relabel.get(&subject_name).copied(), subject_name.clone());
+        let mut renamed = Subject::new(RdfName::new(rename(subject_name)));
+        for (predicate, objects) in subject.predicate_object_pairs() {
+            for object in objects {
+                renamed.add(predicate.clone(), rename(object.clone()));
+            }
+        }
+        ordered.insert(key, renamed);
+    }
+
+    ordered.into_values().collect()
+}
+
 /// use statefull stream to build subject objects and as they become
 /// complete, insert into db.
 ///
@@ -84,6 +304,7 @@ pub async fn export_turtle(db_api: &DbApi) -> Result<(), Box<dyn std::error::Err
 async fn import_line(
     line: &str,
     stream: &mut TurtleStream,
+    tx: &mut Transaction<'_, Sqlite>,
     db_api: &DbApi,
 ) -> Result<(), Box<dyn std::error::Error>> {
     trace!("import line");
@@ -92,7 +313,7 @@ async fn import_line(
     }
 
     match stream.load(line) {
-        Ok(Some(subject)) => db_api.insert(&subject).await?,
+        Ok(Some(subject)) => db_api.insert_into_txn(tx, &subject, None).await?,
         Ok(None) => {} // still loading predicate/object pairs - keep reading stream
         Err(e) => return Err(Box::new(e)),
     }
@@ -101,21 +322,20 @@ async fn import_line(
 }
 
 // This utility function returns the prefix for a given namespace string.
-// It updates the prefixes map and unique_count if the namespace is not already present.
+// It updates the prefixes map if the namespace is not already present.
 fn get_or_insert_prefix<'a>(
     ns: &'a str,
     prefixes: &'a mut HashMap<String, String>,
-    unique_count: &mut u32,
 ) -> Result<&'a str, TriplesError> {
     if !prefixes.contains_key(ns) {
-        *unique_count += 1;
-
+        // the last non empty path segment, `http://ex.com/units/` -> `units`
         let meaningful_substring = ns
-            .split('/')
-            .last()
-            .unwrap_or("ns")
+            .trim_end_matches(['/', '#'])
+            .rsplit(['/', '#'])
+            .next()
+            .unwrap_or_default()
             .chars()
-            .filter(|c| c.is_alphabetic())
+            .filter(char::is_ascii_alphabetic)
             .collect::<String>();
 
         let mut alias = meaningful_substring
@@ -123,10 +343,15 @@ fn get_or_insert_prefix<'a>(
             .take(5)
             .collect::<String>()
             .to_lowercase();
+        if alias.is_empty() {
+            alias = "ns".to_string();
+        }
 
+        // taken aliases are numbered from the same stem, `ef`, `ef1`, `ef2`
+        let stem = alias.chars().take(4).collect::<String>();
         let mut suffix = 1;
         while prefixes.values().any(|v| v == &alias) {
-            alias = format!("{}{}", alias.chars().take(4).collect::<String>(), suffix);
+            alias = format!("{stem}{suffix}");
             suffix += 1;
         }
 
@@ -142,163 +367,134 @@ fn get_or_insert_prefix<'a>(
         .map(String::as_str)
 }
 
-fn handle_name_string<'a>(
-    name_string: &'a str,
-    prefixes: &mut HashMap<String, String>,
-    unique_ns_count: &mut u32,
-) -> Result<Option<&'a str>, Box<dyn std::error::Error>> {
+/// note the namespace of an IRI, IRIs without a valid local name are
+/// written in full and need none
+fn handle_name_string(name_string: &str, namespaces: &mut BTreeSet<String>) {
     trace!("handle_name_string");
-    let (ns, _) = if let Some(idx) = name_string.rfind('#') {
-        let (ns, _) = name_string.split_at(idx + 1); // +1 to include '#' in ns
-        (ns, &name_string[idx + 1..])
-    } else if let Some(idx) = name_string.rfind('/') {
-        let (ns, _) = name_string.split_at(idx + 1); // +1 to include '/' in ns if needed
-        (ns, &name_string[idx + 1..]) // remove '/' from the start of name
-    } else {
-        let e = TriplesError::InvalidIRI {
-            uri: name_string.to_string(),
-        };
-        error!("handle_name_string: {e}");
-        return Err(Box::new(e));
-    };
-
-    get_or_insert_prefix(ns, prefixes, unique_ns_count)?;
-    Ok(Some(ns))
+    if let Some(ns) = iri::namespace(name_string) {
+        namespaces.insert(ns.to_string());
+    }
 }
 
-fn handle_subject(
-    subject: &Subject,
-    prefixes: &mut HashMap<String, String>,
-    unique_ns_count: &mut u32,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if handle_name_string(&subject.name().to_string(), prefixes, unique_ns_count)?.is_none() {
-        let e = TriplesError::InvalidIRI {
-            uri: subject.name().to_string(),
-        };
-        error!("handle_subject: {e}");
-        return Err(Box::new(e));
+fn handle_subject(subject: &Subject, namespaces: &mut BTreeSet<String>) {
+    let subject_name = subject.name().to_string();
+    if !is_blank(&subject_name) {
+        handle_name_string(&subject_name, namespaces);
     }
 
     for pair in subject.predicate_object_pairs() {
-        if handle_name_string(&pair.0.to_string(), prefixes, unique_ns_count)?.is_none() {
-            let e = TriplesError::InvalidIRI {
-                uri: pair.0.to_string(),
-            };
-            error!("handle_subject: {e}");
-            return Err(Box::new(e));
-        };
-        for obj_name in pair.1 {
-            if handle_name_string(obj_name, prefixes, unique_ns_count)?.is_none() {
-                let e = TriplesError::InvalidIRI {
-                    uri: obj_name.to_string(),
-                };
-                error!("handle_subject: {e}");
-                return Err(Box::new(e));
-            };
+        // `rdf:type` is written as `a`
+        if pair.0.to_string() != RDF_TYPE {
+            handle_name_string(&pair.0.to_string(), namespaces);
+        }
+        // literal and blank node objects need no prefix
+        for obj_name in pair.1.iter().filter(|o| is_iri(o)) {
+            handle_name_string(obj_name, namespaces);
         }
     }
-
-    Ok(())
 }
 
-async fn compute_prefixes(
-    subject_names: &Vec<RdfName>,
-    db_api: &DbApi,
-) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+/// a prefix for every namespace used by `subjects`, taken from `preferred`
+/// where it has one and invented otherwise
+///
+/// # Errors
+///
+/// return `Err` if no prefix can be invented for a namespace
+pub fn compute_prefixes(
+    subjects: &[Subject],
+    preferred: &PrefixMap,
+) -> Result<PrefixMap, Box<dyn std::error::Error>> {
     trace!("compute_prefixes");
-    let mut prefixes: HashMap<String, String> = HashMap::new();
-    let mut unique_ns_count = 0;
+    let mut namespaces = BTreeSet::new();
+    for subject in subjects {
+        handle_subject(subject, &mut namespaces);
+    }
 
-    for name in subject_names {
-        if let Some(subject) = db_api.query(name).await? {
-            handle_subject(&subject, &mut prefixes, &mut unique_ns_count)?;
+    let mut prefixes: HashMap<String, String> = HashMap::new();
+    for ns in &namespaces {
+        if let Some(prefix) = preferred.prefix(ns) {
+            prefixes.insert(ns.clone(), prefix.to_string());
         }
     }
 
-    Ok(prefixes)
-}
+    for ns in &namespaces {
+        get_or_insert_prefix(ns, &mut prefixes)?;
+    }
 
-fn print_prefixes(prefixes: &HashMap<String, String>) {
-    for (name, prefix) in prefixes {
-        println!("@prefix {prefix}: <{name}/> .\n");
+    let mut prefix_map = PrefixMap::new();
+    for (ns, prefix) in &prefixes {
+        prefix_map.insert(prefix, ns);
     }
+    Ok(prefix_map)
 }
 
-fn print_predicate_object_pairs(
-    pairs: &[(&RdfName, &HashSet<String>)],
-    prefixes: &HashMap<String, String>,
-) -> Result<(), TriplesError> {
-    trace!("print_predicate_object_pairs");
-    for (idx, (predicate, objects)) in pairs.iter().enumerate() {
-        let name_string = predicate.to_string();
-
-        let (ns, name) = if let Some(idx) = name_string.rfind('#') {
-            let (ns, name) = name_string.split_at(idx + 1); // +1 to include '#' in ns
-            (ns, name)
-        } else if let Some(idx) = name_string.rfind('/') {
-            let (ns, name) = name_string.split_at(idx + 1); // +1 to include '/' in ns if needed
-            (ns, &name[1..]) // remove '/' from the start of name
-        } else {
-            let e = TriplesError::InvalidIRI {
-                uri: name_string.to_string(),
-            };
-            error!("print_predicate_object_pairs: {:?}", e);
-            return Err(e);
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(name: &str, pairs: &[(&str, &str)]) -> Subject {
+        let mut subject = Subject::new(RdfName::new(name.to_string()));
+        for (predicate, object) in pairs {
+            subject.add(
+                RdfName::new((*predicate).to_string()),
+                (*object).to_string(),
+            );
+        }
+        subject
+    }
 
-        match prefixes.get(ns) {
-            Some(prefix) => {
-                let formatted_objects: Vec<String> = objects
-                    .iter()
-                    .map(|object| {
-                        let (ons, objname) = object.rfind('#').map_or_else(
-                            || {
-                                object.rfind('/').map_or(("", object.as_str()), |idx| {
-                                    let (ons, objname) = object.split_at(idx + 1);
-                                    let objname = &objname[1..];
-                                    (ons, objname)
-                                })
-                            },
-                            |idx| {
-                                let (ons, objname) = object.split_at(idx + 1);
-                                (ons, objname)
-                            },
-                        );
-
-                        let fmt_oname = match prefixes.get(ons) {
-                            Some(obj_prefix) if !objname.contains('/') => {
-                                format!("{obj_prefix}:{objname}")
-                            }
-                            _ => object.to_string(),
-                        };
-
-                        if fmt_oname.contains(":/") {
-                            format!("<{fmt_oname}>")
-                        } else {
-                            format!("\"{fmt_oname}\"")
-                        }
-                    })
-                    .collect();
-
-                // Calculate the dynamic indentation
-                let indentation = "    ".len() + prefix.len() + ":".len() + name.len() + 1; // +1 for the space after name
-                let spaces = " ".repeat(indentation);
-
-                let object_list = formatted_objects.join(&format!(" ,\n{spaces}"));
-                let is_last_pair = idx == pairs.len() - 1;
-                if is_last_pair {
-                    println!("    {prefix}:{name} {object_list} .\n");
-                } else {
-                    println!("    {prefix}:{name} {object_list} ;");
-                }
-            }
-            _ => {
-                return Err(TriplesError::UnresolvableURIPrefix {
-                    prefix_name: ns.to_string(),
-                    name: name.to_string(),
-                });
-            }
+    fn names(subjects: &[Subject]) -> Vec<String> {
+        subjects.iter().map(|s| s.name().to_string()).collect()
+    }
+
+    #[test]
+    fn canonical_subjects_relabel_blank_nodes_by_content() {
+        let p = "http://example.com/p";
+        let q = "http://example.com/q";
+        let graph = |first: &str, second: &str| {
+            vec![
+                subject(first, &[(q, "two")]),
+                subject("http://example.com/b", &[(p, first), (p, second)]),
+                subject(second, &[(q, "one")]),
+                subject("http://example.com/a", &[(p, "x")]),
+            ]
         };
+
+        let left = canonical_subjects(graph("_:x9", "_:x1"));
+        let right = canonical_subjects(graph("_:genid7", "_:genid8"));
+
+        assert_eq!(
+            names(&left),
+            vec![
+                "http://example.com/a",
+                "http://example.com/b",
+                "_:b0",
+                "_:b1"
+            ]
+        );
+        assert_eq!(names(&left), names(&right));
+        // "one" sorts first, so its node is _:b0 whatever it was called
+        assert!(left[2]
+            .get(&RdfName::new(q.to_string()))
+            .unwrap()
+            .contains("one"));
+        assert!(right[2]
+            .get(&RdfName::new(q.to_string()))
+            .unwrap()
+            .contains("one"));
+    }
+
+    #[test]
+    fn invented_prefixes_number_one_stem() {
+        let mut prefixes = HashMap::new();
+        let aliases: Vec<String> = (3..7)
+            .map(|n| {
+                let ns = format!("http://example.com/EF-{n}/");
+                get_or_insert_prefix(&ns, &mut prefixes)
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(aliases, ["ef", "ef1", "ef2", "ef3"]);
     }
-    Ok(())
 }
@@ -1,9 +1,56 @@
 use crate::data::{Pre, RdfName, Subject, TriplesError};
+use crate::iri;
 use crate::turtle::LineParser;
+use crate::vocab;
 use std::collections::HashMap;
 use std::fmt;
 use tracing::trace;
 
+/// the value of a quoted string with its escape sequences replaced, unknown
+/// escapes are kept as written
+#[must_use]
+pub fn unescape(text: &str) -> String {
+    let mut value = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some('b') => value.push('\u{8}'),
+            Some('f') => value.push('\u{c}'),
+            Some(escaped @ ('"' | '\'' | '\\')) => value.push(escaped),
+            Some(u @ ('u' | 'U')) => {
+                let len = if u == 'u' { 4 } else { 8 };
+                let hex: String = chars.by_ref().take(len).collect();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(decoded) if hex.len() == len => value.push(decoded),
+                    _ => {
+                        value.push('\\');
+                        value.push(u);
+                        value.push_str(&hex);
+                    }
+                }
+            }
+            Some(other) => {
+                value.push('\\');
+                value.push(other);
+            }
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+/// a `_:label` name is a blank node, not a name in a `_` prefix
+fn blank_node(prefix: &Pre, local_name: &str) -> Option<String> {
+    (prefix.to_string() == "_").then(|| format!("_:{local_name}"))
+}
+
 #[allow(dead_code)] // clippy can't see lalrpop
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum ParsedLine {
@@ -70,6 +117,27 @@ impl TurtleStream {
         }
     }
 
+    /// the IRI declared for a prefix, `rdf:` is always known since the `a`
+    /// keyword expands to `rdf:type`
+    fn namespace(&self, prefix: &Pre) -> Option<RdfName> {
+        self.prefixes
+            .get(prefix)
+            .cloned()
+            .or_else(|| (prefix.to_string() == "rdf").then(|| RdfName::new(vocab::RDF.to_string())))
+    }
+
+    /// the prefixes declared so far, with the namespace IRI each expands to
+    #[must_use]
+    pub fn prefixes(&self) -> Vec<(String, String)> {
+        let mut prefixes: Vec<(String, String)> = self
+            .prefixes
+            .iter()
+            .map(|(prefix, ns)| (prefix.to_string(), ns.to_string()))
+            .collect();
+        prefixes.sort();
+        prefixes
+    }
+
     fn resolve_obj_iri(
         &self,
         prefix: Option<&Pre>,
@@ -78,21 +146,17 @@ impl TurtleStream {
         prefix.map_or_else(
             || Ok(object.clone()),
             |ns| {
-                self.prefixes.get(ns).map_or_else(
+                if let Some(label) = blank_node(ns, object) {
+                    return Ok(label);
+                }
+                self.namespace(ns).map_or_else(
                     || {
                         Err(TriplesError::UnresolvableURIPrefix {
                             prefix_name: ns.to_string(),
                             name: object.to_string(),
                         })
                     },
-                    |ns| {
-                        let ns_str = ns.to_string();
-                        if ns_str.ends_with('#') {
-                            Ok(format!("{ns_str}{object}"))
-                        } else {
-                            Ok(format!("{ns_str}/{object}"))
-                        }
-                    },
+                    |ns| Ok(iri::join(&ns.to_string(), object)),
                 )
             },
         )
@@ -106,7 +170,10 @@ impl TurtleStream {
         prefix.map_or_else(
             || Ok(local_name.to_string()),
             |ns| {
-                self.prefixes.get(ns).map_or_else(
+                if let Some(label) = blank_node(ns, &local_name.to_string()) {
+                    return Ok(label);
+                }
+                self.namespace(ns).map_or_else(
                     || {
                         Err(TriplesError::UnresolvableURIPrefix {
                             prefix_name: ns.to_string(),
@@ -114,13 +181,8 @@ impl TurtleStream {
                         })
                     },
                     |ns| {
-                        let ns_str = ns.to_string();
-                        trace!("resolve_iri for ns: {ns_str}");
-                        if ns_str.ends_with('#') {
-                            Ok(format!("{ns_str}{local_name}"))
-                        } else {
-                            Ok(format!("{ns_str}/{local_name}"))
-                        }
+                        trace!("resolve_iri for ns: {ns}");
+                        Ok(iri::join(&ns.to_string(), &local_name.to_string()))
                     },
                 )
             },
@@ -289,11 +351,13 @@ impl TurtleStream {
                 );
                 if *has_more {
                     self.state = ParserState::ObjectLoading(prefix.clone(), predicate.clone());
+                    result
                 } else {
-                    self.current_subject = None;
+                    // a complete triple on one line finishes its subject
+                    result?;
                     self.state = ParserState::SubjectLoading;
-                };
-                result
+                    Ok(self.current_subject.take())
+                }
             }
             ParsedLine::ContinueObj(_, _, _) => Err(TriplesError::NotImplemented {
                 trace: "subject loading contobj".to_string(),
@@ -0,0 +1,540 @@
+/// Turtle serialization of subjects
+///
+/// * `rdf:type` is written as `a`
+/// * literals are escaped, values spanning lines optionally use `"""`
+///   long strings
+/// * numbers and booleans are written bare, in Turtle's own shorthand
+/// * blank nodes used as the object of exactly one triple are optionally
+///   nested as `[ ... ]` in place, and `rdf:first`/`rdf:rest` chains of
+///   them written as `( ... )` collections
+///
+/// The optional forms are off by default, the line based `TurtleStream`
+/// reader does not read them back.
+///
+use crate::data::{is_iri, lexical_datatype, RdfName, Subject, Term};
+use crate::prefixes::PrefixMap;
+use crate::vocab::{RDF_FIRST, RDF_NIL, RDF_REST, RDF_TYPE};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// layout of the written Turtle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurtleOptions {
+    /// spaces per level of nesting
+    pub indent: usize,
+    /// objects of one predicate written on a line before wrapping
+    pub max_objects_per_line: usize,
+    /// write predicates, `rdf:type` first, and objects in sorted order
+    pub sorted: bool,
+    /// nest blank nodes used as the object of exactly one triple
+    pub nest_blank_nodes: bool,
+    /// write `rdf:first`/`rdf:rest` chains of blank nodes as collections
+    pub collections: bool,
+    /// write values spanning lines as `"""` long strings
+    pub long_strings: bool,
+}
+
+impl Default for TurtleOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            max_objects_per_line: 1,
+            sorted: false,
+            nest_blank_nodes: false,
+            collections: false,
+            long_strings: false,
+        }
+    }
+}
+
+/// writes a set of subjects, in the order given, as one Turtle document
+pub struct TurtleWriter<'a> {
+    subjects: &'a [Subject],
+    by_name: HashMap<String, &'a Subject>,
+    prefixes: &'a PrefixMap,
+    options: TurtleOptions,
+    nested: HashSet<String>,
+}
+
+fn is_blank(name: &str) -> bool {
+    name.starts_with("_:")
+}
+
+/// a literal as a Turtle string, `"""` quoted when it spans lines
+#[must_use]
+pub fn literal(value: &str) -> String {
+    if !value.contains('\n') {
+        return Term::literal(value).to_string();
+    }
+    let mut text = String::from("\"\"\"");
+    for c in value.chars() {
+        match c {
+            '"' => text.push_str("\\\""),
+            '\\' => text.push_str("\\\\"),
+            '\r' => text.push_str("\\r"),
+            _ => text.push(c),
+        }
+    }
+    text.push_str("\"\"\"");
+    text
+}
+
+/// the triples of a subject as N-Triples lines, which are also valid Turtle
+#[must_use]
+pub fn ntriples(subject: &Subject) -> String {
+    let subject_term = Term::from_object(&subject.name().to_string());
+    let mut text = String::new();
+    for (predicate, objects) in subject.predicate_object_pairs() {
+        for object in objects {
+            let _ = writeln!(
+                text,
+                "{subject_term} <{predicate}> {} .",
+                Term::from_object(object)
+            );
+        }
+    }
+    text
+}
+
+impl<'a> TurtleWriter<'a> {
+    #[must_use]
+    pub fn new(subjects: &'a [Subject], prefixes: &'a PrefixMap, options: TurtleOptions) -> Self {
+        let by_name = subjects
+            .iter()
+            .map(|subject| (subject.name().to_string(), subject))
+            .collect();
+        let mut writer = Self {
+            subjects,
+            by_name,
+            prefixes,
+            options,
+            nested: HashSet::new(),
+        };
+        writer.nested = writer.nested_blank_nodes();
+        if !options.nest_blank_nodes {
+            // only the nodes of collections are written in place
+            writer.nested = writer
+                .nested
+                .iter()
+                .filter(|label| options.collections && writer.collection(label).is_some())
+                .cloned()
+                .collect();
+        }
+        writer
+    }
+
+    /// blank nodes that are the object of exactly one triple, less those
+    /// only reachable through a cycle of such nodes, which are written at
+    /// the top level so they are not lost
+    fn nested_blank_nodes(&self) -> HashSet<String> {
+        let mut references: HashMap<&str, usize> = HashMap::new();
+        for subject in self.subjects {
+            for objects in subject.all_objects() {
+                for object in objects.iter().filter(|o| is_blank(o)) {
+                    *references.entry(object).or_default() += 1;
+                }
+            }
+        }
+        let mut nested: HashSet<String> = references
+            .into_iter()
+            .filter(|(_, count)| *count == 1)
+            .map(|(label, _)| label.to_string())
+            .collect();
+
+        let mut reached = HashSet::new();
+        for subject in self.subjects {
+            let name = subject.name().to_string();
+            if !nested.contains(&name) {
+                self.reach(&name, &nested, &mut reached);
+            }
+        }
+        for subject in self.subjects {
+            let name = subject.name().to_string();
+            if nested.contains(&name) && !reached.contains(&name) {
+                nested.remove(&name);
+                self.reach(&name, &nested, &mut reached);
+            }
+        }
+        nested
+    }
+
+    fn reach(&self, name: &str, nested: &HashSet<String>, reached: &mut HashSet<String>) {
+        let Some(subject) = self.by_name.get(name) else {
+            return;
+        };
+        for objects in subject.all_objects() {
+            for object in objects {
+                if nested.contains(object) && reached.insert(object.clone()) {
+                    self.reach(object, nested, reached);
+                }
+            }
+        }
+    }
+
+    fn indent(&self, depth: usize) -> String {
+        " ".repeat(self.options.indent * depth)
+    }
+
+    /// an IRI as a prefixed name when its namespace has a prefix
+    fn name(&self, name: &str) -> String {
+        if is_blank(name) {
+            return name.to_string();
+        }
+        self.prefixes
+            .compact(name)
+            .unwrap_or_else(|| format!("<{name}>"))
+    }
+
+    /// the `@prefix` lines for every declared prefix
+    #[must_use]
+    pub fn prefix_block(&self) -> String {
+        let mut text = String::new();
+        for (prefix, namespace) in self.prefixes.iter() {
+            let _ = writeln!(text, "@prefix {prefix}: <{namespace}> .\n");
+        }
+        text
+    }
+
+    fn pairs(&self, subject: &'a Subject) -> Vec<(&'a RdfName, Vec<&'a String>)> {
+        let mut pairs: Vec<(&RdfName, Vec<&String>)> = subject
+            .predicate_object_pairs()
+            .map(|(predicate, objects)| (predicate, objects.iter().collect()))
+            .collect();
+        if self.options.sorted {
+            pairs.sort_by_key(|(predicate, _)| {
+                let predicate = predicate.to_string();
+                (predicate != RDF_TYPE, predicate)
+            });
+        }
+        pairs
+    }
+
+    /// the items of a collection whose head is `label`, `None` unless every
+    /// node of the list is nested and has only `rdf:first` and `rdf:rest`
+    fn collection(&self, label: &str) -> Option<Vec<&'a String>> {
+        let mut items = Vec::new();
+        let mut node = label;
+        while node != RDF_NIL {
+            if !self.nested.contains(node) {
+                return None;
+            }
+            let subject = self.by_name.get(node)?;
+            let mut first = None;
+            let mut rest = None;
+            for (predicate, objects) in subject.predicate_object_pairs() {
+                let mut objects = objects.iter();
+                let (Some(object), None) = (objects.next(), objects.next()) else {
+                    return None;
+                };
+                match predicate.to_string().as_str() {
+                    RDF_FIRST => first = Some(object),
+                    RDF_REST => rest = Some(object),
+                    _ => return None,
+                }
+            }
+            items.push(first?);
+            node = rest?;
+        }
+        Some(items)
+    }
+
+    fn object(&self, object: &str, depth: usize) -> String {
+        if object == RDF_NIL && self.options.collections {
+            return "()".to_string();
+        }
+        if is_blank(object) && self.nested.contains(object) {
+            if let Some(items) = self.collection(object).filter(|_| self.options.collections) {
+                let items: Vec<String> = items.iter().map(|o| self.object(o, depth)).collect();
+                return format!("( {} )", items.join(" "));
+            }
+            return match self.by_name.get(object) {
+                Some(subject) => format!(
+                    "[\n{}\n{}]",
+                    self.predicate_objects(subject, depth + 1),
+                    self.indent(depth)
+                ),
+                None => "[]".to_string(),
+            };
+        }
+        if is_blank(object) || is_iri(object) {
+            self.name(object)
+        } else if lexical_datatype(object).is_some() {
+            object.to_string()
+        } else if self.options.long_strings {
+            literal(object)
+        } else {
+            Term::literal(object).to_string()
+        }
+    }
+
+    /// the predicate object list of a subject at `depth`, `;` separated
+    fn predicate_objects(&self, subject: &'a Subject, depth: usize) -> String {
+        let indent = self.indent(depth);
+        let lines: Vec<String> = self
+            .pairs(subject)
+            .into_iter()
+            .map(|(predicate, objects)| {
+                let predicate = predicate.to_string();
+                let predicate = if predicate == RDF_TYPE {
+                    "a".to_string()
+                } else {
+                    self.name(&predicate)
+                };
+
+                let mut objects: Vec<String> =
+                    objects.iter().map(|o| self.object(o, depth)).collect();
+                if self.options.sorted {
+                    objects.sort();
+                }
+
+                let spaces = " ".repeat(indent.len() + predicate.chars().count() + 1);
+                let object_lines: Vec<String> = objects
+                    .chunks(self.options.max_objects_per_line.max(1))
+                    .map(|chunk| chunk.join(" , "))
+                    .collect();
+                format!(
+                    "{indent}{predicate} {}",
+                    object_lines.join(&format!(" ,\n{spaces}"))
+                )
+            })
+            .collect();
+        lines.join(" ;\n")
+    }
+
+    /// the statement for one top level subject, empty for nested ones
+    #[must_use]
+    pub fn subject_block(&self, subject: &'a Subject) -> String {
+        let name = subject.name().to_string();
+        if self.nested.contains(&name) || subject.predicate_object_pairs().next().is_none() {
+            return String::new();
+        }
+        format!(
+            "{}\n{} .\n\n",
+            self.name(&name),
+            self.predicate_objects(subject, 1)
+        )
+    }
+
+    fn blocks(&self) -> impl Iterator<Item = String> + '_ {
+        std::iter::once(self.prefix_block()).chain(
+            self.subjects
+                .iter()
+                .map(|subject| self.subject_block(subject)),
+        )
+    }
+
+    /// # Errors
+    ///
+    /// return `Err` if `out` can not be written
+    pub fn write<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        for block in self.blocks() {
+            out.write_all(block.as_bytes())?;
+        }
+        out.flush()
+    }
+
+    /// # Errors
+    ///
+    /// return `Err` if `out` can not be written
+    pub async fn write_async<W: AsyncWrite + Unpin>(&self, out: &mut W) -> io::Result<()> {
+        for block in self.blocks() {
+            out.write_all(block.as_bytes()).await?;
+        }
+        out.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(name: &str, pairs: &[(&str, &str)]) -> Subject {
+        let mut subject = Subject::new(RdfName::new(name.to_string()));
+        for (predicate, object) in pairs {
+            subject.add(
+                RdfName::new((*predicate).to_string()),
+                (*object).to_string(),
+            );
+        }
+        subject
+    }
+
+    fn prefixes() -> PrefixMap {
+        let mut prefixes = PrefixMap::well_known();
+        prefixes.insert("ex", "http://example.com/");
+        prefixes
+    }
+
+    fn turtle(subjects: &[Subject], options: TurtleOptions) -> String {
+        let prefixes = prefixes();
+        let mut out = Vec::new();
+        TurtleWriter::new(subjects, &prefixes, options)
+            .write(&mut out)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        // drop the @prefix lines
+        text.split_once("\n\nex:")
+            .map_or(text.clone(), |(_, body)| format!("ex:{body}"))
+    }
+
+    fn sorted() -> TurtleOptions {
+        TurtleOptions {
+            sorted: true,
+            ..TurtleOptions::default()
+        }
+    }
+
+    fn nested() -> TurtleOptions {
+        TurtleOptions {
+            nest_blank_nodes: true,
+            collections: true,
+            ..sorted()
+        }
+    }
+
+    #[test]
+    fn escapes_literals() {
+        assert_eq!(literal(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+        assert_eq!(literal("two\nlines"), "\"\"\"two\nlines\"\"\"");
+        assert_eq!(literal("a \"\"\" b\n"), "\"\"\"a \\\"\\\"\\\" b\n\"\"\"");
+    }
+
+    #[test]
+    fn writes_a_and_sorted_pairs() {
+        let s = subject(
+            "http://example.com/s",
+            &[
+                ("http://example.com/z", "2"),
+                (RDF_TYPE, "http://example.com/T"),
+                ("http://example.com/a", "1"),
+            ],
+        );
+        assert_eq!(
+            turtle(&[s], sorted()),
+            "ex:s\n    a ex:T ;\n    ex:a 1 ;\n    ex:z 2 .\n\n"
+        );
+    }
+
+    #[test]
+    fn writes_numbers_and_booleans_bare() {
+        let s = subject(
+            "http://example.com/s",
+            &[
+                ("http://example.com/p", "-5"),
+                ("http://example.com/p", "2.50"),
+                ("http://example.com/p", "1e+3"),
+                ("http://example.com/p", "true"),
+                ("http://example.com/p", "1."),
+                ("http://example.com/p", "NaN"),
+            ],
+        );
+        let options = TurtleOptions {
+            max_objects_per_line: 6,
+            ..sorted()
+        };
+        assert_eq!(
+            turtle(&[s], options),
+            "ex:s\n    ex:p \"1.\" , \"NaN\" , -5 , 1e+3 , 2.50 , true .\n\n"
+        );
+    }
+
+    #[test]
+    fn wraps_objects_and_indents() {
+        let s = subject(
+            "http://example.com/s",
+            &[
+                ("http://example.com/p", "a"),
+                ("http://example.com/p", "b"),
+                ("http://example.com/p", "c"),
+            ],
+        );
+        let options = TurtleOptions {
+            indent: 2,
+            max_objects_per_line: 2,
+            ..sorted()
+        };
+        assert_eq!(
+            turtle(&[s], options),
+            "ex:s\n  ex:p \"a\" , \"b\" ,\n       \"c\" .\n\n"
+        );
+    }
+
+    #[test]
+    fn nests_single_use_blank_nodes() {
+        let subjects = [
+            subject(
+                "http://example.com/s",
+                &[
+                    ("http://example.com/p", "_:b0"),
+                    ("http://example.com/q", "_:shared"),
+                ],
+            ),
+            subject("_:b0", &[("http://example.com/name", "inner")]),
+            subject("_:shared", &[("http://example.com/name", "shared")]),
+            subject(
+                "http://example.com/t",
+                &[("http://example.com/q", "_:shared")],
+            ),
+        ];
+        let text = turtle(&subjects, nested());
+        assert!(
+            text.starts_with(
+                "ex:s\n    ex:p [\n        ex:name \"inner\"\n    ] ;\n    ex:q _:shared .\n\n"
+            ),
+            "{text}"
+        );
+        assert!(text.contains("_:shared\n    ex:name \"shared\" .\n"));
+        assert!(!text.contains("_:b0"));
+    }
+
+    #[test]
+    fn writes_collections() {
+        let subjects = [
+            subject("http://example.com/s", &[("http://example.com/p", "_:l0")]),
+            subject(
+                "_:l0",
+                &[(RDF_FIRST, "http://example.com/a"), (RDF_REST, "_:l1")],
+            ),
+            subject("_:l1", &[(RDF_FIRST, "two"), (RDF_REST, RDF_NIL)]),
+        ];
+        assert_eq!(
+            turtle(&subjects, nested()),
+            "ex:s\n    ex:p ( ex:a \"two\" ) .\n\n"
+        );
+    }
+
+    #[test]
+    fn writes_flat_by_default() {
+        let subjects = [
+            subject(
+                "http://example.com/s",
+                &[
+                    ("http://example.com/p", "_:l0"),
+                    ("http://example.com/q", "two\nlines"),
+                ],
+            ),
+            subject("_:l0", &[(RDF_FIRST, "a"), (RDF_REST, RDF_NIL)]),
+        ];
+        let text = turtle(&subjects, sorted());
+        assert!(
+            text.starts_with("ex:s\n    ex:p _:l0 ;\n    ex:q \"two\\nlines\" .\n\n"),
+            "{text}"
+        );
+        assert!(text.contains("_:l0\n    rdf:first \"a\" ;\n    rdf:rest rdf:nil .\n"));
+    }
+
+    #[test]
+    fn keeps_blank_node_cycles() {
+        let subjects = [
+            subject("_:a", &[("http://example.com/p", "_:b")]),
+            subject("_:b", &[("http://example.com/p", "_:a")]),
+        ];
+        let text = turtle(&subjects, nested());
+        assert!(
+            text.ends_with("_:a\n    ex:p [\n        ex:p _:a\n    ] .\n\n"),
+            "{text}"
+        );
+    }
+}
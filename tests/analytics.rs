@@ -0,0 +1,105 @@
+mod common;
+
+use common::{fresh, load, stdout};
+use triples::analytics::ANALYTICS;
+use triples::analytics_file;
+use triples::data::RdfName;
+use triples::db_api::ANALYTICS_GRAPH;
+
+const RES: &str = "http://k8p.navicore.tech/resource/";
+
+fn res(local: &str) -> String {
+    format!("{RES}{local}")
+}
+
+#[tokio::test]
+async fn test_analyse() {
+    let db_api = load("/tmp/triples_analyse.db", &["tests/data/k8p_topology.ttl"]).await;
+
+    let analytics = analytics_file::analyse(&[], &db_api).await.unwrap();
+    assert_eq!(analytics.edges, 6);
+    // two apps with their pods and namespaces, and a resource linking none
+    assert_eq!(analytics.component_sizes(), [5, 3, 1]);
+    assert_eq!(analytics.nodes[0].node, res("stag"));
+    let app = analytics
+        .nodes
+        .iter()
+        .find(|n| n.node == res("app-a"))
+        .unwrap();
+    assert_eq!((app.component, app.in_degree, app.out_degree), (1, 3, 1));
+    let metric = analytics
+        .nodes
+        .iter()
+        .find(|n| n.node == res("metric1"))
+        .unwrap();
+    assert_eq!((metric.component, metric.in_degree), (3, 0));
+    assert_eq!(analytics.degree_distribution()[&0], (5, 3));
+
+    let belongs = ["prop:belongsTo".to_string()];
+    let analytics = analytics_file::analyse(&belongs, &db_api).await.unwrap();
+    assert_eq!(analytics.edges, 4);
+    assert_eq!(analytics.component_sizes(), [4, 2]);
+}
+
+#[tokio::test]
+async fn test_store() {
+    let db_api = load(
+        "/tmp/triples_analytics_store.db",
+        &["tests/data/k8p_topology.ttl"],
+    )
+    .await;
+
+    let analytics = analytics_file::analyse(&[], &db_api).await.unwrap();
+    assert_eq!(analytics_file::store(&analytics, &db_api).await.unwrap(), 9);
+    let graph = RdfName::new(ANALYTICS_GRAPH.to_string());
+    let stored = db_api.graph_subjects(Some(&graph)).await.unwrap();
+    assert_eq!(stored.len(), 9);
+
+    // stored results are not analysed, and storing again replaces them
+    let again = analytics_file::analyse(&[], &db_api).await.unwrap();
+    assert_eq!(again, analytics);
+    analytics_file::store(&again, &db_api).await.unwrap();
+    let stored = db_api.graph_subjects(Some(&graph)).await.unwrap();
+    let values: usize = stored
+        .iter()
+        .flat_map(|s| s.predicate_object_pairs())
+        .map(|(_, objects)| objects.len())
+        .sum();
+    assert_eq!(values, 9 * 4);
+}
+
+#[test]
+fn test_analyze_cli() {
+    let db_file = fresh("/tmp/triples_analyze_cli.db");
+    stdout(
+        db_file,
+        &["import-turtle", "--input", "tests/data/k8p_topology.ttl"],
+    );
+
+    let report = stdout(db_file, &["analyze", "--top", "3"]);
+    assert!(report.starts_with("nodes 9, edges 6, components 3\n"));
+    assert!(report.contains("res:stag"));
+
+    let csv = stdout(db_file, &["analyze", "--csv"]);
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 10);
+    assert_eq!(rows[0], "node,component,in_degree,out_degree,pagerank");
+    assert!(rows[1].starts_with(&format!("{},1,1,0,", res("stag"))));
+
+    let query = format!(
+        "SELECT ?rank WHERE {{ <{}> <{ANALYTICS}pagerank> ?rank }}",
+        res("stag")
+    );
+    // a header and no rows until written
+    assert_eq!(stdout(db_file, &["query", &query]).lines().count(), 1);
+    stdout(db_file, &["analyze", "--write"]);
+    assert_eq!(stdout(db_file, &["query", &query]).lines().count(), 2);
+    assert_eq!(
+        stdout(db_file, &["--exclude-inferred", "query", &query])
+            .lines()
+            .count(),
+        1
+    );
+    stdout(db_file, &["analyze", "--clear"]);
+    assert_eq!(stdout(db_file, &["query", &query]).lines().count(), 1);
+}
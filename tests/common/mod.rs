@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use triples::data::{RdfName, Subject};
+use triples::db_api::DbApi;
+use triples::io_file;
+use triples::ttl_file;
+
+/// `path` with any database left there by an earlier run removed
+pub fn fresh(path: &str) -> &str {
+    let _ = fs::remove_file(path);
+    path
+}
+
+/// a new database at `db_file` holding the Turtle files of `paths`
+pub async fn load(db_file: &str, paths: &[&str]) -> DbApi {
+    let db_api = DbApi::new(fresh(db_file).to_string()).await.unwrap();
+    for path in paths {
+        import(&db_api, path).await;
+    }
+    db_api
+}
+
+/// add the Turtle file at `path` to the database
+pub async fn import(db_api: &DbApi, path: &str) {
+    let input = io_file::open(Path::new(path)).await.unwrap();
+    ttl_file::import_turtle(input, db_api).await.unwrap();
+}
+
+/// the `triples` binary run against `db_file` with `args`
+pub fn triples(db_file: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_triples"))
+        .args(["--db-location", db_file])
+        .args(args)
+        .output()
+        .expect("Failed to run triples")
+}
+
+/// what the `triples` binary prints run against `db_file` with `args`,
+/// which must succeed
+pub fn stdout(db_file: &str, args: &[&str]) -> String {
+    let output = triples(db_file, args);
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// a subject with the one triple
+pub fn triple(subject: &str, predicate: &str, object: &str) -> Subject {
+    let mut subject = Subject::new(RdfName::new(subject.to_string()));
+    subject.add(RdfName::new(predicate.to_string()), object.to_string());
+    subject
+}
+
+/// every triple of a db, one sorted csv line each
+pub fn triples_of(db_file: &str) -> Vec<String> {
+    let text = stdout(db_file, &["export-triples-csv", "--export-ns-name"]);
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    lines.sort();
+    lines
+}
@@ -2,6 +2,7 @@ use std::fs;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
+use triples::csv_file;
 use triples::db_api::DbApi;
 use triples::turtle_stream::TurtleStream;
 
@@ -10,7 +11,7 @@ const TEST_DB_FILE: &str = "/tmp/csv_import_bricks_test.db";
 /// util fixture
 async fn load_bricks_from_ttl() {
     let path = Path::new("tests/data/bricks_ex1.ttl");
-    let file = File::open(&path).expect("Failed to open file");
+    let file = File::open(path).expect("Failed to open file");
     let reader = io::BufReader::new(file);
 
     let mut stream = TurtleStream::new();
@@ -21,27 +22,24 @@ async fn load_bricks_from_ttl() {
 
     for line in reader.lines() {
         let line = line.expect("Failed to read a line");
-        if line.len() == 0 {
+        if line.is_empty() {
             continue;
         }
         match stream.load(&line) {
             Ok(r) => {
-                match r {
-                    Some(subject) => {
-                        // insert into db
-                        db_api.insert(&subject).await.expect("Insert failed");
-                    }
-                    _ => {} // noop
-                };
+                if let Some(subject) = r {
+                    // insert into db
+                    db_api.insert(&subject).await.expect("Insert failed");
+                }
             }
             Err(e) => {
-                assert!(false, "error: {} on input: {}", e, line)
+                panic!("error: {} on input: {}", e, line)
             }
         }
     }
 
     let subject_names = db_api.get_subject_names().await.unwrap();
-    assert_eq!(subject_names.len(), 119);
+    assert_eq!(subject_names.len(), 129);
     tx.commit().await.unwrap();
 }
 
@@ -50,5 +48,38 @@ async fn test_export_bricks_as_csv() {
     tracing_subscriber::fmt::init();
     load_bricks_from_ttl().await;
 
-    let _db_api = DbApi::new(TEST_DB_FILE.to_string()).await.unwrap();
+    let db_api = DbApi::new(TEST_DB_FILE.to_string()).await.unwrap();
+
+    let mut out = Vec::new();
+    csv_file::export_csv(&mut out, false, None, None, None, &db_api)
+        .await
+        .unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 130);
+    // columns ordered by predicate IRI, rows by subject IRI
+    assert!(lines[0].starts_with("subject,type1,type2,hasPoint1,hasPoint2,"));
+    assert!(lines[1].starts_with("1Floor,https://brickschema.org/schema/Brick#Floor,,"));
+
+    // one row per floor, columns in the order asked for
+    let mut out = Vec::new();
+    let columns = ["type".to_string(), "missing".to_string()];
+    csv_file::export_csv(
+        &mut out,
+        false,
+        Some("id".to_string()),
+        Some(&columns),
+        Some("https://brickschema.org/schema/Brick#Floor"),
+        &db_api,
+    )
+    .await
+    .unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[0], "id,type,missing");
+    assert_eq!(
+        lines[9],
+        "9Floor,https://brickschema.org/schema/Brick#Floor,"
+    );
 }
@@ -55,7 +55,7 @@ async fn test_ttl_to_db() {
     let first_subject = first_subject.unwrap();
 
     let mut pairs: Vec<(_, _)> = first_subject.predicate_object_pairs().collect();
-    pairs.sort_by(|(p1, _), (p2, _)| p1.cmp(p2));
+    pairs.sort_by_key(|(p1, _)| *p1);
 
     if let Some((predicate, objects)) = pairs.into_iter().next() {
         assert_eq!(
@@ -103,6 +103,6 @@ async fn test_bricks_to_db() {
     }
 
     let subject_names = db_api.get_subject_names().await.unwrap();
-    assert_eq!(subject_names.len(), 119);
+    assert_eq!(subject_names.len(), 129);
     tx.commit().await.unwrap();
 }
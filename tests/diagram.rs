@@ -0,0 +1,99 @@
+mod common;
+
+use common::{fresh, stdout};
+use std::fs;
+
+const AHU_POINTS: usize = 56;
+
+#[test]
+fn test_dot_export_from_root() {
+    let db_file = fresh("/tmp/triples_diagram_dot.db");
+    stdout(
+        db_file,
+        &["import-turtle", "--input", "tests/data/bricks_ex1.ttl"],
+    );
+
+    let dot = stdout(
+        db_file,
+        &[
+            "export-dot",
+            "--root",
+            "GHC:AHU-2_I",
+            "--depth",
+            "1",
+            "--color-by-type",
+        ],
+    );
+    assert!(dot.starts_with("digraph triples {"));
+    assert!(dot
+        .contains("n0 [label=\"GHC:AHU-2_I\\na owl:NamedIndividual\\na brick:AHU\", style=filled"));
+    assert_eq!(
+        dot.matches("[label=\"brick:hasPoint\"]").count(),
+        AHU_POINTS
+    );
+    assert!(!dot.contains("Floor"));
+    assert!(!dot.contains("rdf:type"));
+}
+
+#[test]
+fn test_mermaid_export_filters_predicates() {
+    let db_file = fresh("/tmp/triples_diagram_mermaid.db");
+    stdout(
+        db_file,
+        &["import-turtle", "--input", "tests/data/bricks_ex1.ttl"],
+    );
+
+    let mermaid = stdout(
+        db_file,
+        &[
+            "export-mermaid",
+            "--root",
+            "http://cmu.edu/building/ontology/ghc#AHU-2_I",
+            "--depth",
+            "0",
+            "--predicates",
+            "brick:hasPoint",
+        ],
+    );
+    assert!(mermaid.starts_with("flowchart LR\n    n0[\"GHC:AHU-2_I\"]\n"));
+    assert_eq!(
+        mermaid.matches("-->|\"brick:hasPoint\"|").count(),
+        AHU_POINTS
+    );
+    assert!(!mermaid.contains("-->|\"rdf:type\"|"));
+    assert!(!mermaid.contains("classDef"));
+}
+
+#[test]
+fn test_depth_follows_only_selected_predicates() {
+    let source = "/tmp/triples_diagram_predicates.ttl";
+    fs::write(
+        source,
+        concat!(
+            "@prefix ex: <http://example.com/> .\n",
+            "ex:a ex:p ex:b .\n",
+            "ex:a ex:q ex:c .\n",
+            "ex:b ex:p ex:d .\n",
+            "ex:c ex:p ex:e .\n",
+        ),
+    )
+    .unwrap();
+    let db_file = fresh("/tmp/triples_diagram_predicates.db");
+    stdout(db_file, &["import-turtle", "--input", source]);
+
+    let mermaid = stdout(
+        db_file,
+        &[
+            "export-mermaid",
+            "--root",
+            "ex:a",
+            "--depth",
+            "2",
+            "--predicates",
+            "ex:p",
+        ],
+    );
+    assert!(mermaid.contains("[\"ex:d\"]"), "{mermaid}");
+    assert!(!mermaid.contains("ex:c"), "{mermaid}");
+    assert!(!mermaid.contains("ex:e"), "{mermaid}");
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::{fresh, stdout, triples, triples_of};
+use std::fs;
+
+#[test]
+fn test_import_globs_and_compressed_round_trip() {
+    let dir = "/tmp/triples_file_io";
+    let _ = fs::remove_dir_all(dir);
+    fs::create_dir_all(dir).unwrap();
+
+    let db_file = fresh("/tmp/triples_file_io.db");
+    stdout(
+        db_file,
+        &[
+            "import-turtle",
+            "--input",
+            "tests/data/k8p_sm.ttl",
+            "tests/data/units.ttl",
+        ],
+    );
+    let expected = triples_of(db_file);
+    assert!(!expected.is_empty());
+
+    stdout(
+        db_file,
+        &["export", "--output", &format!("{dir}/all.ttl.gz")],
+    );
+    stdout(
+        db_file,
+        &["export", "--output", &format!("{dir}/all.nt.zst")],
+    );
+    stdout(
+        db_file,
+        &["export-turtle", "--output", &format!("{dir}/plain.ttl")],
+    );
+    assert!(fs::read_to_string(format!("{dir}/plain.ttl"))
+        .unwrap()
+        .contains("unit:DEG_C"));
+
+    let gz_db = fresh("/tmp/triples_file_io_gz.db");
+    stdout(gz_db, &["import", "--input", &format!("{dir}/*.gz")]);
+    assert_eq!(triples_of(gz_db), expected);
+
+    let zst_db = fresh("/tmp/triples_file_io_zst.db");
+    stdout(zst_db, &["import", "--input", &format!("{dir}/all.nt.zst")]);
+    assert_eq!(triples_of(zst_db), expected);
+}
+
+#[test]
+fn test_import_rejects_unknown_inputs() {
+    let db_file = fresh("/tmp/triples_file_io_unknown.db");
+    for args in [
+        ["import", "--input", "/tmp/triples_file_io_missing/*.ttl"],
+        ["import", "--input", "Cargo.toml"],
+    ] {
+        assert!(!triples(db_file, &args).status.success());
+    }
+}
+
+#[test]
+fn test_jsonl_round_trips_short_typed_names() {
+    let db_file = fresh("/tmp/triples_file_io_jsonl.db");
+    stdout(
+        db_file,
+        &[
+            "import-triples-csv",
+            "--input",
+            "tests/data/test.csv",
+            "--subject-default-ns",
+            "https://myvss.com/id",
+            "--predicate-default-ns",
+            "https://myvss.com/data",
+        ],
+    );
+    let expected = triples_of(db_file);
+
+    let text = stdout(db_file, &["export-jsonl", "--typed"]);
+    assert!(text.contains(r#""k8p_value":[36.0]"#), "{text}");
+    let exported = "/tmp/triples_file_io.jsonl";
+    fs::write(exported, text).unwrap();
+
+    let copy = fresh("/tmp/triples_file_io_jsonl_copy.db");
+    stdout(
+        copy,
+        &[
+            "import-jsonl",
+            "--input",
+            exported,
+            "--subject-default-ns",
+            "https://myvss.com/id",
+            "--predicate-default-ns",
+            "https://myvss.com/data",
+        ],
+    );
+    assert_eq!(triples_of(copy), expected);
+}
+
+#[test]
+fn test_import_reads_ntriples_terms() {
+    let path = "/tmp/triples_file_io_terms.nt";
+    fs::write(
+        path,
+        concat!(
+            "# typed, tagged and escaped literals\n",
+            "<urn:s> <urn:label> \"chat\"@fr-BE .\n",
+            "<urn:s> <urn:count> \"5\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n",
+            "<urn:s> <urn:note> \"a \\\"b\\\"\" .\n",
+            "<urn:s> <urn:part> _:p1 .\n",
+            "\n",
+            "_:p1 <urn:label> \"part\" .\n",
+        ),
+    )
+    .unwrap();
+
+    let db_file = fresh("/tmp/triples_file_io_terms.db");
+    stdout(db_file, &["import", "--input", path]);
+    let exported = "/tmp/triples_file_io_terms_out.nt";
+    stdout(db_file, &["export", "--output", exported]);
+    let text = fs::read_to_string(exported).unwrap();
+    for line in [
+        r#"<urn:s> <urn:label> "chat" ."#,
+        r#"<urn:s> <urn:count> "5" ."#,
+        r#"<urn:s> <urn:note> "a \"b\"" ."#,
+    ] {
+        assert!(text.contains(line), "{line} in {text}");
+    }
+    assert_eq!(triples_of(db_file).len(), 5);
+
+    fs::write(path, "<urn:s> <urn:p> \"open .\n").unwrap();
+    assert!(!triples(db_file, &["import", "--input", path])
+        .status
+        .success());
+}
+
+#[test]
+fn test_failed_import_loads_nothing() {
+    let db_file = fresh("/tmp/triples_file_io_atomic.db");
+    for (path, text) in [
+        (
+            "/tmp/triples_file_io_atomic.csv",
+            "urn:s,urn:p,urn:o\nurn:s\n",
+        ),
+        (
+            "/tmp/triples_file_io_atomic.ttl",
+            "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o .\nex:s ex:p nope:o .\n",
+        ),
+        (
+            "/tmp/triples_file_io_atomic.jsonl",
+            "{\"@id\":\"urn:s\",\"urn:p\":[\"o\"]}\n{\"urn:p\":[\"o\"]}\n",
+        ),
+    ] {
+        fs::write(path, text).unwrap();
+        assert!(!triples(db_file, &["import", "--input", path])
+            .status
+            .success());
+        assert!(triples_of(db_file).is_empty(), "{path}");
+    }
+    let text = stdout(db_file, &["export-turtle"]);
+    assert!(!text.contains("@prefix ex:"), "{text}");
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::load;
+use futures::TryStreamExt;
+use triples::data::{RdfName, Subject, Term};
+use triples::db_api::{DbApi, StoredTriple};
+
+const PROP: &str = "http://k8p.navicore.tech/property/";
+const RES: &str = "http://k8p.navicore.tech/resource/";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const APP: &str = "stag-stag-sbjava-lotsofnames-server";
+
+fn prop(name: &str) -> RdfName {
+    RdfName::new(format!("{PROP}{name}"))
+}
+
+async fn find(
+    db_api: &DbApi,
+    s: Option<&RdfName>,
+    p: Option<&RdfName>,
+    o: Option<&Term>,
+) -> Vec<StoredTriple> {
+    let mut triples: Vec<StoredTriple> = db_api.find(s, p, o).try_collect().await.unwrap();
+    triples.sort();
+    triples
+}
+
+#[tokio::test]
+async fn test_find() {
+    let db_api = load("/tmp/triples_find.db", &["tests/data/k8p_sm.ttl"]).await;
+
+    let everything = find(&db_api, None, None, None).await;
+    assert_eq!(
+        everything.len() as u64,
+        db_api.stats().await.unwrap().triples
+    );
+
+    // all subjects of an app
+    let app = Term::literal(APP);
+    let named = find(&db_api, None, Some(&prop("k8p_appname")), Some(&app)).await;
+    assert_eq!(named.len(), 33);
+    assert!(named
+        .iter()
+        .all(|(_, p, o, g)| *p == format!("{PROP}k8p_appname") && o == APP && g.is_none()));
+
+    // all triples of a predicate
+    let metrics = find(&db_api, None, Some(&prop("k8p_metric_name")), None).await;
+    assert_eq!(metrics.len(), 33);
+
+    let memory = Term::literal("jvm_memory_used_bytes");
+    let used = find(&db_api, None, None, Some(&memory)).await;
+    assert_eq!(used.len(), 4);
+
+    // one subject, narrowed by predicate and object
+    let subject = RdfName::new(format!("{RES}84e296b9-af09-4921-ac4c-a9a8fae376a3"));
+    let described = find(&db_api, Some(&subject), None, None).await;
+    assert_eq!(described.len(), 8);
+    let gauge = Term::literal("gauge");
+    let typed = find(&db_api, Some(&subject), Some(&prop("k8p_type")), None).await;
+    assert_eq!(
+        typed,
+        find(&db_api, Some(&subject), None, Some(&gauge)).await
+    );
+    assert_eq!(
+        typed,
+        find(
+            &db_api,
+            Some(&subject),
+            Some(&prop("k8p_type")),
+            Some(&gauge)
+        )
+        .await
+    );
+    assert_eq!(
+        typed,
+        vec![(
+            subject.to_string(),
+            format!("{PROP}k8p_type"),
+            "gauge".to_string(),
+            None
+        )]
+    );
+
+    // terms never stored match nothing
+    let unknown = prop("unknown");
+    assert!(find(&db_api, None, Some(&unknown), None).await.is_empty());
+    assert!(find(&db_api, Some(&subject), None, Some(&memory))
+        .await
+        .is_empty());
+
+    // IRI objects match by IRI
+    let mut linked = Subject::new(RdfName::new("http://example.com/pod".to_string()));
+    linked.add(prop("k8p_owner"), subject.to_string());
+    db_api.insert(&linked).await.unwrap();
+    let owner = Term::Iri(subject.to_string());
+    let owned = find(&db_api, None, None, Some(&owner)).await;
+    assert_eq!(owned.len(), 1);
+    assert_eq!(owned[0].0, "http://example.com/pod");
+
+    // blank node objects match by label
+    let mut described_by = Subject::new(RdfName::new("http://example.com/pod".to_string()));
+    described_by.add(prop("k8p_spec"), "_:b0".to_string());
+    db_api.insert(&described_by).await.unwrap();
+    let spec = find(
+        &db_api,
+        None,
+        None,
+        Some(&Term::BlankNode("b0".to_string())),
+    )
+    .await;
+    assert_eq!(
+        spec,
+        vec![(
+            "http://example.com/pod".to_string(),
+            format!("{PROP}k8p_spec"),
+            "_:b0".to_string(),
+            None
+        )]
+    );
+
+    // literals match with the datatype their lexical form is stored as
+    let literal = |datatype: Option<&str>, language: Option<&str>| Term::Literal {
+        value: "gauge".to_string(),
+        datatype: datatype.map(|datatype| format!("{XSD}{datatype}")),
+        language: language.map(ToString::to_string),
+    };
+    assert_eq!(
+        find(
+            &db_api,
+            Some(&subject),
+            None,
+            Some(&literal(Some("string"), None))
+        )
+        .await,
+        typed
+    );
+    assert!(find(
+        &db_api,
+        Some(&subject),
+        None,
+        Some(&literal(Some("integer"), None))
+    )
+    .await
+    .is_empty());
+    assert!(find(
+        &db_api,
+        Some(&subject),
+        None,
+        Some(&literal(None, Some("en")))
+    )
+    .await
+    .is_empty());
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{fresh, stdout, triples_of};
+use serde_json::Value;
+use std::fs;
+
+const CONTEXT: &str = "tests/data/building-context.jsonld";
+
+#[test]
+fn test_jsonld_import_with_context_file() {
+    let db_file = fresh("/tmp/triples_jsonld_import.db");
+    stdout(
+        db_file,
+        &[
+            "import-jsonld",
+            "--context",
+            CONTEXT,
+            "--input",
+            "tests/data/building.jsonld",
+        ],
+    );
+
+    let lines = triples_of(db_file);
+    assert!(lines.contains(
+        &"http://example.com/building/TempSensor_1,http://qudt.org/schema/qudt/hasUnit,http://qudt.org/vocab/unit/DEG_C"
+            .to_string()
+    ));
+    assert!(lines.contains(
+        &"http://example.com/building/Room_101,https://brickschema.org/schema/Brick#hasPoint,http://example.com/building/Humidity_1"
+            .to_string()
+    ));
+    assert_eq!(lines.len(), 7, "{lines:?}");
+}
+
+#[test]
+fn test_jsonld_export_frames_subjects_and_round_trips() {
+    let db_file = fresh("/tmp/triples_jsonld_export.db");
+    stdout(
+        db_file,
+        &[
+            "import-jsonld",
+            "--context",
+            CONTEXT,
+            "--input",
+            "tests/data/building.jsonld",
+        ],
+    );
+
+    let exported = stdout(db_file, &["export-jsonld", "--context", CONTEXT]);
+    let document: Value = serde_json::from_str(&exported).unwrap();
+    let graph = document["@graph"].as_array().unwrap();
+    assert_eq!(graph.len(), 2);
+    let room = graph.iter().find(|n| n["@id"] == "ex:Room_101").unwrap();
+    assert_eq!(room["@type"], "brick:Room");
+    assert_eq!(room["label"], "Room \"101\"");
+    assert_eq!(
+        room["hasPoint"],
+        serde_json::json!(["ex:Humidity_1", "ex:TempSensor_1"])
+    );
+
+    let exported_file = "/tmp/triples_jsonld_export.jsonld";
+    fs::write(exported_file, &exported).unwrap();
+    let again = fresh("/tmp/triples_jsonld_export_again.db");
+    stdout(again, &["import", "--input", exported_file]);
+    assert_eq!(triples_of(again), triples_of(db_file));
+
+    // without a context the stored prefixes compact the names
+    let exported = stdout(db_file, &["export-jsonld"]);
+    assert!(
+        exported.contains(r#""@id":"ex:TempSensor_1""#),
+        "{exported}"
+    );
+}
+
+#[test]
+fn test_jsonld_documents_keep_their_blank_nodes_apart() {
+    let mut inputs = Vec::new();
+    for (name, unit) in [("a", "C"), ("b", "F")] {
+        let path = format!("/tmp/triples_jsonld_blank_{name}.jsonld");
+        fs::write(
+            &path,
+            format!(
+                r#"{{"@id": "http://example.com/{name}",
+                    "http://example.com/reading": {{"http://example.com/unit": "{unit}"}}}}"#
+            ),
+        )
+        .unwrap();
+        inputs.push(path);
+    }
+    let db_file = fresh("/tmp/triples_jsonld_blank.db");
+    stdout(db_file, &["import", "--input", &inputs[0], &inputs[1]]);
+
+    let lines = triples_of(db_file);
+    let units: Vec<&String> = lines
+        .iter()
+        .filter(|l| l.contains(",http://example.com/unit,"))
+        .collect();
+    assert_eq!(units.len(), 2, "{lines:?}");
+    let node = |line: &str| line.split(',').next().unwrap().to_string();
+    assert_ne!(node(units[0]), node(units[1]), "{lines:?}");
+}
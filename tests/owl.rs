@@ -0,0 +1,152 @@
+mod common;
+
+use common::{fresh, load, triple, triples};
+use triples::data::TriplesError;
+use triples::db_api::DbApi;
+use triples::owl;
+use triples::sparql_sql;
+
+const BRICK: &str = "https://brickschema.org/schema/Brick#";
+const EX: &str = "http://example.com/building#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// the local names of what `subject` has for `predicate`
+async fn objects(db_api: &DbApi, subject: &str, predicate: &str) -> Vec<String> {
+    let query = sparql_sql::parse(&format!(
+        "SELECT ?o WHERE {{ <{EX}{subject}> <{predicate}> ?o }}"
+    ))
+    .unwrap();
+    let mut values: Vec<String> = db_api
+        .select(&query)
+        .await
+        .unwrap()
+        .rows
+        .into_iter()
+        .filter_map(|row| row.into_iter().next().flatten())
+        .map(|term| {
+            let value = term.value();
+            value.rsplit(['#', '/']).next().unwrap().to_string()
+        })
+        .collect();
+    values.sort();
+    values
+}
+
+#[tokio::test]
+async fn test_owl_rl_entailments() {
+    let db_api = load("/tmp/triples_owl_entailments.db", &["tests/data/owl.ttl"]).await;
+    assert!(owl::infer(&db_api).await.unwrap() > 0);
+
+    // prp-trp then prp-inv1
+    let has_part = format!("{BRICK}hasPart");
+    assert_eq!(
+        objects(&db_api, "building1", &has_part).await,
+        ["floor1", "room1"]
+    );
+    // prp-symp
+    let adjacent = format!("{EX}adjacentTo");
+    assert_eq!(objects(&db_api, "room2", &adjacent).await, ["room1"]);
+    // cax-eqc1
+    assert_eq!(
+        objects(&db_api, "sat1", RDF_TYPE).await,
+        ["AirTempSensor", "Air_Temperature_Sensor"]
+    );
+    // eq-rep-s then prp-inv1
+    let has_point = format!("{BRICK}hasPoint");
+    assert_eq!(
+        objects(&db_api, "ahu1", &has_point).await,
+        ["legacy-sat1", "sat1"]
+    );
+    // cls-maxc2
+    let same_as = "http://www.w3.org/2002/07/owl#sameAs";
+    assert_eq!(objects(&db_api, "zone1", same_as).await, ["vav1-zone"]);
+    assert!(objects(&db_api, "sat1", same_as)
+        .await
+        .contains(&"legacy-sat1".to_string()));
+
+    // retracted with the premise
+    db_api
+        .delete_from(
+            &triple(
+                &format!("{EX}room1"),
+                &format!("{BRICK}isPartOf"),
+                &format!("{EX}floor1"),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(objects(&db_api, "building1", &has_part).await, ["floor1"]);
+}
+
+#[tokio::test]
+async fn test_owl_rl_reports_inconsistencies() {
+    let db_api = load("/tmp/triples_owl_inconsistent.db", &["tests/data/owl.ttl"]).await;
+    assert!(owl::infer(&db_api).await.unwrap() > 0);
+    let has_part = format!("{BRICK}hasPart");
+    db_api
+        .insert(&triple(
+            &format!("{EX}room3"),
+            &format!("{BRICK}isPartOf"),
+            &format!("{EX}floor1"),
+        ))
+        .await
+        .unwrap();
+    db_api
+        .insert(&triple(
+            &format!("{EX}ahu1"),
+            RDF_TYPE,
+            &format!("{BRICK}Point"),
+        ))
+        .await
+        .unwrap();
+
+    let error = owl::infer(&db_api).await.unwrap_err();
+    let Some(TriplesError::Inconsistent { violations }) = error.downcast_ref::<TriplesError>()
+    else {
+        panic!("expected an inconsistency, found {error}");
+    };
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].rule, "cax-dw");
+    assert_eq!(violations[0].bindings["x"], format!("{EX}ahu1"));
+    assert_eq!(violations[0].bindings["c1"], format!("{BRICK}Equipment"));
+
+    // nothing concluded by an inconsistent run is kept
+    assert_eq!(
+        objects(&db_api, "building1", &has_part).await,
+        ["floor1", "room1"]
+    );
+}
+
+#[test]
+fn test_infer_owl_cli() {
+    let db_file = fresh("/tmp/triples_owl_cli.db");
+    let output = triples(db_file, &["import-turtle", "--input", "tests/data/owl.ttl"]);
+    assert!(output.status.success(), "{output:?}");
+    let output = triples(db_file, &["infer-owl"]);
+    assert!(output.status.success(), "{output:?}");
+
+    let query = format!("SELECT ?x WHERE {{ <{EX}room2> <{EX}adjacentTo> ?x }}");
+    let output = triples(db_file, &["query", &query]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 2);
+    let output = triples(db_file, &["--exclude-inferred", "query", &query]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 1);
+
+    let output = triples(
+        db_file,
+        &[
+            "import-turtle",
+            "--input",
+            "tests/data/owl_inconsistent.ttl",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let output = triples(db_file, &["infer-owl"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("cax-dw"));
+
+    let output = triples(db_file, &["infer-owl", "--clear"]);
+    assert!(output.status.success(), "{output:?}");
+    let output = triples(db_file, &["query", &query]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 1);
+}
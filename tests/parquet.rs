@@ -0,0 +1,110 @@
+mod common;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::Float64Type;
+use arrow_array::RecordBatch;
+use arrow_schema::DataType;
+use common::{fresh, stdout};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+
+fn read_batches(path: &str) -> Vec<RecordBatch> {
+    ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn loaded_db(path: &str) -> &str {
+    let db_file = fresh(path);
+    stdout(
+        db_file,
+        &[
+            "import-triples-csv",
+            "--input",
+            "tests/data/test.csv",
+            "--subject-default-ns",
+            "https://myvss.com/id",
+            "--predicate-default-ns",
+            "https://myvss.com/data",
+        ],
+    );
+    db_file
+}
+
+#[test]
+fn test_parquet_long_export_has_one_row_per_triple() {
+    let db_file = loaded_db("/tmp/triples_parquet_long.db");
+    let triple_count = stdout(db_file, &["export-triples-csv"]).lines().count();
+
+    let out = "/tmp/triples_parquet_long.parquet";
+    stdout(
+        db_file,
+        &["export-parquet", "--output", out, "--batch-size", "100"],
+    );
+    let batches = read_batches(out);
+    assert_eq!(
+        batches.iter().map(RecordBatch::num_rows).sum::<usize>(),
+        triple_count
+    );
+
+    let schema = batches[0].schema();
+    let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(
+        names,
+        ["subject", "predicate", "object", "datatype", "graph"]
+    );
+    let datatypes = batches[0].column(3).as_string::<i32>();
+    assert!(datatypes
+        .iter()
+        .any(|d| d == Some("http://www.w3.org/2001/XMLSchema#string")));
+
+    // the generic export writes the same long table
+    let generic = "/tmp/triples_parquet_generic.parquet";
+    stdout(db_file, &["export", "--output", generic]);
+    assert_eq!(
+        read_batches(generic)
+            .iter()
+            .map(RecordBatch::num_rows)
+            .sum::<usize>(),
+        triple_count
+    );
+}
+
+#[test]
+fn test_parquet_wide_export_types_columns() {
+    let db_file = loaded_db("/tmp/triples_parquet_wide.db");
+    let subject_count = stdout(db_file, &["export-csv"]).lines().count() - 1;
+
+    let out = "/tmp/triples_parquet_wide.parquet";
+    stdout(
+        db_file,
+        &[
+            "export-parquet",
+            "--wide",
+            "--output",
+            out,
+            "--subject-column-name",
+            "id",
+        ],
+    );
+    let batches = read_batches(out);
+    assert_eq!(
+        batches.iter().map(RecordBatch::num_rows).sum::<usize>(),
+        subject_count
+    );
+
+    let schema = batches[0].schema();
+    assert_eq!(schema.field(0).name(), "id");
+    let value = schema.field_with_name("k8p_value").unwrap();
+    assert_eq!(value.data_type(), &DataType::Float64);
+    let namespace = schema.field_with_name("k8p_namespace").unwrap();
+    assert_eq!(namespace.data_type(), &DataType::Utf8);
+
+    let values = batches[0]
+        .column(schema.index_of("k8p_value").unwrap())
+        .as_primitive::<Float64Type>();
+    assert!(values.iter().any(|v| v == Some(36.0)));
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{fresh, load, triples};
+use std::fs;
+use triples::profile::ValueKind;
+use triples::profile_file;
+
+const BRICK: &str = "https://brickschema.org/schema/Brick#";
+const EX: &str = "http://example.com/building#";
+
+const DATA: &[&str] = &[
+    "tests/data/shacl_store.ttl",
+    "tests/data/shacl_valid.ttl",
+    "tests/data/shacl_invalid.ttl",
+];
+
+#[tokio::test]
+async fn test_profile() {
+    let db_api = load("/tmp/triples_profile.db", DATA).await;
+
+    let profile = profile_file::profile(&db_api).await.unwrap();
+    assert_eq!(profile.triples(), 13);
+    assert_eq!(profile.predicates.len(), 4);
+
+    let is_point_of = profile
+        .predicates
+        .iter()
+        .find(|p| p.predicate == format!("{BRICK}isPointOf"))
+        .unwrap();
+    assert_eq!(
+        (
+            is_point_of.triples,
+            is_point_of.subjects,
+            is_point_of.objects
+        ),
+        (3, 3, 2)
+    );
+    assert_eq!(
+        (is_point_of.min_per_subject, is_point_of.max_per_subject),
+        (1, 1)
+    );
+    assert_eq!(is_point_of.kind, ValueKind::Iri);
+    let value = profile
+        .predicates
+        .iter()
+        .find(|p| p.predicate == format!("{EX}value"))
+        .unwrap();
+    assert_eq!(value.kind, ValueKind::Literal("string"));
+    assert_eq!(value.examples, ["72.5", "warm"]);
+
+    let classes: Vec<(&str, u64)> = profile
+        .classes
+        .iter()
+        .map(|c| (c.class.trim_start_matches(BRICK), c.instances))
+        .collect();
+    assert_eq!(classes, [("Equipment", 1), ("Point", 4), ("Room", 1)]);
+    let point = &profile.classes[1];
+    let used: Vec<(&str, u64)> = point
+        .predicates
+        .iter()
+        .map(|p| (p.predicate.rsplit('#').next().unwrap(), p.subjects))
+        .collect();
+    assert_eq!(
+        used,
+        [("unit", 2), ("value", 2), ("type", 4), ("isPointOf", 3)]
+    );
+
+    let namespaces = profile.namespaces();
+    let brick = namespaces.iter().find(|n| n.namespace == BRICK).unwrap();
+    assert_eq!((brick.predicates, brick.classes, brick.triples), (1, 3, 3));
+}
+
+#[test]
+fn test_profile_cli() {
+    let db_file = fresh("/tmp/triples_profile_cli.db");
+    let mut args = vec!["import-turtle", "--input"];
+    args.extend(DATA);
+    let output = triples(db_file, &args);
+    assert!(output.status.success(), "{output:?}");
+
+    let shapes = "/tmp/triples_profile_shapes.ttl";
+    let void = "/tmp/triples_profile_void.ttl";
+    let output = triples(db_file, &["profile", "--shapes", shapes, "--void", void]);
+    assert!(output.status.success(), "{output:?}");
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.starts_with("triples 13, predicates 4, classes 3\n"));
+    assert!(report.contains("brick:isPointOf"));
+    assert!(report.contains("xsd:string"));
+
+    let void = fs::read_to_string(void).unwrap();
+    assert!(void.contains("a void:Dataset"));
+    assert!(void.contains("void:triples 13 "), "{void}");
+
+    // the draft shapes hold of the data they were drafted from
+    let output = triples(db_file, &["validate", "--shapes", shapes]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(!String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("sh:ValidationResult"));
+}
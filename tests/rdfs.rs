@@ -0,0 +1,166 @@
+mod common;
+
+use common::{fresh, load, stdout, triple};
+use triples::data::RdfName;
+use triples::db_api::DbApi;
+use triples::sparql_sql;
+
+const BRICK: &str = "https://brickschema.org/schema/Brick#";
+const EX: &str = "http://example.com/building#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const SUB_CLASS_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+
+fn sorted_lines(text: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    lines.sort_unstable();
+    lines
+}
+
+async fn instances(db_api: &DbApi, class: &str) -> Vec<String> {
+    let query = sparql_sql::parse(&format!(
+        "SELECT ?x WHERE {{ ?x <{RDF_TYPE}> <{BRICK}{class}> }}"
+    ))
+    .unwrap();
+    let mut values: Vec<String> = db_api
+        .select(&query)
+        .await
+        .unwrap()
+        .rows
+        .into_iter()
+        .filter_map(|row| row.into_iter().next().flatten())
+        .map(|term| term.value().trim_start_matches(EX).to_string())
+        .collect();
+    values.sort();
+    values
+}
+
+#[tokio::test]
+async fn test_rdfs_entailments_are_materialised() {
+    let db_api = load(
+        "/tmp/triples_rdfs_materialised.db",
+        &["tests/data/rdfs.ttl"],
+    )
+    .await;
+    assert!(instances(&db_api, "Point").await.is_empty());
+
+    assert!(db_api.enable_rdfs().await.unwrap() > 0);
+    assert!(db_api.rdfs_enabled().await.unwrap());
+    // rdfs9 over the rdfs11 closure, and rdfs3 through rdfs7
+    assert_eq!(instances(&db_api, "Point").await, ["sat1", "sp1"]);
+    assert_eq!(instances(&db_api, "Sensor").await, ["sat1"]);
+    // rdfs2 through rdfs7
+    assert_eq!(instances(&db_api, "Equipment").await, ["ahu1"]);
+    let ahu = db_api
+        .query(&RdfName::new(format!("{EX}ahu1")))
+        .await
+        .unwrap()
+        .unwrap();
+    let has_point = ahu.get(&RdfName::new(format!("{BRICK}hasPoint"))).unwrap();
+    assert!(has_point.contains(&format!("{EX}sat1")));
+
+    assert!(db_api.disable_rdfs().await.unwrap() > 0);
+    assert!(!db_api.rdfs_enabled().await.unwrap());
+    assert!(instances(&db_api, "Point").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_rdfs_entailments_follow_inserts_and_deletes() {
+    let db_api = load("/tmp/triples_rdfs_incremental.db", &["tests/data/rdfs.ttl"]).await;
+    db_api.enable_rdfs().await.unwrap();
+
+    // new instances and new schema are both carried through
+    db_api
+        .insert(&triple(
+            &format!("{EX}rat1"),
+            RDF_TYPE,
+            &format!("{BRICK}Air_Temperature_Sensor"),
+        ))
+        .await
+        .unwrap();
+    db_api
+        .insert(&triple(
+            &format!("{BRICK}Point"),
+            SUB_CLASS_OF,
+            &format!("{BRICK}Entity"),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(instances(&db_api, "Point").await, ["rat1", "sat1", "sp1"]);
+    assert_eq!(instances(&db_api, "Entity").await, ["rat1", "sat1", "sp1"]);
+
+    // sat1 stays a point by the range of hasPoint, but is no sensor
+    db_api
+        .delete_from(
+            &triple(
+                &format!("{EX}sat1"),
+                RDF_TYPE,
+                &format!("{BRICK}Air_Temperature_Sensor"),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(instances(&db_api, "Sensor").await, ["rat1"]);
+    assert_eq!(instances(&db_api, "Point").await, ["rat1", "sat1", "sp1"]);
+
+    // deleting schema re-derives everything
+    db_api
+        .delete_from(
+            &triple(
+                &format!("{BRICK}Setpoint"),
+                SUB_CLASS_OF,
+                &format!("{BRICK}Point"),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(instances(&db_api, "Point").await, ["rat1", "sat1"]);
+    assert_eq!(instances(&db_api, "Entity").await, ["rat1", "sat1"]);
+}
+
+#[tokio::test]
+async fn test_rdfs_inferred_triples_can_be_excluded() {
+    let mut db_api = load("/tmp/triples_rdfs_excluded.db", &["tests/data/rdfs.ttl"]).await;
+    db_api.enable_rdfs().await.unwrap();
+    assert_eq!(instances(&db_api, "Point").await, ["sat1", "sp1"]);
+
+    db_api.set_include_inferred(false);
+    assert!(instances(&db_api, "Point").await.is_empty());
+    let sat = db_api
+        .query(&RdfName::new(format!("{EX}sat1")))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        sat.get(&RdfName::new(RDF_TYPE.to_string())).unwrap().len(),
+        1
+    );
+}
+
+#[test]
+fn test_infer_rdfs_cli() {
+    let db_file = fresh("/tmp/triples_rdfs_cli.db");
+    stdout(
+        db_file,
+        &["import-turtle", "--input", "tests/data/rdfs.ttl"],
+    );
+    stdout(db_file, &["infer-rdfs"]);
+
+    let query = format!("SELECT ?x WHERE {{ ?x <{RDF_TYPE}> <{BRICK}Point> }}");
+    let points = stdout(db_file, &["query", &query]);
+    assert_eq!(points.lines().count(), 3);
+    let points = stdout(db_file, &["--exclude-inferred", "query", &query]);
+    assert_eq!(points.lines().count(), 1);
+
+    let all = stdout(db_file, &["export-triples-csv"]);
+    let asserted = stdout(db_file, &["--exclude-inferred", "export-triples-csv"]);
+    assert!(all.contains("Temperature_Sensor"));
+    assert!(all.lines().count() > asserted.lines().count());
+
+    stdout(db_file, &["infer-rdfs", "--disable"]);
+    assert_eq!(
+        sorted_lines(&stdout(db_file, &["export-triples-csv"])),
+        sorted_lines(&asserted)
+    );
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::{fresh, stdout, triples, triples_of};
+use std::fs;
+
+const BUILDING: &str = "tests/data/building.rdf";
+
+#[test]
+fn test_rdfxml_import() {
+    let db_file = fresh("/tmp/triples_rdfxml_import.db");
+    stdout(db_file, &["import-rdfxml", "--input", BUILDING]);
+
+    let lines = triples_of(db_file);
+    for expected in [
+        "http://example.com/building/,http://www.w3.org/2000/01/rdf-schema#comment,Sample building & its sensors",
+        "http://example.com/building/#Room_101,http://www.w3.org/1999/02/22-rdf-syntax-ns#type,https://brickschema.org/schema/Brick#Room",
+        "http://example.com/building/#Room_101,http://www.w3.org/2000/01/rdf-schema#label,Room 101",
+        "http://example.com/building/#Room_101,https://brickschema.org/schema/Brick#hasPoint,http://example.com/building/TempSensor_1",
+        "http://example.com/building/#Room_101,https://brickschema.org/schema/Brick#hasPoint,http://example.com/building/#Humidity_1",
+        "http://example.com/building/TempSensor_1,http://qudt.org/schema/qudt/hasUnit,http://qudt.org/vocab/unit/DEG_C",
+        "http://example.com/building/TempSensor_1,https://brickschema.org/schema/Brick#lastKnownValue,21.5",
+    ] {
+        assert!(lines.contains(&expected.to_string()), "{expected} in {lines:?}");
+    }
+    assert_eq!(lines.len(), 11, "{lines:?}");
+}
+
+#[test]
+fn test_rdfxml_export_round_trips() {
+    let db_file = fresh("/tmp/triples_rdfxml_export.db");
+    stdout(db_file, &["import-rdfxml", "--input", BUILDING]);
+    let before = triples_of(db_file);
+
+    let exported = "/tmp/triples_rdfxml_export.rdf";
+    stdout(db_file, &["export-rdfxml", "--output", exported]);
+    let text = fs::read_to_string(exported).unwrap();
+    assert!(text.contains("<brick:Room rdf:about=\"http://example.com/building/#Room_101\">"));
+    assert!(text.contains("<rdfs:comment>Sample building &amp; its sensors</rdfs:comment>"));
+
+    let copy_file = fresh("/tmp/triples_rdfxml_copy.db");
+    stdout(copy_file, &["import", "--input", exported]);
+    let after = triples_of(copy_file);
+    assert_eq!(before.len(), after.len());
+    // blank node labels may differ, everything else must not
+    let named = |lines: &[String]| -> Vec<String> {
+        lines
+            .iter()
+            .filter(|l| !l.contains("_:"))
+            .cloned()
+            .collect()
+    };
+    assert_eq!(named(&before), named(&after));
+}
+
+#[test]
+fn test_rdfxml_failed_import_loads_nothing() {
+    let broken = "/tmp/triples_rdfxml_broken.rdf";
+    let text = fs::read_to_string(BUILDING).unwrap();
+    // an element outside any namespace, after the first subjects were read
+    let cut = text.rfind("</brick:Room>").unwrap();
+    fs::write(broken, format!("{}<broken/>", &text[..cut])).unwrap();
+
+    let db_file = fresh("/tmp/triples_rdfxml_broken.db");
+    let output = triples(db_file, &["import-rdfxml", "--input", broken]);
+    assert!(!output.status.success());
+    assert!(triples_of(db_file).is_empty());
+}
+
+#[test]
+fn test_rdfxml_documents_keep_their_blank_nodes_apart() {
+    let mut inputs = Vec::new();
+    for (name, unit) in [("a", "C"), ("b", "F")] {
+        let path = format!("/tmp/triples_rdfxml_blank_{name}.rdf");
+        fs::write(
+            &path,
+            format!(
+                r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:ex="http://example.com/">
+  <rdf:Description rdf:about="http://example.com/{name}">
+    <ex:reading rdf:parseType="Resource">
+      <ex:unit>{unit}</ex:unit>
+    </ex:reading>
+  </rdf:Description>
+</rdf:RDF>
+"#
+            ),
+        )
+        .unwrap();
+        inputs.push(path);
+    }
+    let db_file = fresh("/tmp/triples_rdfxml_blank.db");
+    stdout(db_file, &["import", "--input", &inputs[0], &inputs[1]]);
+
+    let lines = triples_of(db_file);
+    let units: Vec<&String> = lines
+        .iter()
+        .filter(|l| l.contains(",http://example.com/unit,"))
+        .collect();
+    assert_eq!(units.len(), 2, "{lines:?}");
+    let node = |line: &str| line.split(',').next().unwrap().to_string();
+    assert_ne!(node(units[0]), node(units[1]), "{lines:?}");
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{fresh, load, stdout, triple};
+use triples::db_api::{DbApi, RULES_GRAPH};
+use triples::rules_file;
+use triples::sparql_sql;
+
+const PROP: &str = "http://k8p.navicore.tech/property/";
+const APP: &str = "http://k8p.navicore.tech/class/App";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const SOLO: &str = "http://k8p.navicore.tech/resource/solo";
+const SOLO_APP: &str = "http://k8p.navicore.tech/app/solo/loner";
+
+async fn count(db_api: &DbApi, pattern: &str) -> usize {
+    let query = sparql_sql::parse(&format!("SELECT ?x WHERE {{ {pattern} }}")).unwrap();
+    db_api.select(&query).await.unwrap().rows.len()
+}
+
+async fn infer(db_api: &DbApi) -> u64 {
+    let rules = rules_file::read_rules(&["tests/data/k8p_rules.n3"])
+        .await
+        .unwrap();
+    rules_file::infer(rules, RULES_GRAPH, db_api).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_conclusions_are_retracted_with_their_premises() {
+    let db_api = load("/tmp/triples_rules_retract.db", &["tests/data/k8p_sm.ttl"]).await;
+    db_api
+        .insert(&triple(SOLO, &format!("{PROP}k8p_appname"), "loner"))
+        .await
+        .unwrap();
+    db_api
+        .insert(&triple(SOLO, &format!("{PROP}k8p_namespace"), "solo"))
+        .await
+        .unwrap();
+
+    // 34 memberships, 2 apps and 10 large values
+    assert_eq!(infer(&db_api).await, 46);
+    let belongs = format!("?x <{PROP}belongsTo> ?app");
+    assert_eq!(count(&db_api, &belongs).await, 34);
+    assert_eq!(count(&db_api, &format!("?x <{RDF_TYPE}> <{APP}>")).await, 2);
+
+    // the chained conclusion goes with the membership it rests on
+    db_api
+        .delete_from(&triple(SOLO, &format!("{PROP}k8p_namespace"), "solo"), None)
+        .await
+        .unwrap();
+    assert_eq!(count(&db_api, &belongs).await, 33);
+    assert_eq!(
+        count(&db_api, &format!("<{SOLO_APP}> <{RDF_TYPE}> ?x")).await,
+        0
+    );
+    assert_eq!(count(&db_api, &format!("?x <{RDF_TYPE}> <{APP}>")).await, 1);
+
+    // running again replaces rather than duplicates
+    assert_eq!(infer(&db_api).await, 44);
+    assert_eq!(count(&db_api, &belongs).await, 33);
+}
+
+#[test]
+fn test_infer_cli() {
+    let db_file = fresh("/tmp/triples_rules_cli.db");
+    stdout(
+        db_file,
+        &["import-turtle", "--input", "tests/data/k8p_sm.ttl"],
+    );
+    stdout(db_file, &["infer", "--rules", "tests/data/k8p_rules.n3"]);
+
+    let query = format!("SELECT ?x ?app WHERE {{ ?x <{PROP}belongsTo> ?app }}");
+    // a header and 33 memberships
+    let members = stdout(db_file, &["query", &query]);
+    assert_eq!(members.lines().count(), 34);
+    assert!(
+        members.contains("http://k8p.navicore.tech/app/stag/stag-stag-sbjava-lotsofnames-server")
+    );
+    let members = stdout(db_file, &["--exclude-inferred", "query", &query]);
+    assert_eq!(members.lines().count(), 1);
+
+    stdout(db_file, &["infer", "--clear"]);
+    assert_eq!(stdout(db_file, &["query", &query]).lines().count(), 1);
+}
+
+#[tokio::test]
+async fn test_conclusions_outlive_rederived_rdfs_inferences() {
+    const EX: &str = "http://example.com/";
+    let db_api = load("/tmp/triples_rules_rdfs.db", &[]).await;
+    db_api.enable_rdfs().await.unwrap();
+    let sub_class_of = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+    db_api
+        .insert(&triple(
+            &format!("{EX}Pod"),
+            sub_class_of,
+            &format!("{EX}Workload"),
+        ))
+        .await
+        .unwrap();
+    let pod = format!("{EX}pod1");
+    db_api
+        .insert(&triple(&pod, RDF_TYPE, &format!("{EX}Pod")))
+        .await
+        .unwrap();
+    db_api
+        .insert(&triple(&pod, &format!("{EX}label"), "first"))
+        .await
+        .unwrap();
+
+    let rules = triples::rules::parse_rules(
+        &format!("{{ ?x <{RDF_TYPE}> <{EX}Workload> . }} => {{ ?x <{EX}monitored> \"yes\" . }} ."),
+        "test.n3",
+    )
+    .unwrap();
+    assert_eq!(
+        rules_file::infer(rules, RULES_GRAPH, &db_api)
+            .await
+            .unwrap(),
+        1
+    );
+    let monitored = format!("<{pod}> <{EX}monitored> ?x");
+
+    // deleting anything about pod1 re-derives it is a workload
+    db_api
+        .delete_from(&triple(&pod, &format!("{EX}label"), "first"), None)
+        .await
+        .unwrap();
+    assert_eq!(count(&db_api, &monitored).await, 1);
+    db_api.enable_rdfs().await.unwrap();
+    assert_eq!(count(&db_api, &monitored).await, 1);
+
+    // and it goes once that no longer follows
+    db_api
+        .delete_from(&triple(&pod, RDF_TYPE, &format!("{EX}Pod")), None)
+        .await
+        .unwrap();
+    assert_eq!(count(&db_api, &monitored).await, 0);
+}
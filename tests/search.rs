@@ -0,0 +1,97 @@
+mod common;
+
+use common::{fresh, load, stdout};
+use triples::data::{RdfName, Subject};
+use triples::search::TEXT_MATCH;
+use triples::sparql_sql;
+
+const PROP: &str = "http://k8p.navicore.tech/property/";
+
+#[tokio::test]
+async fn test_search() {
+    let db_api = load("/tmp/triples_search.db", &["tests/data/k8p_sm.ttl"]).await;
+
+    let hits = db_api.search("peak live thread", &[]).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].predicate, format!("{PROP}k8p_description"));
+    assert!(hits[0].object.starts_with("The peak live thread count"));
+
+    // any case, any order, and a prefix
+    let hits = db_api.search("LIVE thread*", &[]).await.unwrap();
+    assert_eq!(hits.len(), 2);
+    assert!(hits[0].score >= hits[1].score);
+
+    let descriptions = [format!("{PROP}k8p_description")];
+    let all = db_api.search("threads", &[]).await.unwrap();
+    let described = db_api.search("threads", &descriptions).await.unwrap();
+    assert!(described.len() < all.len());
+    assert!(described.iter().all(|hit| hit.predicate == descriptions[0]));
+
+    // IRIs are not literals to search
+    let mut linked = Subject::new(RdfName::new("http://example.com/pool".to_string()));
+    linked.add(
+        RdfName::new("http://example.com/docs".to_string()),
+        "http://example.com/docs/scheduler".to_string(),
+    );
+    linked.add(
+        RdfName::new("http://example.com/label".to_string()),
+        "the scheduler pool".to_string(),
+    );
+    db_api.insert(&linked).await.unwrap();
+    let hits = db_api.search("scheduler", &[]).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].object, "the scheduler pool");
+
+    assert!(db_api.search("  ", &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_text_match_filter() {
+    let db_api = load("/tmp/triples_text_match.db", &["tests/data/k8p_sm.ttl"]).await;
+
+    let query = sparql_sql::parse(&format!(
+        "SELECT ?s ?d WHERE {{ ?s <{PROP}k8p_description> ?d . FILTER(<{TEXT_MATCH}>(?d, \"memory\")) }}"
+    ))
+    .unwrap();
+    let solutions = db_api.select(&query).await.unwrap();
+    assert_eq!(solutions.rows.len(), 10);
+    assert!(solutions.rows.iter().all(|row| row[1]
+        .as_ref()
+        .unwrap()
+        .value()
+        .to_lowercase()
+        .contains("memory")));
+
+    let query = sparql_sql::parse_form(&format!(
+        "ASK {{ FILTER <{TEXT_MATCH}>(?d, \"nonexistent\") ?s <{PROP}k8p_description> ?d }}"
+    ))
+    .unwrap();
+    assert_eq!(
+        db_api.evaluate(&query).await.unwrap(),
+        triples::sparql_data::QueryResults::Boolean(false)
+    );
+}
+
+#[test]
+fn test_search_cli() {
+    let db_file = fresh("/tmp/triples_search_cli.db");
+    stdout(
+        db_file,
+        &["import-turtle", "--input", "tests/data/k8p_sm.ttl"],
+    );
+
+    let hits = stdout(db_file, &["search", "peak live thread"]);
+    let lines: Vec<&str> = hits.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("score"));
+    assert!(lines[1].contains("prop:k8p_description"));
+
+    let hits = stdout(db_file, &["search", "memory", "--limit", "3"]);
+    assert_eq!(hits.lines().count(), 4);
+
+    let query = format!(
+        "SELECT ?s WHERE {{ ?s <{PROP}k8p_description> ?d FILTER(<{TEXT_MATCH}>(?d, \"uptime\")) }}"
+    );
+    // a header and the one match
+    assert_eq!(stdout(db_file, &["query", &query]).lines().count(), 2);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{fresh, import, load, triples};
+use std::path::Path;
+use triples::shacl_file;
+
+const EX: &str = "http://example.com/building#";
+const SH: &str = "http://www.w3.org/ns/shacl#";
+
+#[tokio::test]
+async fn test_validate_store() {
+    let db_api = load(
+        "/tmp/triples_shacl_store.db",
+        &["tests/data/shacl_store.ttl", "tests/data/shacl_valid.ttl"],
+    )
+    .await;
+    let shapes = shacl_file::read_shapes(Path::new("tests/data/shapes.ttl"))
+        .await
+        .unwrap();
+
+    let report = shacl_file::validate(&shapes, &db_api).await.unwrap();
+    assert!(!report.conforms());
+    assert_eq!(report.violations(), 0);
+    assert_eq!(report.results.len(), 1);
+    assert_eq!(report.results[0].focus_node, format!("{EX}rat1"));
+    assert_eq!(
+        report.results[0].component,
+        format!("{SH}PatternConstraintComponent")
+    );
+
+    import(&db_api, "tests/data/shacl_invalid.ttl").await;
+    let report = shacl_file::validate(&shapes, &db_api).await.unwrap();
+    let mut failures: Vec<(&str, &str)> = report
+        .results
+        .iter()
+        .map(|result| {
+            (
+                result.focus_node.trim_start_matches(EX),
+                result.component.trim_start_matches(SH),
+            )
+        })
+        .collect();
+    failures.sort_unstable();
+    assert_eq!(
+        failures,
+        [
+            ("rat1", "PatternConstraintComponent"),
+            ("sat2", "DatatypeConstraintComponent"),
+            ("sat2", "MinCountConstraintComponent"),
+            ("sp1", "ClassConstraintComponent"),
+        ]
+    );
+    assert_eq!(report.violations(), 3);
+}
+
+#[test]
+fn test_validate_cli() {
+    let db_file = fresh("/tmp/triples_shacl_cli.db");
+    for input in ["tests/data/shacl_store.ttl", "tests/data/shacl_valid.ttl"] {
+        let output = triples(db_file, &["import-turtle", "--input", input]);
+        assert!(output.status.success(), "{output:?}");
+    }
+    let output = triples(db_file, &["validate", "--shapes", "tests/data/shapes.ttl"]);
+    assert!(output.status.success(), "{output:?}");
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.contains("sh:ValidationReport"));
+    assert!(report.contains("sh:Warning"));
+
+    let output = triples(
+        db_file,
+        &["import-turtle", "--input", "tests/data/shacl_invalid.ttl"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let output = triples(db_file, &["validate", "--shapes", "tests/data/shapes.ttl"]);
+    assert!(!output.status.success());
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.contains("sh:MinCountConstraintComponent"));
+    assert!(report.contains("sh:ClassConstraintComponent"));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("ShapeViolations"));
+}
+
+#[test]
+fn test_import_validate_gate() {
+    let db_file = fresh("/tmp/triples_shacl_gate.db");
+    let output = triples(
+        db_file,
+        &["import-turtle", "--input", "tests/data/shacl_store.ttl"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let query = "SELECT ?p WHERE { ?p a <https://brickschema.org/schema/Brick#Point> }";
+
+    let output = triples(
+        db_file,
+        &[
+            "import-turtle",
+            "--validate",
+            "tests/data/shapes.ttl",
+            "--input",
+            "tests/data/shacl_invalid.ttl",
+        ],
+    );
+    assert!(!output.status.success());
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.contains("sh:DatatypeConstraintComponent"));
+    let output = triples(db_file, &["query", query]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 1);
+
+    // warnings do not hold up an import
+    let output = triples(
+        db_file,
+        &[
+            "import-turtle",
+            "--validate",
+            "tests/data/shapes.ttl",
+            "--input",
+            "tests/data/shacl_valid.ttl",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let output = triples(db_file, &["query", query]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 3);
+}
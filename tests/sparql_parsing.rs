@@ -2,6 +2,8 @@
 mod tests {
     use triples::sparql::QueryParser;
     use triples::sparql_data;
+    use triples::sparql_data::{GraphTarget, UpdateOperation};
+    use triples::sparql_sql;
 
     #[test]
     fn test_parse_query() {
@@ -27,16 +29,121 @@ mod tests {
                 triples_block: vec![
                     sparql_data::TriplePattern {
                         subject: sparql_data::Variable::Var("s".to_string()),
-                        predicate: "http://k8p.navicore.tech/property/k8p_appname".to_string(),
+                        predicate: sparql_data::PropertyPath::Iri(
+                            "http://k8p.navicore.tech/property/k8p_appname".to_string()
+                        ),
                         object: sparql_data::Variable::Var("appname".to_string())
                     },
                     sparql_data::TriplePattern {
                         subject: sparql_data::Variable::Var("s".to_string()),
-                        predicate: "http://k8p.navicore.tech/property/k8p_metric_name".to_string(),
+                        predicate: sparql_data::PropertyPath::Iri(
+                            "http://k8p.navicore.tech/property/k8p_metric_name".to_string()
+                        ),
                         object: sparql_data::Variable::Var("metric".to_string())
                     }
                 ],
+                filters: vec![],
             }
         );
     }
+
+    #[test]
+    fn test_parse_property_paths() {
+        use sparql_data::{NegatedIri, PropertyPath};
+
+        let parser = QueryParser::new();
+        let input_query = r#"SELECT ?x WHERE {
+            ?s ^<http://ex.com/p>/(<http://ex.com/q>|<http://ex.com/r>)+ ?x .
+            ?x !(a|^<http://ex.com/p>) ?y .
+            ?y <http://ex.com/p>*/<http://ex.com/q>? "z"
+        }"#;
+
+        let result = parser.parse(input_query);
+        assert!(result.is_ok(), "{result:?}");
+        let query = result.unwrap();
+
+        let p = || PropertyPath::Iri("http://ex.com/p".to_string());
+        let q = || PropertyPath::Iri("http://ex.com/q".to_string());
+        let r = || PropertyPath::Iri("http://ex.com/r".to_string());
+
+        assert!(!query.select_clause.distinct);
+        assert_eq!(
+            query.triples_block[0].predicate,
+            PropertyPath::Sequence(vec![
+                PropertyPath::Inverse(Box::new(p())),
+                PropertyPath::OneOrMore(Box::new(PropertyPath::Alternative(vec![q(), r()]))),
+            ])
+        );
+        assert_eq!(
+            query.triples_block[1].predicate,
+            PropertyPath::NegatedSet(vec![
+                NegatedIri::Forward("http://www.w3.org/1999/02/22-rdf-syntax-ns#type".to_string()),
+                NegatedIri::Inverse("http://ex.com/p".to_string()),
+            ])
+        );
+        assert_eq!(
+            query.triples_block[2].predicate,
+            PropertyPath::Sequence(vec![
+                PropertyPath::ZeroOrMore(Box::new(p())),
+                PropertyPath::ZeroOrOne(Box::new(q())),
+            ])
+        );
+        assert_eq!(
+            query.triples_block[2].object,
+            sparql_data::Variable::Literal("z".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_update() {
+        let input = r#"
+        INSERT DATA {
+            <http://ex.com/a> a <http://ex.com/T> .
+            GRAPH <http://ex.com/g> { <http://ex.com/a> <http://ex.com/p> "v" }
+        } ;
+        DELETE DATA { <http://ex.com/a> <http://ex.com/p> <http://ex.com/b> } ;
+        CLEAR GRAPH <http://ex.com/g> ;
+        DROP SILENT ALL
+        "#;
+
+        let operations = sparql_sql::parse_update(input).unwrap();
+        assert_eq!(operations.len(), 4);
+        let UpdateOperation::InsertData(quads) = &operations[0] else {
+            panic!("expected INSERT DATA, got {:?}", operations[0]);
+        };
+        assert_eq!(quads.len(), 2);
+        assert_eq!(quads[0].graph, None);
+        assert_eq!(
+            quads[0].predicate,
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#type"
+        );
+        assert_eq!(quads[1].graph.as_deref(), Some("http://ex.com/g"));
+        assert_eq!(quads[1].object, "v");
+        assert!(matches!(operations[1], UpdateOperation::DeleteData(_)));
+        assert_eq!(
+            operations[2],
+            UpdateOperation::Clear(GraphTarget::Named("http://ex.com/g".to_string()))
+        );
+        assert_eq!(operations[3], UpdateOperation::Clear(GraphTarget::All));
+    }
+
+    #[test]
+    fn test_parse_escaped_literals() {
+        let query = QueryParser::new()
+            .parse(r#"SELECT ?s WHERE { ?s <http://ex.com/p> "say \"hi\"\n\\o/" }"#)
+            .unwrap();
+        assert_eq!(
+            query.triples_block[0].object,
+            sparql_data::Variable::Literal("say \"hi\"\n\\o/".to_string())
+        );
+
+        let operations = sparql_sql::parse_update(
+            r#"INSERT DATA { <http://ex.com/a> <http://ex.com/p> "a\tb" }"#,
+        )
+        .unwrap();
+        let UpdateOperation::InsertData(quads) = &operations[0] else {
+            panic!("expected INSERT DATA, got {:?}", operations[0]);
+        };
+        assert_eq!(quads[0].object, "a\tb");
+    }
 }
@@ -0,0 +1,133 @@
+mod common;
+
+use common::load;
+use triples::db_api::DbApi;
+use triples::sparql_data::QueryResults;
+use triples::sparql_sql;
+
+const EX: &str = "http://example#";
+
+async fn select_column(db_api: &DbApi, query: &str) -> Vec<String> {
+    let query = sparql_sql::parse(query).unwrap();
+    let solutions = db_api.select(&query).await.unwrap();
+    let mut values: Vec<String> = solutions
+        .rows
+        .into_iter()
+        .filter_map(|row| row.into_iter().next().flatten())
+        .map(|term| term.value().to_string())
+        .collect();
+    values.sort();
+    values
+}
+
+#[tokio::test]
+async fn test_sequence_and_inverse_paths() {
+    let db_api = load(
+        "/tmp/triples_sparql_paths_seq.db",
+        &["tests/data/bricks.ttl"],
+    )
+    .await;
+
+    // all points of equipment located in a room that is part of the building
+    let points = select_column(
+        &db_api,
+        r"SELECT DISTINCT ?point WHERE {
+            <http://example#ExampleBuilding> ^<https://brickschema.org/schema/1.1/Brick#isPartOf>/^<https://brickschema.org/schema/1.1/Brick#isLocatedIn>/<https://brickschema.org/schema/1.1/Brick#hasPoint> ?point
+        }",
+    )
+    .await;
+    assert_eq!(
+        points,
+        vec![
+            format!("{EX}FanControl"),
+            format!("{EX}TempSensor"),
+            format!("{EX}TempSetpoint"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_recursive_paths() {
+    let db_api = load(
+        "/tmp/triples_sparql_paths_rec.db",
+        &["tests/data/bricks.ttl"],
+    )
+    .await;
+
+    // everything the fan reaches through any chain of brick relationships
+    let reachable = select_column(
+        &db_api,
+        r"SELECT DISTINCT ?x WHERE {
+            <http://example#Room101_Fan> (<https://brickschema.org/schema/1.1/Brick#isLocatedIn>|<https://brickschema.org/schema/1.1/Brick#isPartOf>)+ ?x
+        }",
+    )
+    .await;
+    assert_eq!(
+        reachable,
+        vec![format!("{EX}ExampleBuilding"), format!("{EX}Room_101")]
+    );
+
+    // zero length paths include the start node
+    let star = select_column(
+        &db_api,
+        r"SELECT DISTINCT ?x WHERE {
+            <http://example#Room_101> <https://brickschema.org/schema/1.1/Brick#isPartOf>* ?x
+        }",
+    )
+    .await;
+    assert_eq!(
+        star,
+        vec![format!("{EX}ExampleBuilding"), format!("{EX}Room_101")]
+    );
+
+    let optional = select_column(
+        &db_api,
+        r"SELECT DISTINCT ?x WHERE {
+            <http://example#ExampleBuilding> <https://brickschema.org/schema/1.1/Brick#isPartOf>? ?x
+        }",
+    )
+    .await;
+    assert_eq!(optional, vec![format!("{EX}ExampleBuilding")]);
+}
+
+#[tokio::test]
+async fn test_negated_property_set() {
+    let db_api = load(
+        "/tmp/triples_sparql_paths_neg.db",
+        &["tests/data/bricks.ttl"],
+    )
+    .await;
+
+    let targets = select_column(
+        &db_api,
+        r"SELECT DISTINCT ?x WHERE {
+            <http://example#Room101_Fan> !(a|<https://brickschema.org/schema/1.1/Brick#hasPoint>) ?x
+        }",
+    )
+    .await;
+    assert_eq!(targets, vec![format!("{EX}Room_101")]);
+}
+
+#[tokio::test]
+async fn test_ask_query() {
+    let db_api = load(
+        "/tmp/triples_sparql_paths_ask.db",
+        &["tests/data/bricks.ttl"],
+    )
+    .await;
+
+    for (text, expected) in [
+        (
+            "ASK { <http://example#Room101_Fan> <https://brickschema.org/schema/1.1/Brick#isLocatedIn>/<https://brickschema.org/schema/1.1/Brick#isPartOf>+ <http://example#ExampleBuilding> }",
+            true,
+        ),
+        (
+            "ASK WHERE { <http://example#ExampleBuilding> <https://brickschema.org/schema/1.1/Brick#isLocatedIn> ?x }",
+            false,
+        ),
+    ] {
+        let form = sparql_sql::parse_form(text).unwrap();
+        let results = db_api.evaluate(&form).await.unwrap();
+        assert_eq!(results, QueryResults::Boolean(expected), "{text}");
+    }
+}
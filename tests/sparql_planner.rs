@@ -0,0 +1,61 @@
+mod common;
+
+use common::load;
+use triples::sparql_sql;
+
+const APPNAME: &str = "http://k8p.navicore.tech/property/k8p_appname";
+const METRIC: &str = "http://k8p.navicore.tech/property/k8p_metric_name";
+
+#[tokio::test]
+async fn test_statistics_maintained_on_insert() {
+    let db_api = load("/tmp/triples_planner_stats.db", &["tests/data/k8p_sm.ttl"]).await;
+
+    let stats = db_api.planner_statistics().await.unwrap();
+    let appname = stats.predicates.get(APPNAME).unwrap();
+    assert_eq!(appname.triples, 33);
+    assert_eq!(appname.subjects, 33);
+    assert!(appname.objects < appname.subjects);
+
+    let metric = stats.predicates.get(METRIC).unwrap();
+    assert_eq!(metric.triples, 33);
+}
+
+#[tokio::test]
+async fn test_explain_orders_selective_pattern_first() {
+    let db_api = load(
+        "/tmp/triples_planner_explain.db",
+        &["tests/data/k8p_sm.ttl"],
+    )
+    .await;
+
+    let text = format!(
+        r#"SELECT DISTINCT ?s WHERE {{
+            ?s <{APPNAME}> ?app .
+            ?s <{METRIC}> "system_cpu_count"
+        }}"#
+    );
+    let query = sparql_sql::parse(&text).unwrap();
+    let explanation = db_api.explain(&query).await.unwrap();
+
+    assert_eq!(explanation.plan.steps[0].pattern, 1);
+    assert!(explanation.sql.sql.contains("CROSS JOIN"));
+
+    // the selective pattern is read by predicate and object, then joined
+    // by predicate and subject
+    let indexes: Vec<&str> = explanation
+        .database_plan
+        .iter()
+        .filter_map(|detail| {
+            ["idx_pos", "idx_pso", "idx_osp"]
+                .into_iter()
+                .find(|index| detail.contains(index))
+        })
+        .collect();
+    assert_eq!(indexes, ["idx_pos", "idx_pso"]);
+
+    // the planned query returns what the written order returns
+    let planned = db_api.select(&query).await.unwrap();
+    let naive = sparql_sql::compile(&query).unwrap();
+    assert_eq!(planned.variables, naive.variables);
+    assert_eq!(planned.rows.len(), 1);
+}
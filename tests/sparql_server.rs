@@ -0,0 +1,271 @@
+use std::fs;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use triples::data::{RdfName, Subject};
+use triples::db_api::DbApi;
+use triples::sparql_server;
+
+async fn start_server(db_file: &str) -> SocketAddr {
+    let _ = fs::remove_file(db_file);
+    let db_api = DbApi::new(db_file.to_string()).await.unwrap();
+
+    let mut subject = Subject::new(RdfName::new("http://example#fan".to_string()));
+    subject.add(
+        RdfName::new("http://example#locatedIn".to_string()),
+        "http://example#room".to_string(),
+    );
+    db_api.insert(&subject).await.unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = sparql_server::router(Arc::new(db_api));
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    addr
+}
+
+/// send one HTTP/1.1 request and return the status code and body
+async fn request(
+    addr: SocketAddr,
+    method: &str,
+    target: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut text = format!("{method} {target} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        text.push_str(&format!("{name}: {value}\r\n"));
+    }
+    text.push_str(&format!("Content-Length: {}\r\n\r\n{body}", body.len()));
+    stream.write_all(text.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let status = response[9..12].parse().unwrap();
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default();
+    (status, body)
+}
+
+#[tokio::test]
+async fn test_query_content_negotiation() {
+    let addr = start_server("/tmp/triples_server_query.db").await;
+
+    let query = "/sparql?query=SELECT%20%3Fs%20WHERE%20%7B%20%3Fs%20%3Chttp%3A%2F%2Fexample%23locatedIn%3E%20%3Fo%20%7D";
+
+    let (status, body) = request(addr, "GET", query, &[], "").await;
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""value":"http://example#fan""#), "{body}");
+
+    let (status, body) = request(addr, "GET", query, &[("Accept", "text/csv")], "").await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "s\r\nhttp://example#fan\r\n");
+
+    let (status, _) = request(addr, "GET", query, &[("Accept", "image/png")], "").await;
+    assert_eq!(status, 406);
+
+    let (status, body) = request(
+        addr,
+        "POST",
+        "/sparql",
+        &[
+            ("Content-Type", "application/sparql-query"),
+            ("Accept", "application/sparql-results+xml"),
+        ],
+        "ASK { <http://example#fan> ?p ?o }",
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert!(body.contains("<boolean>true</boolean>"), "{body}");
+
+    let (status, _) = request(
+        addr,
+        "POST",
+        "/sparql",
+        &[("Content-Type", "application/sparql-query")],
+        "SELECT nonsense",
+    )
+    .await;
+    assert_eq!(status, 400);
+
+    // parsed but not translatable, still the client's error
+    let (status, body) = request(
+        addr,
+        "POST",
+        "/sparql",
+        &[("Content-Type", "application/sparql-query")],
+        "SELECT <http://example#fan> WHERE { ?s ?p ?o }",
+    )
+    .await;
+    assert_eq!(status, 400, "{body}");
+}
+
+#[tokio::test]
+async fn test_update_and_graph_store() {
+    let addr = start_server("/tmp/triples_server_graphs.db").await;
+    let graph = "/graphs?graph=http%3A%2F%2Fexample%23g";
+
+    let (status, _) = request(addr, "GET", graph, &[], "").await;
+    assert_eq!(status, 404);
+
+    let (status, _) = request(
+        addr,
+        "POST",
+        "/sparql",
+        &[("Content-Type", "application/x-www-form-urlencoded")],
+        "update=INSERT+DATA+%7B+GRAPH+%3Chttp%3A%2F%2Fexample%23g%3E+%7B+%3Chttp%3A%2F%2Fexample%23a%3E+%3Chttp%3A%2F%2Fexample%23p%3E+%22one%22+%7D+%7D",
+    )
+    .await;
+    assert_eq!(status, 204);
+
+    let (status, body) = request(addr, "GET", graph, &[], "").await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "<http://example#a> <http://example#p> \"one\" .\n");
+
+    // the default graph is untouched by writes to a named graph
+    let (_, body) = request(addr, "GET", "/graphs?default", &[], "").await;
+    assert!(!body.contains("example#a"), "{body}");
+
+    let (status, _) = request(
+        addr,
+        "PUT",
+        graph,
+        &[("Content-Type", "text/turtle")],
+        "<http://example#b> <http://example#p> \"two\" .\n",
+    )
+    .await;
+    assert_eq!(status, 204);
+
+    let (_, body) = request(addr, "GET", graph, &[], "").await;
+    assert_eq!(body, "<http://example#b> <http://example#p> \"two\" .\n");
+
+    let (status, body) = request(addr, "GET", graph, &[("Accept", "text/turtle")], "").await;
+    assert_eq!(status, 200);
+    assert!(body.starts_with("@prefix "), "{body}");
+    assert!(body.contains("\"two\""), "{body}");
+
+    let (_, body) = request(
+        addr,
+        "GET",
+        graph,
+        &[("Accept", "text/turtle;q=0.5, application/n-triples")],
+        "",
+    )
+    .await;
+    assert_eq!(body, "<http://example#b> <http://example#p> \"two\" .\n");
+
+    let (status, _) = request(addr, "GET", graph, &[("Accept", "image/png")], "").await;
+    assert_eq!(status, 406);
+
+    let (status, _) = request(
+        addr,
+        "POST",
+        "/sparql",
+        &[("Content-Type", "application/sparql-update")],
+        r#"DELETE DATA { GRAPH <http://example#g> { <http://example#b> <http://example#p> "two" } }"#,
+    )
+    .await;
+    assert_eq!(status, 204);
+
+    let (status, _) = request(addr, "DELETE", graph, &[], "").await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_graph_store_reads_tagged_and_typed_literals() {
+    let addr = start_server("/tmp/triples_server_literals.db").await;
+    let graph = "/graphs?graph=http%3A%2F%2Fexample%23g";
+
+    let (status, body) = request(
+        addr,
+        "PUT",
+        graph,
+        &[("Content-Type", "application/n-triples")],
+        concat!(
+            "<http://example#a> <http://example#label> \"fan\"@en .\n",
+            "<http://example#a> <http://example#speed> ",
+            "\"5\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n",
+            "<urn:x:b> <http://example#p> _:b1 .\n",
+        ),
+    )
+    .await;
+    assert_eq!(status, 201, "{body}");
+    let (_, body) = request(addr, "GET", graph, &[], "").await;
+    assert!(body.contains("<http://example#label> \"fan\" ."), "{body}");
+    assert!(body.contains("<http://example#speed> \"5\" ."), "{body}");
+    assert!(
+        body.contains("<urn:x:b> <http://example#p> _:b1 ."),
+        "{body}"
+    );
+
+    let (status, body) = request(
+        addr,
+        "PUT",
+        graph,
+        &[("Content-Type", "text/turtle")],
+        concat!(
+            "@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n",
+            "<http://example#c> <http://example#label> \"pompe\"@fr-BE ;\n",
+            "    <http://example#speed> \"7\"^^xsd:integer .\n",
+        ),
+    )
+    .await;
+    assert_eq!(status, 204, "{body}");
+    let (_, body) = request(addr, "GET", graph, &[], "").await;
+    assert!(
+        body.contains("<http://example#label> \"pompe\" ."),
+        "{body}"
+    );
+    assert!(body.contains("<http://example#speed> \"7\" ."), "{body}");
+
+    let (status, _) = request(
+        addr,
+        "PUT",
+        graph,
+        &[("Content-Type", "application/n-triples")],
+        "<http://example#a> <http://example#p> \"open .\n",
+    )
+    .await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn test_graph_store_turtle_round_trips() {
+    let addr = start_server("/tmp/triples_server_round_trip.db").await;
+    let graph = "/graphs?graph=http%3A%2F%2Fexample%23g";
+
+    let (status, body) = request(
+        addr,
+        "PUT",
+        graph,
+        &[("Content-Type", "application/n-triples")],
+        concat!(
+            "<http://example#a> <http://example#p> _:b1 .\n",
+            "_:b1 <http://example#note> \"two\\nlines\" .\n",
+        ),
+    )
+    .await;
+    assert_eq!(status, 201, "{body}");
+
+    let (status, turtle) = request(addr, "GET", graph, &[("Accept", "text/turtle")], "").await;
+    assert_eq!(status, 200);
+    let (_, before) = request(addr, "GET", graph, &[], "").await;
+
+    let (status, body) = request(
+        addr,
+        "PUT",
+        graph,
+        &[("Content-Type", "text/turtle")],
+        &turtle,
+    )
+    .await;
+    assert_eq!(status, 204, "{body}\n{turtle}");
+
+    let (_, after) = request(addr, "GET", graph, &[], "").await;
+    assert_eq!(before, after);
+    assert!(after.contains("<http://example#p> _:b1 ."), "{after}");
+}
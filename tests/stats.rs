@@ -0,0 +1,102 @@
+mod common;
+
+use common::{fresh, triples};
+use triples::data::{RdfName, Subject};
+use triples::db_api::DbApi;
+
+const BRICK: &str = "https://brickschema.org/schema/Brick#";
+const EX: &str = "http://example.com/building#";
+const GRAPH: &str = "http://example.com/graphs/2024";
+
+async fn load(db_file: &str) -> DbApi {
+    let db_api = common::load(
+        db_file,
+        &["tests/data/shacl_store.ttl", "tests/data/shacl_valid.ttl"],
+    )
+    .await;
+    let mut subject = Subject::new(RdfName::new(format!("{EX}sat1")));
+    subject.add(RdfName::new(format!("{EX}value")), "71.5".to_string());
+    subject.add(RdfName::new(format!("{EX}unit")), "degF".to_string());
+    db_api
+        .insert_into(&subject, Some(&RdfName::new(GRAPH.to_string())))
+        .await
+        .unwrap();
+    db_api
+}
+
+#[tokio::test]
+async fn test_stats() {
+    let mut db_api = load("/tmp/triples_stats.db").await;
+
+    let stats = db_api.stats().await.unwrap();
+    assert_eq!(
+        (
+            stats.triples,
+            stats.subjects,
+            stats.predicates,
+            stats.objects
+        ),
+        (11, 4, 4, 8)
+    );
+    let graphs: Vec<(Option<&str>, u64, u64)> = stats
+        .graphs
+        .iter()
+        .map(|g| (g.graph.as_deref(), g.triples, g.subjects))
+        .collect();
+    assert_eq!(graphs, [(None, 9, 4), (Some(GRAPH), 2, 1)]);
+    assert_eq!(
+        stats.classes,
+        [
+            (format!("{BRICK}Equipment"), 1),
+            (format!("{BRICK}Point"), 2),
+            (format!("{BRICK}Room"), 1)
+        ]
+    );
+    assert!(stats.file_size > 0);
+    assert!(stats
+        .storage
+        .iter()
+        .any(|s| s.name == "idx_predicate" && s.kind == "index" && s.bytes > 0));
+
+    let mut point = Subject::new(RdfName::new(format!("{BRICK}Point")));
+    point.add(
+        RdfName::new("http://www.w3.org/2000/01/rdf-schema#subClassOf".to_string()),
+        format!("{BRICK}Entity"),
+    );
+    db_api.insert(&point).await.unwrap();
+    db_api.enable_rdfs().await.unwrap();
+    assert_eq!(db_api.stats().await.unwrap().graphs.len(), 3);
+    db_api.set_include_inferred(false);
+    let stats = db_api.stats().await.unwrap();
+    assert_eq!(stats.triples, 12);
+    assert_eq!(stats.graphs.len(), 2);
+}
+
+#[test]
+fn test_stats_cli() {
+    let db_file = fresh("/tmp/triples_stats_cli.db");
+    let output = triples(
+        db_file,
+        &[
+            "import-turtle",
+            "--input",
+            "tests/data/shacl_store.ttl",
+            "tests/data/shacl_valid.ttl",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    let output = triples(db_file, &["stats"]);
+    assert!(output.status.success(), "{output:?}");
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.starts_with("triples 9, subjects 4, predicates 4, objects 7\n"));
+    assert!(report.contains("brick:Point"));
+    assert!(report.contains("idx_subject"));
+
+    let output = triples(db_file, &["stats", "--void"]);
+    assert!(output.status.success(), "{output:?}");
+    let void = String::from_utf8(output.stdout).unwrap();
+    assert!(void.contains("a void:Dataset"));
+    assert!(void.contains("void:triples 9 "), "{void}");
+    assert!(void.contains("void:classPartition"));
+}
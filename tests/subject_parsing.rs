@@ -1,6 +1,7 @@
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
+use triples::data::RdfName;
 use triples::turtle_stream::TurtleStream;
 
 #[test]
@@ -55,3 +56,57 @@ fn test_ttl_to_subject() {
         }
     }
 }
+
+#[test]
+fn test_a_without_rdf_prefix() {
+    let mut stream = TurtleStream::new();
+    assert!(stream
+        .load("@prefix ex: <http://example#> .")
+        .unwrap()
+        .is_none());
+
+    // `a` expands to rdf:type even though no rdf: prefix is declared
+    assert!(stream.load("ex:fan a ex:Device ,").unwrap().is_none());
+    let fan = stream.load("    ex:Fan .").unwrap().unwrap();
+    assert_eq!(fan.name().to_string(), "http://example#fan");
+    let rdf_type = RdfName::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type".to_string());
+    assert!(fan
+        .get(&rdf_type)
+        .unwrap()
+        .contains(&"http://example#Fan".to_string()));
+}
+
+#[test]
+fn test_single_line_triple_finishes_its_subject() {
+    let mut stream = TurtleStream::new();
+    assert!(stream
+        .load("@prefix ex: <http://example#> .")
+        .unwrap()
+        .is_none());
+
+    let room = stream
+        .load("ex:room ex:contains ex:fan .")
+        .unwrap()
+        .unwrap();
+    assert_eq!(room.name().to_string(), "http://example#room");
+    let contains = RdfName::new("http://example#contains".to_string());
+    assert!(room
+        .get(&contains)
+        .unwrap()
+        .contains(&"http://example#fan".to_string()));
+
+    // the next line starts a subject of its own
+    let fan = stream.load("ex:fan ex:speed \"5\" .").unwrap().unwrap();
+    assert_eq!(fan.name().to_string(), "http://example#fan");
+    assert!(fan.get(&contains).is_none());
+}
+
+#[test]
+fn test_blank_node_labels() {
+    let mut stream = TurtleStream::new();
+    stream.load("@prefix ex: <http://example.com/> .").unwrap();
+    let subject = stream.load("_:shape ex:property _:p1 .").unwrap().unwrap();
+    assert_eq!(subject.name().to_string(), "_:shape");
+    let objects: Vec<&String> = subject.all_objects().flatten().collect();
+    assert_eq!(objects, ["_:p1"]);
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use common::{fresh, load, triples};
+use triples::traversal::{Direction, Step};
+
+const BRICK: &str = "https://brickschema.org/schema/Brick#";
+const EX: &str = "http://example.com/building#";
+
+fn ex(local: &str) -> String {
+    format!("{EX}{local}")
+}
+
+fn local(iri: &str) -> &str {
+    iri.rsplit(['#', '/']).next().unwrap()
+}
+
+/// each step as its local names, `^` marking a step against the edge
+fn described(steps: &[Step]) -> Vec<String> {
+    steps
+        .iter()
+        .map(|step| {
+            let mark = if step.forward { "" } else { "^" };
+            format!(
+                "{} {mark}{} {} {}",
+                local(&step.subject),
+                local(&step.predicate),
+                local(&step.object),
+                step.hops
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_neighbourhood() {
+    let db_api = load(
+        "/tmp/triples_neighbourhood.db",
+        &["tests/data/traversal.ttl"],
+    )
+    .await;
+
+    let steps = db_api
+        .neighbourhood(&ex("ahu1"), 1, Direction::Outgoing, &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        described(&steps),
+        ["ahu1 type AHU 1", "ahu1 feeds vav1 1", "ahu1 feeds vav2 1"]
+    );
+
+    let steps = db_api
+        .neighbourhood(&ex("ahu1"), 2, Direction::Incoming, &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        described(&steps),
+        ["rat1 ^isPointOf ahu1 1", "sat1 ^isPointOf ahu1 1"]
+    );
+
+    // literals are not nodes, so a shared value does not make neighbours
+    let steps = db_api
+        .neighbourhood(&ex("sat1"), 1, Direction::Both, &[])
+        .await
+        .unwrap();
+    assert_eq!(described(&steps), ["sat1 isPointOf ahu1 1"]);
+
+    let steps = db_api
+        .neighbourhood(&ex("zone1"), 3, Direction::Both, &[ex("adjacentTo")])
+        .await
+        .unwrap();
+    assert_eq!(
+        described(&steps),
+        [
+            "zone1 adjacentTo zone2 1",
+            "zone2 ^adjacentTo zone1 1",
+            "zone1 ^adjacentTo zone2 2",
+            "zone2 adjacentTo zone1 2"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_shortest_path() {
+    let db_api = load(
+        "/tmp/triples_shortest_path.db",
+        &["tests/data/traversal.ttl"],
+    )
+    .await;
+
+    let path = db_api
+        .shortest_path(&ex("sat1"), &ex("building1"), 10, Direction::Outgoing, &[])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        described(&path),
+        [
+            "sat1 isPointOf ahu1 1",
+            "ahu1 feeds vav1 2",
+            "vav1 feeds zone1 3",
+            "zone1 isPartOf floor1 4",
+            "floor1 isPartOf building1 5"
+        ]
+    );
+    assert!(db_api
+        .shortest_path(&ex("sat1"), &ex("building1"), 4, Direction::Outgoing, &[])
+        .await
+        .unwrap()
+        .is_none());
+
+    let path = db_api
+        .shortest_path(&ex("sat1"), &ex("rat1"), 10, Direction::Both, &[])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        described(&path),
+        ["sat1 isPointOf ahu1 1", "rat1 ^isPointOf ahu1 2"]
+    );
+    // the walk stops at the hop reaching the end, however far it may go
+    let path = db_api
+        .shortest_path(&ex("sat1"), &ex("rat1"), usize::MAX, Direction::Both, &[])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(path.len(), 2);
+    assert_eq!(
+        db_api
+            .shortest_path(&ex("sat1"), &ex("sat1"), 10, Direction::Both, &[])
+            .await
+            .unwrap(),
+        Some(Vec::new())
+    );
+
+    let feeds = [format!("{BRICK}feeds")];
+    assert!(db_api
+        .shortest_path(&ex("sat1"), &ex("zone1"), 10, Direction::Outgoing, &feeds)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_reachable() {
+    let db_api = load("/tmp/triples_reachable.db", &["tests/data/traversal.ttl"]).await;
+
+    let feeds = [format!("{BRICK}feeds")];
+    let reached = db_api
+        .reachable(&ex("ahu1"), Direction::Outgoing, &feeds)
+        .await
+        .unwrap();
+    assert_eq!(reached, ["vav1", "vav2", "zone1", "zone2"].map(ex));
+
+    // cycles end, and the resource is not among what it reaches
+    let reached = db_api
+        .reachable(&ex("zone1"), Direction::Outgoing, &[ex("adjacentTo")])
+        .await
+        .unwrap();
+    assert_eq!(reached, [ex("zone2")]);
+
+    let reached = db_api
+        .reachable(&ex("building1"), Direction::Incoming, &[])
+        .await
+        .unwrap();
+    assert_eq!(reached.len(), 8);
+}
+
+#[test]
+fn test_path_cli() {
+    let db_file = fresh("/tmp/triples_path_cli.db");
+    let output = triples(
+        db_file,
+        &["import-turtle", "--input", "tests/data/traversal.ttl"],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    let output = triples(db_file, &["path", "ex:sat1", "ex:building1"]);
+    assert!(output.status.success(), "{output:?}");
+    let path = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(path.lines().count(), 5);
+    assert_eq!(path.lines().next(), Some("ex:sat1 brick:isPointOf ex:ahu1"));
+
+    let output = triples(
+        db_file,
+        &["path", "ex:sat1", "ex:rat1", "--direction", "both"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let path = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        path.lines().last(),
+        Some("ex:rat1 ^brick:isPointOf ex:ahu1")
+    );
+
+    let output = triples(
+        db_file,
+        &["path", "ex:sat1", "ex:zone1", "--predicates", "brick:feeds"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("NoPath"));
+}
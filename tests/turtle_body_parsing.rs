@@ -129,7 +129,7 @@ fn test_escaped_quotes_in_object() {
             Some(Pre::new("prop".to_string())),
             RdfName::new("k8p_description".to_string()),
             None,
-            r#"The \"recent cpu usage\" of the system the application is running in"#.to_string(),
+            r#"The "recent cpu usage" of the system the application is running in"#.to_string(),
             true
         )
     );
@@ -0,0 +1,194 @@
+use std::fs;
+use std::fs::File;
+use std::process::Command;
+use triples::data::Subject;
+use triples::db_api::DbApi;
+use triples::turtle_stream::TurtleStream;
+
+fn read_subjects(path: &str) -> Vec<Subject> {
+    let text = fs::read_to_string(path).expect("Failed to read file");
+    let mut stream = TurtleStream::new();
+    text.lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| stream.load(line).unwrap())
+        .collect()
+}
+
+async fn load(db_file: &str, subjects: impl Iterator<Item = &Subject>) {
+    let _ = fs::remove_file(db_file);
+    let db_api = DbApi::new(db_file.to_string()).await.unwrap();
+    for subject in subjects {
+        db_api.insert(subject).await.expect("Insert failed");
+    }
+}
+
+fn export(db_file: &str, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_triples"))
+        .args(["--db-location", db_file, "export-turtle"])
+        .args(args)
+        .output()
+        .expect("Failed to run triples");
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn export_canonical(db_file: &str) -> String {
+    export(db_file, &["--canonical"])
+}
+
+fn import(db_file: &str, ttl_file: &str) {
+    let _ = fs::remove_file(db_file);
+    let status = Command::new(env!("CARGO_BIN_EXE_triples"))
+        .args(["--db-location", db_file, "import-turtle"])
+        .stdin(File::open(ttl_file).expect("Failed to open file"))
+        .status()
+        .expect("Failed to run triples");
+    assert!(status.success());
+}
+
+#[tokio::test]
+async fn test_canonical_export_ignores_load_order() {
+    let subjects = read_subjects("tests/data/k8p_sm.ttl");
+    load("/tmp/triples_export_forward.db", subjects.iter()).await;
+    load("/tmp/triples_export_reverse.db", subjects.iter().rev()).await;
+
+    let forward = export_canonical("/tmp/triples_export_forward.db");
+    let reverse = export_canonical("/tmp/triples_export_reverse.db");
+
+    assert!(!forward.is_empty());
+    assert_eq!(forward, reverse);
+    assert_eq!(forward, export_canonical("/tmp/triples_export_forward.db"));
+}
+
+#[test]
+fn test_export_reuses_declared_prefixes() {
+    let db_file = "/tmp/triples_export_prefixes.db";
+    import(db_file, "tests/data/k8p_sm.ttl");
+
+    let exported = export(db_file, &["--canonical"]);
+    assert!(exported.contains("@prefix res: <http://k8p.navicore.tech/resource/"));
+    assert!(exported.contains("@prefix prop: <http://k8p.navicore.tech/property/"));
+
+    // a prefix file overrides the declared names
+    let prefix_file = "/tmp/triples_export_prefixes.ttl";
+    fs::write(
+        prefix_file,
+        "@prefix r: <http://k8p.navicore.tech/resource/> .\n",
+    )
+    .unwrap();
+    let exported = export(db_file, &["--canonical", "--prefixes", prefix_file]);
+    assert!(exported.contains("@prefix r: <http://k8p.navicore.tech/resource/"));
+    assert!(exported.contains("@prefix prop: <http://k8p.navicore.tech/property/"));
+    assert!(!exported.contains("@prefix res:"));
+}
+
+#[test]
+fn test_export_round_trips_slash_and_hash_namespaces() {
+    let db_file = "/tmp/triples_export_units.db";
+    import(db_file, "tests/data/units.ttl");
+    let exported = export(db_file, &["--canonical"]);
+
+    assert!(exported.contains("@prefix unit: <http://qudt.org/vocab/unit/> ."));
+    assert!(exported.contains("qudt:hasUnit unit:DEG_C"), "{exported}");
+    assert!(exported.contains("ex:TempSensor_1.2"), "{exported}");
+    assert!(exported.contains("    a brick:Air_Temperature_Sensor ;"));
+    assert!(exported.contains("<http://example.com/query?id=1>"));
+
+    let exported_file = "/tmp/triples_export_units.ttl";
+    fs::write(exported_file, &exported).unwrap();
+    let reimported_db = "/tmp/triples_export_units_again.db";
+    import(reimported_db, exported_file);
+    assert_eq!(exported, export(reimported_db, &["--canonical"]));
+}
+
+#[test]
+fn test_export_escapes_literals() {
+    let db_file = "/tmp/triples_export_escapes.db";
+    import(db_file, "tests/data/units.ttl");
+    let exported = export(db_file, &["--canonical", "--indent", "2"]);
+
+    assert!(
+        exported.contains(r#"  ex:comment "the \"big\" room\tnorth" ;"#),
+        "{exported}"
+    );
+    assert!(!exported.contains("@prefix rdf:"), "{exported}");
+}
+
+#[test]
+fn test_export_round_trips_bricks() {
+    let db_file = "/tmp/triples_export_bricks.db";
+    import(db_file, "tests/data/bricks_ex1.ttl");
+    let exported = export(db_file, &[]);
+    assert!(exported.contains("@prefix ahui: "), "{exported}");
+
+    let exported_file = "/tmp/triples_export_bricks.ttl";
+    fs::write(exported_file, &exported).unwrap();
+    let reimported_db = "/tmp/triples_export_bricks_again.db";
+    import(reimported_db, exported_file);
+    assert_eq!(export_canonical(db_file), export_canonical(reimported_db));
+}
+
+#[test]
+fn test_export_round_trips_bare_numbers_and_booleans() {
+    let source = "/tmp/triples_export_numbers_source.ttl";
+    fs::write(
+        source,
+        concat!(
+            "@prefix ex: <http://example.com/> .\n",
+            "ex:s ex:p \"-5\" ,\n",
+            "        \"+5\" ,\n",
+            "        \"1e+3\" ,\n",
+            "        \"1.5e-3\" ,\n",
+            "        \".5\" .\n",
+            "ex:s ex:q \"true\" ;\n",
+            "    ex:r \"2.50\" ,\n",
+            "        \"-0.5\" .\n",
+        ),
+    )
+    .unwrap();
+    let db_file = "/tmp/triples_export_numbers.db";
+    import(db_file, source);
+    let exported = export_canonical(db_file);
+    assert!(exported.contains("ex:q true ;"), "{exported}");
+    assert!(exported.contains("ex:p +5 ,\n         -5 ,"), "{exported}");
+    assert!(!exported.contains('"'), "{exported}");
+
+    let exported_file = "/tmp/triples_export_numbers.ttl";
+    fs::write(exported_file, &exported).unwrap();
+    let reimported_db = "/tmp/triples_export_numbers_again.db";
+    import(reimported_db, exported_file);
+    assert_eq!(exported, export_canonical(reimported_db));
+}
+
+#[test]
+fn test_export_round_trips_blank_nodes_and_multi_line_literals() {
+    let source = "/tmp/triples_export_blank_source.ttl";
+    fs::write(
+        source,
+        concat!(
+            "@prefix ex: <http://example.com/> .\n",
+            "ex:s ex:p _:b0 .\n",
+            "_:b0 ex:name \"inner\" .\n",
+            "ex:s ex:note \"two\\nlines\" .\n",
+        ),
+    )
+    .unwrap();
+    let db_file = "/tmp/triples_export_blank.db";
+    import(db_file, source);
+
+    let exported = export(db_file, &[]);
+    assert!(!exported.contains('['), "{exported}");
+    assert!(!exported.contains("\"\"\""), "{exported}");
+
+    let exported_file = "/tmp/triples_export_blank.ttl";
+    fs::write(exported_file, &exported).unwrap();
+    let reimported_db = "/tmp/triples_export_blank_again.db";
+    import(reimported_db, exported_file);
+    let reexported = export_canonical(reimported_db);
+    assert_eq!(export_canonical(db_file), reexported);
+    assert!(
+        reexported.contains(r#"ex:note "two\nlines""#),
+        "{reexported}"
+    );
+    assert!(reexported.contains("ex:name \"inner\""), "{reexported}");
+}
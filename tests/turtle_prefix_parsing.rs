@@ -47,3 +47,10 @@ fn test_error_handling() {
     let input = "\t@pefix myns:<https://example.com/myns#>.";
     test_bad_prefix(input);
 }
+
+#[test]
+fn test_prefix_starting_with_a() {
+    let parser = PrefixParser::new();
+    let (ns, _) = parser.parse("@prefix ahu: <http://cmu.edu/x/> .").unwrap();
+    assert_eq!(ns, Pre::new("ahu".to_string()));
+}